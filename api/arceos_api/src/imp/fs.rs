@@ -1,10 +1,11 @@
-use alloc::string::String;
+use alloc::{string::String, vec::Vec};
 use axerrno::AxResult;
 use axfs::fops::{Directory, File};
 
 pub use axfs::fops::DirEntry as AxDirEntry;
 pub use axfs::fops::FileAttr as AxFileAttr;
 pub use axfs::fops::FilePerm as AxFilePerm;
+pub use axfs::fops::FileSystemInfo as AxFileSystemInfo;
 pub use axfs::fops::FileType as AxFileType;
 pub use axfs::fops::OpenOptions as AxOpenOptions;
 pub use axio::SeekFrom as AxSeekFrom;
@@ -85,3 +86,11 @@ pub fn ax_current_dir() -> AxResult<String> {
 pub fn ax_set_current_dir(path: &str) -> AxResult {
     axfs::api::set_current_dir(path)
 }
+
+pub fn ax_statfs(path: &str) -> AxResult<AxFileSystemInfo> {
+    axfs::api::statfs(path)
+}
+
+pub fn ax_mount_points() -> Vec<String> {
+    axfs::api::mount_points()
+}
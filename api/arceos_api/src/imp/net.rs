@@ -1,6 +1,6 @@
 use crate::io::AxPollState;
 use axerrno::AxResult;
-use axnet::{UdpSocket, TcpSocket};
+use axnet::{TcpSocket, UdpSocket};
 use core::net::{IpAddr, SocketAddr};
 
 /// A handle to a TCP socket.
@@ -48,6 +48,14 @@ pub fn ax_tcp_accept(socket: &AxTcpSocketHandle) -> AxResult<(AxTcpSocketHandle,
     Ok((AxTcpSocketHandle(new_sock), addr))
 }
 
+pub async fn ax_tcp_accept_async(
+    socket: &AxTcpSocketHandle,
+) -> AxResult<(AxTcpSocketHandle, SocketAddr)> {
+    let new_sock = socket.0.accept_async().await?;
+    let addr = new_sock.peer_addr()?;
+    Ok((AxTcpSocketHandle(new_sock), addr))
+}
+
 pub fn ax_tcp_send(socket: &AxTcpSocketHandle, buf: &[u8]) -> AxResult<usize> {
     socket.0.send(buf)
 }
@@ -56,6 +64,14 @@ pub fn ax_tcp_recv(socket: &AxTcpSocketHandle, buf: &mut [u8]) -> AxResult<usize
     socket.0.recv(buf)
 }
 
+pub async fn ax_tcp_send_async(socket: &AxTcpSocketHandle, buf: &[u8]) -> AxResult<usize> {
+    socket.0.send_async(buf).await
+}
+
+pub async fn ax_tcp_recv_async(socket: &AxTcpSocketHandle, buf: &mut [u8]) -> AxResult<usize> {
+    socket.0.recv_async(buf).await
+}
+
 pub fn ax_tcp_poll(socket: &AxTcpSocketHandle) -> AxResult<AxPollState> {
     socket.0.poll()
 }
@@ -89,11 +105,24 @@ pub fn ax_udp_bind(socket: &AxUdpSocketHandle, addr: SocketAddr) -> AxResult {
     socket.0.bind(addr)
 }
 
-pub fn ax_udp_recv_from(socket: &AxUdpSocketHandle, buf: &mut [u8]) -> AxResult<(usize, SocketAddr)> {
+pub fn ax_udp_recv_from(
+    socket: &AxUdpSocketHandle,
+    buf: &mut [u8],
+) -> AxResult<(usize, SocketAddr)> {
     socket.0.recv_from(buf)
 }
 
-pub fn ax_udp_peek_from(socket: &AxUdpSocketHandle, buf: &mut [u8]) -> AxResult<(usize, SocketAddr)> {
+pub async fn ax_udp_recv_from_async(
+    socket: &AxUdpSocketHandle,
+    buf: &mut [u8],
+) -> AxResult<(usize, SocketAddr)> {
+    socket.0.recv_from_async(buf).await
+}
+
+pub fn ax_udp_peek_from(
+    socket: &AxUdpSocketHandle,
+    buf: &mut [u8],
+) -> AxResult<(usize, SocketAddr)> {
     socket.0.peek_from(buf)
 }
 
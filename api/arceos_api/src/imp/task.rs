@@ -17,6 +17,8 @@ pub fn ax_yield_now() {
 }
 
 pub fn ax_exit(_exit_code: i32) -> ! {
+    #[cfg(feature = "fs")]
+    axfs::api::sync().ok();
     #[cfg(feature = "multitask")]
     axtask::exit(_exit_code);
     #[cfg(not(feature = "multitask"))]
@@ -24,6 +26,8 @@ pub fn ax_exit(_exit_code: i32) -> ! {
 }
 
 cfg_task! {
+    use core::future::Future;
+    use core::pin::Pin;
     use core::time::Duration;
 
     /// A handle to a task.
@@ -52,6 +56,19 @@ cfg_task! {
         }
     }
 
+    /// A handle to an executor that runs futures on tasks.
+    ///
+    /// It can be cloned to spawn futures from within the futures.
+    #[derive(Clone, Default)]
+    pub struct AxExecutorHandle(axtask::future::Executor);
+
+    impl AxExecutorHandle {
+        /// Creates a new executor without futures.
+        pub fn new() -> Self {
+            Self(axtask::future::Executor::new())
+        }
+    }
+
     pub fn ax_current_task_id() -> u64 {
         axtask::current().id().as_u64()
     }
@@ -67,12 +84,32 @@ cfg_task! {
         }
     }
 
+    pub fn ax_spawn_on<F>(
+        f: F,
+        name: alloc::string::String,
+        stack_size: usize,
+        cpu_id: usize,
+    ) -> crate::AxResult<AxTaskHandle>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        if cpu_id >= axconfig::SMP {
+            return axerrno::ax_err!(InvalidInput, "ax_spawn_on: invalid CPU ID");
+        }
+        let cpumask = axtask::CpuMask::one(cpu_id);
+        let inner = axtask::spawn_raw_on(f, name, stack_size, cpumask);
+        Ok(AxTaskHandle {
+            id: inner.id().as_u64(),
+            inner,
+        })
+    }
+
     pub fn ax_wait_for_exit(task: AxTaskHandle) -> Option<i32> {
         task.inner.join()
     }
 
     pub fn ax_set_current_priority(prio: isize) -> crate::AxResult {
-        if axtask::set_priority(prio) {
+        if axtask::set_priority(axtask::current().as_task_ref(), prio) {
             Ok(())
         } else {
             axerrno::ax_err!(
@@ -82,19 +119,60 @@ cfg_task! {
         }
     }
 
+    pub fn ax_set_priority(task: &AxTaskHandle, prio: isize) -> crate::AxResult {
+        if axtask::set_priority(&task.inner, prio) {
+            Ok(())
+        } else {
+            axerrno::ax_err!(BadState, "ax_set_priority: failed to set task priority")
+        }
+    }
+
+    pub fn ax_get_priority(task: &AxTaskHandle) -> isize {
+        axtask::get_priority(&task.inner)
+    }
+
+    pub fn ax_set_current_affinity(cpu_ids: &[usize]) -> crate::AxResult {
+        let mut cpumask = axtask::CpuMask::empty();
+        for &cpu_id in cpu_ids {
+            if cpu_id >= axconfig::SMP {
+                return axerrno::ax_err!(InvalidInput, "ax_set_current_affinity: invalid CPU ID");
+            }
+            cpumask.set(cpu_id, true);
+        }
+        if axtask::set_current_affinity(cpumask) {
+            Ok(())
+        } else {
+            axerrno::ax_err!(
+                InvalidInput,
+                "ax_set_current_affinity: failed to set task affinity"
+            )
+        }
+    }
+
     pub fn ax_wait_queue_wait(
         wq: &AxWaitQueueHandle,
         until_condition: impl Fn() -> bool,
         timeout: Option<Duration>,
     ) -> bool {
-        #[cfg(feature = "irq")]
         if let Some(dur) = timeout {
+            #[cfg(feature = "irq")]
             return wq.0.wait_timeout_until(dur, until_condition);
-        }
 
-        if timeout.is_some() {
-            axlog::warn!("ax_wait_queue_wait: the `timeout` argument is ignored without the `irq` feature");
+            // No timer interrupts to wake us up, keep yielding until the
+            // condition is met or the time is up.
+            #[cfg(not(feature = "irq"))]
+            {
+                let deadline = axhal::time::current_time() + dur;
+                while !until_condition() {
+                    if axhal::time::current_time() >= deadline {
+                        return true;
+                    }
+                    axtask::yield_now();
+                }
+                return false;
+            }
         }
+
         wq.0.wait_until(until_condition);
         false
     }
@@ -108,4 +186,20 @@ cfg_task! {
             }
         }
     }
+
+    pub fn ax_block_on(fut: Pin<&mut dyn Future<Output = ()>>) {
+        axtask::future::block_on(fut)
+    }
+
+    pub fn ax_executor_spawn(executor: &AxExecutorHandle, fut: impl Future<Output = ()> + Send + 'static) {
+        executor.0.spawn(fut)
+    }
+
+    pub fn ax_executor_run(executor: &AxExecutorHandle) {
+        executor.0.run()
+    }
+
+    pub async fn ax_sleep_until_async(deadline: crate::time::AxTimeValue) {
+        axtask::future::sleep_until(deadline).await
+    }
 }
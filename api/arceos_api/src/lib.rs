@@ -85,6 +85,7 @@ pub mod task {
         @cfg "multitask";
         pub type AxTaskHandle;
         pub type AxWaitQueueHandle;
+        pub type AxExecutorHandle;
     }
 
     define_api! {
@@ -114,15 +115,33 @@ pub mod task {
             name: alloc::string::String,
             stack_size: usize
         ) -> AxTaskHandle;
+        /// Spawns a new task that runs only on the given CPU.
+        pub fn ax_spawn_on(
+            f: impl FnOnce() + Send + 'static,
+            name: alloc::string::String,
+            stack_size: usize,
+            cpu_id: usize
+        ) -> crate::AxResult<AxTaskHandle>;
         /// Waits for the given task to exit, and returns its exit code (the
         /// argument of [`ax_exit`]).
         pub fn ax_wait_for_exit(task: AxTaskHandle) -> Option<i32>;
         /// Sets the priority of the current task.
         pub fn ax_set_current_priority(prio: isize) -> crate::AxResult;
+        /// Sets the priority of the given task, which may be running, ready,
+        /// or blocked.
+        pub fn ax_set_priority(task: &AxTaskHandle, prio: isize) -> crate::AxResult;
+        /// Returns the priority of the given task.
+        pub fn ax_get_priority(task: &AxTaskHandle) -> isize;
+        /// Sets the CPUs on which the current task is allowed to run, and
+        /// moves it to one of them if needed.
+        pub fn ax_set_current_affinity(cpu_ids: &[usize]) -> crate::AxResult;
 
         /// Blocks the current task and put it into the wait queue, until the
         /// given condition becomes true, or the the given duration has elapsed
-        /// (if specified).
+        /// (if specified). Returns `true` if it timed out.
+        ///
+        /// If the feature `irq` is not enabled, timed waits keep yielding the
+        /// CPU instead of sleeping.
         pub fn ax_wait_queue_wait(
             wq: &AxWaitQueueHandle,
             until_condition: impl Fn() -> bool,
@@ -133,6 +152,28 @@ pub mod task {
         /// The maximum number of tasks to wake up is specified by `count`. If
         /// `count` is `u32::MAX`, it will wake up all tasks in the wait queue.
         pub fn ax_wait_queue_wake(wq: &AxWaitQueueHandle, count: u32);
+
+        /// Runs the future to completion on the current task, which is blocked
+        /// while the future is pending.
+        pub fn ax_block_on(fut: core::pin::Pin<&mut dyn core::future::Future<Output = ()>>);
+        /// Spawns a future on the executor.
+        pub fn ax_executor_spawn(
+            executor: &AxExecutorHandle,
+            fut: impl core::future::Future<Output = ()> + Send + 'static,
+        );
+        /// Runs the futures of the executor on the current task, until all of
+        /// them are completed.
+        pub fn ax_executor_run(executor: &AxExecutorHandle);
+    }
+
+    define_async_api! {
+        @cfg "multitask";
+
+        /// Waits until the given deadline asynchronously.
+        ///
+        /// If the feature `irq` is not enabled, the future is polled again and
+        /// again until the time is up.
+        pub async fn ax_sleep_until_async(deadline: crate::time::AxTimeValue);
     }
 }
 
@@ -150,6 +191,7 @@ pub mod fs {
         pub type AxFilePerm;
         pub type AxDirEntry;
         pub type AxSeekFrom;
+        pub type AxFileSystemInfo;
         #[cfg(feature = "myfs")]
         pub type AxDisk;
         #[cfg(feature = "myfs")]
@@ -218,6 +260,11 @@ pub mod fs {
         pub fn ax_current_dir() -> AxResult<alloc::string::String>;
         /// Changes the current working directory to the specified path.
         pub fn ax_set_current_dir(path: &str) -> AxResult;
+
+        /// Returns the statistics of the filesystem that contains `path`.
+        pub fn ax_statfs(path: &str) -> AxResult<AxFileSystemInfo>;
+        /// Returns the paths of all mount points, starting with the root `/`.
+        pub fn ax_mount_points() -> alloc::vec::Vec<alloc::string::String>;
     }
 }
 
@@ -314,6 +361,21 @@ pub mod net {
         /// packets to the NIC.
         pub fn ax_poll_interfaces() -> AxResult;
     }
+
+    define_async_api! {
+        @cfg "net";
+
+        /// Accepts a new connection on the TCP socket asynchronously, see
+        /// [`ax_tcp_accept`].
+        pub async fn ax_tcp_accept_async(socket: &AxTcpSocketHandle) -> AxResult<(AxTcpSocketHandle, SocketAddr)>;
+        /// Transmits data in the given buffer on the TCP socket asynchronously.
+        pub async fn ax_tcp_send_async(socket: &AxTcpSocketHandle, buf: &[u8]) -> AxResult<usize>;
+        /// Receives data on the TCP socket asynchronously, and stores it in the
+        /// given buffer. On success, returns the number of bytes read.
+        pub async fn ax_tcp_recv_async(socket: &AxTcpSocketHandle, buf: &mut [u8]) -> AxResult<usize>;
+        /// Receives a single datagram message on the UDP socket asynchronously.
+        pub async fn ax_udp_recv_from_async(socket: &AxUdpSocketHandle, buf: &mut [u8]) -> AxResult<(usize, SocketAddr)>;
+    }
 }
 
 /// Graphics manipulation operations.
@@ -1,17 +1,32 @@
 fn main() {
     use std::io::Write;
 
-    fn gen_pthread_mutex(out_file: &str) -> std::io::Result<()> {
+    fn gen_pthread_sync(out_file: &str) -> std::io::Result<()> {
         // TODO: generate size and initial content automatically.
-        let (mutex_size, mutex_init) = if cfg!(feature = "multitask") {
+        // Each initializer is the content of the lock created by its own
+        // `const fn new()`, and they have the same size: a wait queue followed
+        // by a word of state.
+        let (mutex_size, mutex_init, cond_init, rwlock_init) = if cfg!(feature = "multitask") {
             if cfg!(feature = "smp") {
-                (6, "{0, 8, 0, 0, 0, 0}") // core::mem::transmute::<_, [usize; 6]>(axsync::Mutex::new(()))
+                (
+                    6,
+                    "{0, 0, 8, 0, 0, 0}", // core::mem::transmute::<_, [usize; 6]>(axsync::Mutex::new(()))
+                    "{0, 0, 8, 0, 0, 0}", // core::mem::transmute::<_, [usize; 6]>(axsync::Condvar::new())
+                    "{0, 0, 8, 0, 0, 0}", // core::mem::transmute::<_, [usize; 6]>(axsync::RwLock::new(()))
+                )
             } else {
-                (5, "{8, 0, 0, 0, 0}") // core::mem::transmute::<_, [usize; 5]>(axsync::Mutex::new(()))
+                (
+                    5,
+                    "{0, 8, 0, 0, 0}", // core::mem::transmute::<_, [usize; 5]>(axsync::Mutex::new(()))
+                    "{0, 8, 0, 0, 0}", // core::mem::transmute::<_, [usize; 5]>(axsync::Condvar::new())
+                    "{0, 8, 0, 0, 0}", // core::mem::transmute::<_, [usize; 5]>(axsync::RwLock::new(()))
+                )
             }
         } else {
-            (1, "{0}")
+            (1, "{0}", "{0}", "{0}")
         };
+        // `Barrier` has two more words than the others.
+        let barrier_size = mutex_size + 2;
 
         let mut output = Vec::new();
         writeln!(
@@ -21,11 +36,34 @@ fn main() {
         writeln!(
             output,
             r#"
+#ifndef _AX_PTHREAD_SYNC_H
+#define _AX_PTHREAD_SYNC_H
+
 typedef struct {{
     long __l[{mutex_size}];
 }} pthread_mutex_t;
 
+typedef struct {{
+    long __l[{mutex_size}];
+}} pthread_cond_t;
+
+typedef struct {{
+    long __l[{mutex_size}];
+}} pthread_rwlock_t;
+
+typedef struct {{
+    long __l[{barrier_size}];
+}} pthread_barrier_t;
+
+typedef struct {{
+    long __l[{mutex_size}];
+}} sem_t;
+
 #define PTHREAD_MUTEX_INITIALIZER {{ .__l = {mutex_init}}}
+#define PTHREAD_COND_INITIALIZER {{ .__l = {cond_init}}}
+#define PTHREAD_RWLOCK_INITIALIZER {{ .__l = {rwlock_init}}}
+
+#endif // _AX_PTHREAD_SYNC_H
 "#
         )?;
         std::fs::write(out_file, output)?;
@@ -37,10 +75,13 @@ typedef struct {{
 
         let allow_types = [
             "stat",
+            "statfs",
             "size_t",
             "ssize_t",
             "off_t",
             "mode_t",
+            "uid_t",
+            "gid_t",
             "sock.*",
             "fd_set",
             "timeval",
@@ -48,10 +89,27 @@ typedef struct {{
             "pthread_attr_t",
             "pthread_mutex_t",
             "pthread_mutexattr_t",
+            "pthread_cond_t",
+            "pthread_condattr_t",
+            "pthread_rwlock_t",
+            "pthread_rwlockattr_t",
+            "pthread_barrier_t",
+            "pthread_barrierattr_t",
+            "sem_t",
+            "pollfd",
+            "nfds_t",
             "epoll_event",
+            "itimerspec",
+            "signalfd_siginfo",
             "iovec",
             "clockid_t",
+            "pid_t",
+            "dirent",
+            "id_t",
             "rlimit",
+            "rusage",
+            "sched_param",
+            "sigset_t",
             "aibuf",
         ];
         let allow_vars = [
@@ -60,11 +118,27 @@ typedef struct {{
             "SOCK_.*",
             "IPPROTO_.*",
             "FD_.*",
+            "AT_.*",
+            "RENAME_.*",
+            "UTIME_.*",
+            "[RWX]_OK",
             "F_.*",
             "_SC_.*",
+            "POLL.*",
             "EPOLL_CTL_.*",
             "EPOLL.*",
+            "EFD_.*",
+            "TFD_.*",
+            "SFD_.*",
+            "SIG.*",
+            "SI_USER",
             "RLIMIT_.*",
+            "PRIO_.*",
+            "RUSAGE_.*",
+            "CLOCK_.*",
+            "PTHREAD_BARRIER_SERIAL_THREAD",
+            "PTHREAD_CANCEL_.*",
+            "SEM_VALUE_MAX",
             "EAI_.*",
             "MAXADDRS",
         ];
@@ -74,7 +148,7 @@ typedef struct {{
 
         impl bindgen::callbacks::ParseCallbacks for MyCallbacks {
             fn include_file(&self, fname: &str) {
-                if !fname.contains("ax_pthread_mutex.h") {
+                if !fname.contains("ax_pthread_sync.h") {
                     println!("cargo:rerun-if-changed={}", fname);
                 }
             }
@@ -101,6 +175,6 @@ typedef struct {{
             .expect("Couldn't write bindings!");
     }
 
-    gen_pthread_mutex("../../ulib/axlibc/include/ax_pthread_mutex.h").unwrap();
+    gen_pthread_sync("../../ulib/axlibc/include/ax_pthread_sync.h").unwrap();
     gen_c_to_rust_bindings("ctypes.h", "src/ctypes_gen.rs");
 }
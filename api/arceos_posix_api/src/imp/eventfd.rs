@@ -0,0 +1,142 @@
+use alloc::sync::Arc;
+use core::ffi::{c_int, c_uint};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use axerrno::{LinuxError, LinuxResult};
+use axio::PollState;
+use axsync::{Mutex, MutexGuard};
+
+use super::fd_ops::{add_file_like, FileLike, PollSet};
+use crate::ctypes;
+
+/// The maximum value of the counter of an eventfd.
+const EVENTFD_MAX: u64 = u64::MAX - 1;
+
+pub struct EventFd {
+    count: Mutex<u64>,
+    semaphore: bool,
+    nonblocking: AtomicBool,
+    poll_set: PollSet,
+    #[cfg(feature = "multitask")]
+    count_cv: axsync::Condvar,
+}
+
+impl EventFd {
+    fn new(initval: u64, flags: u32) -> Self {
+        Self {
+            count: Mutex::new(initval),
+            semaphore: flags & ctypes::EFD_SEMAPHORE != 0,
+            nonblocking: AtomicBool::new(flags & ctypes::EFD_NONBLOCK != 0),
+            poll_set: PollSet::new(),
+            #[cfg(feature = "multitask")]
+            count_cv: axsync::Condvar::new(),
+        }
+    }
+
+    /// Blocks until `ready` returns `true` for the counter, or returns
+    /// `EAGAIN` if the eventfd is nonblocking.
+    fn wait_until<'a>(
+        &'a self,
+        mut count: MutexGuard<'a, u64>,
+        ready: impl Fn(u64) -> bool,
+    ) -> LinuxResult<MutexGuard<'a, u64>> {
+        while !ready(*count) {
+            if self.nonblocking.load(Ordering::Relaxed) {
+                return Err(LinuxError::EAGAIN);
+            }
+            #[cfg(feature = "multitask")]
+            {
+                count = self.count_cv.wait(count);
+            }
+            #[cfg(not(feature = "multitask"))]
+            {
+                drop(count);
+                crate::sys_sched_yield();
+                count = self.count.lock();
+            }
+        }
+        Ok(count)
+    }
+
+    /// Wakes up the tasks waiting for the counter to change.
+    fn notify(&self) {
+        #[cfg(feature = "multitask")]
+        self.count_cv.notify_all();
+        self.poll_set.wake();
+    }
+}
+
+impl FileLike for EventFd {
+    fn read(&self, buf: &mut [u8]) -> LinuxResult<usize> {
+        let buf = buf.get_mut(..8).ok_or(LinuxError::EINVAL)?;
+        let mut count = self.wait_until(self.count.lock(), |count| count > 0)?;
+        let value = if self.semaphore { 1 } else { *count };
+        *count -= value;
+        drop(count);
+        buf.copy_from_slice(&value.to_ne_bytes());
+        self.notify();
+        Ok(8)
+    }
+
+    fn write(&self, buf: &[u8]) -> LinuxResult<usize> {
+        let value = match buf.get(..8) {
+            Some(bytes) => u64::from_ne_bytes(bytes.try_into().unwrap()),
+            None => return Err(LinuxError::EINVAL),
+        };
+        if value == u64::MAX {
+            return Err(LinuxError::EINVAL);
+        }
+        let mut count = self.wait_until(self.count.lock(), |count| value <= EVENTFD_MAX - count)?;
+        *count += value;
+        drop(count);
+        self.notify();
+        Ok(8)
+    }
+
+    fn stat(&self) -> LinuxResult<ctypes::stat> {
+        let st_mode = 0o600u32; // rw-------
+        Ok(ctypes::stat {
+            st_ino: 1,
+            st_nlink: 1,
+            st_mode,
+            ..Default::default()
+        })
+    }
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn core::any::Any + Send + Sync> {
+        self
+    }
+
+    fn poll(&self) -> LinuxResult<PollState> {
+        let count = *self.count.lock();
+        Ok(PollState {
+            readable: count > 0,
+            writable: count < EVENTFD_MAX,
+            hangup: false,
+        })
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> LinuxResult {
+        self.nonblocking.store(nonblocking, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn poll_set(&self) -> Option<&PollSet> {
+        Some(&self.poll_set)
+    }
+}
+
+/// Create a file descriptor for event notification, with `initval` as the
+/// initial value of its counter.
+///
+/// Return the new file descriptor if succeed
+pub fn sys_eventfd(initval: c_uint, flags: c_int) -> c_int {
+    debug!("sys_eventfd <= initval: {}, flags: {:#x}", initval, flags);
+    syscall_body!(sys_eventfd, {
+        let flags = flags as u32;
+        if flags & !(ctypes::EFD_SEMAPHORE | ctypes::EFD_NONBLOCK | ctypes::EFD_CLOEXEC) != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        add_file_like(Arc::new(EventFd::new(initval as u64, flags)))
+    })
+}
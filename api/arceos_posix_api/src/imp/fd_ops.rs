@@ -1,8 +1,11 @@
-use alloc::sync::Arc;
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
 use core::ffi::c_int;
+use core::time::Duration;
 
 use axerrno::{LinuxError, LinuxResult};
 use axio::PollState;
+use axsync::spin::SpinNoIrq;
 use flatten_objects::FlattenObjects;
 use spin::RwLock;
 
@@ -11,6 +14,16 @@ use crate::ctypes;
 
 pub const AX_FILE_LIMIT: usize = 1024;
 
+/// The interval at which the tasks waiting for files wake up to call
+/// [`poll_files`], if some of the files are polled (see [`PollSet::new_polled`]).
+///
+/// It is one scheduler tick. The network devices and the console do not
+/// raise interrupts, so this is the latency of the events of sockets and the
+/// standard input that no task is polling for, and a task waiting for them
+/// still wakes up at every tick while idle.
+pub const POLL_INTERVAL: Duration =
+    Duration::from_nanos(axhal::time::NANOS_PER_SEC / axconfig::TICKS_PER_SEC as u64);
+
 pub trait FileLike: Send + Sync {
     fn read(&self, buf: &mut [u8]) -> LinuxResult<usize>;
     fn write(&self, buf: &[u8]) -> LinuxResult<usize>;
@@ -18,6 +31,146 @@ pub trait FileLike: Send + Sync {
     fn into_any(self: Arc<Self>) -> Arc<dyn core::any::Any + Send + Sync>;
     fn poll(&self) -> LinuxResult<PollState>;
     fn set_nonblocking(&self, nonblocking: bool) -> LinuxResult;
+
+    /// Returns the watchers to be woken up when the readiness of the file may
+    /// have changed, or `None` if it never changes (e.g., regular files).
+    fn poll_set(&self) -> Option<&PollSet> {
+        None
+    }
+}
+
+/// An object interested in the readiness of files, e.g., an epoll instance.
+pub trait PollWatcher: Send + Sync {
+    /// Whether the watcher is exclusive (`EPOLLEXCLUSIVE`).
+    fn exclusive(&self) -> bool;
+
+    /// Called when the readiness of the watched file may have changed. It may
+    /// be called in the timer interrupt handler, so it must not block.
+    ///
+    /// Returns `true` if a task waiting on the watcher was woken up.
+    fn wake(&self) -> bool;
+}
+
+/// The watchers of one or more files.
+///
+/// A wakeup is delivered to all non-exclusive watchers, but only to one of the
+/// exclusive watchers, i.e., the first one that has a task waiting on it.
+pub struct PollSet {
+    watchers: SpinNoIrq<Vec<Weak<dyn PollWatcher>>>,
+    polled: bool,
+}
+
+impl PollSet {
+    /// Creates an empty set for files that wake up their watchers by
+    /// themselves when their readiness changes, e.g., pipes.
+    pub const fn new() -> Self {
+        Self {
+            watchers: SpinNoIrq::new(Vec::new()),
+            polled: false,
+        }
+    }
+
+    /// Creates an empty set for files that cannot detect their readiness
+    /// changes, e.g., sockets, as the network stack is driven by polling.
+    /// Their watchers are woken up by [`poll_files`], so the waiting tasks
+    /// need to call it periodically.
+    pub const fn new_polled() -> Self {
+        Self {
+            watchers: SpinNoIrq::new(Vec::new()),
+            polled: true,
+        }
+    }
+
+    /// Whether the set is created by [`PollSet::new_polled`].
+    pub const fn is_polled(&self) -> bool {
+        self.polled
+    }
+
+    /// Whether there are any watchers.
+    pub fn has_watchers(&self) -> bool {
+        self.watchers.lock().iter().any(|w| w.strong_count() > 0)
+    }
+
+    /// Adds a watcher. It is removed automatically after being dropped.
+    pub fn register(&self, watcher: Weak<dyn PollWatcher>) {
+        let mut watchers = self.watchers.lock();
+        watchers.retain(|w| w.strong_count() > 0);
+        watchers.push(watcher);
+    }
+
+    /// Wakes up the watchers.
+    pub fn wake(&self) {
+        // Do not hold the lock while waking up, as it may reschedule.
+        let watchers: Vec<_> = self
+            .watchers
+            .lock()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect();
+        let mut exclusive_woken = false;
+        for watcher in watchers {
+            if !watcher.exclusive() {
+                watcher.wake();
+            } else if !exclusive_woken {
+                exclusive_woken = watcher.wake();
+            }
+        }
+    }
+}
+
+impl Default for PollSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Polls the event sources that do not notify, i.e., the network stack, the
+/// console input, and the timers if there are no timer interrupts, and wakes
+/// up the watchers of the files that may have become ready.
+pub fn poll_files() {
+    #[cfg(feature = "net")]
+    super::net::poll_network();
+    super::stdio::poll_stdin();
+    #[cfg(not(all(feature = "multitask", feature = "irq")))]
+    super::timerfd::poll_timers();
+}
+
+/// Blocks the current task on `wq` until `condition` becomes true, or the
+/// `deadline` is reached. Returns `false` if it timed out.
+///
+/// If the feature `irq` is not enabled, it keeps yielding the CPU until the
+/// deadline instead.
+#[cfg(feature = "multitask")]
+pub fn wait_until<F>(
+    wq: &axtask::WaitQueue,
+    deadline: Option<axhal::time::TimeValue>,
+    condition: F,
+) -> bool
+where
+    F: Fn() -> bool,
+{
+    let Some(deadline) = deadline else {
+        wq.wait_until(condition);
+        return true;
+    };
+    if condition() {
+        return true;
+    }
+    #[cfg(feature = "irq")]
+    {
+        let timeout = deadline.saturating_sub(axhal::time::current_time());
+        !wq.wait_timeout_until(timeout, condition)
+    }
+    #[cfg(not(feature = "irq"))]
+    {
+        while !condition() {
+            if axhal::time::current_time() >= deadline {
+                return false;
+            }
+            axtask::yield_now();
+        }
+        true
+    }
 }
 
 lazy_static::lazy_static! {
@@ -1,8 +1,10 @@
-use alloc::sync::Arc;
-use core::ffi::{c_char, c_int};
+use alloc::{format, string::String, sync::Arc};
+use core::ffi::{c_char, c_int, c_uint, c_void};
+use core::mem::offset_of;
+use core::time::Duration;
 
-use axerrno::{LinuxError, LinuxResult};
-use axfs::fops::OpenOptions;
+use axerrno::{AxError, LinuxError, LinuxResult};
+use axfs::fops::{DirEntry, FileAttr, FilePerm, FileSetAttr, FileSystemInfo, OpenOptions};
 use axio::{PollState, SeekFrom};
 use axsync::Mutex;
 
@@ -42,21 +44,7 @@ impl FileLike for File {
     }
 
     fn stat(&self) -> LinuxResult<ctypes::stat> {
-        let metadata = self.inner.lock().get_attr()?;
-        let ty = metadata.file_type() as u8;
-        let perm = metadata.perm().bits() as u32;
-        let st_mode = ((ty as u32) << 12) | perm;
-        Ok(ctypes::stat {
-            st_ino: 1,
-            st_nlink: 1,
-            st_mode,
-            st_uid: 1000,
-            st_gid: 1000,
-            st_size: metadata.size() as _,
-            st_blocks: metadata.blocks() as _,
-            st_blksize: 512,
-            ..Default::default()
-        })
+        Ok(attr_to_stat(&self.inner.lock().get_attr()?))
     }
 
     fn into_any(self: Arc<Self>) -> Arc<dyn core::any::Any + Send + Sync> {
@@ -67,6 +55,78 @@ impl FileLike for File {
         Ok(PollState {
             readable: true,
             writable: true,
+            hangup: false,
+        })
+    }
+
+    fn set_nonblocking(&self, _nonblocking: bool) -> LinuxResult {
+        Ok(())
+    }
+}
+
+/// An opened directory, whose entries can be read by [`sys_getdents64`]. It
+/// can also be the base directory of the `*at` syscalls.
+pub struct Directory {
+    inner: Mutex<axfs::fops::Directory>,
+    /// The absolute path when the directory is opened.
+    path: String,
+}
+
+impl Directory {
+    fn new(inner: axfs::fops::Directory, path: String) -> Self {
+        Self {
+            inner: Mutex::new(inner),
+            path,
+        }
+    }
+
+    fn add_to_fd_table(self) -> LinuxResult<c_int> {
+        super::fd_ops::add_file_like(Arc::new(self))
+    }
+
+    fn from_fd(fd: c_int) -> LinuxResult<Arc<Self>> {
+        let f = super::fd_ops::get_file_like(fd)?;
+        f.into_any()
+            .downcast::<Self>()
+            .map_err(|_| LinuxError::ENOTDIR)
+    }
+
+    /// Moves the cursor to an entry, the position is the index of the entry.
+    fn seek(&self, pos: SeekFrom) -> LinuxResult<u64> {
+        let mut dir = self.inner.lock();
+        let new_pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::Current(off) => (dir.position() as u64).checked_add_signed(off),
+            SeekFrom::End(_) => None,
+        };
+        let new_pos = new_pos.ok_or(LinuxError::EINVAL)?;
+        dir.set_position(new_pos as usize);
+        Ok(new_pos)
+    }
+}
+
+impl FileLike for Directory {
+    fn read(&self, _buf: &mut [u8]) -> LinuxResult<usize> {
+        Err(LinuxError::EISDIR)
+    }
+
+    fn write(&self, _buf: &[u8]) -> LinuxResult<usize> {
+        Err(LinuxError::EBADF)
+    }
+
+    fn stat(&self) -> LinuxResult<ctypes::stat> {
+        Ok(attr_to_stat(&self.inner.lock().get_attr()?))
+    }
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn core::any::Any + Send + Sync> {
+        self
+    }
+
+    fn poll(&self) -> LinuxResult<PollState> {
+        Ok(PollState {
+            readable: true,
+            writable: false,
+            hangup: false,
         })
     }
 
@@ -75,6 +135,43 @@ impl FileLike for File {
     }
 }
 
+/// Convert [`FileSystemInfo`] to the `statfs` structure.
+fn info_to_statfs(info: &FileSystemInfo) -> ctypes::statfs {
+    ctypes::statfs {
+        f_type: info.fs_type() as _,
+        f_bsize: info.block_size() as _,
+        f_blocks: info.blocks(),
+        f_bfree: info.blocks_free(),
+        f_bavail: info.blocks_avail(),
+        f_files: info.files(),
+        f_ffree: info.files_free(),
+        f_namelen: info.name_len() as _,
+        f_frsize: info.block_size() as _,
+        ..Default::default()
+    }
+}
+
+/// Convert [`FileAttr`] to the `stat` structure.
+fn attr_to_stat(metadata: &FileAttr) -> ctypes::stat {
+    let ty = metadata.file_type() as u8;
+    let perm = metadata.perm().bits() as u32;
+    let st_mode = ((ty as u32) << 12) | perm;
+    ctypes::stat {
+        st_ino: metadata.ino(),
+        st_nlink: metadata.nlink() as _,
+        st_mode,
+        st_uid: metadata.uid(),
+        st_gid: metadata.gid(),
+        st_size: metadata.size() as _,
+        st_blocks: metadata.blocks() as _,
+        st_blksize: 512,
+        st_atime: metadata.atime().into(),
+        st_mtime: metadata.mtime().into(),
+        st_ctime: metadata.ctime().into(),
+        ..Default::default()
+    }
+}
+
 /// Convert open flags to [`OpenOptions`].
 fn flags_to_options(flags: c_int, _mode: ctypes::mode_t) -> OpenOptions {
     let flags = flags as u32;
@@ -99,20 +196,116 @@ fn flags_to_options(flags: c_int, _mode: ctypes::mode_t) -> OpenOptions {
     if flags & ctypes::O_EXEC != 0 {
         options.create_new(true);
     }
+    if flags & ctypes::O_NOFOLLOW != 0 {
+        options.no_follow(true);
+    }
     options
 }
 
+/// Runs `f` with the directory `dirfd` that `path` is relative to, or with
+/// [`None`] if `dirfd` is `AT_FDCWD` or `path` is absolute.
+fn with_dir_at<T>(
+    dirfd: c_int,
+    path: &str,
+    f: impl FnOnce(Option<&Directory>) -> LinuxResult<T>,
+) -> LinuxResult<T> {
+    if dirfd == ctypes::AT_FDCWD || path.starts_with('/') {
+        f(None)
+    } else {
+        f(Some(&*Directory::from_fd(dirfd)?))
+    }
+}
+
+/// Converts `path` relative to `dir`, or to the current directory if `dir` is
+/// [`None`], to an absolute path.
+fn absolute_path_at(dir: Option<&Directory>, path: &str) -> LinuxResult<String> {
+    let path = match dir {
+        Some(dir) if !path.starts_with('/') => format!("{}/{}", dir.path, path),
+        _ => String::from(path),
+    };
+    Ok(axfs::api::canonicalize(&path)?)
+}
+
+/// Gets the file attributes of `path` relative to `dir`.
+fn attr_at(dir: Option<&Directory>, path: &str, no_follow: bool) -> LinuxResult<FileAttr> {
+    let attr = match dir {
+        Some(dir) => dir.inner.lock().get_attr_at(path, no_follow)?,
+        None if no_follow => *axfs::api::symlink_metadata(path)?.raw_metadata(),
+        None => *axfs::api::metadata(path)?.raw_metadata(),
+    };
+    Ok(attr)
+}
+
+/// Changes the attributes of the file or directory `fd`.
+fn set_attr_fd(fd: c_int, attr: &FileSetAttr) -> LinuxResult {
+    let f = get_file_like(fd)?.into_any();
+    if let Ok(file) = f.clone().downcast::<File>() {
+        file.inner.lock().set_attr(attr)?;
+    } else if let Ok(dir) = f.downcast::<Directory>() {
+        dir.inner.lock().set_attr(attr)?;
+    } else {
+        return Err(LinuxError::EINVAL);
+    }
+    Ok(())
+}
+
+/// Opens a file or a directory at `filename` relative to `dir`, and inserts
+/// it into the file descriptor table.
+fn open_at(
+    dir: Option<&Directory>,
+    filename: &str,
+    flags: c_int,
+    mode: ctypes::mode_t,
+) -> LinuxResult<c_int> {
+    let options = flags_to_options(flags, mode);
+    if flags as u32 & ctypes::O_DIRECTORY == 0 {
+        let file = match dir {
+            Some(dir) => dir.inner.lock().open_file_at(filename, &options)?,
+            None => axfs::fops::File::open(filename, &options)?,
+        };
+        if !file.get_attr()?.is_dir() {
+            return File::new(file).add_to_fd_table();
+        }
+    }
+    let inner = match dir {
+        Some(dir) => dir.inner.lock().open_dir_at(filename, &options)?,
+        None => axfs::fops::Directory::open_dir(filename, &options)?,
+    };
+    Directory::new(inner, absolute_path_at(dir, filename)?).add_to_fd_table()
+}
+
 /// Open a file by `filename` and insert it into the file descriptor table.
 ///
+/// A directory is opened if `filename` is a directory opened for reading, or
+/// `O_DIRECTORY` is set.
+///
 /// Return its index in the file table (`fd`). Return `EMFILE` if it already
 /// has the maximum number of files open.
 pub fn sys_open(filename: *const c_char, flags: c_int, mode: ctypes::mode_t) -> c_int {
     let filename = char_ptr_to_str(filename);
     debug!("sys_open <= {:?} {:#o} {:#o}", filename, flags, mode);
-    syscall_body!(sys_open, {
-        let options = flags_to_options(flags, mode);
-        let file = axfs::fops::File::open(filename?, &options)?;
-        File::new(file).add_to_fd_table()
+    syscall_body!(sys_open, { open_at(None, filename?, flags, mode) })
+}
+
+/// Open a file by `filename` relative to the directory `dirfd`, like
+/// [`sys_open`].
+///
+/// `dirfd` can be `AT_FDCWD` for the current directory. It is not used if
+/// `filename` is absolute.
+pub fn sys_openat(
+    dirfd: c_int,
+    filename: *const c_char,
+    flags: c_int,
+    mode: ctypes::mode_t,
+) -> c_int {
+    let filename = char_ptr_to_str(filename);
+    debug!(
+        "sys_openat <= {} {:?} {:#o} {:#o}",
+        dirfd, filename, flags, mode
+    );
+    syscall_body!(sys_openat, {
+        let filename = filename?;
+        with_dir_at(dirfd, filename, |dir| open_at(dir, filename, flags, mode))
     })
 }
 
@@ -128,7 +321,14 @@ pub fn sys_lseek(fd: c_int, offset: ctypes::off_t, whence: c_int) -> ctypes::off
             2 => SeekFrom::End(offset as _),
             _ => return Err(LinuxError::EINVAL),
         };
-        let off = File::from_fd(fd)?.inner.lock().seek(pos)?;
+        let f = get_file_like(fd)?.into_any();
+        let off = if let Some(file) = f.downcast_ref::<File>() {
+            file.inner.lock().seek(pos)?
+        } else if let Some(dir) = f.downcast_ref::<Directory>() {
+            dir.seek(pos)?
+        } else {
+            return Err(LinuxError::EINVAL);
+        };
         Ok(off)
     })
 }
@@ -177,7 +377,114 @@ pub unsafe fn sys_lstat(path: *const c_char, buf: *mut ctypes::stat) -> ctypes::
         if buf.is_null() {
             return Err(LinuxError::EFAULT);
         }
-        unsafe { *buf = Default::default() }; // TODO
+        let metadata = axfs::api::symlink_metadata(path?)?;
+        unsafe { *buf = attr_to_stat(metadata.raw_metadata()) };
+        Ok(0)
+    })
+}
+
+/// Get the statistics of the filesystem that contains `path` and write into
+/// `buf`.
+///
+/// Return 0 if success.
+pub unsafe fn sys_statfs(path: *const c_char, buf: *mut ctypes::statfs) -> c_int {
+    let path = char_ptr_to_str(path);
+    debug!("sys_statfs <= {:?} {:#x}", path, buf as usize);
+    syscall_body!(sys_statfs, {
+        if buf.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let info = axfs::api::statfs(path?)?;
+        unsafe { *buf = info_to_statfs(&info) };
+        Ok(0)
+    })
+}
+
+/// Get the statistics of the filesystem that contains the file or directory
+/// `fd` and write into `buf`.
+///
+/// Return 0 if success.
+pub unsafe fn sys_fstatfs(fd: c_int, buf: *mut ctypes::statfs) -> c_int {
+    debug!("sys_fstatfs <= {} {:#x}", fd, buf as usize);
+    syscall_body!(sys_fstatfs, {
+        if buf.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let f = get_file_like(fd)?.into_any();
+        let info = if let Some(file) = f.downcast_ref::<File>() {
+            file.inner.lock().statfs()?
+        } else if let Some(dir) = f.downcast_ref::<Directory>() {
+            dir.inner.lock().statfs()?
+        } else {
+            return Err(LinuxError::EINVAL);
+        };
+        unsafe { *buf = info_to_statfs(&info) };
+        Ok(0)
+    })
+}
+
+/// Read the target of the symbolic link `path` into `buf`.
+///
+/// Return the number of bytes placed in `buf`, the target is truncated if
+/// `buf` is too small. No terminating null byte is appended.
+pub fn sys_readlink(path: *const c_char, buf: *mut c_char, bufsize: usize) -> ctypes::ssize_t {
+    sys_readlinkat(ctypes::AT_FDCWD, path, buf, bufsize)
+}
+
+/// Read the target of the symbolic link `path` relative to the directory
+/// `dirfd` into `buf`, like [`sys_readlink`].
+pub fn sys_readlinkat(
+    dirfd: c_int,
+    path: *const c_char,
+    buf: *mut c_char,
+    bufsize: usize,
+) -> ctypes::ssize_t {
+    let path = char_ptr_to_str(path);
+    debug!(
+        "sys_readlinkat <= {} {:?} {:#x} {}",
+        dirfd, path, buf as usize, bufsize
+    );
+    syscall_body!(sys_readlinkat, {
+        if buf.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let path = path?;
+        let target = with_dir_at(dirfd, path, |dir| match dir {
+            Some(dir) => Ok(dir.inner.lock().read_link(path)?),
+            None => Ok(axfs::api::read_link(path)?),
+        })?;
+        let len = target.len().min(bufsize);
+        let dst = unsafe { core::slice::from_raw_parts_mut(buf as *mut u8, len) };
+        dst.copy_from_slice(&target.as_bytes()[..len]);
+        Ok(len)
+    })
+}
+
+/// Create a symbolic link `linkpath` which contains the string `target`.
+///
+/// Return 0 if success.
+pub fn sys_symlink(target: *const c_char, linkpath: *const c_char) -> c_int {
+    syscall_body!(sys_symlink, {
+        let target = char_ptr_to_str(target)?;
+        let linkpath = char_ptr_to_str(linkpath)?;
+        debug!(
+            "sys_symlink <= target: {:?}, linkpath: {:?}",
+            target, linkpath
+        );
+        axfs::api::symlink(target, linkpath)?;
+        Ok(0)
+    })
+}
+
+/// Create a new hard link `new` to the existing file `old`.
+///
+/// Return 0 if success.
+pub fn sys_link(old: *const c_char, new: *const c_char) -> c_int {
+    syscall_body!(sys_link, {
+        let old_path = char_ptr_to_str(old)?;
+        let new_path = char_ptr_to_str(new)?;
+        debug!("sys_link <= old: {:?}, new: {:?}", old_path, new_path);
+        axfs::api::hard_link(old_path, new_path)?;
         Ok(0)
     })
 }
@@ -215,3 +522,535 @@ pub fn sys_rename(old: *const c_char, new: *const c_char) -> c_int {
         Ok(0)
     })
 }
+
+/// Rename `old` relative to the directory `olddirfd` to `new` relative to
+/// `newdirfd`, like [`sys_rename`].
+///
+/// `flags` can be `RENAME_NOREPLACE`, which fails with `EEXIST` if `new`
+/// exists. Other flags are not supported.
+pub fn sys_renameat2(
+    olddirfd: c_int,
+    old: *const c_char,
+    newdirfd: c_int,
+    new: *const c_char,
+    flags: c_uint,
+) -> c_int {
+    syscall_body!(sys_renameat2, {
+        let old = char_ptr_to_str(old)?;
+        let new = char_ptr_to_str(new)?;
+        debug!(
+            "sys_renameat2 <= old: {} {:?}, new: {} {:?}, flags: {:#x}",
+            olddirfd, old, newdirfd, new, flags
+        );
+        let old_path = with_dir_at(olddirfd, old, |dir| absolute_path_at(dir, old))?;
+        let new_path = with_dir_at(newdirfd, new, |dir| absolute_path_at(dir, new))?;
+        match flags {
+            0 => {}
+            ctypes::RENAME_NOREPLACE => {
+                if axfs::api::symlink_metadata(&new_path).is_ok() {
+                    return Err(LinuxError::EEXIST);
+                }
+            }
+            _ => return Err(LinuxError::EINVAL),
+        }
+        axfs::api::rename(&old_path, &new_path)?;
+        Ok(0)
+    })
+}
+
+/// Create a directory `path`. The permission bits in `mode` are ignored.
+///
+/// Return 0 if success.
+pub fn sys_mkdir(path: *const c_char, mode: ctypes::mode_t) -> c_int {
+    sys_mkdirat(ctypes::AT_FDCWD, path, mode)
+}
+
+/// Create a directory `path` relative to the directory `dirfd`, like
+/// [`sys_mkdir`].
+pub fn sys_mkdirat(dirfd: c_int, path: *const c_char, mode: ctypes::mode_t) -> c_int {
+    let path = char_ptr_to_str(path);
+    debug!("sys_mkdirat <= {} {:?} {:#o}", dirfd, path, mode);
+    syscall_body!(sys_mkdirat, {
+        let path = path?;
+        with_dir_at(dirfd, path, |dir| match dir {
+            Some(dir) => Ok(dir.inner.lock().create_dir(path)?),
+            None => Ok(axfs::api::create_dir(path)?),
+        })?;
+        Ok(0)
+    })
+}
+
+/// Remove the file `path`, which must not be a directory.
+///
+/// Return 0 if success.
+pub fn sys_unlink(path: *const c_char) -> c_int {
+    sys_unlinkat(ctypes::AT_FDCWD, path, 0)
+}
+
+/// Remove the directory `path`, which must be empty.
+///
+/// Return 0 if success.
+pub fn sys_rmdir(path: *const c_char) -> c_int {
+    sys_unlinkat(ctypes::AT_FDCWD, path, ctypes::AT_REMOVEDIR as _)
+}
+
+/// Remove the file `path` relative to the directory `dirfd`, like
+/// [`sys_unlink`], or the directory like [`sys_rmdir`] if `flags` is
+/// `AT_REMOVEDIR`.
+pub fn sys_unlinkat(dirfd: c_int, path: *const c_char, flags: c_int) -> c_int {
+    let path = char_ptr_to_str(path);
+    debug!("sys_unlinkat <= {} {:?} {:#x}", dirfd, path, flags);
+    syscall_body!(sys_unlinkat, {
+        let path = path?;
+        let remove_dir = match flags as u32 {
+            0 => false,
+            ctypes::AT_REMOVEDIR => true,
+            _ => return Err(LinuxError::EINVAL),
+        };
+        with_dir_at(dirfd, path, |dir| match dir {
+            Some(dir) if remove_dir => Ok(dir.inner.lock().remove_dir(path)?),
+            Some(dir) => Ok(dir.inner.lock().remove_file(path)?),
+            None if remove_dir => Ok(axfs::api::remove_dir(path)?),
+            None => Ok(axfs::api::remove_file(path)?),
+        })?;
+        Ok(0)
+    })
+}
+
+/// Truncate or extend the file `fd` to `length` bytes. The file must be
+/// opened for writing.
+///
+/// Return 0 if success.
+pub fn sys_ftruncate(fd: c_int, length: ctypes::off_t) -> c_int {
+    debug!("sys_ftruncate <= {} {}", fd, length);
+    syscall_body!(sys_ftruncate, {
+        if length < 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        let file = File::from_fd(fd)?;
+        file.inner
+            .lock()
+            .truncate(length as _)
+            .map_err(|e| match e {
+                AxError::PermissionDenied => LinuxError::EINVAL,
+                e => e.into(),
+            })?;
+        Ok(0)
+    })
+}
+
+/// Flush the data and metadata of the file `fd` to the storage device.
+///
+/// Return 0 if success.
+pub fn sys_fsync(fd: c_int) -> c_int {
+    debug!("sys_fsync <= {}", fd);
+    syscall_body!(sys_fsync, {
+        let f = get_file_like(fd)?.into_any();
+        if let Some(file) = f.downcast_ref::<File>() {
+            file.inner.lock().flush()?;
+        } else if f.downcast_ref::<Directory>().is_none() {
+            return Err(LinuxError::EINVAL);
+        }
+        Ok(0)
+    })
+}
+
+/// Change the current directory to `path`.
+///
+/// Return 0 if success.
+pub fn sys_chdir(path: *const c_char) -> c_int {
+    let path = char_ptr_to_str(path);
+    debug!("sys_chdir <= {:?}", path);
+    syscall_body!(sys_chdir, {
+        axfs::api::set_current_dir(path?)?;
+        Ok(0)
+    })
+}
+
+/// Check whether the file `path` exists (`F_OK`), or can be accessed with
+/// the permissions in `mode` (`R_OK`, `W_OK` and `X_OK`). The permissions
+/// of the owner are checked.
+///
+/// Return 0 if success.
+pub fn sys_access(path: *const c_char, mode: c_int) -> c_int {
+    sys_faccessat(ctypes::AT_FDCWD, path, mode, 0)
+}
+
+/// Check the permissions of the file `path` relative to the directory
+/// `dirfd`, like [`sys_access`].
+///
+/// `flags` can contain `AT_SYMLINK_NOFOLLOW`, and `AT_EACCESS` which makes no
+/// difference as there are no users.
+pub fn sys_faccessat(dirfd: c_int, path: *const c_char, mode: c_int, flags: c_int) -> c_int {
+    let path = char_ptr_to_str(path);
+    debug!(
+        "sys_faccessat <= {} {:?} {:#o} {:#x}",
+        dirfd, path, mode, flags
+    );
+    syscall_body!(sys_faccessat, {
+        let (mode, flags) = (mode as u32, flags as u32);
+        if mode & !(ctypes::R_OK | ctypes::W_OK | ctypes::X_OK) != 0
+            || flags & !(ctypes::AT_EACCESS | ctypes::AT_SYMLINK_NOFOLLOW) != 0
+        {
+            return Err(LinuxError::EINVAL);
+        }
+        let path = path?;
+        let no_follow = flags & ctypes::AT_SYMLINK_NOFOLLOW != 0;
+        let perm = with_dir_at(dirfd, path, |dir| attr_at(dir, path, no_follow))?.perm();
+        if (mode & ctypes::R_OK != 0 && !perm.owner_readable())
+            || (mode & ctypes::W_OK != 0 && !perm.owner_writable())
+            || (mode & ctypes::X_OK != 0 && !perm.owner_executable())
+        {
+            return Err(LinuxError::EACCES);
+        }
+        Ok(0)
+    })
+}
+
+/// Read the entries of the directory `fd` into `dirp` as `struct dirent`s,
+/// up to `count` bytes.
+///
+/// Each entry is followed by the position of the next entry in `d_off`,
+/// which can be passed to `lseek`. `d_ino` is the inode number of the entry,
+/// the same as `st_ino` returned by `lstat`.
+///
+/// Return the number of bytes read, or 0 at the end of the directory.
+pub unsafe fn sys_getdents64(fd: c_int, dirp: *mut c_void, count: usize) -> ctypes::ssize_t {
+    debug!("sys_getdents64 <= {} {:#x} {}", fd, dirp as usize, count);
+    syscall_body!(sys_getdents64, {
+        if dirp.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let dir = Directory::from_fd(fd)?;
+        let mut dir = dir.inner.lock();
+        let buf = unsafe { core::slice::from_raw_parts_mut(dirp as *mut u8, count) };
+
+        let name_offset = offset_of!(ctypes::dirent, d_name);
+        let mut entry = [DirEntry::default()];
+        let mut len = 0;
+        loop {
+            let pos = dir.position();
+            if dir.read_dir(&mut entry)? == 0 {
+                break;
+            }
+            let name = entry[0].name_as_bytes();
+            let reclen = (name_offset + name.len() + 1).next_multiple_of(8);
+            if len + reclen > buf.len() {
+                // read it next time
+                dir.set_position(pos);
+                if len == 0 {
+                    return Err(LinuxError::EINVAL);
+                }
+                break;
+            }
+
+            // the entry may have been removed since it was read
+            let ino = core::str::from_utf8(name)
+                .ok()
+                .and_then(|name| dir.get_attr_at(name, true).ok())
+                .map_or(0, |attr| attr.ino());
+            let next_pos = pos as u64 + 1;
+            let rec = &mut buf[len..len + reclen];
+            rec.fill(0);
+            let mut put = |offset: usize, bytes: &[u8]| {
+                rec[offset..offset + bytes.len()].copy_from_slice(bytes);
+            };
+            put(offset_of!(ctypes::dirent, d_ino), &ino.to_ne_bytes());
+            put(offset_of!(ctypes::dirent, d_off), &next_pos.to_ne_bytes());
+            put(
+                offset_of!(ctypes::dirent, d_reclen),
+                &(reclen as u16).to_ne_bytes(),
+            );
+            put(
+                offset_of!(ctypes::dirent, d_type),
+                &[entry[0].entry_type() as u8],
+            );
+            put(name_offset, name);
+            len += reclen;
+        }
+        Ok(len)
+    })
+}
+
+/// Get the metadata of the file `path` relative to the directory `dirfd` and
+/// write into `buf`. It is also known as `newfstatat`.
+///
+/// The symbolic link is not followed if `flags` contains
+/// `AT_SYMLINK_NOFOLLOW`. If `path` is empty and `flags` contains
+/// `AT_EMPTY_PATH`, the metadata of `dirfd` itself is returned.
+///
+/// Return 0 if success.
+pub unsafe fn sys_fstatat(
+    dirfd: c_int,
+    path: *const c_char,
+    buf: *mut ctypes::stat,
+    flags: c_int,
+) -> c_int {
+    let path = char_ptr_to_str(path);
+    debug!(
+        "sys_fstatat <= {} {:?} {:#x} {:#x}",
+        dirfd, path, buf as usize, flags
+    );
+    syscall_body!(sys_fstatat, {
+        if buf.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let flags = flags as u32;
+        if flags & !(ctypes::AT_SYMLINK_NOFOLLOW | ctypes::AT_EMPTY_PATH) != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        let path = path?;
+        let st = if !path.is_empty() {
+            let no_follow = flags & ctypes::AT_SYMLINK_NOFOLLOW != 0;
+            attr_to_stat(&with_dir_at(dirfd, path, |dir| {
+                attr_at(dir, path, no_follow)
+            })?)
+        } else if flags & ctypes::AT_EMPTY_PATH == 0 {
+            return Err(LinuxError::ENOENT);
+        } else if dirfd == ctypes::AT_FDCWD {
+            attr_to_stat(&attr_at(None, ".", false)?)
+        } else {
+            get_file_like(dirfd)?.stat()?
+        };
+        unsafe { *buf = st };
+        Ok(0)
+    })
+}
+
+/// Change the permissions of the file `path` to `mode`.
+///
+/// Return 0 if success.
+pub fn sys_chmod(path: *const c_char, mode: ctypes::mode_t) -> c_int {
+    sys_fchmodat(ctypes::AT_FDCWD, path, mode, 0)
+}
+
+/// Change the permissions of the opened file `fd` to `mode`.
+///
+/// Return 0 if success.
+pub fn sys_fchmod(fd: c_int, mode: ctypes::mode_t) -> c_int {
+    debug!("sys_fchmod <= {} {:#o}", fd, mode);
+    syscall_body!(sys_fchmod, {
+        let attr = FileSetAttr {
+            mode: Some(FilePerm::from_bits_truncate(mode as u16)),
+            ..Default::default()
+        };
+        set_attr_fd(fd, &attr)?;
+        Ok(0)
+    })
+}
+
+/// Change the permissions of the file `path` relative to the directory
+/// `dirfd`, like [`sys_chmod`].
+///
+/// Symbolic links have no permissions of their own, so `AT_SYMLINK_NOFOLLOW`
+/// is not supported.
+pub fn sys_fchmodat(
+    dirfd: c_int,
+    path: *const c_char,
+    mode: ctypes::mode_t,
+    flags: c_int,
+) -> c_int {
+    let path = char_ptr_to_str(path);
+    debug!(
+        "sys_fchmodat <= {} {:?} {:#o} {:#x}",
+        dirfd, path, mode, flags
+    );
+    syscall_body!(sys_fchmodat, {
+        let flags = flags as u32;
+        if flags == ctypes::AT_SYMLINK_NOFOLLOW {
+            return Err(LinuxError::EOPNOTSUPP);
+        } else if flags != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        let path = path?;
+        let perm = FilePerm::from_bits_truncate(mode as u16);
+        with_dir_at(dirfd, path, |dir| {
+            match dir {
+                Some(dir) => {
+                    let attr = FileSetAttr {
+                        mode: Some(perm),
+                        ..Default::default()
+                    };
+                    dir.inner.lock().set_attr_at(path, &attr, false)?
+                }
+                None => axfs::api::set_permissions(path, perm)?,
+            }
+            Ok(())
+        })?;
+        Ok(0)
+    })
+}
+
+/// Change the owner and group of the file `path`. An id of `-1` is left
+/// unchanged.
+///
+/// Return 0 if success.
+pub fn sys_chown(path: *const c_char, uid: ctypes::uid_t, gid: ctypes::gid_t) -> c_int {
+    sys_fchownat(ctypes::AT_FDCWD, path, uid, gid, 0)
+}
+
+/// Change the owner and group of the file `path`, like [`sys_chown`], but do
+/// not follow it if it is a symbolic link.
+pub fn sys_lchown(path: *const c_char, uid: ctypes::uid_t, gid: ctypes::gid_t) -> c_int {
+    sys_fchownat(
+        ctypes::AT_FDCWD,
+        path,
+        uid,
+        gid,
+        ctypes::AT_SYMLINK_NOFOLLOW as _,
+    )
+}
+
+/// Change the owner and group of the opened file `fd`, like [`sys_chown`].
+pub fn sys_fchown(fd: c_int, uid: ctypes::uid_t, gid: ctypes::gid_t) -> c_int {
+    debug!("sys_fchown <= {} {} {}", fd, uid, gid);
+    syscall_body!(sys_fchown, {
+        let (uid, gid) = (id_to_set(uid), id_to_set(gid));
+        set_attr_fd(
+            fd,
+            &FileSetAttr {
+                uid,
+                gid,
+                ..Default::default()
+            },
+        )?;
+        Ok(0)
+    })
+}
+
+/// Change the owner and group of the file `path` relative to the directory
+/// `dirfd`, like [`sys_chown`].
+///
+/// `flags` can contain `AT_SYMLINK_NOFOLLOW`, and `AT_EMPTY_PATH` which
+/// changes `dirfd` itself if `path` is empty.
+pub fn sys_fchownat(
+    dirfd: c_int,
+    path: *const c_char,
+    uid: ctypes::uid_t,
+    gid: ctypes::gid_t,
+    flags: c_int,
+) -> c_int {
+    let path = char_ptr_to_str(path);
+    debug!(
+        "sys_fchownat <= {} {:?} {} {} {:#x}",
+        dirfd, path, uid, gid, flags
+    );
+    syscall_body!(sys_fchownat, {
+        let flags = flags as u32;
+        if flags & !(ctypes::AT_SYMLINK_NOFOLLOW | ctypes::AT_EMPTY_PATH) != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        let path = path?;
+        let (uid, gid) = (id_to_set(uid), id_to_set(gid));
+        let attr = FileSetAttr {
+            uid,
+            gid,
+            ..Default::default()
+        };
+        let no_follow = flags & ctypes::AT_SYMLINK_NOFOLLOW != 0;
+        if path.is_empty() {
+            if flags & ctypes::AT_EMPTY_PATH == 0 {
+                return Err(LinuxError::ENOENT);
+            } else if dirfd != ctypes::AT_FDCWD {
+                set_attr_fd(dirfd, &attr)?;
+                return Ok(0);
+            }
+        }
+        let path = if path.is_empty() { "." } else { path };
+        with_dir_at(dirfd, path, |dir| {
+            match dir {
+                Some(dir) => dir.inner.lock().set_attr_at(path, &attr, no_follow)?,
+                None if no_follow => axfs::api::set_symlink_owner(path, uid, gid)?,
+                None => axfs::api::set_owner(path, uid, gid)?,
+            }
+            Ok(())
+        })?;
+        Ok(0)
+    })
+}
+
+/// Converts a user or group ID argument of `chown`, where `-1` means
+/// unchanged.
+fn id_to_set(id: u32) -> Option<u32> {
+    (id != u32::MAX).then_some(id)
+}
+
+/// Converts a timestamp argument of `utimensat`, which may be `UTIME_NOW` or
+/// `UTIME_OMIT`.
+fn timespec_to_set(ts: &ctypes::timespec, now: Duration) -> LinuxResult<Option<Duration>> {
+    match ts.tv_nsec as u32 {
+        _ if ts.tv_nsec < 0 => Err(LinuxError::EINVAL),
+        ctypes::UTIME_NOW => Ok(Some(now)),
+        ctypes::UTIME_OMIT => Ok(None),
+        nsec if nsec < 1_000_000_000 && ts.tv_sec >= 0 => Ok(Some((*ts).into())),
+        _ => Err(LinuxError::EINVAL),
+    }
+}
+
+/// Change the last access and modification times of the file `path`
+/// relative to the directory `dirfd` to `times[0]` and `times[1]`.
+///
+/// A time with `tv_nsec` set to `UTIME_NOW` is set to the current time, and
+/// one set to `UTIME_OMIT` is left unchanged. Both are set to the current
+/// time if `times` is null. If `path` is null, the times of `dirfd` itself
+/// are changed. `flags` can contain `AT_SYMLINK_NOFOLLOW`.
+///
+/// Return 0 if success.
+pub unsafe fn sys_utimensat(
+    dirfd: c_int,
+    path: *const c_char,
+    times: *const ctypes::timespec,
+    flags: c_int,
+) -> c_int {
+    let path_str = if path.is_null() {
+        Ok("")
+    } else {
+        char_ptr_to_str(path)
+    };
+    debug!(
+        "sys_utimensat <= {} {:?} {:#x} {:#x}",
+        dirfd, path_str, times as usize, flags
+    );
+    syscall_body!(sys_utimensat, {
+        let flags = flags as u32;
+        if flags & !ctypes::AT_SYMLINK_NOFOLLOW != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        let now = axhal::time::current_time();
+        let (atime, mtime) = if times.is_null() {
+            (Some(now), Some(now))
+        } else {
+            let times = unsafe { core::slice::from_raw_parts(times, 2) };
+            (
+                timespec_to_set(&times[0], now)?,
+                timespec_to_set(&times[1], now)?,
+            )
+        };
+        let attr = FileSetAttr {
+            atime,
+            mtime,
+            ..Default::default()
+        };
+        if path.is_null() {
+            set_attr_fd(dirfd, &attr)?;
+            return Ok(0);
+        }
+        let path = path_str?;
+        let no_follow = flags & ctypes::AT_SYMLINK_NOFOLLOW != 0;
+        with_dir_at(dirfd, path, |dir| {
+            match dir {
+                Some(dir) => dir.inner.lock().set_attr_at(path, &attr, no_follow)?,
+                None if no_follow => axfs::api::set_symlink_times(path, atime, mtime)?,
+                None => axfs::api::set_times(path, atime, mtime)?,
+            }
+            Ok(())
+        })?;
+        Ok(0)
+    })
+}
+
+/// Change the last access and modification times of the opened file `fd`,
+/// like [`sys_utimensat`].
+pub unsafe fn sys_futimens(fd: c_int, times: *const ctypes::timespec) -> c_int {
+    unsafe { sys_utimensat(fd, core::ptr::null(), times, 0) }
+}
@@ -1,21 +1,114 @@
 //! `epoll` implementation.
 //!
-//! TODO: do not support `EPOLLET` flag
+//! Each registered file descriptor has an [`EpollInterest`], which watches the
+//! file through its [`PollSet`](crate::imp::fd_ops::PollSet), and is put into
+//! the ready list of the epoll instance when the file may have become ready.
+//! `epoll_wait` only checks the files in the ready list, and blocks until the
+//! list is not empty.
 
-use alloc::collections::btree_map::Entry;
-use alloc::collections::BTreeMap;
-use alloc::sync::Arc;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use core::{ffi::c_int, time::Duration};
 
 use axerrno::{LinuxError, LinuxResult};
-use axhal::time::current_time;
-use axsync::Mutex;
+use axhal::time::{current_time, TimeValue};
+use axsync::{spin::SpinNoIrq, Mutex};
 
 use crate::ctypes;
-use crate::imp::fd_ops::{add_file_like, get_file_like, FileLike};
+use crate::imp::fd_ops::{add_file_like, get_file_like, poll_files, FileLike, PollWatcher};
+
+/// The events that can be used together with `EPOLLEXCLUSIVE`.
+const EXCLUSIVE_EVENTS: u32 = ctypes::EPOLLIN
+    | ctypes::EPOLLOUT
+    | ctypes::EPOLLWAKEUP
+    | ctypes::EPOLLET
+    | ctypes::EPOLLEXCLUSIVE;
+
+/// A file descriptor registered in an epoll instance.
+struct EpollInterest {
+    file: Weak<dyn FileLike>,
+    event: Mutex<ctypes::epoll_event>,
+    exclusive: bool,
+    /// Whether the file is polled, see
+    /// [`PollSet::new_polled`](crate::imp::fd_ops::PollSet::new_polled).
+    polled: bool,
+    /// Whether it is in the ready list.
+    queued: AtomicBool,
+    /// Whether it has been removed, or disabled after an event with `EPOLLONESHOT`.
+    disabled: AtomicBool,
+    epoll: Weak<EpollInstance>,
+    this: Weak<EpollInterest>,
+}
+
+impl EpollInterest {
+    fn new(
+        file: &Arc<dyn FileLike>,
+        event: ctypes::epoll_event,
+        epoll: &Arc<EpollInstance>,
+    ) -> Arc<Self> {
+        Arc::new_cyclic(|this| Self {
+            file: Arc::downgrade(file),
+            event: Mutex::new(event),
+            exclusive: event.events & ctypes::EPOLLEXCLUSIVE != 0,
+            polled: file.poll_set().is_some_and(|set| set.is_polled()),
+            queued: AtomicBool::new(false),
+            disabled: AtomicBool::new(false),
+            epoll: Arc::downgrade(epoll),
+            this: this.clone(),
+        })
+    }
+
+    /// Returns the events of the file to report. Errors and hangups are
+    /// always reported.
+    fn poll(&self, file: &dyn FileLike, events: u32) -> u32 {
+        match file.poll() {
+            Ok(state) => {
+                let mut revents = 0;
+                if state.readable {
+                    revents |= ctypes::EPOLLIN;
+                }
+                if state.writable {
+                    revents |= ctypes::EPOLLOUT;
+                }
+                revents &= events;
+                if state.hangup {
+                    revents |= ctypes::EPOLLHUP;
+                }
+                revents
+            }
+            Err(_) => ctypes::EPOLLERR,
+        }
+    }
+}
+
+impl PollWatcher for EpollInterest {
+    fn exclusive(&self) -> bool {
+        self.exclusive
+    }
+
+    fn wake(&self) -> bool {
+        if self.disabled.load(Ordering::Acquire) {
+            return false;
+        }
+        match (self.epoll.upgrade(), self.this.upgrade()) {
+            (Some(epoll), Some(this)) => epoll.push_ready(this),
+            _ => false,
+        }
+    }
+}
 
 pub struct EpollInstance {
-    events: Mutex<BTreeMap<usize, ctypes::epoll_event>>,
+    interests: Mutex<BTreeMap<c_int, Arc<EpollInterest>>>,
+    /// Also locked when the interests are woken up in interrupt handlers.
+    ready_list: SpinNoIrq<VecDeque<Arc<EpollInterest>>>,
+    #[cfg(feature = "multitask")]
+    ready_wq: axtask::WaitQueue,
+    /// The number of tasks blocked in `epoll_wait`.
+    waiters: AtomicUsize,
+    /// The number of interests in polled files.
+    polled: AtomicUsize,
 }
 
 unsafe impl Send for ctypes::epoll_event {}
@@ -25,7 +118,12 @@ impl EpollInstance {
     // TODO: parse flags
     pub fn new(_flags: usize) -> Self {
         Self {
-            events: Mutex::new(BTreeMap::new()),
+            interests: Mutex::new(BTreeMap::new()),
+            ready_list: SpinNoIrq::new(VecDeque::new()),
+            #[cfg(feature = "multitask")]
+            ready_wq: axtask::WaitQueue::new(),
+            waiters: AtomicUsize::new(0),
+            polled: AtomicUsize::new(0),
         }
     }
 
@@ -36,72 +134,171 @@ impl EpollInstance {
             .map_err(|_| LinuxError::EINVAL)
     }
 
-    fn control(&self, op: usize, fd: usize, event: &ctypes::epoll_event) -> LinuxResult<usize> {
-        match get_file_like(fd as c_int) {
-            Ok(_) => {}
-            Err(e) => return Err(e),
+    /// Puts the interest into the ready list, and wakes up a waiting task.
+    ///
+    /// Returns `true` if there was a task waiting.
+    fn push_ready(&self, interest: Arc<EpollInterest>) -> bool {
+        if !interest.queued.swap(true, Ordering::AcqRel) {
+            self.ready_list.lock().push_back(interest);
         }
+        #[cfg(feature = "multitask")]
+        self.ready_wq.notify_one(true);
+        self.waiters.load(Ordering::Acquire) > 0
+    }
 
-        match op as u32 {
+    fn remove_interest(&self, interest: &EpollInterest) {
+        interest.disabled.store(true, Ordering::Release);
+        if interest.polled {
+            self.polled.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    fn control(
+        self: &Arc<Self>,
+        op: u32,
+        fd: c_int,
+        event: Option<ctypes::epoll_event>,
+    ) -> LinuxResult {
+        let file = get_file_like(fd)?;
+        let mut interests = self.interests.lock();
+        match op {
             ctypes::EPOLL_CTL_ADD => {
-                if let Entry::Vacant(e) = self.events.lock().entry(fd) {
-                    e.insert(*event);
-                } else {
-                    return Err(LinuxError::EEXIST);
+                let event = event.ok_or(LinuxError::EFAULT)?;
+                let events = event.events;
+                if events & ctypes::EPOLLEXCLUSIVE != 0 && events & !EXCLUSIVE_EVENTS != 0 {
+                    return Err(LinuxError::EINVAL);
                 }
+                if let Some(old) = interests.get(&fd) {
+                    if old.file.strong_count() > 0 {
+                        return Err(LinuxError::EEXIST);
+                    }
+                    // The file has been closed, and `fd` is reused.
+                    self.remove_interest(old);
+                }
+
+                let interest = EpollInterest::new(&file, event, self);
+                if let Some(poll_set) = file.poll_set() {
+                    let watcher = Arc::downgrade(&interest);
+                    poll_set.register(watcher);
+                }
+                if interest.polled {
+                    self.polled.fetch_add(1, Ordering::Relaxed);
+                }
+                interests.insert(fd, interest.clone());
+                drop(interests);
+                // Check whether it is ready now.
+                self.push_ready(interest);
             }
             ctypes::EPOLL_CTL_MOD => {
-                let mut events = self.events.lock();
-                if let Entry::Occupied(mut ocp) = events.entry(fd) {
-                    ocp.insert(*event);
-                } else {
-                    return Err(LinuxError::ENOENT);
+                let event = event.ok_or(LinuxError::EFAULT)?;
+                let interest = interests.get(&fd).cloned().ok_or(LinuxError::ENOENT)?;
+                if interest.exclusive || event.events & ctypes::EPOLLEXCLUSIVE != 0 {
+                    return Err(LinuxError::EINVAL);
                 }
+                drop(interests);
+                *interest.event.lock() = event;
+                // Re-arm it if disabled by `EPOLLONESHOT`.
+                interest.disabled.store(false, Ordering::Release);
+                self.push_ready(interest);
             }
             ctypes::EPOLL_CTL_DEL => {
-                let mut events = self.events.lock();
-                if let Entry::Occupied(ocp) = events.entry(fd) {
-                    ocp.remove_entry();
-                } else {
-                    return Err(LinuxError::ENOENT);
-                }
-            }
-            _ => {
-                return Err(LinuxError::EINVAL);
+                let interest = interests.remove(&fd).ok_or(LinuxError::ENOENT)?;
+                self.remove_interest(&interest);
             }
+            _ => return Err(LinuxError::EINVAL),
         }
-        Ok(0)
+        Ok(())
     }
 
-    fn poll_all(&self, events: &mut [ctypes::epoll_event]) -> LinuxResult<usize> {
-        let ready_list = self.events.lock();
+    /// Checks the interests in the ready list, and fills `events` with the
+    /// events to report.
+    ///
+    /// Level-triggered interests stay in the ready list after an event is
+    /// reported, so that they are checked again in the next call. The others
+    /// leave the list until the file wakes them up again.
+    fn poll_ready(&self, events: &mut [ctypes::epoll_event]) -> usize {
+        let mut ready_list = core::mem::take(&mut *self.ready_list.lock());
+        let mut level_triggered = Vec::new();
         let mut events_num = 0;
+        while events_num < events.len() {
+            let Some(interest) = ready_list.pop_front() else {
+                break;
+            };
+            interest.queued.store(false, Ordering::Release);
+            if interest.disabled.load(Ordering::Acquire) {
+                continue;
+            }
+            let Some(file) = interest.file.upgrade() else {
+                continue;
+            };
 
-        for (infd, ev) in ready_list.iter() {
-            match get_file_like(*infd as c_int)?.poll() {
-                Err(_) => {
-                    if (ev.events & ctypes::EPOLLERR) != 0 {
-                        events[events_num].events = ctypes::EPOLLERR;
-                        events[events_num].data = ev.data;
-                        events_num += 1;
-                    }
+            let event = *interest.event.lock();
+            let revents = interest.poll(file.as_ref(), event.events);
+            if revents == 0 {
+                continue;
+            }
+            events[events_num] = ctypes::epoll_event {
+                events: revents,
+                data: event.data,
+            };
+            events_num += 1;
+
+            if event.events & ctypes::EPOLLONESHOT != 0 {
+                interest.disabled.store(true, Ordering::Release);
+            } else if event.events & ctypes::EPOLLET == 0 {
+                level_triggered.push(interest);
+            }
+        }
+
+        // The unchecked ones are still marked as queued.
+        let mut list = self.ready_list.lock();
+        for interest in ready_list.into_iter().rev() {
+            list.push_front(interest);
+        }
+        for interest in level_triggered {
+            if !interest.queued.swap(true, Ordering::AcqRel) {
+                list.push_back(interest);
+            }
+        }
+        events_num
+    }
+
+    fn wait(&self, events: &mut [ctypes::epoll_event], deadline: Option<TimeValue>) -> usize {
+        loop {
+            poll_files();
+            let events_num = self.poll_ready(events);
+            if events_num > 0 {
+                // Let another waiting task handle the remaining events.
+                #[cfg(feature = "multitask")]
+                if !self.ready_list.lock().is_empty() {
+                    self.ready_wq.notify_one(true);
                 }
-                Ok(state) => {
-                    if state.readable && (ev.events & ctypes::EPOLLIN != 0) {
-                        events[events_num].events = ctypes::EPOLLIN;
-                        events[events_num].data = ev.data;
-                        events_num += 1;
-                    }
+                return events_num;
+            }
 
-                    if state.writable && (ev.events & ctypes::EPOLLOUT != 0) {
-                        events[events_num].events = ctypes::EPOLLOUT;
-                        events[events_num].data = ev.data;
-                        events_num += 1;
-                    }
+            let now = current_time();
+            if deadline.is_some_and(|ddl| now >= ddl) {
+                debug!("    timeout!");
+                return 0;
+            }
+
+            #[cfg(feature = "multitask")]
+            {
+                use crate::imp::fd_ops::{wait_until, POLL_INTERVAL};
+                let mut wake_time = deadline;
+                if self.polled.load(Ordering::Relaxed) > 0 {
+                    let next_poll = now + POLL_INTERVAL;
+                    wake_time = Some(wake_time.map_or(next_poll, |t| t.min(next_poll)));
                 }
+                self.waiters.fetch_add(1, Ordering::AcqRel);
+                wait_until(&self.ready_wq, wake_time, || {
+                    !self.ready_list.lock().is_empty()
+                });
+                self.waiters.fetch_sub(1, Ordering::AcqRel);
             }
+            #[cfg(not(feature = "multitask"))]
+            crate::sys_sched_yield();
         }
-        Ok(events_num)
     }
 }
 
@@ -152,6 +349,8 @@ pub fn sys_epoll_create(size: c_int) -> c_int {
 }
 
 /// Control interface for an epoll file descriptor
+///
+/// `EPOLLET`, `EPOLLONESHOT` and `EPOLLEXCLUSIVE` are supported.
 pub unsafe fn sys_epoll_ctl(
     epfd: c_int,
     op: c_int,
@@ -160,10 +359,12 @@ pub unsafe fn sys_epoll_ctl(
 ) -> c_int {
     debug!("sys_epoll_ctl <= epfd: {} op: {} fd: {}", epfd, op, fd);
     syscall_body!(sys_epoll_ctl, {
-        let ret = unsafe {
-            EpollInstance::from_fd(epfd)?.control(op as usize, fd as usize, &(*event))? as c_int
-        };
-        Ok(ret)
+        if fd == epfd {
+            return Err(LinuxError::EINVAL);
+        }
+        let event = unsafe { event.as_ref().copied() };
+        EpollInstance::from_fd(epfd)?.control(op as u32, fd, event)?;
+        Ok(0)
     })
 }
 
@@ -187,19 +388,6 @@ pub unsafe fn sys_epoll_wait(
         let deadline = (!timeout.is_negative())
             .then(|| current_time() + Duration::from_millis(timeout as u64));
         let epoll_instance = EpollInstance::from_fd(epfd)?;
-        loop {
-            #[cfg(feature = "net")]
-            axnet::poll_interfaces();
-            let events_num = epoll_instance.poll_all(events)?;
-            if events_num > 0 {
-                return Ok(events_num as c_int);
-            }
-
-            if deadline.map_or(false, |ddl| current_time() >= ddl) {
-                debug!("    timeout!");
-                return Ok(0);
-            }
-            crate::sys_sched_yield();
-        }
+        Ok(epoll_instance.wait(events, deadline) as c_int)
     })
 }
@@ -1,16 +1,129 @@
 //! I/O multiplexing:
 //!
 //! * [`select`](select::sys_select)
+//! * [`poll`](poll::sys_poll)
+//! * [`ppoll`](poll::sys_ppoll)
 //! * [`epoll_create`](epoll::sys_epoll_create)
 //! * [`epoll_ctl`](epoll::sys_epoll_ctl)
 //! * [`epoll_wait`](epoll::sys_epoll_wait)
 
 #[cfg(feature = "epoll")]
 mod epoll;
+#[cfg(feature = "poll")]
+mod poll;
 #[cfg(feature = "select")]
 mod select;
 
 #[cfg(feature = "epoll")]
 pub use self::epoll::{sys_epoll_create, sys_epoll_ctl, sys_epoll_wait};
+#[cfg(feature = "poll")]
+pub use self::poll::{sys_poll, sys_ppoll};
 #[cfg(feature = "select")]
 pub use self::select::sys_select;
+
+#[cfg(any(feature = "select", feature = "poll"))]
+use {
+    super::fd_ops::{poll_files, FileLike, PollWatcher},
+    alloc::sync::Arc,
+    axerrno::LinuxResult,
+    axhal::time::{current_time, TimeValue},
+    core::sync::atomic::{AtomicBool, Ordering},
+};
+
+#[cfg(all(any(feature = "select", feature = "poll"), feature = "multitask"))]
+use super::fd_ops::{wait_until, POLL_INTERVAL};
+
+/// Watches the files of a `select` or `poll` call, and wakes up the calling
+/// task when one of them may have become ready.
+#[cfg(any(feature = "select", feature = "poll"))]
+struct FileWaiter {
+    woken: AtomicBool,
+    #[cfg(feature = "multitask")]
+    wq: axtask::WaitQueue,
+}
+
+#[cfg(any(feature = "select", feature = "poll"))]
+impl FileWaiter {
+    /// Creates a waiter, and registers it in the poll sets of `files`.
+    fn register(files: &[Arc<dyn FileLike>]) -> Arc<Self> {
+        let waiter = Arc::new(Self {
+            woken: AtomicBool::new(false),
+            #[cfg(feature = "multitask")]
+            wq: axtask::WaitQueue::new(),
+        });
+        for file in files {
+            if let Some(poll_set) = file.poll_set() {
+                let watcher = Arc::downgrade(&waiter);
+                poll_set.register(watcher);
+            }
+        }
+        waiter
+    }
+}
+
+#[cfg(any(feature = "select", feature = "poll"))]
+impl PollWatcher for FileWaiter {
+    fn exclusive(&self) -> bool {
+        false
+    }
+
+    fn wake(&self) -> bool {
+        self.woken.store(true, Ordering::Release);
+        #[cfg(feature = "multitask")]
+        self.wq.notify_one(true);
+        true
+    }
+}
+
+/// Calls `poll_all` repeatedly until it reports some ready files, or the
+/// `deadline` is reached (returns 0).
+///
+/// Between two calls, the current task is blocked until one of the `files`
+/// may have become ready (see [`PollSet`](super::fd_ops::PollSet)), or the
+/// deadline. If some of the files can only be polled, it also wakes up every
+/// [`POLL_INTERVAL`](super::fd_ops::POLL_INTERVAL).
+#[cfg(any(feature = "select", feature = "poll"))]
+fn wait_ready<F>(
+    files: &[Arc<dyn FileLike>],
+    deadline: Option<TimeValue>,
+    mut poll_all: F,
+) -> LinuxResult<usize>
+where
+    F: FnMut() -> LinuxResult<usize>,
+{
+    let waiter = FileWaiter::register(files);
+    #[cfg(feature = "multitask")]
+    let polled = files
+        .iter()
+        .any(|f| f.poll_set().is_some_and(|set| set.is_polled()));
+    loop {
+        poll_files();
+        // Clear the flag before polling, so that the wakeups happening in
+        // between are not missed.
+        waiter.woken.store(false, Ordering::Release);
+        let res = poll_all()?;
+        if res > 0 {
+            return Ok(res);
+        }
+
+        let now = current_time();
+        if deadline.is_some_and(|ddl| now >= ddl) {
+            debug!("    timeout!");
+            return Ok(0);
+        }
+
+        #[cfg(feature = "multitask")]
+        {
+            let mut wake_time = deadline;
+            if polled {
+                let next_poll = now + POLL_INTERVAL;
+                wake_time = Some(wake_time.map_or(next_poll, |t| t.min(next_poll)));
+            }
+            wait_until(&waiter.wq, wake_time, || {
+                waiter.woken.load(Ordering::Acquire)
+            });
+        }
+        #[cfg(not(feature = "multitask"))]
+        crate::sys_sched_yield();
+    }
+}
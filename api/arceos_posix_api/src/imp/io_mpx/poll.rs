@@ -0,0 +1,110 @@
+use alloc::vec::Vec;
+use core::ffi::{c_int, c_short};
+use core::time::Duration;
+
+use axerrno::{LinuxError, LinuxResult};
+use axhal::time::{current_time, TimeValue};
+
+use crate::ctypes;
+use crate::imp::fd_ops::{get_file_like, AX_FILE_LIMIT};
+
+fn poll_all(fds: &mut [ctypes::pollfd]) -> LinuxResult<usize> {
+    let mut res_num = 0;
+    for pfd in fds.iter_mut() {
+        pfd.revents = 0;
+        if pfd.fd < 0 {
+            continue;
+        }
+        let events = pfd.events as u32;
+        match get_file_like(pfd.fd).map(|f| f.poll()) {
+            Ok(Ok(state)) => {
+                if state.readable && events & ctypes::POLLIN != 0 {
+                    pfd.revents |= ctypes::POLLIN as c_short;
+                }
+                if state.writable && events & ctypes::POLLOUT != 0 {
+                    pfd.revents |= ctypes::POLLOUT as c_short;
+                }
+                if state.hangup {
+                    pfd.revents |= ctypes::POLLHUP as c_short;
+                }
+            }
+            Ok(Err(e)) => {
+                debug!("    error: {} {:?}", pfd.fd, e);
+                pfd.revents = ctypes::POLLERR as c_short;
+            }
+            Err(_) => pfd.revents = ctypes::POLLNVAL as c_short,
+        }
+        if pfd.revents != 0 {
+            res_num += 1;
+        }
+    }
+    Ok(res_num)
+}
+
+fn do_poll(
+    fds: *mut ctypes::pollfd,
+    nfds: ctypes::nfds_t,
+    deadline: Option<TimeValue>,
+) -> LinuxResult<usize> {
+    if nfds as usize > AX_FILE_LIMIT {
+        return Err(LinuxError::EINVAL);
+    }
+    if nfds == 0 {
+        return super::wait_ready(&[], deadline, || Ok(0));
+    }
+    if fds.is_null() {
+        return Err(LinuxError::EFAULT);
+    }
+    let fds = unsafe { core::slice::from_raw_parts_mut(fds, nfds as usize) };
+    let files: Vec<_> = fds
+        .iter()
+        .filter(|pfd| pfd.fd >= 0)
+        .filter_map(|pfd| get_file_like(pfd.fd).ok())
+        .collect();
+    super::wait_ready(&files, deadline, || poll_all(fds))
+}
+
+/// Wait for some event on the file descriptors in `fds`.
+///
+/// `timeout` is in milliseconds, a negative value means an infinite timeout.
+/// Return the number of file descriptors with events or errors reported in
+/// their `revents` fields, or 0 if timed out.
+pub unsafe fn sys_poll(fds: *mut ctypes::pollfd, nfds: ctypes::nfds_t, timeout: c_int) -> c_int {
+    debug!(
+        "sys_poll <= fds: {:#x}, nfds: {}, timeout: {}",
+        fds as usize, nfds, timeout
+    );
+    syscall_body!(sys_poll, {
+        let deadline = (!timeout.is_negative())
+            .then(|| current_time() + Duration::from_millis(timeout as u64));
+        do_poll(fds, nfds, deadline)
+    })
+}
+
+/// Like [`sys_poll`], but the timeout is given by a `timespec`, and a null
+/// `timeout` means an infinite timeout.
+///
+/// TODO: `sigmask` is ignored, as signals are not supported yet.
+pub unsafe fn sys_ppoll(
+    fds: *mut ctypes::pollfd,
+    nfds: ctypes::nfds_t,
+    timeout: *const ctypes::timespec,
+    _sigmask: *const ctypes::sigset_t,
+) -> c_int {
+    debug!(
+        "sys_ppoll <= fds: {:#x}, nfds: {}, timeout: {:#x}",
+        fds as usize, nfds, timeout as usize
+    );
+    syscall_body!(sys_ppoll, {
+        let deadline = match unsafe { timeout.as_ref() } {
+            Some(ts) => {
+                if ts.tv_sec < 0 || !(0..1_000_000_000).contains(&ts.tv_nsec) {
+                    return Err(LinuxError::EINVAL);
+                }
+                Some(current_time() + (*ts).into())
+            }
+            None => None,
+        };
+        do_poll(fds, nfds, deadline)
+    })
+}
@@ -1,9 +1,11 @@
+use alloc::{sync::Arc, vec::Vec};
 use core::ffi::c_int;
 
 use axerrno::{LinuxError, LinuxResult};
 use axhal::time::current_time;
 
-use crate::{ctypes, imp::fd_ops::get_file_like};
+use crate::ctypes;
+use crate::imp::fd_ops::{get_file_like, FileLike};
 
 const FD_SETSIZE: usize = 1024;
 const BITS_PER_USIZE: usize = usize::BITS as usize;
@@ -46,6 +48,17 @@ impl FdSets {
         Self { nfds, bits }
     }
 
+    /// Returns the files in any of the sets.
+    fn files(&self) -> Vec<Arc<dyn FileLike>> {
+        (0..self.nfds)
+            .filter(|&fd| {
+                let (i, bit) = (fd / BITS_PER_USIZE, 1 << (fd % BITS_PER_USIZE));
+                (0..3).any(|set| self.bits[set * FD_SETSIZE_USIZES + i] & bit != 0)
+            })
+            .filter_map(|fd| get_file_like(fd as _).ok())
+            .collect()
+    }
+
     fn poll_all(
         &self,
         res_read_fds: *mut ctypes::fd_set,
@@ -133,20 +146,9 @@ pub unsafe fn sys_select(
             zero_fd_set(exceptfds, nfds);
         }
 
-        loop {
-            #[cfg(feature = "net")]
-            axnet::poll_interfaces();
-            let res = fd_sets.poll_all(readfds, writefds, exceptfds)?;
-            if res > 0 {
-                return Ok(res);
-            }
-
-            if deadline.map_or(false, |ddl| current_time() >= ddl) {
-                debug!("    timeout!");
-                return Ok(0);
-            }
-            crate::sys_sched_yield();
-        }
+        super::wait_ready(&fd_sets.files(), deadline, || {
+            fd_sets.poll_all(readfds, writefds, exceptfds)
+        })
     })
 }
 
@@ -2,15 +2,18 @@ mod stdio;
 
 pub mod io;
 pub mod resources;
+pub mod signal;
 pub mod sys;
 pub mod task;
 pub mod time;
 
+#[cfg(feature = "fd")]
+pub mod eventfd;
 #[cfg(feature = "fd")]
 pub mod fd_ops;
 #[cfg(feature = "fs")]
 pub mod fs;
-#[cfg(any(feature = "select", feature = "epoll"))]
+#[cfg(any(feature = "select", feature = "poll", feature = "epoll"))]
 pub mod io_mpx;
 #[cfg(feature = "net")]
 pub mod net;
@@ -18,3 +21,7 @@ pub mod net;
 pub mod pipe;
 #[cfg(feature = "multitask")]
 pub mod pthread;
+#[cfg(feature = "fd")]
+pub mod signalfd;
+#[cfg(feature = "fd")]
+pub mod timerfd;
@@ -1,25 +1,83 @@
-use alloc::{sync::Arc, vec, vec::Vec};
+use alloc::sync::{Arc, Weak};
+use alloc::{vec, vec::Vec};
 use core::ffi::{c_char, c_int, c_void};
 use core::mem::size_of;
 use core::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
+use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
 
 use axerrno::{LinuxError, LinuxResult};
 use axio::PollState;
 use axnet::{TcpSocket, UdpSocket};
 use axsync::Mutex;
 
-use super::fd_ops::FileLike;
+use super::fd_ops::{FileLike, PollSet};
 use crate::ctypes;
 use crate::utils::char_ptr_to_str;
 
-pub enum Socket {
+/// The sockets whose readiness is checked by [`poll_network`].
+static SOCKETS: Mutex<Vec<Weak<Socket>>> = Mutex::new(Vec::new());
+
+/// Polls the network stack, and wakes up the watchers of the sockets that
+/// may have become ready.
+pub fn poll_network() {
+    axnet::poll_interfaces();
+    wake_sockets();
+}
+
+/// Wakes up the watchers of the sockets that may have become ready, if some
+/// packets have been processed since the last call, either by
+/// [`poll_network`] or by the polls inside socket operations.
+fn wake_sockets() {
+    static LAST_EVENTS: AtomicUsize = AtomicUsize::new(0);
+    let events = axnet::poll_events();
+    if LAST_EVENTS.swap(events, Ordering::AcqRel) == events {
+        return;
+    }
+    // Do not hold the lock while waking up, as it may reschedule.
+    let sockets: Vec<_> = {
+        let mut sockets = SOCKETS.lock();
+        sockets.retain(|s| s.strong_count() > 0);
+        sockets.iter().filter_map(Weak::upgrade).collect()
+    };
+    for socket in sockets {
+        socket.check_ready();
+    }
+}
+
+// The bits of the readiness of a socket, see `Socket::check_ready`.
+const READY_READ: u8 = 1 << 0;
+const READY_WRITE: u8 = 1 << 1;
+const READY_HANGUP: u8 = 1 << 2;
+const READY_ERROR: u8 = 1 << 3;
+/// The readiness has not been checked yet.
+const READY_UNKNOWN: u8 = u8::MAX;
+
+enum SocketInner {
     Udp(Mutex<UdpSocket>),
     Tcp(Mutex<TcpSocket>),
 }
 
+pub struct Socket {
+    inner: SocketInner,
+    /// The watchers of the socket, woken up by [`poll_network`].
+    poll_set: PollSet,
+    /// The readiness seen by the last [`Socket::check_ready`].
+    last_ready: AtomicU8,
+}
+
 impl Socket {
+    fn new(inner: SocketInner) -> Self {
+        Self {
+            inner,
+            poll_set: PollSet::new_polled(),
+            last_ready: AtomicU8::new(READY_UNKNOWN),
+        }
+    }
+
     fn add_to_fd_table(self) -> LinuxResult<c_int> {
-        super::fd_ops::add_file_like(Arc::new(self))
+        let socket = Arc::new(self);
+        SOCKETS.lock().push(Arc::downgrade(&socket));
+        super::fd_ops::add_file_like(socket)
     }
 
     fn from_fd(fd: c_int) -> LinuxResult<Arc<Self>> {
@@ -29,98 +87,142 @@ impl Socket {
             .map_err(|_| LinuxError::EINVAL)
     }
 
+    // The operations that may poll the network stack wake up the watchers of
+    // the sockets at the end, with the socket unlocked.
+
     fn send(&self, buf: &[u8]) -> LinuxResult<usize> {
-        match self {
-            Socket::Udp(udpsocket) => Ok(udpsocket.lock().send(buf)?),
-            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().send(buf)?),
-        }
+        let res = match &self.inner {
+            SocketInner::Udp(udpsocket) => udpsocket.lock().send(buf),
+            SocketInner::Tcp(tcpsocket) => tcpsocket.lock().send(buf),
+        };
+        wake_sockets();
+        Ok(res?)
     }
 
     fn recv(&self, buf: &mut [u8]) -> LinuxResult<usize> {
-        match self {
-            Socket::Udp(udpsocket) => Ok(udpsocket.lock().recv_from(buf).map(|e| e.0)?),
-            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().recv(buf)?),
-        }
+        let res = match &self.inner {
+            SocketInner::Udp(udpsocket) => udpsocket.lock().recv_from(buf).map(|e| e.0),
+            SocketInner::Tcp(tcpsocket) => tcpsocket.lock().recv(buf),
+        };
+        wake_sockets();
+        Ok(res?)
     }
 
     pub fn poll(&self) -> LinuxResult<PollState> {
-        match self {
-            Socket::Udp(udpsocket) => Ok(udpsocket.lock().poll()?),
-            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().poll()?),
+        match &self.inner {
+            SocketInner::Udp(udpsocket) => Ok(udpsocket.lock().poll()?),
+            SocketInner::Tcp(tcpsocket) => Ok(tcpsocket.lock().poll()?),
         }
     }
 
     fn local_addr(&self) -> LinuxResult<SocketAddr> {
-        match self {
-            Socket::Udp(udpsocket) => Ok(udpsocket.lock().local_addr()?),
-            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().local_addr()?),
+        match &self.inner {
+            SocketInner::Udp(udpsocket) => Ok(udpsocket.lock().local_addr()?),
+            SocketInner::Tcp(tcpsocket) => Ok(tcpsocket.lock().local_addr()?),
         }
     }
 
     fn peer_addr(&self) -> LinuxResult<SocketAddr> {
-        match self {
-            Socket::Udp(udpsocket) => Ok(udpsocket.lock().peer_addr()?),
-            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().peer_addr()?),
+        match &self.inner {
+            SocketInner::Udp(udpsocket) => Ok(udpsocket.lock().peer_addr()?),
+            SocketInner::Tcp(tcpsocket) => Ok(tcpsocket.lock().peer_addr()?),
         }
     }
 
     fn bind(&self, addr: SocketAddr) -> LinuxResult {
-        match self {
-            Socket::Udp(udpsocket) => Ok(udpsocket.lock().bind(addr)?),
-            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().bind(addr)?),
+        match &self.inner {
+            SocketInner::Udp(udpsocket) => Ok(udpsocket.lock().bind(addr)?),
+            SocketInner::Tcp(tcpsocket) => Ok(tcpsocket.lock().bind(addr)?),
         }
     }
 
     fn connect(&self, addr: SocketAddr) -> LinuxResult {
-        match self {
-            Socket::Udp(udpsocket) => Ok(udpsocket.lock().connect(addr)?),
-            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().connect(addr)?),
-        }
+        let res = match &self.inner {
+            SocketInner::Udp(udpsocket) => udpsocket.lock().connect(addr),
+            SocketInner::Tcp(tcpsocket) => tcpsocket.lock().connect(addr),
+        };
+        wake_sockets();
+        Ok(res?)
     }
 
     fn sendto(&self, buf: &[u8], addr: SocketAddr) -> LinuxResult<usize> {
-        match self {
+        let res = match &self.inner {
             // diff: must bind before sendto
-            Socket::Udp(udpsocket) => Ok(udpsocket.lock().send_to(buf, addr)?),
-            Socket::Tcp(_) => Err(LinuxError::EISCONN),
-        }
+            SocketInner::Udp(udpsocket) => udpsocket.lock().send_to(buf, addr),
+            SocketInner::Tcp(_) => return Err(LinuxError::EISCONN),
+        };
+        wake_sockets();
+        Ok(res?)
     }
 
     fn recvfrom(&self, buf: &mut [u8]) -> LinuxResult<(usize, Option<SocketAddr>)> {
-        match self {
+        let res = match &self.inner {
             // diff: must bind before recvfrom
-            Socket::Udp(udpsocket) => Ok(udpsocket
+            SocketInner::Udp(udpsocket) => udpsocket
                 .lock()
                 .recv_from(buf)
-                .map(|res| (res.0, Some(res.1)))?),
-            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().recv(buf).map(|res| (res, None))?),
-        }
+                .map(|res| (res.0, Some(res.1))),
+            SocketInner::Tcp(tcpsocket) => tcpsocket.lock().recv(buf).map(|res| (res, None)),
+        };
+        wake_sockets();
+        Ok(res?)
     }
 
     fn listen(&self) -> LinuxResult {
-        match self {
-            Socket::Udp(_) => Err(LinuxError::EOPNOTSUPP),
-            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().listen()?),
+        match &self.inner {
+            SocketInner::Udp(_) => Err(LinuxError::EOPNOTSUPP),
+            SocketInner::Tcp(tcpsocket) => Ok(tcpsocket.lock().listen()?),
         }
     }
 
     fn accept(&self) -> LinuxResult<TcpSocket> {
-        match self {
-            Socket::Udp(_) => Err(LinuxError::EOPNOTSUPP),
-            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().accept()?),
+        let res = match &self.inner {
+            SocketInner::Udp(_) => return Err(LinuxError::EOPNOTSUPP),
+            SocketInner::Tcp(tcpsocket) => tcpsocket.lock().accept(),
+        };
+        wake_sockets();
+        Ok(res?)
+    }
+
+    /// Wakes up the watchers if the socket has become ready or its readiness
+    /// has changed since the last check. A readable socket is always
+    /// considered changed, as some new data may have arrived.
+    fn check_ready(&self) {
+        if !self.poll_set.has_watchers() {
+            return;
+        }
+        let ready = match self.poll() {
+            Ok(state) => {
+                let mut ready = 0;
+                if state.readable {
+                    ready |= READY_READ;
+                }
+                if state.writable {
+                    ready |= READY_WRITE;
+                }
+                if state.hangup {
+                    ready |= READY_HANGUP;
+                }
+                ready
+            }
+            Err(_) => READY_ERROR,
+        };
+        let last_ready = self.last_ready.swap(ready, Ordering::AcqRel);
+        if ready != last_ready || ready & READY_READ != 0 {
+            self.poll_set.wake();
         }
     }
 
     fn shutdown(&self) -> LinuxResult {
-        match self {
-            Socket::Udp(udpsocket) => {
+        match &self.inner {
+            SocketInner::Udp(udpsocket) => {
                 let udpsocket = udpsocket.lock();
                 udpsocket.peer_addr()?;
                 udpsocket.shutdown()?;
                 Ok(())
             }
 
-            Socket::Tcp(tcpsocket) => {
+            SocketInner::Tcp(tcpsocket) => {
                 let tcpsocket = tcpsocket.lock();
                 tcpsocket.peer_addr()?;
                 tcpsocket.shutdown()?;
@@ -162,12 +264,16 @@ impl FileLike for Socket {
     }
 
     fn set_nonblocking(&self, nonblock: bool) -> LinuxResult {
-        match self {
-            Socket::Udp(udpsocket) => udpsocket.lock().set_nonblocking(nonblock),
-            Socket::Tcp(tcpsocket) => tcpsocket.lock().set_nonblocking(nonblock),
+        match &self.inner {
+            SocketInner::Udp(udpsocket) => udpsocket.lock().set_nonblocking(nonblock),
+            SocketInner::Tcp(tcpsocket) => tcpsocket.lock().set_nonblocking(nonblock),
         }
         Ok(())
     }
+
+    fn poll_set(&self) -> Option<&PollSet> {
+        Some(&self.poll_set)
+    }
 }
 
 impl From<SocketAddrV4> for ctypes::sockaddr_in {
@@ -236,11 +342,11 @@ pub fn sys_socket(domain: c_int, socktype: c_int, protocol: c_int) -> c_int {
         match (domain, socktype, protocol) {
             (ctypes::AF_INET, ctypes::SOCK_STREAM, ctypes::IPPROTO_TCP)
             | (ctypes::AF_INET, ctypes::SOCK_STREAM, 0) => {
-                Socket::Tcp(Mutex::new(TcpSocket::new())).add_to_fd_table()
+                Socket::new(SocketInner::Tcp(Mutex::new(TcpSocket::new()))).add_to_fd_table()
             }
             (ctypes::AF_INET, ctypes::SOCK_DGRAM, ctypes::IPPROTO_UDP)
             | (ctypes::AF_INET, ctypes::SOCK_DGRAM, 0) => {
-                Socket::Udp(Mutex::new(UdpSocket::new())).add_to_fd_table()
+                Socket::new(SocketInner::Udp(Mutex::new(UdpSocket::new()))).add_to_fd_table()
             }
             _ => Err(LinuxError::EINVAL),
         }
@@ -419,7 +525,7 @@ pub unsafe fn sys_accept(
         let socket = Socket::from_fd(socket_fd)?;
         let new_socket = socket.accept()?;
         let addr = new_socket.peer_addr()?;
-        let new_fd = Socket::add_to_fd_table(Socket::Tcp(Mutex::new(new_socket)))?;
+        let new_fd = Socket::new(SocketInner::Tcp(Mutex::new(new_socket))).add_to_fd_table()?;
         unsafe {
             (*socket_addr, *socket_len) = into_sockaddr(addr);
         }
@@ -3,9 +3,9 @@ use core::ffi::c_int;
 
 use axerrno::{LinuxError, LinuxResult};
 use axio::PollState;
-use axsync::Mutex;
+use axsync::{Mutex, MutexGuard};
 
-use super::fd_ops::{add_file_like, close_file_like, FileLike};
+use super::fd_ops::{add_file_like, close_file_like, FileLike, PollSet};
 use crate::ctypes;
 
 #[derive(Copy, Clone, PartialEq)]
@@ -22,6 +22,7 @@ pub struct PipeRingBuffer {
     head: usize,
     tail: usize,
     status: RingBufferStatus,
+    write_end_closed: bool,
 }
 
 impl PipeRingBuffer {
@@ -31,6 +32,7 @@ impl PipeRingBuffer {
             head: 0,
             tail: 0,
             status: RingBufferStatus::Empty,
+            write_end_closed: false,
         }
     }
 
@@ -74,21 +76,34 @@ impl PipeRingBuffer {
     }
 }
 
+/// The state shared by both ends of a pipe.
+struct PipeInner {
+    buffer: Mutex<PipeRingBuffer>,
+    poll_set: PollSet,
+    #[cfg(feature = "multitask")]
+    buffer_cv: axsync::Condvar,
+}
+
 pub struct Pipe {
     readable: bool,
-    buffer: Arc<Mutex<PipeRingBuffer>>,
+    inner: Arc<PipeInner>,
 }
 
 impl Pipe {
     pub fn new() -> (Pipe, Pipe) {
-        let buffer = Arc::new(Mutex::new(PipeRingBuffer::new()));
+        let inner = Arc::new(PipeInner {
+            buffer: Mutex::new(PipeRingBuffer::new()),
+            poll_set: PollSet::new(),
+            #[cfg(feature = "multitask")]
+            buffer_cv: axsync::Condvar::new(),
+        });
         let read_end = Pipe {
             readable: true,
-            buffer: buffer.clone(),
+            inner: inner.clone(),
         };
         let write_end = Pipe {
             readable: false,
-            buffer,
+            inner,
         };
         (read_end, write_end)
     }
@@ -102,7 +117,40 @@ impl Pipe {
     }
 
     pub fn write_end_close(&self) -> bool {
-        Arc::strong_count(&self.buffer) == 1
+        self.inner.buffer.lock().write_end_closed
+    }
+
+    /// Blocks until the buffer is changed by the other end.
+    fn wait<'a>(
+        &'a self,
+        buffer: MutexGuard<'a, PipeRingBuffer>,
+    ) -> MutexGuard<'a, PipeRingBuffer> {
+        #[cfg(feature = "multitask")]
+        {
+            self.inner.buffer_cv.wait(buffer)
+        }
+        #[cfg(not(feature = "multitask"))]
+        {
+            drop(buffer);
+            crate::sys_sched_yield();
+            self.inner.buffer.lock()
+        }
+    }
+
+    /// Wakes up the tasks waiting for both ends to become ready.
+    fn notify(&self) {
+        #[cfg(feature = "multitask")]
+        self.inner.buffer_cv.notify_all();
+        self.inner.poll_set.wake();
+    }
+}
+
+impl Drop for Pipe {
+    fn drop(&mut self) {
+        if self.writable() {
+            self.inner.buffer.lock().write_end_closed = true;
+        }
+        self.notify();
     }
 }
 
@@ -111,28 +159,21 @@ impl FileLike for Pipe {
         if !self.readable() {
             return Err(LinuxError::EPERM);
         }
-        let mut read_size = 0usize;
-        let max_len = buf.len();
-        loop {
-            let mut ring_buffer = self.buffer.lock();
-            let loop_read = ring_buffer.available_read();
-            if loop_read == 0 {
-                if self.write_end_close() {
-                    return Ok(read_size);
-                }
-                drop(ring_buffer);
-                // Data not ready, wait for write end
-                crate::sys_sched_yield(); // TODO: use synconize primitive
-                continue;
-            }
-            for _ in 0..loop_read {
-                if read_size == max_len {
-                    return Ok(read_size);
-                }
-                buf[read_size] = ring_buffer.read_byte();
-                read_size += 1;
+        let mut ring_buffer = self.inner.buffer.lock();
+        let read_size = loop {
+            let read_size = ring_buffer.available_read().min(buf.len());
+            if read_size > 0 || buf.is_empty() || ring_buffer.write_end_closed {
+                break read_size;
             }
+            // Data not ready, wait for write end
+            ring_buffer = self.wait(ring_buffer);
+        };
+        for c in buf[..read_size].iter_mut() {
+            *c = ring_buffer.read_byte();
         }
+        drop(ring_buffer);
+        self.notify();
+        Ok(read_size)
     }
 
     fn write(&self, buf: &[u8]) -> LinuxResult<usize> {
@@ -141,22 +182,27 @@ impl FileLike for Pipe {
         }
         let mut write_size = 0usize;
         let max_len = buf.len();
+        let mut ring_buffer = self.inner.buffer.lock();
         loop {
-            let mut ring_buffer = self.buffer.lock();
             let loop_write = ring_buffer.available_write();
             if loop_write == 0 {
-                drop(ring_buffer);
                 // Buffer is full, wait for read end to consume
-                crate::sys_sched_yield(); // TODO: use synconize primitive
+                ring_buffer = self.wait(ring_buffer);
                 continue;
             }
             for _ in 0..loop_write {
                 if write_size == max_len {
-                    return Ok(write_size);
+                    break;
                 }
                 ring_buffer.write_byte(buf[write_size]);
                 write_size += 1;
             }
+            drop(ring_buffer);
+            self.notify();
+            if write_size == max_len {
+                return Ok(write_size);
+            }
+            ring_buffer = self.inner.buffer.lock();
         }
     }
 
@@ -178,16 +224,21 @@ impl FileLike for Pipe {
     }
 
     fn poll(&self) -> LinuxResult<PollState> {
-        let buf = self.buffer.lock();
+        let buf = self.inner.buffer.lock();
         Ok(PollState {
-            readable: self.readable() && buf.available_read() > 0,
+            readable: self.readable() && (buf.available_read() > 0 || buf.write_end_closed),
             writable: self.writable() && buf.available_write() > 0,
+            hangup: self.readable() && buf.write_end_closed,
         })
     }
 
     fn set_nonblocking(&self, _nonblocking: bool) -> LinuxResult {
         Ok(())
     }
+
+    fn poll_set(&self) -> Option<&PollSet> {
+        Some(&self.inner.poll_set)
+    }
 }
 
 /// Create a pipe
@@ -0,0 +1,69 @@
+use crate::{ctypes, utils::check_null_mut_ptr};
+
+use axerrno::LinuxError;
+use axsync::Barrier;
+
+use core::ffi::{c_int, c_uint};
+use core::mem::size_of;
+
+static_assertions::const_assert_eq!(
+    size_of::<PthreadBarrier>(),
+    size_of::<ctypes::pthread_barrier_t>()
+);
+
+#[repr(C)]
+pub struct PthreadBarrier(Barrier);
+
+/// Initialize a barrier that blocks until `count` threads have called
+/// [`sys_pthread_barrier_wait`].
+pub fn sys_pthread_barrier_init(
+    barrier: *mut ctypes::pthread_barrier_t,
+    _attr: *const ctypes::pthread_barrierattr_t,
+    count: c_uint,
+) -> c_int {
+    debug!(
+        "sys_pthread_barrier_init <= {:#x} {}",
+        barrier as usize, count
+    );
+    syscall_body!(sys_pthread_barrier_init, {
+        check_null_mut_ptr(barrier)?;
+        if count == 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        unsafe {
+            barrier
+                .cast::<PthreadBarrier>()
+                .write(PthreadBarrier(Barrier::new(count as usize)));
+        }
+        Ok(0)
+    })
+}
+
+/// Destroy a barrier.
+pub fn sys_pthread_barrier_destroy(barrier: *mut ctypes::pthread_barrier_t) -> c_int {
+    debug!("sys_pthread_barrier_destroy <= {:#x}", barrier as usize);
+    syscall_body!(sys_pthread_barrier_destroy, {
+        check_null_mut_ptr(barrier)?;
+        unsafe {
+            core::ptr::drop_in_place(barrier.cast::<PthreadBarrier>());
+        }
+        Ok(0)
+    })
+}
+
+/// Wait on the barrier until enough threads have reached it.
+///
+/// Returns `PTHREAD_BARRIER_SERIAL_THREAD` in one of the threads and 0 in the
+/// others.
+pub fn sys_pthread_barrier_wait(barrier: *mut ctypes::pthread_barrier_t) -> c_int {
+    debug!("sys_pthread_barrier_wait <= {:#x}", barrier as usize);
+    syscall_body!(sys_pthread_barrier_wait, {
+        check_null_mut_ptr(barrier)?;
+        let res = unsafe { (*barrier.cast::<PthreadBarrier>()).0.wait() };
+        if res.is_leader() {
+            Ok(ctypes::PTHREAD_BARRIER_SERIAL_THREAD)
+        } else {
+            Ok(0)
+        }
+    })
+}
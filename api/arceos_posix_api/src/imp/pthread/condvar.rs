@@ -0,0 +1,140 @@
+use crate::{ctypes, utils::check_null_mut_ptr};
+
+use axerrno::{LinuxError, LinuxResult};
+use axsync::Condvar;
+
+use core::ffi::c_int;
+use core::mem::size_of;
+use core::time::Duration;
+
+use super::mutex::PthreadMutex;
+
+static_assertions::const_assert_eq!(
+    size_of::<PthreadCond>(),
+    size_of::<ctypes::pthread_cond_t>()
+);
+
+#[repr(C)]
+pub struct PthreadCond(Condvar);
+
+impl PthreadCond {
+    const fn new() -> Self {
+        Self(Condvar::new())
+    }
+
+    /// Waits on the condition variable, which is a cancellation point. The
+    /// thread exits with the mutex locked if it is canceled.
+    fn wait(&self, mutex: &PthreadMutex, timeout: Option<Duration>) -> LinuxResult {
+        super::test_cancel();
+        // The mutex is locked by the caller, whose guard does not exist in Rust.
+        let guard = unsafe { mutex.0.make_guard_unchecked() };
+        let (guard, timed_out) = match timeout {
+            Some(dur) => {
+                let (guard, res) = self.0.wait_timeout_cancelable(guard, dur);
+                (guard, res.timed_out())
+            }
+            None => (self.0.wait_cancelable(guard), false),
+        };
+        // Keep the mutex locked when returning to the caller.
+        core::mem::forget(guard);
+        super::test_cancel();
+        if timed_out {
+            Err(LinuxError::ETIMEDOUT)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Initialize a condition variable.
+pub fn sys_pthread_cond_init(
+    cond: *mut ctypes::pthread_cond_t,
+    _attr: *const ctypes::pthread_condattr_t,
+) -> c_int {
+    debug!("sys_pthread_cond_init <= {:#x}", cond as usize);
+    syscall_body!(sys_pthread_cond_init, {
+        check_null_mut_ptr(cond)?;
+        unsafe {
+            cond.cast::<PthreadCond>().write(PthreadCond::new());
+        }
+        Ok(0)
+    })
+}
+
+/// Destroy a condition variable.
+pub fn sys_pthread_cond_destroy(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    debug!("sys_pthread_cond_destroy <= {:#x}", cond as usize);
+    syscall_body!(sys_pthread_cond_destroy, {
+        check_null_mut_ptr(cond)?;
+        unsafe {
+            core::ptr::drop_in_place(cond.cast::<PthreadCond>());
+        }
+        Ok(0)
+    })
+}
+
+/// Unlock the given mutex and wait on the condition variable, then lock the
+/// mutex again before returning.
+pub fn sys_pthread_cond_wait(
+    cond: *mut ctypes::pthread_cond_t,
+    mutex: *mut ctypes::pthread_mutex_t,
+) -> c_int {
+    debug!(
+        "sys_pthread_cond_wait <= {:#x} {:#x}",
+        cond as usize, mutex as usize
+    );
+    syscall_body!(sys_pthread_cond_wait, {
+        check_null_mut_ptr(cond)?;
+        check_null_mut_ptr(mutex)?;
+        unsafe {
+            (*cond.cast::<PthreadCond>()).wait(&*mutex.cast::<PthreadMutex>(), None)?;
+        }
+        Ok(0)
+    })
+}
+
+/// Same as [`sys_pthread_cond_wait`], but fails with `ETIMEDOUT` if the
+/// condition variable is not signaled before the absolute time `abstime`.
+pub fn sys_pthread_cond_timedwait(
+    cond: *mut ctypes::pthread_cond_t,
+    mutex: *mut ctypes::pthread_mutex_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    debug!(
+        "sys_pthread_cond_timedwait <= {:#x} {:#x}",
+        cond as usize, mutex as usize
+    );
+    syscall_body!(sys_pthread_cond_timedwait, {
+        check_null_mut_ptr(cond)?;
+        check_null_mut_ptr(mutex)?;
+        let timeout = unsafe { super::timeout_from_abstime(abstime)? };
+        unsafe {
+            (*cond.cast::<PthreadCond>()).wait(&*mutex.cast::<PthreadMutex>(), Some(timeout))?;
+        }
+        Ok(0)
+    })
+}
+
+/// Wake up one task waiting on the condition variable.
+pub fn sys_pthread_cond_signal(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    debug!("sys_pthread_cond_signal <= {:#x}", cond as usize);
+    syscall_body!(sys_pthread_cond_signal, {
+        check_null_mut_ptr(cond)?;
+        unsafe {
+            (*cond.cast::<PthreadCond>()).0.notify_one();
+        }
+        Ok(0)
+    })
+}
+
+/// Wake up all tasks waiting on the condition variable.
+pub fn sys_pthread_cond_broadcast(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    debug!("sys_pthread_cond_broadcast <= {:#x}", cond as usize);
+    syscall_body!(sys_pthread_cond_broadcast, {
+        check_null_mut_ptr(cond)?;
+        unsafe {
+            (*cond.cast::<PthreadCond>()).0.notify_all();
+        }
+        Ok(0)
+    })
+}
@@ -1,14 +1,21 @@
 use alloc::{boxed::Box, collections::BTreeMap, sync::Arc};
 use core::cell::UnsafeCell;
 use core::ffi::{c_int, c_void};
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use core::time::Duration;
 
 use axerrno::{LinuxError, LinuxResult};
+use axsync::spin::SpinNoIrq;
 use axtask::AxTaskRef;
 use spin::RwLock;
 
 use crate::ctypes;
 
+pub mod barrier;
+pub mod condvar;
 pub mod mutex;
+pub mod rwlock;
+pub mod semaphore;
 
 lazy_static::lazy_static! {
     static ref TID_TO_PTHREAD: RwLock<BTreeMap<u64, ForceSendSync<ctypes::pthread_t>>> = {
@@ -17,9 +24,7 @@ lazy_static::lazy_static! {
         let main_tid = main_task.id().as_u64();
         let main_thread = Pthread {
             inner: main_task.as_task_ref().clone(),
-            retval: Arc::new(Packet {
-                result: UnsafeCell::new(core::ptr::null_mut()),
-            }),
+            retval: Arc::new(Packet::new(core::ptr::null_mut())),
         };
         let ptr = Box::into_raw(Box::new(main_thread)) as *mut c_void;
         map.insert(main_tid, ForceSendSync(ptr));
@@ -27,8 +32,42 @@ lazy_static::lazy_static! {
     };
 }
 
+/// The return value of a thread that exits by cancellation.
+const PTHREAD_CANCELED: *mut c_void = usize::MAX as *mut c_void;
+
+/// The thread is not joined or detached yet.
+const THREAD_JOINABLE: u8 = 0;
+/// The thread is detached, it frees itself on exit.
+const THREAD_DETACHED: u8 = 1;
+/// The thread has exited while joinable, it is freed by the joiner, or when
+/// it is detached.
+const THREAD_EXITED: u8 = 2;
+
+/// Whether a thread is requested to be canceled, and whether the
+/// cancellation is disabled. They are changed together with the cancellation
+/// request of the task, which is only set while the cancellation is enabled.
+#[derive(Default)]
+struct CancelState {
+    requested: bool,
+    disabled: bool,
+}
+
 struct Packet<T> {
     result: UnsafeCell<T>,
+    state: AtomicU8,
+    cancel: SpinNoIrq<CancelState>,
+    cancel_async: AtomicBool,
+}
+
+impl<T> Packet<T> {
+    fn new(result: T) -> Self {
+        Self {
+            result: UnsafeCell::new(result),
+            state: AtomicU8::new(THREAD_JOINABLE),
+            cancel: SpinNoIrq::new(CancelState::default()),
+            cancel_async: AtomicBool::new(false),
+        }
+    }
 }
 
 unsafe impl<T> Send for Packet<T> {}
@@ -47,15 +86,13 @@ impl Pthread {
     ) -> LinuxResult<ctypes::pthread_t> {
         let arg_wrapper = ForceSendSync(arg);
 
-        let my_packet: Arc<Packet<*mut c_void>> = Arc::new(Packet {
-            result: UnsafeCell::new(core::ptr::null_mut()),
-        });
+        let my_packet: Arc<Packet<*mut c_void>> = Arc::new(Packet::new(core::ptr::null_mut()));
         let their_packet = my_packet.clone();
 
         let main = move || {
             let arg = arg_wrapper;
             let ret = start_routine(arg.0);
-            unsafe { *their_packet.result.get() = ret };
+            Self::finish_current(&their_packet, ret);
             drop(their_packet);
         };
 
@@ -84,15 +121,46 @@ impl Pthread {
 
     fn exit_current(retval: *mut c_void) -> ! {
         let thread = Self::current().expect("fail to get current thread");
-        unsafe { *thread.retval.result.get() = retval };
+        // the thread may be freed in `finish_current`
+        let packet = thread.retval.clone();
+        Self::finish_current(&packet, retval);
+        drop(packet);
         axtask::exit(0);
     }
 
+    /// Stores the return value of the current thread, and frees the thread if
+    /// it is detached.
+    fn finish_current(packet: &Packet<*mut c_void>, retval: *mut c_void) {
+        unsafe { *packet.result.get() = retval };
+        if packet.state.swap(THREAD_EXITED, Ordering::AcqRel) == THREAD_DETACHED {
+            Self::free(axtask::current().id().as_u64());
+        }
+    }
+
+    fn free(tid: u64) {
+        let ptr = TID_TO_PTHREAD.write().remove(&tid);
+        if let Some(ptr) = ptr {
+            drop(unsafe { Box::from_raw(ptr.0 as *mut Pthread) });
+        }
+    }
+
     fn join(ptr: ctypes::pthread_t) -> LinuxResult<*mut c_void> {
         if core::ptr::eq(ptr, Self::current_ptr() as _) {
             return Err(LinuxError::EDEADLK);
         }
 
+        let packet = unsafe { &(*(ptr as *const Pthread)).retval };
+        if packet.state.load(Ordering::Acquire) == THREAD_DETACHED {
+            return Err(LinuxError::EINVAL);
+        }
+        // a cancellation point while waiting, the thread is still joinable
+        if unsafe { &*(ptr as *const Pthread) }
+            .inner
+            .join_cancelable()
+            .is_none()
+        {
+            Self::test_cancel();
+        }
         let thread = unsafe { Box::from_raw(ptr as *mut Pthread) };
         thread.inner.join();
         let tid = thread.inner.id().as_u64();
@@ -101,6 +169,60 @@ impl Pthread {
         drop(thread);
         Ok(retval)
     }
+
+    fn detach(ptr: ctypes::pthread_t) -> LinuxResult {
+        let thread = unsafe { &*(ptr as *const Pthread) };
+        // the thread may free itself once detached
+        let task = thread.inner.clone();
+        match thread.retval.state.compare_exchange(
+            THREAD_JOINABLE,
+            THREAD_DETACHED,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => {
+                axtask::detach(task);
+                Ok(())
+            }
+            Err(THREAD_EXITED) => {
+                Self::free(task.id().as_u64());
+                Ok(())
+            }
+            Err(_) => Err(LinuxError::EINVAL),
+        }
+    }
+
+    fn cancel(ptr: ctypes::pthread_t) -> LinuxResult {
+        let thread = unsafe { &*(ptr as *const Pthread) };
+        let packet = &thread.retval;
+        if packet.state.load(Ordering::Acquire) == THREAD_EXITED {
+            return Err(LinuxError::ESRCH);
+        }
+        let mut cancel = packet.cancel.lock();
+        cancel.requested = true;
+        // otherwise, it is done when the cancellation is enabled
+        if !cancel.disabled {
+            axtask::cancel(&thread.inner);
+        }
+        Ok(())
+    }
+
+    /// Exits the current thread if it is canceled and the cancellation is
+    /// enabled.
+    fn test_cancel() {
+        if let Some(thread) = Self::current() {
+            let cancel = thread.retval.cancel.lock();
+            if cancel.requested && !cancel.disabled {
+                drop(cancel);
+                Self::exit_current(PTHREAD_CANCELED);
+            }
+        }
+    }
+}
+
+/// A cancellation point, see [`sys_pthread_testcancel`].
+pub(crate) fn test_cancel() {
+    Pthread::test_cancel()
 }
 
 /// Returns the `pthread` struct of current thread.
@@ -138,6 +260,7 @@ pub fn sys_pthread_exit(retval: *mut c_void) -> ! {
 /// Waits for the given thread to exit, and stores the return value in `retval`.
 pub unsafe fn sys_pthread_join(thread: ctypes::pthread_t, retval: *mut *mut c_void) -> c_int {
     debug!("sys_pthread_join <= {:#x}", retval as usize);
+    Pthread::test_cancel();
     syscall_body!(sys_pthread_join, {
         let ret = Pthread::join(thread)?;
         if !retval.is_null() {
@@ -147,6 +270,109 @@ pub unsafe fn sys_pthread_join(thread: ctypes::pthread_t, retval: *mut *mut c_vo
     })
 }
 
+/// Marks the given thread as detached, so that it is freed when it exits,
+/// and can not be joined.
+pub fn sys_pthread_detach(thread: ctypes::pthread_t) -> c_int {
+    debug!("sys_pthread_detach <= {:#x}", thread as usize);
+    syscall_body!(sys_pthread_detach, {
+        Pthread::detach(thread)?;
+        Ok(0)
+    })
+}
+
+/// Requests the given thread to be canceled.
+///
+/// The cancellation is deferred: the thread exits with `PTHREAD_CANCELED`
+/// at the next cancellation point, i.e., [`sys_pthread_testcancel`],
+/// [`sys_pthread_join`], `sys_pthread_cond_wait`, `sys_pthread_cond_timedwait`
+/// and `sys_nanosleep`. The sleeping or waiting thread is woken up at once.
+pub fn sys_pthread_cancel(thread: ctypes::pthread_t) -> c_int {
+    debug!("sys_pthread_cancel <= {:#x}", thread as usize);
+    syscall_body!(sys_pthread_cancel, {
+        Pthread::cancel(thread)?;
+        Ok(0)
+    })
+}
+
+/// Enables or disables the cancellation of the current thread, and stores
+/// the old state in `oldstate`.
+pub unsafe fn sys_pthread_setcancelstate(state: c_int, oldstate: *mut c_int) -> c_int {
+    debug!("sys_pthread_setcancelstate <= {}", state);
+    syscall_body!(sys_pthread_setcancelstate, {
+        let disabled = match state as u32 {
+            ctypes::PTHREAD_CANCEL_ENABLE => false,
+            ctypes::PTHREAD_CANCEL_DISABLE => true,
+            _ => return Err(LinuxError::EINVAL),
+        };
+        let thread = Pthread::current().ok_or(LinuxError::ESRCH)?;
+        let mut cancel = thread.retval.cancel.lock();
+        let old = core::mem::replace(&mut cancel.disabled, disabled);
+        if disabled {
+            // the cancellation points block again until it is enabled
+            axtask::clear_cancel();
+        } else if old && cancel.requested {
+            axtask::cancel(&thread.inner);
+        }
+        drop(cancel);
+        if !oldstate.is_null() {
+            let old = if old {
+                ctypes::PTHREAD_CANCEL_DISABLE
+            } else {
+                ctypes::PTHREAD_CANCEL_ENABLE
+            };
+            unsafe { *oldstate = old as c_int };
+        }
+        Ok(0)
+    })
+}
+
+/// Sets the cancellation type of the current thread, and stores the old type
+/// in `oldtype`.
+///
+/// The asynchronous type is accepted, but the cancellation is still deferred
+/// to the next cancellation point.
+pub unsafe fn sys_pthread_setcanceltype(ty: c_int, oldtype: *mut c_int) -> c_int {
+    debug!("sys_pthread_setcanceltype <= {}", ty);
+    syscall_body!(sys_pthread_setcanceltype, {
+        let is_async = match ty as u32 {
+            ctypes::PTHREAD_CANCEL_DEFERRED => false,
+            ctypes::PTHREAD_CANCEL_ASYNCHRONOUS => true,
+            _ => return Err(LinuxError::EINVAL),
+        };
+        let thread = Pthread::current().ok_or(LinuxError::ESRCH)?;
+        let old = thread.retval.cancel_async.swap(is_async, Ordering::Relaxed);
+        if !oldtype.is_null() {
+            let old = if old {
+                ctypes::PTHREAD_CANCEL_ASYNCHRONOUS
+            } else {
+                ctypes::PTHREAD_CANCEL_DEFERRED
+            };
+            unsafe { *oldtype = old as c_int };
+        }
+        Ok(0)
+    })
+}
+
+/// Exits the current thread with `PTHREAD_CANCELED` if it is canceled and
+/// the cancellation is enabled.
+pub fn sys_pthread_testcancel() {
+    Pthread::test_cancel()
+}
+
+/// Converts the absolute time `abstime` of timed waits to the duration from
+/// now on. The time is measured by the same clock as `clock_gettime`.
+unsafe fn timeout_from_abstime(abstime: *const ctypes::timespec) -> LinuxResult<Duration> {
+    if abstime.is_null() {
+        return Err(LinuxError::EFAULT);
+    }
+    let ts = unsafe { *abstime };
+    if ts.tv_sec < 0 || !(0..1_000_000_000).contains(&ts.tv_nsec) {
+        return Err(LinuxError::EINVAL);
+    }
+    let deadline = Duration::from(ts);
+    Ok(deadline.saturating_sub(axhal::time::current_time()))
+}
+
 #[derive(Clone, Copy)]
 struct ForceSendSync<T>(T);
 
@@ -0,0 +1,143 @@
+use crate::{ctypes, utils::check_null_mut_ptr};
+
+use axerrno::{LinuxError, LinuxResult};
+use axsync::RwLock;
+
+use core::ffi::c_int;
+use core::mem::{size_of, ManuallyDrop};
+
+static_assertions::const_assert_eq!(
+    size_of::<PthreadRwLock>(),
+    size_of::<ctypes::pthread_rwlock_t>()
+);
+
+#[repr(C)]
+pub struct PthreadRwLock(RwLock<()>);
+
+impl PthreadRwLock {
+    const fn new() -> Self {
+        Self(RwLock::new(()))
+    }
+
+    fn rdlock(&self) -> LinuxResult {
+        let _guard = ManuallyDrop::new(self.0.read());
+        Ok(())
+    }
+
+    fn tryrdlock(&self) -> LinuxResult {
+        let _guard = ManuallyDrop::new(self.0.try_read().ok_or(LinuxError::EBUSY)?);
+        Ok(())
+    }
+
+    fn wrlock(&self) -> LinuxResult {
+        let _guard = ManuallyDrop::new(self.0.write());
+        Ok(())
+    }
+
+    fn trywrlock(&self) -> LinuxResult {
+        let _guard = ManuallyDrop::new(self.0.try_write().ok_or(LinuxError::EBUSY)?);
+        Ok(())
+    }
+
+    fn unlock(&self) -> LinuxResult {
+        // While a writer holds the lock, there can be no readers.
+        if self.0.is_write_locked() {
+            unsafe { self.0.force_write_unlock() };
+        } else if self.0.reader_count() > 0 {
+            unsafe { self.0.force_read_unlock() };
+        } else {
+            return Err(LinuxError::EPERM);
+        }
+        Ok(())
+    }
+}
+
+/// Initialize a readers-writer lock.
+pub fn sys_pthread_rwlock_init(
+    rwlock: *mut ctypes::pthread_rwlock_t,
+    _attr: *const ctypes::pthread_rwlockattr_t,
+) -> c_int {
+    debug!("sys_pthread_rwlock_init <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_init, {
+        check_null_mut_ptr(rwlock)?;
+        unsafe {
+            rwlock.cast::<PthreadRwLock>().write(PthreadRwLock::new());
+        }
+        Ok(0)
+    })
+}
+
+/// Destroy a readers-writer lock.
+pub fn sys_pthread_rwlock_destroy(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    debug!("sys_pthread_rwlock_destroy <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_destroy, {
+        check_null_mut_ptr(rwlock)?;
+        unsafe {
+            core::ptr::drop_in_place(rwlock.cast::<PthreadRwLock>());
+        }
+        Ok(0)
+    })
+}
+
+/// Lock the given readers-writer lock for reading.
+pub fn sys_pthread_rwlock_rdlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    debug!("sys_pthread_rwlock_rdlock <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_rdlock, {
+        check_null_mut_ptr(rwlock)?;
+        unsafe {
+            (*rwlock.cast::<PthreadRwLock>()).rdlock()?;
+        }
+        Ok(0)
+    })
+}
+
+/// Try to lock the given readers-writer lock for reading, fails with `EBUSY`
+/// if a writer holds it.
+pub fn sys_pthread_rwlock_tryrdlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    debug!("sys_pthread_rwlock_tryrdlock <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_tryrdlock, {
+        check_null_mut_ptr(rwlock)?;
+        unsafe {
+            (*rwlock.cast::<PthreadRwLock>()).tryrdlock()?;
+        }
+        Ok(0)
+    })
+}
+
+/// Lock the given readers-writer lock for writing.
+pub fn sys_pthread_rwlock_wrlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    debug!("sys_pthread_rwlock_wrlock <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_wrlock, {
+        check_null_mut_ptr(rwlock)?;
+        unsafe {
+            (*rwlock.cast::<PthreadRwLock>()).wrlock()?;
+        }
+        Ok(0)
+    })
+}
+
+/// Try to lock the given readers-writer lock for writing, fails with `EBUSY`
+/// if it is held by a reader or a writer.
+pub fn sys_pthread_rwlock_trywrlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    debug!("sys_pthread_rwlock_trywrlock <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_trywrlock, {
+        check_null_mut_ptr(rwlock)?;
+        unsafe {
+            (*rwlock.cast::<PthreadRwLock>()).trywrlock()?;
+        }
+        Ok(0)
+    })
+}
+
+/// Unlock the given readers-writer lock, which is held for either reading or
+/// writing.
+pub fn sys_pthread_rwlock_unlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    debug!("sys_pthread_rwlock_unlock <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_unlock, {
+        check_null_mut_ptr(rwlock)?;
+        unsafe {
+            (*rwlock.cast::<PthreadRwLock>()).unlock()?;
+        }
+        Ok(0)
+    })
+}
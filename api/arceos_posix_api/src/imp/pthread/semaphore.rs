@@ -0,0 +1,106 @@
+use crate::{ctypes, utils::check_null_mut_ptr};
+
+use axerrno::LinuxError;
+use axsync::Semaphore;
+
+use core::ffi::{c_int, c_uint};
+use core::mem::size_of;
+
+static_assertions::const_assert_eq!(size_of::<Semaphore>(), size_of::<ctypes::sem_t>());
+
+/// Initialize an unnamed semaphore with the given value.
+pub fn sys_sem_init(sem: *mut ctypes::sem_t, _pshared: c_int, value: c_uint) -> c_int {
+    debug!("sys_sem_init <= {:#x} {}", sem as usize, value);
+    syscall_body!(sys_sem_init, {
+        check_null_mut_ptr(sem)?;
+        if value > ctypes::SEM_VALUE_MAX {
+            return Err(LinuxError::EINVAL);
+        }
+        unsafe {
+            sem.cast::<Semaphore>()
+                .write(Semaphore::new(value as usize));
+        }
+        Ok(0)
+    })
+}
+
+/// Destroy an unnamed semaphore.
+pub fn sys_sem_destroy(sem: *mut ctypes::sem_t) -> c_int {
+    debug!("sys_sem_destroy <= {:#x}", sem as usize);
+    syscall_body!(sys_sem_destroy, {
+        check_null_mut_ptr(sem)?;
+        unsafe {
+            core::ptr::drop_in_place(sem.cast::<Semaphore>());
+        }
+        Ok(0)
+    })
+}
+
+/// Decrement the semaphore, blocking until its value is greater than zero.
+pub fn sys_sem_wait(sem: *mut ctypes::sem_t) -> c_int {
+    debug!("sys_sem_wait <= {:#x}", sem as usize);
+    syscall_body!(sys_sem_wait, {
+        check_null_mut_ptr(sem)?;
+        unsafe {
+            (*sem.cast::<Semaphore>()).acquire();
+        }
+        Ok(0)
+    })
+}
+
+/// Decrement the semaphore, fails with `EAGAIN` if its value is zero.
+pub fn sys_sem_trywait(sem: *mut ctypes::sem_t) -> c_int {
+    debug!("sys_sem_trywait <= {:#x}", sem as usize);
+    syscall_body!(sys_sem_trywait, {
+        check_null_mut_ptr(sem)?;
+        if unsafe { (*sem.cast::<Semaphore>()).try_acquire() } {
+            Ok(0)
+        } else {
+            Err(LinuxError::EAGAIN)
+        }
+    })
+}
+
+/// Same as [`sys_sem_wait`], but fails with `ETIMEDOUT` if the semaphore
+/// can not be decremented before the absolute time `abstime`.
+pub fn sys_sem_timedwait(sem: *mut ctypes::sem_t, abstime: *const ctypes::timespec) -> c_int {
+    debug!("sys_sem_timedwait <= {:#x}", sem as usize);
+    syscall_body!(sys_sem_timedwait, {
+        check_null_mut_ptr(sem)?;
+        let sem = unsafe { &*sem.cast::<Semaphore>() };
+        if sem.try_acquire() {
+            return Ok(0);
+        }
+        let timeout = unsafe { super::timeout_from_abstime(abstime)? };
+        if sem.acquire_timeout(timeout) {
+            Ok(0)
+        } else {
+            Err(LinuxError::ETIMEDOUT)
+        }
+    })
+}
+
+/// Increment the semaphore, and wake up a thread waiting on it.
+pub fn sys_sem_post(sem: *mut ctypes::sem_t) -> c_int {
+    debug!("sys_sem_post <= {:#x}", sem as usize);
+    syscall_body!(sys_sem_post, {
+        check_null_mut_ptr(sem)?;
+        unsafe {
+            (*sem.cast::<Semaphore>()).release();
+        }
+        Ok(0)
+    })
+}
+
+/// Get the current value of the semaphore.
+pub fn sys_sem_getvalue(sem: *mut ctypes::sem_t, sval: *mut c_int) -> c_int {
+    debug!("sys_sem_getvalue <= {:#x}", sem as usize);
+    syscall_body!(sys_sem_getvalue, {
+        check_null_mut_ptr(sem)?;
+        check_null_mut_ptr(sval)?;
+        unsafe {
+            *sval = (*sem.cast::<Semaphore>()).available_permits() as c_int;
+        }
+        Ok(0)
+    })
+}
@@ -49,3 +49,43 @@ pub unsafe fn sys_setrlimit(resource: c_int, rlimits: *mut crate::ctypes::rlimit
         Ok(0)
     })
 }
+
+/// Get resource usage
+///
+/// `who` is `RUSAGE_SELF` for all threads, `RUSAGE_THREAD` for the current
+/// thread, or `RUSAGE_CHILDREN`, which is always empty. Only the CPU times and
+/// the numbers of context switches are filled.
+pub unsafe fn sys_getrusage(who: c_int, usage: *mut ctypes::rusage) -> c_int {
+    debug!("sys_getrusage <= {} {:#x}", who, usage as usize);
+    syscall_body!(sys_getrusage, {
+        if usage.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let mut ru = ctypes::rusage::default();
+        if who != ctypes::RUSAGE_CHILDREN {
+            match who as u32 {
+                ctypes::RUSAGE_SELF | ctypes::RUSAGE_THREAD => {}
+                _ => return Err(LinuxError::EINVAL),
+            }
+            #[cfg(feature = "multitask")]
+            {
+                let cpu_usage = if who as u32 == ctypes::RUSAGE_SELF {
+                    axtask::total_cpu_usage()
+                } else {
+                    axtask::current().cpu_usage()
+                };
+                ru.ru_utime = cpu_usage.user_time.into();
+                ru.ru_stime = cpu_usage.kernel_time.into();
+                ru.ru_nvcsw = cpu_usage.voluntary_switches as _;
+                ru.ru_nivcsw = cpu_usage.involuntary_switches as _;
+            }
+            // the only thread has been running since booting
+            #[cfg(not(feature = "multitask"))]
+            {
+                ru.ru_utime = axhal::time::current_time().into();
+            }
+        }
+        unsafe { *usage = ru };
+        Ok(0)
+    })
+}
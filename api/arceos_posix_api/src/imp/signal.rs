@@ -0,0 +1,162 @@
+//! Signals of the process.
+//!
+//! ArceOS runs a single process and does not run signal handlers, so a signal
+//! sent by [`sys_kill`] takes its default action unless it is blocked. A
+//! blocked signal becomes pending, and stays so until it is read from a
+//! signalfd whose mask contains it. Like standard signals, a pending signal is
+//! not queued again until it is read.
+
+use core::ffi::c_int;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use axerrno::LinuxError;
+use axsync::spin::SpinNoIrq;
+
+use crate::ctypes;
+
+/// The number of signals, numbered from 1.
+const NSIG: usize = 64;
+
+/// A set of signals, where signal `n` is the bit `n - 1`.
+pub type SigSet = u64;
+
+/// The signals that can not be blocked or caught.
+pub const UNBLOCKABLE: SigSet = sig_bit(ctypes::SIGKILL as _) | sig_bit(ctypes::SIGSTOP as _);
+
+struct PendingSignals {
+    set: SigSet,
+    /// The process IDs of the senders of the pending signals.
+    senders: [c_int; NSIG],
+}
+
+static PENDING: SpinNoIrq<PendingSignals> = SpinNoIrq::new(PendingSignals {
+    set: 0,
+    senders: [0; NSIG],
+});
+
+/// The blocked signals, only recorded for `sigprocmask`.
+static BLOCKED: AtomicU64 = AtomicU64::new(0);
+
+/// The signals that are ignored by default.
+const IGNORED: SigSet = sig_bit(ctypes::SIGCHLD as _)
+    | sig_bit(ctypes::SIGCONT as _)
+    | sig_bit(ctypes::SIGURG as _)
+    | sig_bit(ctypes::SIGWINCH as _);
+
+/// The signals that stop the process by default.
+const STOPPING: SigSet = sig_bit(ctypes::SIGSTOP as _)
+    | sig_bit(ctypes::SIGTSTP as _)
+    | sig_bit(ctypes::SIGTTIN as _)
+    | sig_bit(ctypes::SIGTTOU as _);
+
+const fn sig_bit(sig: usize) -> SigSet {
+    1 << (sig - 1)
+}
+
+/// Terminates the process as the default action of the signal `sig`.
+fn terminate(sig: c_int) -> ! {
+    info!("killed by signal {}", sig);
+    #[cfg(feature = "fs")]
+    axfs::api::sync().ok();
+    axhal::misc::terminate();
+}
+
+/// Converts a `sigset_t` to a [`SigSet`].
+pub fn sigset_from_c(set: &ctypes::sigset_t) -> SigSet {
+    set.__bits[0] as SigSet
+}
+
+fn sigset_to_c(set: SigSet) -> ctypes::sigset_t {
+    let mut c_set = ctypes::sigset_t::default();
+    c_set.__bits[0] = set as _;
+    c_set
+}
+
+/// Whether any signal in `mask` is pending.
+#[cfg(feature = "fd")]
+pub fn has_pending(mask: SigSet) -> bool {
+    PENDING.lock().set & mask != 0
+}
+
+/// Takes the lowest pending signal in `mask`, and returns it with the process
+/// ID of its sender.
+#[cfg(feature = "fd")]
+pub fn take_pending(mask: SigSet) -> Option<(c_int, c_int)> {
+    let mut pending = PENDING.lock();
+    let set = pending.set & mask;
+    if set == 0 {
+        return None;
+    }
+    let idx = set.trailing_zeros() as usize;
+    pending.set &= !(1 << idx);
+    Some((idx as c_int + 1, pending.senders[idx]))
+}
+
+/// Send the signal `sig` to the process.
+///
+/// ArceOS runs a single process, so every positive `pid`, 0 and -1 refer to
+/// it. If `sig` is 0, only the arguments are checked.
+///
+/// A blocked signal becomes pending. Otherwise the default action is taken:
+/// the process is terminated, or the signal is discarded. Stopping the process
+/// is not supported, so the stop signals fail with `ENOSYS` unless blocked.
+pub fn sys_kill(pid: c_int, sig: c_int) -> c_int {
+    debug!("sys_kill <= {} {}", pid, sig);
+    syscall_body!(sys_kill, {
+        if pid < -1 {
+            return Err(LinuxError::ESRCH);
+        }
+        if !(0..=NSIG as c_int).contains(&sig) {
+            return Err(LinuxError::EINVAL);
+        }
+        if sig == 0 {
+            return Ok(0);
+        }
+        let idx = sig as usize - 1;
+        let bit = 1 << idx;
+        if bit & BLOCKED.load(Ordering::Acquire) == 0 {
+            if bit & IGNORED != 0 {
+                return Ok(0);
+            } else if bit & STOPPING != 0 {
+                return Err(LinuxError::ENOSYS);
+            }
+            terminate(sig);
+        }
+        let mut pending = PENDING.lock();
+        pending.set |= bit;
+        pending.senders[idx] = super::task::sys_getpid();
+        drop(pending);
+        #[cfg(feature = "fd")]
+        super::signalfd::notify_signal();
+        Ok(0)
+    })
+}
+
+/// Examine and change the blocked signals.
+///
+/// The mask is only recorded, as signals never interrupt the tasks.
+pub unsafe fn sys_sigprocmask(
+    how: c_int,
+    set: *const ctypes::sigset_t,
+    oldset: *mut ctypes::sigset_t,
+) -> c_int {
+    debug!("sys_sigprocmask <= {} {:#x}", how, set as usize);
+    syscall_body!(sys_sigprocmask, {
+        let old = match unsafe { set.as_ref() } {
+            Some(set) => {
+                let set = sigset_from_c(set) & !UNBLOCKABLE;
+                match how as u32 {
+                    ctypes::SIG_BLOCK => BLOCKED.fetch_or(set, Ordering::AcqRel),
+                    ctypes::SIG_UNBLOCK => BLOCKED.fetch_and(!set, Ordering::AcqRel),
+                    ctypes::SIG_SETMASK => BLOCKED.swap(set, Ordering::AcqRel),
+                    _ => return Err(LinuxError::EINVAL),
+                }
+            }
+            None => BLOCKED.load(Ordering::Acquire),
+        };
+        if let Some(oldset) = unsafe { oldset.as_mut() } {
+            *oldset = sigset_to_c(old);
+        }
+        Ok(0)
+    })
+}
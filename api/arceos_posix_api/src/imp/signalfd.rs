@@ -0,0 +1,152 @@
+//! File descriptors for accepting signals.
+//!
+//! A signalfd reads the pending signals of the process in its mask (see
+//! [`signal`](super::signal)). All signalfds share a poll set, which is woken
+//! up whenever a signal is sent.
+
+use alloc::sync::Arc;
+use core::ffi::c_int;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use axerrno::{LinuxError, LinuxResult};
+use axio::PollState;
+
+use super::fd_ops::{add_file_like, get_file_like, FileLike, PollSet};
+use super::signal::{has_pending, sigset_from_c, take_pending, SigSet, UNBLOCKABLE};
+use crate::ctypes;
+
+/// The watchers of all signalfds.
+static SIGNAL_POLL_SET: PollSet = PollSet::new();
+
+/// The tasks blocked in reading signalfds.
+#[cfg(feature = "multitask")]
+static SIGNAL_WQ: axtask::WaitQueue = axtask::WaitQueue::new();
+
+/// Wakes up the tasks waiting for signals, called after a signal is sent.
+pub fn notify_signal() {
+    #[cfg(feature = "multitask")]
+    SIGNAL_WQ.notify_all(true);
+    SIGNAL_POLL_SET.wake();
+}
+
+pub struct SignalFd {
+    mask: AtomicU64,
+    nonblocking: AtomicBool,
+}
+
+impl SignalFd {
+    fn new(mask: SigSet, flags: u32) -> Self {
+        Self {
+            mask: AtomicU64::new(mask),
+            nonblocking: AtomicBool::new(flags & ctypes::SFD_NONBLOCK != 0),
+        }
+    }
+
+    fn from_fd(fd: c_int) -> LinuxResult<Arc<Self>> {
+        get_file_like(fd)?
+            .into_any()
+            .downcast::<SignalFd>()
+            .map_err(|_| LinuxError::EINVAL)
+    }
+
+    fn mask(&self) -> SigSet {
+        self.mask.load(Ordering::Acquire)
+    }
+}
+
+impl FileLike for SignalFd {
+    fn read(&self, buf: &mut [u8]) -> LinuxResult<usize> {
+        const INFO_SIZE: usize = core::mem::size_of::<ctypes::signalfd_siginfo>();
+        if buf.len() < INFO_SIZE {
+            return Err(LinuxError::EINVAL);
+        }
+        loop {
+            let mut read_len = 0;
+            while read_len + INFO_SIZE <= buf.len() {
+                let Some((signo, pid)) = take_pending(self.mask()) else {
+                    break;
+                };
+                let info = ctypes::signalfd_siginfo {
+                    ssi_signo: signo as _,
+                    ssi_code: ctypes::SI_USER as _,
+                    ssi_pid: pid as _,
+                    ..Default::default()
+                };
+                // Safety: `signalfd_siginfo` is a plain C struct.
+                let bytes: [u8; INFO_SIZE] = unsafe { core::mem::transmute(info) };
+                buf[read_len..read_len + INFO_SIZE].copy_from_slice(&bytes);
+                read_len += INFO_SIZE;
+            }
+            if read_len > 0 {
+                return Ok(read_len);
+            }
+            if self.nonblocking.load(Ordering::Relaxed) {
+                return Err(LinuxError::EAGAIN);
+            }
+            #[cfg(feature = "multitask")]
+            SIGNAL_WQ.wait_until(|| has_pending(self.mask()));
+            #[cfg(not(feature = "multitask"))]
+            crate::sys_sched_yield();
+        }
+    }
+
+    fn write(&self, _buf: &[u8]) -> LinuxResult<usize> {
+        Err(LinuxError::EINVAL)
+    }
+
+    fn stat(&self) -> LinuxResult<ctypes::stat> {
+        let st_mode = 0o600u32; // rw-------
+        Ok(ctypes::stat {
+            st_ino: 1,
+            st_nlink: 1,
+            st_mode,
+            ..Default::default()
+        })
+    }
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn core::any::Any + Send + Sync> {
+        self
+    }
+
+    fn poll(&self) -> LinuxResult<PollState> {
+        Ok(PollState {
+            readable: has_pending(self.mask()),
+            writable: false,
+            hangup: false,
+        })
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> LinuxResult {
+        self.nonblocking.store(nonblocking, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn poll_set(&self) -> Option<&PollSet> {
+        Some(&SIGNAL_POLL_SET)
+    }
+}
+
+/// Create a file descriptor for accepting the signals in `mask`, or update
+/// the mask of the signalfd `fd` if it is not -1.
+///
+/// `SIGKILL` and `SIGSTOP` in `mask` are ignored.
+pub unsafe fn sys_signalfd(fd: c_int, mask: *const ctypes::sigset_t, flags: c_int) -> c_int {
+    debug!("sys_signalfd <= {} {:#x} {:#x}", fd, mask as usize, flags);
+    syscall_body!(sys_signalfd, {
+        let mask = unsafe { mask.as_ref() }.ok_or(LinuxError::EFAULT)?;
+        let mask = sigset_from_c(mask) & !UNBLOCKABLE;
+        let flags = flags as u32;
+        if flags & !(ctypes::SFD_NONBLOCK | ctypes::SFD_CLOEXEC) != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        if fd == -1 {
+            add_file_like(Arc::new(SignalFd::new(mask, flags)))
+        } else {
+            let signalfd = SignalFd::from_fd(fd)?;
+            signalfd.mask.store(mask, Ordering::Release);
+            // The new mask may contain pending signals.
+            notify_signal();
+            Ok(fd)
+        }
+    })
+}
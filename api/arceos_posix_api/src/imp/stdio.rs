@@ -3,7 +3,23 @@ use axio::{prelude::*, BufReader};
 use axsync::Mutex;
 
 #[cfg(feature = "fd")]
-use {alloc::sync::Arc, axerrno::LinuxError, axerrno::LinuxResult, axio::PollState};
+use {
+    super::fd_ops::PollSet,
+    alloc::sync::Arc,
+    axerrno::LinuxError,
+    axerrno::LinuxResult,
+    axio::PollState,
+    core::sync::atomic::{AtomicBool, Ordering},
+};
+
+/// The watchers of the standard input, woken up by [`poll_stdin`].
+#[cfg(feature = "fd")]
+static STDIN_POLL_SET: PollSet = PollSet::new_polled();
+
+/// Whether the watchers of the standard input have been woken up for the
+/// input that has not been read yet.
+#[cfg(feature = "fd")]
+static STDIN_WOKEN: AtomicBool = AtomicBool::new(false);
 
 fn console_read_bytes() -> Option<u8> {
     axhal::console::getchar().map(|c| if c == b'\r' { b'\n' } else { c })
@@ -48,6 +64,15 @@ pub struct Stdin {
 }
 
 impl Stdin {
+    /// Whether some input can be read without blocking.
+    #[cfg(feature = "fd")]
+    fn readable(&self) -> bool {
+        self.inner
+            .lock()
+            .fill_buf()
+            .is_ok_and(|buf| !buf.is_empty())
+    }
+
     // Block until at least one byte is read.
     fn read_blocked(&self, buf: &mut [u8]) -> AxResult<usize> {
         let read_len = self.inner.lock().read(buf)?;
@@ -100,7 +125,9 @@ pub fn stdout() -> Stdout {
 #[cfg(feature = "fd")]
 impl super::fd_ops::FileLike for Stdin {
     fn read(&self, buf: &mut [u8]) -> LinuxResult<usize> {
-        Ok(self.read_blocked(buf)?)
+        let read_len = self.read_blocked(buf)?;
+        STDIN_WOKEN.store(false, Ordering::Release);
+        Ok(read_len)
     }
 
     fn write(&self, _buf: &[u8]) -> LinuxResult<usize> {
@@ -123,14 +150,19 @@ impl super::fd_ops::FileLike for Stdin {
 
     fn poll(&self) -> LinuxResult<PollState> {
         Ok(PollState {
-            readable: true,
+            readable: self.readable(),
             writable: true,
+            hangup: false,
         })
     }
 
     fn set_nonblocking(&self, _nonblocking: bool) -> LinuxResult {
         Ok(())
     }
+
+    fn poll_set(&self) -> Option<&PollSet> {
+        Some(&STDIN_POLL_SET)
+    }
 }
 
 #[cfg(feature = "fd")]
@@ -161,6 +193,7 @@ impl super::fd_ops::FileLike for Stdout {
         Ok(PollState {
             readable: true,
             writable: true,
+            hangup: false,
         })
     }
 
@@ -168,3 +201,14 @@ impl super::fd_ops::FileLike for Stdout {
         Ok(())
     }
 }
+
+/// Wakes up the watchers of the standard input if new input arrives.
+#[cfg(feature = "fd")]
+pub fn poll_stdin() {
+    if STDIN_POLL_SET.has_watchers()
+        && stdin().readable()
+        && !STDIN_WOKEN.swap(true, Ordering::AcqRel)
+    {
+        STDIN_POLL_SET.wake();
+    }
+}
@@ -1,5 +1,11 @@
 use core::ffi::c_int;
 
+#[cfg(feature = "multitask")]
+use {
+    crate::ctypes,
+    axerrno::{LinuxError, LinuxResult},
+};
+
 /// Relinquish the CPU, and switches to another task.
 ///
 /// For single-threaded configuration (`multitask` feature is disabled), we just
@@ -33,8 +39,101 @@ pub fn sys_getpid() -> c_int {
 /// Exit current task
 pub fn sys_exit(exit_code: c_int) -> ! {
     debug!("sys_exit <= {}", exit_code);
+    #[cfg(feature = "fs")]
+    axfs::api::sync().ok();
     #[cfg(feature = "multitask")]
     axtask::exit(exit_code);
     #[cfg(not(feature = "multitask"))]
     axhal::misc::terminate();
 }
+
+/// Finds the task by ID, or the current task if `pid` is 0.
+#[cfg(feature = "multitask")]
+fn find_task(pid: c_int) -> LinuxResult<axtask::AxTaskRef> {
+    if pid == 0 {
+        Ok(axtask::current().as_task_ref().clone())
+    } else if pid > 0 {
+        axtask::find_task(pid as u64).ok_or(LinuxError::ESRCH)
+    } else {
+        Err(LinuxError::EINVAL)
+    }
+}
+
+/// Get the nice value of a thread.
+///
+/// Only `PRIO_PROCESS` is supported, where `who` is a thread ID, or 0 for
+/// the current thread. As the Linux syscall, it returns `20 - nice` to avoid
+/// negative values.
+#[cfg(feature = "multitask")]
+pub fn sys_getpriority(which: c_int, who: ctypes::id_t) -> c_int {
+    debug!("sys_getpriority <= {} {}", which, who);
+    syscall_body!(sys_getpriority, {
+        if which as u32 != ctypes::PRIO_PROCESS {
+            return Err(LinuxError::EINVAL);
+        }
+        let task = find_task(who as c_int)?;
+        Ok(20 - axtask::get_priority(&task))
+    })
+}
+
+/// Set the nice value of a thread.
+///
+/// Only `PRIO_PROCESS` is supported, where `who` is a thread ID, or 0 for
+/// the current thread. The value is passed to the scheduler as the priority,
+/// so it only takes effect with schedulers using nice values (e.g., CFS).
+#[cfg(feature = "multitask")]
+pub fn sys_setpriority(which: c_int, who: ctypes::id_t, prio: c_int) -> c_int {
+    debug!("sys_setpriority <= {} {} {}", which, who, prio);
+    syscall_body!(sys_setpriority, {
+        if which as u32 != ctypes::PRIO_PROCESS {
+            return Err(LinuxError::EINVAL);
+        }
+        let task = find_task(who as c_int)?;
+        // out of range values are clamped as in Linux
+        let nice = prio.clamp(-20, 19) as isize;
+        if axtask::set_priority(&task, nice) {
+            Ok(0)
+        } else {
+            Err(LinuxError::EINVAL)
+        }
+    })
+}
+
+/// Get the scheduling parameters of a thread, or the current thread if `pid`
+/// is 0.
+///
+/// `sched_priority` is the priority of the thread in the underlying scheduler.
+#[cfg(feature = "multitask")]
+pub unsafe fn sys_sched_getparam(pid: ctypes::pid_t, param: *mut ctypes::sched_param) -> c_int {
+    debug!("sys_sched_getparam <= {} {:#x}", pid, param as usize);
+    syscall_body!(sys_sched_getparam, {
+        if param.is_null() {
+            return Err(LinuxError::EINVAL);
+        }
+        let task = find_task(pid)?;
+        unsafe { (*param).sched_priority = axtask::get_priority(&task) as c_int };
+        Ok(0)
+    })
+}
+
+/// Set the scheduling parameters of a thread, or the current thread if `pid`
+/// is 0.
+///
+/// `sched_priority` is passed to the underlying scheduler as the priority,
+/// e.g., the real-time priority of a real-time thread.
+#[cfg(feature = "multitask")]
+pub unsafe fn sys_sched_setparam(pid: ctypes::pid_t, param: *const ctypes::sched_param) -> c_int {
+    debug!("sys_sched_setparam <= {} {:#x}", pid, param as usize);
+    syscall_body!(sys_sched_setparam, {
+        if param.is_null() {
+            return Err(LinuxError::EINVAL);
+        }
+        let task = find_task(pid)?;
+        let prio = unsafe { (*param).sched_priority } as isize;
+        if axtask::set_priority(&task, prio) {
+            Ok(0)
+        } else {
+            Err(LinuxError::EINVAL)
+        }
+    })
+}
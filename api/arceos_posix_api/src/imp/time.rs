@@ -35,12 +35,24 @@ impl From<Duration> for ctypes::timeval {
 }
 
 /// Get clock time since booting
-pub unsafe fn sys_clock_gettime(_clk: ctypes::clockid_t, ts: *mut ctypes::timespec) -> c_int {
+///
+/// `CLOCK_PROCESS_CPUTIME_ID` is the CPU time used by all threads, and
+/// `CLOCK_THREAD_CPUTIME_ID` is the CPU time used by the current thread. Other
+/// clocks are the time since booting.
+pub unsafe fn sys_clock_gettime(clk: ctypes::clockid_t, ts: *mut ctypes::timespec) -> c_int {
     syscall_body!(sys_clock_gettime, {
         if ts.is_null() {
             return Err(LinuxError::EFAULT);
         }
-        let now = axhal::time::current_time().into();
+        let now = match clk as u32 {
+            #[cfg(feature = "multitask")]
+            ctypes::CLOCK_PROCESS_CPUTIME_ID => axtask::total_cpu_usage().cpu_time(),
+            #[cfg(feature = "multitask")]
+            ctypes::CLOCK_THREAD_CPUTIME_ID => axtask::current().cpu_usage().cpu_time(),
+            // without `multitask`, the only thread has been running since booting
+            _ => axhal::time::current_time(),
+        }
+        .into();
         unsafe { *ts = now };
         debug!("sys_clock_gettime: {}.{:09}s", now.tv_sec, now.tv_nsec);
         Ok(0)
@@ -51,7 +63,7 @@ pub unsafe fn sys_clock_gettime(_clk: ctypes::clockid_t, ts: *mut ctypes::timesp
 ///
 /// TODO: should be woken by signals, and set errno
 pub unsafe fn sys_nanosleep(req: *const ctypes::timespec, rem: *mut ctypes::timespec) -> c_int {
-    syscall_body!(sys_nanosleep, {
+    let ret = syscall_body!(sys_nanosleep, {
         unsafe {
             if req.is_null() || (*req).tv_nsec < 0 || (*req).tv_nsec > 999999999 {
                 return Err(LinuxError::EINVAL);
@@ -80,5 +92,9 @@ pub unsafe fn sys_nanosleep(req: *const ctypes::timespec, rem: *mut ctypes::time
             return Err(LinuxError::EINTR);
         }
         Ok(0)
-    })
+    });
+    // it is a cancellation point
+    #[cfg(feature = "multitask")]
+    super::pthread::test_cancel();
+    ret
 }
@@ -0,0 +1,369 @@
+//! Timers that notify via file descriptors.
+//!
+//! An armed timer sets an alarm in the axtask timer list (see
+//! [`axtask::set_alarm`]), which counts the expirations and wakes up the
+//! waiting tasks in the timer interrupt handler. Without the timer interrupts,
+//! the timers are polled by [`poll_files`](super::fd_ops::poll_files)
+//! instead, and the tasks blocked in `read` keep yielding until the deadline.
+
+use alloc::sync::{Arc, Weak};
+#[cfg(not(all(feature = "multitask", feature = "irq")))]
+use alloc::vec::Vec;
+use core::ffi::c_int;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+
+use axerrno::{LinuxError, LinuxResult};
+use axhal::time::{current_time, TimeValue};
+use axio::PollState;
+use axsync::spin::SpinNoIrq;
+
+use super::fd_ops::{add_file_like, get_file_like, FileLike, PollSet};
+use crate::ctypes;
+
+/// All the timers, checked by [`poll_timers`].
+#[cfg(not(all(feature = "multitask", feature = "irq")))]
+static TIMERS: SpinNoIrq<Vec<Weak<TimerFd>>> = SpinNoIrq::new(Vec::new());
+
+struct TimerState {
+    /// The next expiration time, or `None` if disarmed.
+    deadline: Option<TimeValue>,
+    /// The period of the timer, or zero if it expires only once.
+    interval: Duration,
+    /// The number of expirations that have not been read.
+    expirations: u64,
+    /// The alarm set at `deadline`.
+    #[cfg(all(feature = "multitask", feature = "irq"))]
+    alarm: Option<axtask::Alarm>,
+    /// Increased each time an alarm is set, so that the alarms replaced
+    /// are ignored if they fire anyway.
+    #[cfg(all(feature = "multitask", feature = "irq"))]
+    generation: u64,
+}
+
+impl TimerState {
+    /// Counts the expirations up to `now`, and advances the deadline.
+    ///
+    /// Returns `true` if there are new expirations.
+    fn update(&mut self, now: TimeValue) -> bool {
+        let Some(deadline) = self.deadline.filter(|&ddl| ddl <= now) else {
+            return false;
+        };
+        let count = if self.interval.is_zero() {
+            self.deadline = None;
+            1
+        } else {
+            let interval = self.interval.as_nanos();
+            let count = (now - deadline).as_nanos() / interval + 1;
+            self.deadline = Some(deadline + Duration::from_nanos((interval * count) as u64));
+            count as u64
+        };
+        self.expirations = self.expirations.saturating_add(count);
+        true
+    }
+
+    /// Returns the time until the next expiration and the interval.
+    fn get_time(&self, now: TimeValue) -> ctypes::itimerspec {
+        let value = self
+            .deadline
+            .map_or(Duration::ZERO, |ddl| ddl.saturating_sub(now));
+        ctypes::itimerspec {
+            it_interval: self.interval.into(),
+            it_value: value.into(),
+        }
+    }
+}
+
+pub struct TimerFd {
+    state: SpinNoIrq<TimerState>,
+    nonblocking: AtomicBool,
+    poll_set: PollSet,
+    #[cfg(feature = "multitask")]
+    wq: axtask::WaitQueue,
+    #[cfg(all(feature = "multitask", feature = "irq"))]
+    this: Weak<TimerFd>,
+}
+
+impl TimerFd {
+    fn new(flags: u32) -> Arc<Self> {
+        #[cfg_attr(
+            not(all(feature = "multitask", feature = "irq")),
+            allow(unused_variables)
+        )]
+        let timer = Arc::new_cyclic(|this| Self {
+            state: SpinNoIrq::new(TimerState {
+                deadline: None,
+                interval: Duration::ZERO,
+                expirations: 0,
+                #[cfg(all(feature = "multitask", feature = "irq"))]
+                alarm: None,
+                #[cfg(all(feature = "multitask", feature = "irq"))]
+                generation: 0,
+            }),
+            nonblocking: AtomicBool::new(flags & ctypes::TFD_NONBLOCK != 0),
+            poll_set: if cfg!(all(feature = "multitask", feature = "irq")) {
+                PollSet::new()
+            } else {
+                PollSet::new_polled()
+            },
+            #[cfg(feature = "multitask")]
+            wq: axtask::WaitQueue::new(),
+            #[cfg(all(feature = "multitask", feature = "irq"))]
+            this: this.clone(),
+        });
+        #[cfg(not(all(feature = "multitask", feature = "irq")))]
+        {
+            let mut timers = TIMERS.lock();
+            timers.retain(|t| t.strong_count() > 0);
+            timers.push(Arc::downgrade(&timer));
+        }
+        timer
+    }
+
+    fn from_fd(fd: c_int) -> LinuxResult<Arc<Self>> {
+        get_file_like(fd)?
+            .into_any()
+            .downcast::<TimerFd>()
+            .map_err(|_| LinuxError::EINVAL)
+    }
+
+    /// Arms or disarms the timer, and returns the old setting.
+    fn set_time(
+        &self,
+        new_value: &ctypes::itimerspec,
+        abstime: bool,
+    ) -> LinuxResult<ctypes::itimerspec> {
+        let value = timespec_to_duration(&new_value.it_value)?;
+        let interval = timespec_to_duration(&new_value.it_interval)?;
+
+        let now = current_time();
+        let mut state = self.state.lock();
+        state.update(now);
+        let old_value = state.get_time(now);
+        state.deadline = if value.is_zero() {
+            None
+        } else if abstime {
+            // Both `CLOCK_REALTIME` and `CLOCK_MONOTONIC` are the time since booting.
+            Some(value)
+        } else {
+            Some(now + value)
+        };
+        state.interval = interval;
+        state.expirations = 0;
+        self.set_alarm(&mut state);
+        drop(state);
+
+        // Let the tasks blocked in `read` wait for the new deadline.
+        #[cfg(feature = "multitask")]
+        self.wq.notify_all(true);
+        Ok(old_value)
+    }
+
+    /// Replaces the alarm with a new one at the deadline, if armed.
+    #[cfg(all(feature = "multitask", feature = "irq"))]
+    fn set_alarm(&self, state: &mut TimerState) {
+        if let Some(alarm) = state.alarm.take() {
+            axtask::cancel_alarm(alarm);
+        }
+        if let Some(deadline) = state.deadline {
+            state.generation += 1;
+            let timer = self.this.clone();
+            let generation = state.generation;
+            state.alarm = Some(axtask::set_alarm(deadline, move |now| {
+                if let Some(timer) = timer.upgrade() {
+                    timer.on_alarm(generation, now);
+                }
+            }));
+        }
+    }
+
+    #[cfg(not(all(feature = "multitask", feature = "irq")))]
+    fn set_alarm(&self, _state: &mut TimerState) {}
+
+    /// Called in the timer interrupt handler when the alarm fires. It counts
+    /// the expirations, sets the alarm for the next one, and wakes up the
+    /// waiting tasks.
+    #[cfg(all(feature = "multitask", feature = "irq"))]
+    fn on_alarm(&self, generation: u64, now: TimeValue) {
+        let mut state = self.state.lock();
+        if state.generation != generation {
+            // The alarm has been replaced.
+            return;
+        }
+        state.alarm = None;
+        state.update(now);
+        self.set_alarm(&mut state);
+        // The expirations may have been counted by the tasks already, but
+        // the watchers still need to be woken up.
+        let expired = state.expirations > 0;
+        drop(state);
+        if expired {
+            self.notify();
+        }
+    }
+
+    /// Counts the expirations up to now, and returns whether the timer can
+    /// be read.
+    fn is_expired(&self) -> bool {
+        let mut state = self.state.lock();
+        state.update(current_time());
+        state.expirations > 0
+    }
+
+    /// Wakes up the tasks waiting for the timer to expire.
+    fn notify(&self) {
+        #[cfg(feature = "multitask")]
+        self.wq.notify_all(true);
+        self.poll_set.wake();
+    }
+}
+
+impl Drop for TimerFd {
+    fn drop(&mut self) {
+        #[cfg(all(feature = "multitask", feature = "irq"))]
+        if let Some(alarm) = self.state.get_mut().alarm.take() {
+            axtask::cancel_alarm(alarm);
+        }
+    }
+}
+
+impl FileLike for TimerFd {
+    fn read(&self, buf: &mut [u8]) -> LinuxResult<usize> {
+        let buf = buf.get_mut(..8).ok_or(LinuxError::EINVAL)?;
+        loop {
+            let mut state = self.state.lock();
+            state.update(current_time());
+            if state.expirations > 0 {
+                let expirations = core::mem::take(&mut state.expirations);
+                buf.copy_from_slice(&expirations.to_ne_bytes());
+                return Ok(8);
+            }
+            #[cfg_attr(not(feature = "multitask"), allow(unused_variables))]
+            let deadline = state.deadline;
+            drop(state);
+            if self.nonblocking.load(Ordering::Relaxed) {
+                return Err(LinuxError::EAGAIN);
+            }
+            #[cfg(feature = "multitask")]
+            super::fd_ops::wait_until(&self.wq, deadline, || self.is_expired());
+            #[cfg(not(feature = "multitask"))]
+            crate::sys_sched_yield();
+        }
+    }
+
+    fn write(&self, _buf: &[u8]) -> LinuxResult<usize> {
+        Err(LinuxError::EINVAL)
+    }
+
+    fn stat(&self) -> LinuxResult<ctypes::stat> {
+        let st_mode = 0o600u32; // rw-------
+        Ok(ctypes::stat {
+            st_ino: 1,
+            st_nlink: 1,
+            st_mode,
+            ..Default::default()
+        })
+    }
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn core::any::Any + Send + Sync> {
+        self
+    }
+
+    fn poll(&self) -> LinuxResult<PollState> {
+        Ok(PollState {
+            readable: self.is_expired(),
+            writable: false,
+            hangup: false,
+        })
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> LinuxResult {
+        self.nonblocking.store(nonblocking, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn poll_set(&self) -> Option<&PollSet> {
+        Some(&self.poll_set)
+    }
+}
+
+/// Counts the expirations of all the timers, and wakes up the waiting tasks
+/// of the expired ones. Called by [`poll_files`](super::fd_ops::poll_files)
+/// if the timers can not expire by alarms.
+#[cfg(not(all(feature = "multitask", feature = "irq")))]
+pub fn poll_timers() {
+    let timers: Vec<_> = TIMERS.lock().iter().filter_map(Weak::upgrade).collect();
+    let now = current_time();
+    for timer in timers {
+        if timer.state.lock().update(now) {
+            timer.notify();
+        }
+    }
+}
+
+fn timespec_to_duration(ts: &ctypes::timespec) -> LinuxResult<Duration> {
+    if ts.tv_sec < 0 || !(0..1_000_000_000).contains(&ts.tv_nsec) {
+        return Err(LinuxError::EINVAL);
+    }
+    Ok((*ts).into())
+}
+
+/// Create a timer that notifies via a file descriptor.
+///
+/// Only `CLOCK_REALTIME` and `CLOCK_MONOTONIC` are supported.
+pub fn sys_timerfd_create(clockid: ctypes::clockid_t, flags: c_int) -> c_int {
+    debug!("sys_timerfd_create <= {} {:#x}", clockid, flags);
+    syscall_body!(sys_timerfd_create, {
+        match clockid as u32 {
+            ctypes::CLOCK_REALTIME | ctypes::CLOCK_MONOTONIC => {}
+            _ => return Err(LinuxError::EINVAL),
+        }
+        let flags = flags as u32;
+        if flags & !(ctypes::TFD_NONBLOCK | ctypes::TFD_CLOEXEC) != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        add_file_like(TimerFd::new(flags))
+    })
+}
+
+/// Arm or disarm the timer referred to by `fd`.
+///
+/// A zero `it_value` disarms the timer. With `TFD_TIMER_ABSTIME`, `it_value`
+/// is an absolute time instead of a relative one. If `old_value` is not null,
+/// the previous setting is returned in it.
+pub unsafe fn sys_timerfd_settime(
+    fd: c_int,
+    flags: c_int,
+    new_value: *const ctypes::itimerspec,
+    old_value: *mut ctypes::itimerspec,
+) -> c_int {
+    debug!("sys_timerfd_settime <= {} {:#x}", fd, flags);
+    syscall_body!(sys_timerfd_settime, {
+        let flags = flags as u32;
+        if flags & !(ctypes::TFD_TIMER_ABSTIME | ctypes::TFD_TIMER_CANCEL_ON_SET) != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        let new_value = unsafe { new_value.as_ref() }.ok_or(LinuxError::EFAULT)?;
+        let old =
+            TimerFd::from_fd(fd)?.set_time(new_value, flags & ctypes::TFD_TIMER_ABSTIME != 0)?;
+        if let Some(old_value) = unsafe { old_value.as_mut() } {
+            *old_value = old;
+        }
+        Ok(0)
+    })
+}
+
+/// Get the time until the next expiration of the timer referred to by `fd`,
+/// and its interval.
+pub unsafe fn sys_timerfd_gettime(fd: c_int, curr_value: *mut ctypes::itimerspec) -> c_int {
+    debug!("sys_timerfd_gettime <= {}", fd);
+    syscall_body!(sys_timerfd_gettime, {
+        let curr_value = unsafe { curr_value.as_mut() }.ok_or(LinuxError::EFAULT)?;
+        let timer = TimerFd::from_fd(fd)?;
+        let now = current_time();
+        let mut state = timer.state.lock();
+        state.update(now);
+        *curr_value = state.get_time(now);
+        Ok(0)
+    })
+}
@@ -33,19 +33,31 @@ pub mod config {
 pub mod ctypes;
 
 pub use imp::io::{sys_read, sys_write, sys_writev};
-pub use imp::resources::{sys_getrlimit, sys_setrlimit};
+pub use imp::resources::{sys_getrlimit, sys_getrusage, sys_setrlimit};
+pub use imp::signal::{sys_kill, sys_sigprocmask};
 pub use imp::sys::sys_sysconf;
 pub use imp::task::{sys_exit, sys_getpid, sys_sched_yield};
 pub use imp::time::{sys_clock_gettime, sys_nanosleep};
 
+#[cfg(feature = "fd")]
+pub use imp::eventfd::sys_eventfd;
 #[cfg(feature = "fd")]
 pub use imp::fd_ops::{sys_close, sys_dup, sys_dup2, sys_fcntl};
 #[cfg(feature = "fs")]
-pub use imp::fs::{sys_fstat, sys_getcwd, sys_lseek, sys_lstat, sys_open, sys_rename, sys_stat};
+pub use imp::fs::{
+    sys_access, sys_chdir, sys_chmod, sys_chown, sys_faccessat, sys_fchmod, sys_fchmodat,
+    sys_fchown, sys_fchownat, sys_fstat, sys_fstatat, sys_fstatfs, sys_fsync, sys_ftruncate,
+    sys_futimens, sys_getcwd, sys_getdents64, sys_lchown, sys_link, sys_lseek, sys_lstat,
+    sys_mkdir, sys_mkdirat, sys_open, sys_openat, sys_readlink, sys_readlinkat, sys_rename,
+    sys_renameat2, sys_rmdir, sys_stat, sys_statfs, sys_symlink, sys_unlink, sys_unlinkat,
+    sys_utimensat,
+};
 #[cfg(feature = "select")]
 pub use imp::io_mpx::sys_select;
 #[cfg(feature = "epoll")]
 pub use imp::io_mpx::{sys_epoll_create, sys_epoll_ctl, sys_epoll_wait};
+#[cfg(feature = "poll")]
+pub use imp::io_mpx::{sys_poll, sys_ppoll};
 #[cfg(feature = "net")]
 pub use imp::net::{
     sys_accept, sys_bind, sys_connect, sys_freeaddrinfo, sys_getaddrinfo, sys_getpeername,
@@ -55,8 +67,38 @@ pub use imp::net::{
 #[cfg(feature = "pipe")]
 pub use imp::pipe::sys_pipe;
 #[cfg(feature = "multitask")]
+pub use imp::pthread::barrier::{
+    sys_pthread_barrier_destroy, sys_pthread_barrier_init, sys_pthread_barrier_wait,
+};
+#[cfg(feature = "multitask")]
+pub use imp::pthread::condvar::{
+    sys_pthread_cond_broadcast, sys_pthread_cond_destroy, sys_pthread_cond_init,
+    sys_pthread_cond_signal, sys_pthread_cond_timedwait, sys_pthread_cond_wait,
+};
+#[cfg(feature = "multitask")]
 pub use imp::pthread::mutex::{
     sys_pthread_mutex_init, sys_pthread_mutex_lock, sys_pthread_mutex_unlock,
 };
 #[cfg(feature = "multitask")]
-pub use imp::pthread::{sys_pthread_create, sys_pthread_exit, sys_pthread_join, sys_pthread_self};
+pub use imp::pthread::rwlock::{
+    sys_pthread_rwlock_destroy, sys_pthread_rwlock_init, sys_pthread_rwlock_rdlock,
+    sys_pthread_rwlock_tryrdlock, sys_pthread_rwlock_trywrlock, sys_pthread_rwlock_unlock,
+    sys_pthread_rwlock_wrlock,
+};
+#[cfg(feature = "multitask")]
+pub use imp::pthread::semaphore::{
+    sys_sem_destroy, sys_sem_getvalue, sys_sem_init, sys_sem_post, sys_sem_timedwait,
+    sys_sem_trywait, sys_sem_wait,
+};
+#[cfg(feature = "multitask")]
+pub use imp::pthread::{
+    sys_pthread_cancel, sys_pthread_create, sys_pthread_detach, sys_pthread_exit, sys_pthread_join,
+    sys_pthread_self, sys_pthread_setcancelstate, sys_pthread_setcanceltype,
+    sys_pthread_testcancel,
+};
+#[cfg(feature = "fd")]
+pub use imp::signalfd::sys_signalfd;
+#[cfg(feature = "multitask")]
+pub use imp::task::{sys_getpriority, sys_sched_getparam, sys_sched_setparam, sys_setpriority};
+#[cfg(feature = "fd")]
+pub use imp::timerfd::{sys_timerfd_create, sys_timerfd_gettime, sys_timerfd_settime};
@@ -30,10 +30,18 @@ pub fn check_null_mut_ptr<T>(ptr: *mut T) -> LinuxResult {
     }
 }
 
+/// Runs the body of a system call, and converts the result to the return
+/// value, i.e., `-errno` on errors.
+///
+/// The time spent in the body is accounted as the kernel time of the task.
 macro_rules! syscall_body {
     ($fn: ident, $($stmt: tt)*) => {{
+        #[cfg(feature = "multitask")]
+        axtask::enter_kernel();
         #[allow(clippy::redundant_closure_call)]
         let res = (|| -> axerrno::LinuxResult<_> { $($stmt)* })();
+        #[cfg(feature = "multitask")]
+        axtask::leave_kernel();
         match res {
             Ok(_) | Err(axerrno::LinuxError::EAGAIN) => debug!(concat!(stringify!($fn), " => {:?}"),  res),
             Err(_) => info!(concat!(stringify!($fn), " => {:?}"), res),
@@ -49,8 +57,12 @@ macro_rules! syscall_body {
 
 macro_rules! syscall_body_no_debug {
     ($($stmt: tt)*) => {{
+        #[cfg(feature = "multitask")]
+        axtask::enter_kernel();
         #[allow(clippy::redundant_closure_call)]
         let res = (|| -> axerrno::LinuxResult<_> { $($stmt)* })();
+        #[cfg(feature = "multitask")]
+        axtask::leave_kernel();
         match res {
             Ok(v) => v as _,
             Err(e) => {
@@ -1,7 +1,10 @@
 #include <assert.h>
+#include <errno.h>
 #include <pthread.h>
+#include <semaphore.h>
 #include <stdio.h>
 #include <stdlib.h>
+#include <time.h>
 #include <unistd.h>
 
 void *ThreadFunc1(void *arg)
@@ -117,6 +120,150 @@ void test_mutex()
     assert(data == NUM_THREADS);
 }
 
+static pthread_cond_t cond = PTHREAD_COND_INITIALIZER;
+static int ready = 0;
+
+void *ThreadFunc4(void *arg)
+{
+    pthread_mutex_lock(&lock);
+    ready = 1;
+    pthread_cond_signal(&cond);
+    pthread_mutex_unlock(&lock);
+    return NULL;
+}
+
+void test_condvar()
+{
+    pthread_t t;
+    if (pthread_create(&t, NULL, ThreadFunc4, NULL) != 0) {
+        puts("pthread create fail");
+        return;
+    }
+
+    pthread_mutex_lock(&lock);
+    while (!ready) pthread_cond_wait(&cond, &lock);
+
+    // nobody signals again
+    struct timespec ts;
+    clock_gettime(CLOCK_REALTIME, &ts);
+    ts.tv_nsec += 10000000;
+    if (ts.tv_nsec >= 1000000000) {
+        ts.tv_sec += 1;
+        ts.tv_nsec -= 1000000000;
+    }
+    int res = pthread_cond_timedwait(&cond, &lock, &ts);
+    pthread_mutex_unlock(&lock);
+    pthread_join(t, NULL);
+
+    printf("test_condvar: ready = %d, timedwait = %s\n", ready,
+           res == ETIMEDOUT ? "ETIMEDOUT" : "OK");
+    assert(ready == 1 && res == ETIMEDOUT);
+}
+
+static pthread_rwlock_t rwlock = PTHREAD_RWLOCK_INITIALIZER;
+
+void *ThreadFunc5(void *arg)
+{
+    pthread_rwlock_wrlock(&rwlock);
+    int value = *(int *)arg;
+    for (int i = 0; i < 1000; i++) getpid();
+    *(int *)arg = value + 1;
+    pthread_rwlock_unlock(&rwlock);
+    return NULL;
+}
+
+void test_rwlock()
+{
+    const int NUM_THREADS = 10;
+    int data = 0;
+    pthread_t t[NUM_THREADS];
+
+    pthread_rwlock_rdlock(&rwlock);
+    assert(pthread_rwlock_tryrdlock(&rwlock) == 0);
+    assert(pthread_rwlock_trywrlock(&rwlock) == EBUSY);
+    for (int i = 0; i < NUM_THREADS; i++) {
+        if (pthread_create(&t[i], NULL, ThreadFunc5, &data) != 0) {
+            puts("pthread create fail");
+            return;
+        }
+    }
+    // writers are blocked by the readers
+    assert(data == 0);
+    pthread_rwlock_unlock(&rwlock);
+    pthread_rwlock_unlock(&rwlock);
+
+    for (int i = 0; i < NUM_THREADS; i++) pthread_join(t[i], NULL);
+    pthread_rwlock_rdlock(&rwlock);
+    printf("test_rwlock: data = %d\n", data);
+    assert(data == NUM_THREADS);
+    pthread_rwlock_unlock(&rwlock);
+}
+
+static sem_t sem;
+
+void *ThreadFunc6(void *arg)
+{
+    for (int i = 0; i < 3; i++) {
+        sem_wait(&sem);
+        __atomic_fetch_add((int *)arg, 1, __ATOMIC_SEQ_CST);
+    }
+    return NULL;
+}
+
+void test_semaphore()
+{
+    int data = 0;
+    pthread_t t;
+
+    sem_init(&sem, 0, 0);
+    assert(sem_trywait(&sem) == -1 && errno == EAGAIN);
+    if (pthread_create(&t, NULL, ThreadFunc6, &data) != 0) {
+        puts("pthread create fail");
+        return;
+    }
+    for (int i = 0; i < 3; i++) sem_post(&sem);
+    pthread_join(t, NULL);
+
+    int value;
+    sem_getvalue(&sem, &value);
+    sem_destroy(&sem);
+    printf("test_semaphore: data = %d, value = %d\n", data, value);
+    assert(data == 3 && value == 0);
+}
+
+static pthread_barrier_t barrier;
+static int serial_threads = 0;
+
+void *ThreadFunc7(void *arg)
+{
+    __atomic_fetch_add((int *)arg, 1, __ATOMIC_SEQ_CST);
+    if (pthread_barrier_wait(&barrier) == PTHREAD_BARRIER_SERIAL_THREAD)
+        __atomic_fetch_add(&serial_threads, 1, __ATOMIC_SEQ_CST);
+    // all threads have arrived
+    assert(__atomic_load_n((int *)arg, __ATOMIC_SEQ_CST) == 5);
+    return NULL;
+}
+
+void test_barrier()
+{
+    const int NUM_THREADS = 5;
+    int data = 0;
+    pthread_t t[NUM_THREADS];
+
+    pthread_barrier_init(&barrier, NULL, NUM_THREADS);
+    for (int i = 0; i < NUM_THREADS; i++) {
+        if (pthread_create(&t[i], NULL, ThreadFunc7, &data) != 0) {
+            puts("pthread create fail");
+            return;
+        }
+    }
+    for (int i = 0; i < NUM_THREADS; i++) pthread_join(t[i], NULL);
+    pthread_barrier_destroy(&barrier);
+
+    printf("test_barrier: data = %d, serial threads = %d\n", data, serial_threads);
+    assert(serial_threads == 1);
+}
+
 int main()
 {
     pthread_t main_thread = pthread_self();
@@ -125,6 +272,10 @@ int main()
     test_create_join();
     test_create_exit();
     test_mutex();
+    test_condvar();
+    test_rwlock();
+    test_semaphore();
+    test_barrier();
     puts("(C)Pthread basic tests run OK!");
 
     return 0;
@@ -0,0 +1,48 @@
+#include <assert.h>
+#include <poll.h>
+#include <signal.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/signalfd.h>
+#include <unistd.h>
+
+void test_signalfd()
+{
+    sigset_t mask;
+    sigemptyset(&mask);
+    sigaddset(&mask, SIGUSR1);
+    assert(sigprocmask(SIG_BLOCK, &mask, NULL) == 0);
+
+    int fd = signalfd(-1, &mask, SFD_NONBLOCK);
+    assert(fd >= 0);
+    struct pollfd pfd = {.fd = fd, .events = POLLIN};
+    assert(poll(&pfd, 1, 0) == 0);
+
+    assert(raise(SIGUSR1) == 0);
+    assert(poll(&pfd, 1, 0) == 1 && (pfd.revents & POLLIN));
+
+    struct signalfd_siginfo info;
+    assert(read(fd, &info, sizeof(info)) == sizeof(info));
+    assert(info.ssi_signo == SIGUSR1);
+    assert(read(fd, &info, sizeof(info)) < 0);
+    close(fd);
+    printf("test_signalfd: ssi_signo = %u\n", info.ssi_signo);
+}
+
+void test_default_action()
+{
+    assert(kill(getpid(), SIGCHLD) == 0);
+    assert(kill(getpid(), SIGSTOP) < 0);
+    puts("test_default_action: OK");
+}
+
+int main()
+{
+    test_signalfd();
+    test_default_action();
+    puts("(C)signal tests run OK!");
+
+    int ret = kill(getpid(), SIGKILL);
+    printf("kill(SIGKILL) returned %d\n", ret);
+    abort();
+}
@@ -0,0 +1,57 @@
+#include <assert.h>
+#include <dirent.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <sys/statfs.h>
+#include <unistd.h>
+
+#define DEVFS_MAGIC 0x1373
+#define RAMFS_MAGIC 0x01021994
+
+void test_statfs()
+{
+    struct statfs buf;
+    assert(statfs("/", &buf) == 0);
+    assert(buf.f_bsize > 0 && buf.f_bfree <= buf.f_blocks);
+    assert(statfs("/dev/null", &buf) == 0);
+    assert(buf.f_type == DEVFS_MAGIC);
+    printf("test_statfs: f_type = 0x%lx\n", buf.f_type);
+}
+
+void test_fstatfs_file()
+{
+    struct statfs buf;
+    int fd = open("/tmp/statfs.txt", O_RDWR | O_CREAT, 0644);
+    assert(fd >= 0);
+    assert(fstatfs(fd, &buf) == 0);
+    assert(buf.f_type == RAMFS_MAGIC);
+    close(fd);
+    unlink("/tmp/statfs.txt");
+    printf("test_fstatfs_file: f_type = 0x%lx\n", buf.f_type);
+}
+
+void test_fstatfs_dir()
+{
+    struct statfs buf;
+    int fd = open("/tmp", O_RDONLY | O_DIRECTORY);
+    assert(fd >= 0);
+    assert(fstatfs(fd, &buf) == 0);
+    assert(buf.f_type == RAMFS_MAGIC);
+    close(fd);
+
+    DIR *dir = opendir("/dev");
+    assert(dir != NULL);
+    assert(fstatfs(dirfd(dir), &buf) == 0);
+    assert(buf.f_type == DEVFS_MAGIC);
+    closedir(dir);
+    printf("test_fstatfs_dir: f_type = 0x%lx\n", buf.f_type);
+}
+
+int main()
+{
+    test_statfs();
+    test_fstatfs_file();
+    test_fstatfs_dir();
+    puts("(C)statfs tests run OK!");
+    return 0;
+}
@@ -19,6 +19,7 @@ type CmdHandler = fn(&str);
 const CMD_TABLE: &[(&str, CmdHandler)] = &[
     ("cat", do_cat),
     ("cd", do_cd),
+    ("df", do_df),
     ("echo", do_echo),
     ("exit", do_exit),
     ("help", do_help),
@@ -245,6 +246,56 @@ fn do_pwd(_args: &str) {
     println!("{}", path_to_str!(pwd));
 }
 
+#[cfg(feature = "axstd")]
+fn do_df(args: &str) {
+    use std::os::arceos::api::fs::{ax_mount_points, ax_statfs};
+
+    fn df_one(path: &str) -> io::Result<()> {
+        let info = ax_statfs(path)?;
+        let to_kb = |blocks: u64| blocks * info.block_size() / 1024;
+        let total = to_kb(info.blocks());
+        let used = to_kb(info.blocks() - info.blocks_free());
+        let avail = to_kb(info.blocks_avail());
+        let usage = if used + avail > 0 {
+            std::format!("{}%", (used * 100).div_ceil(used + avail))
+        } else {
+            String::from("-")
+        };
+        println!(
+            "{:<#10x} {:>10} {:>10} {:>10} {:>4} {}",
+            info.fs_type(),
+            total,
+            used,
+            avail,
+            usage,
+            path
+        );
+        Ok(())
+    }
+
+    let mount_points;
+    let paths = if args.is_empty() {
+        mount_points = ax_mount_points();
+        mount_points.iter().map(String::as_str).collect::<Vec<_>>()
+    } else {
+        args.split_whitespace().collect()
+    };
+    println!(
+        "{:<10} {:>10} {:>10} {:>10} {:>4} {}",
+        "Filesystem", "1K-blocks", "Used", "Available", "Use%", "Mounted on"
+    );
+    for path in paths {
+        if let Err(e) = df_one(path) {
+            print_err!("df", path, e);
+        }
+    }
+}
+
+#[cfg(not(feature = "axstd"))]
+fn do_df(_args: &str) {
+    print_err!("df", "not supported");
+}
+
 fn do_uname(_args: &str) {
     let arch = option_env!("AX_ARCH").unwrap_or("");
     let platform = option_env!("AX_PLATFORM").unwrap_or("");
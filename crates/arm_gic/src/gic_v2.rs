@@ -4,7 +4,7 @@
 
 use core::ptr::NonNull;
 
-use crate::{TriggerMode, GIC_MAX_IRQ, SPI_RANGE};
+use crate::{TriggerMode, GIC_MAX_IRQ, SGI_RANGE, SPI_RANGE};
 use tock_registers::interfaces::{Readable, Writeable};
 use tock_registers::register_structs;
 use tock_registers::registers::{ReadOnly, ReadWrite, WriteOnly};
@@ -177,6 +177,17 @@ impl GicDistributor {
         }
     }
 
+    /// Sends the given SGI to the CPU interface `target_cpu`. (write GICD_SGIR)
+    pub fn send_sgi(&mut self, vector: usize, target_cpu: usize) {
+        if vector >= SGI_RANGE.end || target_cpu >= 8 {
+            return;
+        }
+        // TargetListFilter = 0b00: forward to the CPUs in CPUTargetList
+        self.regs()
+            .SGIR
+            .set((1 << (16 + target_cpu)) | vector as u32);
+    }
+
     /// Initializes the GIC distributor.
     ///
     /// It disables all interrupts, sets the target of all SPIs to CPU 0,
@@ -41,8 +41,12 @@ pub enum AxError {
     ConnectionRefused,
     /// The connection was reset by the remote server.
     ConnectionReset,
+    /// An operation attempted to link or rename across filesystems.
+    CrossesDevices,
     /// A non-empty directory was specified where an empty directory was expected.
     DirectoryNotEmpty,
+    /// Too many levels of symbolic links were encountered during path resolution.
+    FilesystemLoop,
     /// Data not valid for the operation were encountered.
     ///
     /// Unlike [`InvalidInput`], this typically means that the operation
@@ -194,7 +198,9 @@ impl AxError {
             AlreadyExists => "Entity already exists",
             ConnectionRefused => "Connection refused",
             ConnectionReset => "Connection reset",
+            CrossesDevices => "Cross-device link",
             DirectoryNotEmpty => "Directory not empty",
+            FilesystemLoop => "Too many levels of symbolic links",
             InvalidData => "Invalid data",
             InvalidInput => "Invalid input parameter",
             Io => "I/O error",
@@ -247,7 +253,9 @@ impl From<AxError> for LinuxError {
             BadAddress | BadState => LinuxError::EFAULT,
             ConnectionRefused => LinuxError::ECONNREFUSED,
             ConnectionReset => LinuxError::ECONNRESET,
+            CrossesDevices => LinuxError::EXDEV,
             DirectoryNotEmpty => LinuxError::ENOTEMPTY,
+            FilesystemLoop => LinuxError::ELOOP,
             InvalidInput | InvalidData => LinuxError::EINVAL,
             Io => LinuxError::EIO,
             IsADirectory => LinuxError::EISDIR,
@@ -283,7 +291,7 @@ mod tests {
     #[test]
     fn test_try_from() {
         let max_code = core::mem::variant_count::<AxError>() as i32;
-        assert_eq!(max_code, 22);
+        assert_eq!(max_code, 24);
         assert_eq!(max_code, AxError::WriteZero.code());
 
         assert_eq!(AxError::AddrInUse.code(), 1);
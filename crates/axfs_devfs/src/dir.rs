@@ -1,4 +1,5 @@
 use alloc::collections::BTreeMap;
+use alloc::string::String;
 use alloc::sync::{Arc, Weak};
 use axfs_vfs::{VfsDirEntry, VfsNodeAttr, VfsNodeOps, VfsNodeRef, VfsNodeType};
 use axfs_vfs::{VfsError, VfsResult};
@@ -9,7 +10,7 @@ use spin::RwLock;
 /// It implements [`axfs_vfs::VfsNodeOps`].
 pub struct DirNode {
     parent: RwLock<Weak<dyn VfsNodeOps>>,
-    children: RwLock<BTreeMap<&'static str, VfsNodeRef>>,
+    children: RwLock<BTreeMap<String, VfsNodeRef>>,
 }
 
 impl DirNode {
@@ -25,23 +26,54 @@ impl DirNode {
         *self.parent.write() = parent.map_or(Weak::<Self>::new() as _, Arc::downgrade);
     }
 
-    /// Create a subdirectory at this directory.
-    pub fn mkdir(self: &Arc<Self>, name: &'static str) -> Arc<Self> {
+    /// Create a subdirectory at this directory, or returns the existing one.
+    pub fn mkdir(self: &Arc<Self>, name: &str) -> Arc<Self> {
+        let mut children = self.children.write();
+        if let Some(node) = children.get(name).filter(|node| node.as_any().is::<Self>()) {
+            // SAFETY: the node is checked to be a `DirNode`
+            return unsafe { Arc::from_raw(Arc::into_raw(node.clone()) as *const Self) };
+        }
         let parent = self.clone() as VfsNodeRef;
         let node = Self::new(Some(&parent));
-        self.children.write().insert(name, node.clone());
+        children.insert(name.into(), node.clone());
         node
     }
 
-    /// Add a node to this directory.
-    pub fn add(&self, name: &'static str, node: VfsNodeRef) {
-        self.children.write().insert(name, node);
+    /// Add a node to this directory, replacing the existing one with the
+    /// same name.
+    pub fn add(&self, name: &str, node: VfsNodeRef) {
+        self.children.write().insert(name.into(), node);
+    }
+
+    /// Removes the node from this directory, returns it if it exists.
+    pub fn remove_node(&self, name: &str) -> Option<VfsNodeRef> {
+        self.children.write().remove(name)
+    }
+
+    /// Returns the number of nodes in this directory tree, including itself.
+    pub(crate) fn node_count(&self) -> u64 {
+        let children = self.children.read();
+        children.values().fold(1, |count, node| {
+            match node.as_any().downcast_ref::<DirNode>() {
+                Some(dir) => count + dir.node_count(),
+                None => count + 1,
+            }
+        })
     }
 }
 
 impl VfsNodeOps for DirNode {
     fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
-        Ok(VfsNodeAttr::new_dir(4096, 0))
+        // each subdirectory has a link `..` to this directory
+        let subdirs = self
+            .children
+            .read()
+            .values()
+            .filter(|node| node.as_any().is::<DirNode>())
+            .count();
+        Ok(VfsNodeAttr::new_dir(4096, 0)
+            .with_ino(crate::node_ino(self))
+            .with_nlink(2 + subdirs as u64))
     }
 
     fn parent(&self) -> Option<VfsNodeRef> {
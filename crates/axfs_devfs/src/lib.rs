@@ -8,6 +8,7 @@ extern crate alloc;
 
 mod dir;
 mod null;
+mod random;
 mod zero;
 
 #[cfg(test)]
@@ -15,12 +16,21 @@ mod tests;
 
 pub use self::dir::DirNode;
 pub use self::null::NullDev;
+pub use self::random::RandomDev;
 pub use self::zero::ZeroDev;
 
 use alloc::sync::Arc;
-use axfs_vfs::{VfsNodeRef, VfsOps, VfsResult};
+use axfs_vfs::{FileSystemInfo, VfsNodeRef, VfsOps, VfsResult};
 use spin::once::Once;
 
+/// Magic number of the device filesystem, the same as `DEVFS_SUPER_MAGIC` in
+/// Linux.
+pub const DEVFS_MAGIC: u64 = 0x1373;
+/// Block size reported by [`VfsOps::statfs`].
+const BLOCK_SIZE: u64 = 4096;
+/// Maximum length of file names reported by [`VfsOps::statfs`].
+const MAX_NAME_LEN: u64 = 255;
+
 /// A device filesystem that implements [`axfs_vfs::VfsOps`].
 pub struct DeviceFileSystem {
     parent: Once<VfsNodeRef>,
@@ -36,17 +46,32 @@ impl DeviceFileSystem {
         }
     }
 
-    /// Create a subdirectory at the root directory.
-    pub fn mkdir(&self, name: &'static str) -> Arc<DirNode> {
+    /// Create a subdirectory at the root directory, or returns the existing
+    /// one.
+    pub fn mkdir(&self, name: &str) -> Arc<DirNode> {
         self.root.mkdir(name)
     }
 
     /// Add a node to the root directory.
     ///
     /// The node must implement [`axfs_vfs::VfsNodeOps`], and be wrapped in [`Arc`].
-    pub fn add(&self, name: &'static str, node: VfsNodeRef) {
+    pub fn add(&self, name: &str, node: VfsNodeRef) {
         self.root.add(name, node);
     }
+
+    /// Add a node at `path` relative to the root directory, such as
+    /// `input/event0`. Missing parent directories are created.
+    pub fn add_path(&self, path: &str, node: VfsNodeRef) {
+        let mut dir = self.root.clone();
+        let mut names = path.split('/').filter(|name| !name.is_empty()).peekable();
+        while let Some(name) = names.next() {
+            if names.peek().is_none() {
+                dir.add(name, node);
+                return;
+            }
+            dir = dir.mkdir(name);
+        }
+    }
 }
 
 impl VfsOps for DeviceFileSystem {
@@ -59,11 +84,25 @@ impl VfsOps for DeviceFileSystem {
         Ok(())
     }
 
+    fn statfs(&self) -> VfsResult<FileSystemInfo> {
+        // device nodes take no space, and cannot be created by users
+        let files = self.root.node_count();
+        Ok(FileSystemInfo::new(DEVFS_MAGIC, BLOCK_SIZE, MAX_NAME_LEN).with_files(files, 0))
+    }
+
     fn root_dir(&self) -> VfsNodeRef {
         self.root.clone()
     }
 }
 
+/// Returns the inode number of a node in the device filesystem.
+///
+/// Device nodes are created once and live as long as the filesystem, so their
+/// addresses are used as unique and stable inode numbers.
+pub(crate) fn node_ino<T>(node: &T) -> u64 {
+    node as *const T as usize as u64
+}
+
 impl Default for DeviceFileSystem {
     fn default() -> Self {
         Self::new()
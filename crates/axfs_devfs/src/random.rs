@@ -0,0 +1,159 @@
+use alloc::sync::Arc;
+use axfs_vfs::{VfsNodeAttr, VfsNodeOps, VfsNodePerm, VfsNodeType, VfsResult};
+use spin::Mutex;
+
+/// Number of bytes of a ChaCha20 key.
+const KEY_LEN: usize = 32;
+/// Number of bytes of a ChaCha20 block.
+const BLOCK_LEN: usize = 64;
+
+/// A random device behaves like `/dev/random` and `/dev/urandom`.
+///
+/// Random bytes are generated by a CSPRNG based on the ChaCha20 stream
+/// cipher. The key is replaced by the generator itself after each read, so
+/// that previous outputs cannot be recovered from the current state. Data
+/// written to the device is mixed into the key.
+///
+/// The output is only as unpredictable as the seed and the entropy source.
+/// Without a hardware random number generator, they are usually derived from
+/// timers, so the device must not be relied on for cryptographic purposes.
+///
+/// Clones of the device share the same generator.
+#[derive(Clone)]
+pub struct RandomDev {
+    rng: Arc<Mutex<ChaChaRng>>,
+    entropy: Option<fn() -> u64>,
+}
+
+/// The ChaCha20 generator with fast key erasure.
+pub(crate) struct ChaChaRng {
+    key: [u32; 8],
+}
+
+impl RandomDev {
+    /// Create a new random device with the seed.
+    ///
+    /// The seed should contain enough entropy, since the output is fully
+    /// determined by it and the data written later.
+    pub fn new(seed: &[u8]) -> Self {
+        let mut rng = ChaChaRng { key: [0; 8] };
+        rng.mix(seed);
+        Self {
+            rng: Arc::new(Mutex::new(rng)),
+            entropy: None,
+        }
+    }
+
+    /// Sets a source of entropy that is mixed into the key before each read,
+    /// such as a high-resolution timer.
+    pub fn with_entropy(self, entropy: fn() -> u64) -> Self {
+        Self {
+            entropy: Some(entropy),
+            ..self
+        }
+    }
+
+    /// Fills `buf` with random bytes.
+    pub fn fill_bytes(&self, buf: &mut [u8]) {
+        let mut rng = self.rng.lock();
+        if let Some(entropy) = self.entropy {
+            rng.mix(&entropy().to_le_bytes());
+        }
+        rng.fill_bytes(buf);
+    }
+}
+
+impl ChaChaRng {
+    /// Returns the ChaCha20 block of `key` at `counter` and `nonce`.
+    pub(crate) fn block(key: &[u32; 8], counter: u64, nonce: [u32; 2]) -> [u8; BLOCK_LEN] {
+        let mut state = [0u32; 16];
+        state[..4].copy_from_slice(&[0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574]);
+        state[4..12].copy_from_slice(key);
+        state[12] = counter as u32;
+        state[13] = (counter >> 32) as u32;
+        state[14..].copy_from_slice(&nonce);
+
+        let mut x = state;
+        for _ in 0..10 {
+            quarter_round(&mut x, 0, 4, 8, 12);
+            quarter_round(&mut x, 1, 5, 9, 13);
+            quarter_round(&mut x, 2, 6, 10, 14);
+            quarter_round(&mut x, 3, 7, 11, 15);
+            quarter_round(&mut x, 0, 5, 10, 15);
+            quarter_round(&mut x, 1, 6, 11, 12);
+            quarter_round(&mut x, 2, 7, 8, 13);
+            quarter_round(&mut x, 3, 4, 9, 14);
+        }
+
+        let mut out = [0; BLOCK_LEN];
+        for (i, chunk) in out.chunks_exact_mut(4).enumerate() {
+            chunk.copy_from_slice(&x[i].wrapping_add(state[i]).to_le_bytes());
+        }
+        out
+    }
+
+    /// Replaces the key with the first bytes of `block`.
+    fn rekey(&mut self, block: &[u8; BLOCK_LEN]) {
+        for (k, chunk) in self.key.iter_mut().zip(block[..KEY_LEN].chunks_exact(4)) {
+            *k = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
+    }
+
+    /// Mixes `data` into the key.
+    fn mix(&mut self, data: &[u8]) {
+        for chunk in data.chunks(KEY_LEN) {
+            for (i, b) in chunk.iter().enumerate() {
+                self.key[i / 4] ^= (*b as u32) << (i % 4 * 8);
+            }
+            // the nonce is different from the one used for outputs
+            let block = Self::block(&self.key, 0, [0, 1]);
+            self.rekey(&block);
+        }
+    }
+
+    fn fill_bytes(&mut self, buf: &mut [u8]) {
+        // the first block is used for the next key
+        for (counter, chunk) in (1..).zip(buf.chunks_mut(BLOCK_LEN)) {
+            let block = Self::block(&self.key, counter, [0, 0]);
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+        let block = Self::block(&self.key, 0, [0, 0]);
+        self.rekey(&block);
+    }
+}
+
+fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(16);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(12);
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(8);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(7);
+}
+
+impl VfsNodeOps for RandomDev {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        Ok(
+            VfsNodeAttr::new(VfsNodePerm::default_file(), VfsNodeType::CharDevice, 0, 0)
+                .with_ino(crate::node_ino(self)),
+        )
+    }
+
+    fn read_at(&self, _offset: u64, buf: &mut [u8]) -> VfsResult<usize> {
+        self.fill_bytes(buf);
+        Ok(buf.len())
+    }
+
+    fn write_at(&self, _offset: u64, buf: &[u8]) -> VfsResult<usize> {
+        self.rng.lock().mix(buf);
+        Ok(buf.len())
+    }
+
+    fn truncate(&self, _size: u64) -> VfsResult {
+        Ok(())
+    }
+
+    axfs_vfs::impl_vfs_non_dir_default! {}
+}
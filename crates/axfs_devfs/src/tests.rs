@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use axfs_vfs::{VfsError, VfsNodeType, VfsResult};
+use axfs_vfs::{VfsDirEntry, VfsError, VfsNodeOps, VfsNodeRef, VfsNodeType, VfsResult};
 
 use crate::*;
 
@@ -56,6 +56,17 @@ fn test_devfs_ops(devfs: &DeviceFileSystem) -> VfsResult {
         VfsNodeType::Dir
     );
 
+    // inode numbers are unique and stable
+    let root = devfs.root_dir();
+    let null_ino = root.clone().lookup("null")?.get_attr()?.ino();
+    assert_eq!(root.clone().lookup("null")?.get_attr()?.ino(), null_ino);
+    assert_ne!(root.clone().lookup("zero")?.get_attr()?.ino(), null_ino);
+    assert_ne!(
+        root.clone().lookup("foo/bar/f1")?.get_attr()?.ino(),
+        null_ino
+    );
+    assert_eq!(root.get_attr()?.nlink(), 3);
+
     Ok(())
 }
 
@@ -112,4 +123,88 @@ fn test_devfs() {
 
     test_devfs_ops(&devfs).unwrap();
     test_get_parent(&devfs).unwrap();
+
+    let info = devfs.statfs().unwrap();
+    assert_eq!(info.fs_type(), 0x1373);
+    assert_eq!(info.blocks(), 0);
+    assert_eq!((info.files(), info.files_free()), (7, 0));
+}
+
+#[test]
+fn test_chacha20_block() {
+    // test vector in section 2.3.2 of RFC 7539
+    let key = core::array::from_fn(|i| u32::from_le_bytes([0, 1, 2, 3].map(|b| (i * 4 + b) as u8)));
+    let block = crate::random::ChaChaRng::block(&key, 0x0900_0000 << 32 | 1, [0x4a00_0000, 0]);
+    assert_eq!(
+        block[..16],
+        [
+            0x10, 0xf1, 0xe7, 0xe4, 0xd1, 0x3b, 0x59, 0x15, 0x50, 0x0f, 0xdd, 0x1f, 0xa3, 0x20,
+            0x71, 0xc4
+        ]
+    );
+    assert_eq!(block[60..], [0xa2, 0x50, 0x3c, 0x4e]);
+}
+
+#[test]
+fn test_random() -> VfsResult {
+    let devfs = DeviceFileSystem::new();
+    let random = RandomDev::new(b"seed");
+    devfs.add("random", Arc::new(random.clone()));
+    devfs.add("urandom", Arc::new(random));
+
+    let root = devfs.root_dir();
+    let random = root.clone().lookup("random")?;
+    let urandom = root.lookup("urandom")?;
+    assert_eq!(random.get_attr()?.file_type(), VfsNodeType::CharDevice);
+
+    let mut buf1 = [0; 100];
+    let mut buf2 = [0; 100];
+    assert_eq!(random.read_at(0, &mut buf1)?, 100);
+    assert_eq!(urandom.read_at(0, &mut buf2)?, 100);
+    assert_ne!(buf1, buf2);
+    assert_ne!(buf1, [0; 100]);
+
+    // the same seed generates the same bytes
+    let mut buf3 = [0; 100];
+    RandomDev::new(b"seed").fill_bytes(&mut buf3);
+    assert_eq!(buf1, buf3);
+    let other = RandomDev::new(b"seed");
+    assert_eq!(other.write_at(0, b"more entropy")?, 12);
+    other.fill_bytes(&mut buf3);
+    assert_ne!(buf1, buf3);
+    Ok(())
+}
+
+#[test]
+fn test_add_path() -> VfsResult {
+    let devfs = DeviceFileSystem::new();
+    devfs.add_path("input/event0", Arc::new(ZeroDev));
+    devfs.add_path("/input//event1", Arc::new(NullDev));
+    let input = devfs.mkdir("input");
+    input.add("mice", Arc::new(NullDev));
+
+    let root = devfs.root_dir();
+    assert!(Arc::ptr_eq(
+        &(input.clone() as VfsNodeRef),
+        &root.clone().lookup("input")?
+    ));
+    let mut names = Vec::new();
+    let mut dirents: [_; 8] = core::array::from_fn(|_| VfsDirEntry::default());
+    let n = input.read_dir(0, &mut dirents)?;
+    for ent in &dirents[..n] {
+        names.push(
+            core::str::from_utf8(ent.name_as_bytes())
+                .unwrap()
+                .to_string(),
+        );
+    }
+    assert_eq!(names, [".", "..", "event0", "event1", "mice"]);
+    assert_eq!(
+        root.clone().lookup("input/event0")?.read_at(0, &mut [1])?,
+        1
+    );
+
+    assert!(input.remove_node("mice").is_some());
+    assert_eq!(root.lookup("input/mice").err(), Some(VfsError::NotFound));
+    Ok(())
 }
@@ -1,26 +1,30 @@
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::sync::{Arc, Weak};
 use alloc::{string::String, vec::Vec};
 
 use axfs_vfs::{VfsDirEntry, VfsNodeAttr, VfsNodeOps, VfsNodeRef, VfsNodeType};
-use axfs_vfs::{VfsError, VfsResult};
+use axfs_vfs::{VfsError, VfsResult, VfsSetAttr};
 use spin::RwLock;
 
 use crate::file::FileNode;
+use crate::meta::{Clock, NodeMeta};
+use crate::symlink::SymlinkNode;
 
 /// The directory node in the RAM filesystem.
 ///
 /// It implements [`axfs_vfs::VfsNodeOps`].
 pub struct DirNode {
     this: Weak<DirNode>,
+    meta: NodeMeta,
     parent: RwLock<Weak<dyn VfsNodeOps>>,
     children: RwLock<BTreeMap<String, VfsNodeRef>>,
 }
 
 impl DirNode {
-    pub(super) fn new(parent: Option<Weak<dyn VfsNodeOps>>) -> Arc<Self> {
+    pub(super) fn new(parent: Option<Weak<dyn VfsNodeOps>>, dev: u64, clock: Clock) -> Arc<Self> {
         Arc::new_cyclic(|this| Self {
             this: this.clone(),
+            meta: NodeMeta::new(dev, clock),
             parent: RwLock::new(parent.unwrap_or_else(|| Weak::<Self>::new())),
             children: RwLock::new(BTreeMap::new()),
         })
@@ -46,15 +50,72 @@ impl DirNode {
             log::error!("AlreadyExists {}", name);
             return Err(VfsError::AlreadyExists);
         }
+        let (dev, clock) = (self.meta.dev(), self.meta.clock());
         let node: VfsNodeRef = match ty {
-            VfsNodeType::File => Arc::new(FileNode::new()),
-            VfsNodeType::Dir => Self::new(Some(self.this.clone())),
+            VfsNodeType::File => Arc::new(FileNode::new(dev, clock)),
+            VfsNodeType::Dir => Self::new(Some(self.this.clone()), dev, clock),
             _ => return Err(VfsError::Unsupported),
         };
         self.children.write().insert(name.into(), node);
+        self.meta.touch_mtime();
         Ok(())
     }
 
+    /// Creates a new symbolic link with the given name in this directory,
+    /// which points to `target`.
+    pub fn create_symlink(&self, name: &str, target: &str) -> VfsResult {
+        if self.exist(name) {
+            log::error!("AlreadyExists {}", name);
+            return Err(VfsError::AlreadyExists);
+        }
+        let node = Arc::new(SymlinkNode::new(target, self.meta.dev(), self.meta.clock()));
+        self.children.write().insert(name.into(), node);
+        self.meta.touch_mtime();
+        Ok(())
+    }
+
+    /// Adds a hard link with the given name in this directory, which refers
+    /// to the existing `node`.
+    ///
+    /// Only files and symbolic links of the same RAM filesystem can be linked.
+    pub fn link_node(&self, name: &str, node: VfsNodeRef) -> VfsResult {
+        if self.exist(name) {
+            log::error!("AlreadyExists {}", name);
+            return Err(VfsError::AlreadyExists);
+        }
+        if node.as_any().is::<DirNode>() {
+            return Err(VfsError::PermissionDenied); // hard links to directories are not allowed
+        }
+        let meta = node_meta(&node)
+            .filter(|meta| meta.dev() == self.meta.dev())
+            .ok_or(VfsError::CrossesDevices)?;
+        meta.inc_nlink();
+        self.children.write().insert(name.into(), node);
+        self.meta.touch_mtime();
+        Ok(())
+    }
+
+    /// Returns the total size of files and the number of nodes in this
+    /// directory tree, including itself.
+    ///
+    /// Nodes in `seen` are skipped, so hard links are only counted once.
+    pub(crate) fn usage(&self, seen: &mut BTreeSet<u64>) -> (u64, u64) {
+        let (mut size, mut count) = (0, 1);
+        for node in self.children.read().values() {
+            if let Some(dir) = node.as_any().downcast_ref::<DirNode>() {
+                let (dir_size, dir_count) = dir.usage(seen);
+                size += dir_size;
+                count += dir_count;
+            } else if let Ok(attr) = node.get_attr() {
+                if seen.insert(attr.ino()) {
+                    size += attr.size();
+                    count += 1;
+                }
+            }
+        }
+        (size, count)
+    }
+
     /// Removes a node by the given name in this directory.
     pub fn remove_node(&self, name: &str) -> VfsResult {
         let mut children = self.children.write();
@@ -63,15 +124,31 @@ impl DirNode {
             if !dir.children.read().is_empty() {
                 return Err(VfsError::DirectoryNotEmpty);
             }
+        } else if let Some(meta) = node_meta(node) {
+            meta.dec_nlink();
         }
         children.remove(name);
+        self.meta.touch_mtime();
         Ok(())
     }
 }
 
 impl VfsNodeOps for DirNode {
     fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
-        Ok(VfsNodeAttr::new_dir(4096, 0))
+        // each subdirectory has a link `..` to this directory
+        let subdirs = self
+            .children
+            .read()
+            .values()
+            .filter(|node| node.as_any().is::<DirNode>())
+            .count();
+        let attr = self.meta.fill(VfsNodeAttr::new_dir(4096, 0));
+        Ok(attr.with_nlink(2 + subdirs as u64))
+    }
+
+    fn set_attr(&self, attr: &VfsSetAttr) -> VfsResult {
+        self.meta.set_attr(attr);
+        Ok(())
     }
 
     fn parent(&self) -> Option<VfsNodeRef> {
@@ -165,9 +242,70 @@ impl VfsNodeOps for DirNode {
         }
     }
 
+    fn symlink(&self, path: &str, target: &str) -> VfsResult {
+        log::debug!("symlink at ramfs: {} -> {}", path, target);
+        let (name, rest) = split_path(path);
+        if let Some(rest) = rest {
+            match name {
+                "" | "." => self.symlink(rest, target),
+                ".." => self
+                    .parent()
+                    .ok_or(VfsError::NotFound)?
+                    .symlink(rest, target),
+                _ => {
+                    let subdir = self
+                        .children
+                        .read()
+                        .get(name)
+                        .ok_or(VfsError::NotFound)?
+                        .clone();
+                    subdir.symlink(rest, target)
+                }
+            }
+        } else if name.is_empty() || name == "." || name == ".." {
+            Err(VfsError::AlreadyExists)
+        } else {
+            self.create_symlink(name, target)
+        }
+    }
+
+    fn link(&self, path: &str, node: VfsNodeRef) -> VfsResult {
+        log::debug!("link at ramfs: {}", path);
+        let (name, rest) = split_path(path);
+        if let Some(rest) = rest {
+            match name {
+                "" | "." => self.link(rest, node),
+                ".." => self.parent().ok_or(VfsError::NotFound)?.link(rest, node),
+                _ => {
+                    let subdir = self
+                        .children
+                        .read()
+                        .get(name)
+                        .ok_or(VfsError::NotFound)?
+                        .clone();
+                    subdir.link(rest, node)
+                }
+            }
+        } else if name.is_empty() || name == "." || name == ".." {
+            Err(VfsError::AlreadyExists)
+        } else {
+            self.link_node(name, node)
+        }
+    }
+
     axfs_vfs::impl_vfs_dir_default! {}
 }
 
+/// Returns the metadata of a non-directory node in the RAM filesystem.
+fn node_meta(node: &VfsNodeRef) -> Option<&NodeMeta> {
+    let any = node.as_any();
+    if let Some(file) = any.downcast_ref::<FileNode>() {
+        Some(file.meta())
+    } else {
+        any.downcast_ref::<SymlinkNode>().map(SymlinkNode::meta)
+    }
+}
+
 fn split_path(path: &str) -> (&str, Option<&str>) {
     let trimmed_path = path.trim_start_matches('/');
     trimmed_path.find('/').map_or((trimmed_path, None), |n| {
@@ -1,25 +1,39 @@
 use alloc::vec::Vec;
-use axfs_vfs::{impl_vfs_non_dir_default, VfsNodeAttr, VfsNodeOps, VfsResult};
+use axfs_vfs::{impl_vfs_non_dir_default, VfsNodeAttr, VfsNodeOps, VfsResult, VfsSetAttr};
 use spin::RwLock;
 
+use crate::meta::{Clock, NodeMeta};
+
 /// The file node in the RAM filesystem.
 ///
 /// It implements [`axfs_vfs::VfsNodeOps`].
 pub struct FileNode {
+    meta: NodeMeta,
     content: RwLock<Vec<u8>>,
 }
 
 impl FileNode {
-    pub(super) const fn new() -> Self {
+    pub(super) fn new(dev: u64, clock: Clock) -> Self {
         Self {
+            meta: NodeMeta::new(dev, clock),
             content: RwLock::new(Vec::new()),
         }
     }
+
+    pub(super) fn meta(&self) -> &NodeMeta {
+        &self.meta
+    }
 }
 
 impl VfsNodeOps for FileNode {
     fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
-        Ok(VfsNodeAttr::new_file(self.content.read().len() as _, 0))
+        let size = self.content.read().len() as _;
+        Ok(self.meta.fill(VfsNodeAttr::new_file(size, 0)))
+    }
+
+    fn set_attr(&self, attr: &VfsSetAttr) -> VfsResult {
+        self.meta.set_attr(attr);
+        Ok(())
     }
 
     fn truncate(&self, size: u64) -> VfsResult {
@@ -29,6 +43,7 @@ impl VfsNodeOps for FileNode {
         } else {
             content.resize(size as _, 0);
         }
+        self.meta.touch_mtime();
         Ok(())
     }
 
@@ -38,6 +53,7 @@ impl VfsNodeOps for FileNode {
         let end = content.len().min(offset as usize + buf.len());
         let src = &content[start..end];
         buf[..src.len()].copy_from_slice(src);
+        self.meta.touch_atime();
         Ok(src.len())
     }
 
@@ -49,6 +65,7 @@ impl VfsNodeOps for FileNode {
         }
         let dst = &mut content[offset..offset + buf.len()];
         dst.copy_from_slice(&buf[..dst.len()]);
+        self.meta.touch_mtime();
         Ok(buf.len())
     }
 
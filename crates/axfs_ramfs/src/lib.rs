@@ -8,32 +8,63 @@ extern crate alloc;
 
 mod dir;
 mod file;
+mod meta;
+mod symlink;
 
 #[cfg(test)]
 mod tests;
 
 pub use self::dir::DirNode;
 pub use self::file::FileNode;
+pub use self::meta::Clock;
+pub use self::symlink::SymlinkNode;
 
-use alloc::sync::Arc;
-use axfs_vfs::{VfsNodeRef, VfsOps, VfsResult};
+use alloc::{collections::BTreeSet, sync::Arc};
+use axfs_vfs::{FileSystemInfo, VfsNodeRef, VfsOps, VfsResult};
 use spin::once::Once;
 
+/// Magic number of the RAM filesystem, the same as `TMPFS_MAGIC` in Linux.
+pub const RAMFS_MAGIC: u64 = 0x0102_1994;
+/// Block size reported by [`VfsOps::statfs`].
+const BLOCK_SIZE: u64 = 4096;
+/// Maximum length of file names reported by [`VfsOps::statfs`].
+const MAX_NAME_LEN: u64 = 255;
+
 /// A RAM filesystem that implements [`axfs_vfs::VfsOps`].
 pub struct RamFileSystem {
     parent: Once<VfsNodeRef>,
     root: Arc<DirNode>,
+    available_memory: Option<fn() -> usize>,
 }
 
 impl RamFileSystem {
     /// Create a new instance.
+    ///
+    /// All timestamps of the nodes are zero, use [`RamFileSystem::with_clock`]
+    /// to record the real time.
     pub fn new() -> Self {
+        Self::with_clock(meta::zero_clock)
+    }
+
+    /// Create a new instance, which gets the timestamps of the nodes from
+    /// `clock`.
+    pub fn with_clock(clock: Clock) -> Self {
         Self {
             parent: Once::new(),
-            root: DirNode::new(None),
+            root: DirNode::new(None, meta::alloc_dev(), clock),
+            available_memory: None,
         }
     }
 
+    /// Sets the function that returns the free memory in bytes, which is
+    /// reported as free space by [`VfsOps::statfs`].
+    ///
+    /// If not set, the filesystem is reported as full.
+    pub fn with_available_memory(mut self, available_memory: fn() -> usize) -> Self {
+        self.available_memory = Some(available_memory);
+        self
+    }
+
     /// Returns the root directory node in [`Arc<DirNode>`](DirNode).
     pub fn root_dir_node(&self) -> Arc<DirNode> {
         self.root.clone()
@@ -50,6 +81,16 @@ impl VfsOps for RamFileSystem {
         Ok(())
     }
 
+    fn statfs(&self) -> VfsResult<FileSystemInfo> {
+        let (used_bytes, used_files) = self.root.usage(&mut BTreeSet::new());
+        let used = used_bytes.div_ceil(BLOCK_SIZE);
+        let free = self.available_memory.map_or(0, |f| f() as u64 / BLOCK_SIZE);
+        // there is no limit on nodes, assume each new node takes one block
+        Ok(FileSystemInfo::new(RAMFS_MAGIC, BLOCK_SIZE, MAX_NAME_LEN)
+            .with_blocks(used + free, free, free)
+            .with_files(used_files + free, free))
+    }
+
     fn root_dir(&self) -> VfsNodeRef {
         self.root.clone()
     }
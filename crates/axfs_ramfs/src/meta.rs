@@ -0,0 +1,125 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+use axfs_vfs::{VfsNodeAttr, VfsNodePerm, VfsSetAttr, DEFAULT_GID, DEFAULT_UID};
+use spin::RwLock;
+
+/// A function that returns the current time, since the Unix epoch.
+pub type Clock = fn() -> Duration;
+
+/// The clock used if no clock is given, which always returns zero.
+pub(crate) fn zero_clock() -> Duration {
+    Duration::ZERO
+}
+
+static NEXT_INO: AtomicU64 = AtomicU64::new(1);
+static NEXT_DEV: AtomicU64 = AtomicU64::new(1);
+
+/// Allocates a device ID for a new RAM filesystem, which identifies the
+/// filesystem its nodes belong to.
+pub(crate) fn alloc_dev() -> u64 {
+    NEXT_DEV.fetch_add(1, Ordering::Relaxed)
+}
+
+struct MetaInner {
+    mode: Option<VfsNodePerm>,
+    uid: u32,
+    gid: u32,
+    nlink: u64,
+    atime: Duration,
+    mtime: Duration,
+    ctime: Duration,
+}
+
+/// Metadata of a node in the RAM filesystem, which is not determined by its
+/// content.
+pub(crate) struct NodeMeta {
+    ino: u64,
+    dev: u64,
+    clock: Clock,
+    inner: RwLock<MetaInner>,
+}
+
+impl NodeMeta {
+    pub fn new(dev: u64, clock: Clock) -> Self {
+        let now = clock();
+        Self {
+            ino: NEXT_INO.fetch_add(1, Ordering::Relaxed),
+            dev,
+            clock,
+            inner: RwLock::new(MetaInner {
+                mode: None,
+                uid: DEFAULT_UID,
+                gid: DEFAULT_GID,
+                nlink: 1,
+                atime: now,
+                mtime: now,
+                ctime: now,
+            }),
+        }
+    }
+
+    /// Returns the device ID of the filesystem that the node belongs to.
+    pub fn dev(&self) -> u64 {
+        self.dev
+    }
+
+    pub fn clock(&self) -> Clock {
+        self.clock
+    }
+
+    /// Updates the access time to now.
+    pub fn touch_atime(&self) {
+        self.inner.write().atime = (self.clock)();
+    }
+
+    /// Updates the modification and status change time to now.
+    pub fn touch_mtime(&self) {
+        let now = (self.clock)();
+        let mut inner = self.inner.write();
+        inner.mtime = now;
+        inner.ctime = now;
+    }
+
+    /// Increases the number of hard links by one.
+    pub fn inc_nlink(&self) {
+        let now = (self.clock)();
+        let mut inner = self.inner.write();
+        inner.nlink += 1;
+        inner.ctime = now;
+    }
+
+    /// Decreases the number of hard links by one.
+    pub fn dec_nlink(&self) {
+        let now = (self.clock)();
+        let mut inner = self.inner.write();
+        inner.nlink = inner.nlink.saturating_sub(1);
+        inner.ctime = now;
+    }
+
+    /// Fills the metadata into `attr`, which only contains the type, size
+    /// and default permission of the node.
+    pub fn fill(&self, mut attr: VfsNodeAttr) -> VfsNodeAttr {
+        let inner = self.inner.read();
+        if let Some(mode) = inner.mode {
+            attr.set_perm(mode);
+        }
+        attr.with_ino(self.ino)
+            .with_nlink(inner.nlink)
+            .with_owner(inner.uid, inner.gid)
+            .with_times(inner.atime, inner.mtime, inner.ctime)
+    }
+
+    pub fn set_attr(&self, attr: &VfsSetAttr) {
+        let now = (self.clock)();
+        let mut inner = self.inner.write();
+        if attr.mode.is_some() {
+            inner.mode = attr.mode;
+        }
+        inner.uid = attr.uid.unwrap_or(inner.uid);
+        inner.gid = attr.gid.unwrap_or(inner.gid);
+        inner.atime = attr.atime.unwrap_or(inner.atime);
+        inner.mtime = attr.mtime.unwrap_or(inner.mtime);
+        inner.ctime = now;
+    }
+}
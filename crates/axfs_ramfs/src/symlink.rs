@@ -0,0 +1,56 @@
+use alloc::string::String;
+use axfs_vfs::{impl_vfs_non_dir_default, VfsNodeAttr, VfsNodeOps, VfsResult, VfsSetAttr};
+
+use crate::meta::{Clock, NodeMeta};
+
+/// The symbolic link node in the RAM filesystem.
+///
+/// It implements [`axfs_vfs::VfsNodeOps`].
+pub struct SymlinkNode {
+    meta: NodeMeta,
+    target: String,
+}
+
+impl SymlinkNode {
+    pub(super) fn new(target: &str, dev: u64, clock: Clock) -> Self {
+        Self {
+            meta: NodeMeta::new(dev, clock),
+            target: target.into(),
+        }
+    }
+
+    pub(super) fn meta(&self) -> &NodeMeta {
+        &self.meta
+    }
+
+    /// Returns the target path of the symbolic link.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+}
+
+impl VfsNodeOps for SymlinkNode {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        Ok(self
+            .meta
+            .fill(VfsNodeAttr::new_symlink(self.target.len() as _)))
+    }
+
+    fn set_attr(&self, attr: &VfsSetAttr) -> VfsResult {
+        // the permission of symbolic links is always `0o777`
+        self.meta.set_attr(&VfsSetAttr {
+            mode: None,
+            ..*attr
+        });
+        Ok(())
+    }
+
+    fn readlink(&self, buf: &mut [u8]) -> VfsResult<usize> {
+        let len = buf.len().min(self.target.len());
+        buf[..len].copy_from_slice(&self.target.as_bytes()[..len]);
+        self.meta.touch_atime();
+        Ok(len)
+    }
+
+    impl_vfs_non_dir_default! {}
+}
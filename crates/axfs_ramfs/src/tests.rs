@@ -1,6 +1,10 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
-use axfs_vfs::{VfsError, VfsNodeType, VfsResult};
+use axfs_vfs::{
+    VfsError, VfsNodePerm, VfsNodeType, VfsResult, VfsSetAttr, DEFAULT_GID, DEFAULT_UID,
+};
 
 use crate::*;
 
@@ -134,3 +138,176 @@ fn test_ramfs() {
     assert_eq!(root.remove("./foo"), Ok(()));
     assert!(ramfs.root_dir_node().get_entries().is_empty());
 }
+
+#[test]
+fn test_symlink_and_link() {
+    let ramfs = RamFileSystem::new();
+    let root = ramfs.root_dir();
+    root.create("foo", VfsNodeType::Dir).unwrap();
+    root.create("foo/f1", VfsNodeType::File).unwrap();
+
+    // symbolic links
+    assert_eq!(root.symlink("l1", "foo/f1"), Ok(()));
+    assert_eq!(root.symlink("foo/l2", "/not/exist"), Ok(()));
+    assert_eq!(
+        root.symlink("l1", "foo").err(),
+        Some(VfsError::AlreadyExists)
+    );
+    assert_eq!(
+        root.symlink("bar/l3", "foo").err(),
+        Some(VfsError::NotFound)
+    );
+
+    let l1 = root.clone().lookup("l1").unwrap();
+    let attr = l1.get_attr().unwrap();
+    assert!(attr.is_symlink());
+    assert_eq!(attr.size(), 6);
+    let mut buf = [0; 32];
+    assert_eq!(l1.readlink(&mut buf), Ok(6));
+    assert_eq!(&buf[..6], b"foo/f1");
+    assert_eq!(l1.readlink(&mut buf[..3]), Ok(3));
+    assert_eq!(&buf[..3], b"foo");
+    assert_eq!(l1.read_at(0, &mut buf).err(), Some(VfsError::InvalidInput));
+    assert_eq!(l1.clone().lookup("f1").err(), Some(VfsError::NotADirectory));
+    let l2 = root.clone().lookup("foo/l2").unwrap();
+    assert_eq!(l2.readlink(&mut buf), Ok(10));
+    assert_eq!(&buf[..10], b"/not/exist");
+
+    // hard links
+    let f1 = root.clone().lookup("foo/f1").unwrap();
+    assert_eq!(f1.write_at(0, b"hello"), Ok(5));
+    assert_eq!(root.link("h1", f1.clone()), Ok(()));
+    assert_eq!(root.link("foo/h2", l1.clone()), Ok(()));
+    let h1 = root.clone().lookup("h1").unwrap();
+    assert!(Arc::ptr_eq(&f1, &h1));
+    assert_eq!(h1.read_at(0, &mut buf), Ok(5));
+    assert_eq!(&buf[..5], b"hello");
+    assert!(root
+        .clone()
+        .lookup("foo/h2")
+        .unwrap()
+        .get_attr()
+        .unwrap()
+        .is_symlink());
+    assert_eq!(
+        root.link("h3", root.clone().lookup("foo").unwrap()).err(),
+        Some(VfsError::PermissionDenied)
+    );
+    assert_eq!(
+        root.link("h1", f1.clone()).err(),
+        Some(VfsError::AlreadyExists)
+    );
+    let other = RamFileSystem::new();
+    assert_eq!(
+        other.root_dir().link("h4", f1.clone()).err(),
+        Some(VfsError::CrossesDevices)
+    );
+
+    // removing a link does not affect the other names
+    assert_eq!(root.remove("foo/f1"), Ok(()));
+    assert_eq!(h1.read_at(0, &mut buf), Ok(5));
+    assert_eq!(root.remove("l1"), Ok(()));
+    assert!(root.clone().lookup("foo/h2").is_ok());
+
+    let mut entries = ramfs.root_dir_node().get_entries();
+    entries.sort();
+    assert_eq!(entries, ["foo", "h1"]);
+}
+
+static NOW_SECS: AtomicU64 = AtomicU64::new(100);
+
+fn fake_clock() -> Duration {
+    Duration::from_secs(NOW_SECS.load(Ordering::Relaxed))
+}
+
+#[test]
+fn test_metadata() {
+    let ramfs = RamFileSystem::with_clock(fake_clock);
+    let root = ramfs.root_dir();
+    root.create("foo", VfsNodeType::Dir).unwrap();
+    root.create("foo/bar", VfsNodeType::Dir).unwrap();
+    root.create("foo/f1", VfsNodeType::File).unwrap();
+
+    let f1 = root.clone().lookup("foo/f1").unwrap();
+    let attr = f1.get_attr().unwrap();
+    assert_ne!(attr.ino(), root.get_attr().unwrap().ino());
+    assert_eq!(attr.ino(), f1.get_attr().unwrap().ino());
+    assert_eq!(attr.nlink(), 1);
+    assert_eq!((attr.uid(), attr.gid()), (DEFAULT_UID, DEFAULT_GID));
+    assert_eq!(attr.atime(), Duration::from_secs(100));
+    assert_eq!(attr.mtime(), Duration::from_secs(100));
+    assert_eq!(attr.ctime(), Duration::from_secs(100));
+    assert_eq!(
+        root.clone()
+            .lookup("foo")
+            .unwrap()
+            .get_attr()
+            .unwrap()
+            .nlink(),
+        3
+    );
+
+    // reading updates atime, writing updates mtime and ctime
+    NOW_SECS.store(200, Ordering::Relaxed);
+    assert_eq!(f1.write_at(0, b"hello"), Ok(5));
+    let attr = f1.get_attr().unwrap();
+    assert_eq!(attr.atime(), Duration::from_secs(100));
+    assert_eq!(attr.mtime(), Duration::from_secs(200));
+    NOW_SECS.store(300, Ordering::Relaxed);
+    assert_eq!(f1.read_at(0, &mut [0; 8]), Ok(5));
+    let attr = f1.get_attr().unwrap();
+    assert_eq!(attr.atime(), Duration::from_secs(300));
+    assert_eq!(attr.mtime(), Duration::from_secs(200));
+
+    // hard links
+    assert_eq!(root.link("h1", f1.clone()), Ok(()));
+    assert_eq!(f1.get_attr().unwrap().nlink(), 2);
+    assert_eq!(root.remove("foo/f1"), Ok(()));
+    assert_eq!(f1.get_attr().unwrap().nlink(), 1);
+
+    // chmod, chown and utimens
+    NOW_SECS.store(400, Ordering::Relaxed);
+    let chmod = VfsSetAttr {
+        mode: Some(VfsNodePerm::from_bits_truncate(0o600)),
+        ..Default::default()
+    };
+    let chown = VfsSetAttr {
+        uid: Some(0),
+        gid: Some(100),
+        ..Default::default()
+    };
+    let utimens = VfsSetAttr {
+        atime: Some(Duration::from_secs(1)),
+        mtime: Some(Duration::from_secs(2)),
+        ..Default::default()
+    };
+    for attr in [chmod, chown, utimens] {
+        assert_eq!(f1.set_attr(&attr), Ok(()));
+    }
+    let attr = f1.get_attr().unwrap();
+    assert_eq!(attr.perm().mode(), 0o600);
+    assert_eq!((attr.uid(), attr.gid()), (0, 100));
+    assert_eq!(attr.atime(), Duration::from_secs(1));
+    assert_eq!(attr.mtime(), Duration::from_secs(2));
+    assert_eq!(attr.ctime(), Duration::from_secs(400));
+}
+
+#[test]
+fn test_statfs() {
+    let ramfs = RamFileSystem::new().with_available_memory(|| 16 * 4096);
+    let root = ramfs.root_dir();
+    root.create("foo", VfsNodeType::Dir).unwrap();
+    root.create("foo/f1", VfsNodeType::File).unwrap();
+    let f1 = root.clone().lookup("foo/f1").unwrap();
+    assert_eq!(f1.write_at(0, &[1; 5000]), Ok(5000));
+    assert_eq!(root.link("h1", f1), Ok(()));
+
+    let info = ramfs.statfs().unwrap();
+    assert_eq!(info.block_size(), 4096);
+    assert_eq!(info.blocks(), 2 + 16);
+    assert_eq!(info.blocks_free(), 16);
+    assert_eq!(info.blocks_avail(), 16);
+    assert_eq!(info.files(), 3 + 16);
+    assert_eq!(info.files_free(), 16);
+    assert_eq!(info.name_len(), 255);
+}
@@ -1,8 +1,8 @@
 //! Virtual filesystem interfaces used by [ArceOS](https://github.com/rcore-os/arceos).
 //!
-//! A filesystem is a set of files and directories (symbol links are not
-//! supported currently), collectively referred to as **nodes**, which are
-//! conceptually similar to [inodes] in Linux. A file system needs to implement
+//! A filesystem is a set of files, directories and symbolic links, collectively
+//! referred to as **nodes**, which are conceptually similar to [inodes] in
+//! Linux. A file system needs to implement
 //! the [`VfsOps`] trait, its files and directories need to implement the
 //! [`VfsNodeOps`] trait.
 //!
@@ -22,15 +22,19 @@
 //! | [`open()`](VfsNodeOps::open) | Do something when the node is opened | both |
 //! | [`release()`](VfsNodeOps::release) | Do something when the node is closed | both |
 //! | [`get_attr()`](VfsNodeOps::get_attr) | Get the attributes of the node | both |
+//! | [`set_attr()`](VfsNodeOps::set_attr) | Change the mode, owner or timestamps of the node | both |
 //! | [`read_at()`](VfsNodeOps::read_at) | Read data from the file | file |
 //! | [`write_at()`](VfsNodeOps::write_at) | Write data to the file | file |
 //! | [`fsync()`](VfsNodeOps::fsync) | Synchronize the file data to disk | file |
 //! | [`truncate()`](VfsNodeOps::truncate) | Truncate the file | file |
+//! | [`readlink()`](VfsNodeOps::readlink) | Read the target of the symbolic link | symlink |
 //! | [`parent()`](VfsNodeOps::parent) | Get the parent directory | directory |
 //! | [`lookup()`](VfsNodeOps::lookup) | Lookup the node with the given path | directory |
 //! | [`create()`](VfsNodeOps::create) | Create a new node with the given path | directory |
 //! | [`remove()`](VfsNodeOps::remove) | Remove the node with the given path | directory |
 //! | [`read_dir()`](VfsNodeOps::read_dir) | Read directory entries | directory |
+//! | [`symlink()`](VfsNodeOps::symlink) | Create a symbolic link with the given path | directory |
+//! | [`link()`](VfsNodeOps::link) | Create a hard link to an existing node | directory |
 //!
 //! [inodes]: https://en.wikipedia.org/wiki/Inode
 
@@ -46,7 +50,10 @@ pub mod path;
 use alloc::sync::Arc;
 use axerrno::{ax_err, AxError, AxResult};
 
-pub use self::structs::{FileSystemInfo, VfsDirEntry, VfsNodeAttr, VfsNodePerm, VfsNodeType};
+pub use self::structs::{
+    FileSystemInfo, VfsDirEntry, VfsNodeAttr, VfsNodePerm, VfsNodeType, VfsSetAttr, DEFAULT_GID,
+    DEFAULT_UID,
+};
 
 /// A wrapper of [`Arc<dyn VfsNodeOps>`].
 pub type VfsNodeRef = Arc<dyn VfsNodeOps>;
@@ -65,7 +72,14 @@ pub trait VfsOps: Send + Sync {
     }
 
     /// Do something when the filesystem is unmounted.
+    ///
+    /// All cached data should be written to the storage.
     fn umount(&self) -> VfsResult {
+        self.sync()
+    }
+
+    /// Write all cached data of the filesystem to the storage.
+    fn sync(&self) -> VfsResult {
         Ok(())
     }
 
@@ -100,6 +114,12 @@ pub trait VfsNodeOps: Send + Sync {
         ax_err!(Unsupported)
     }
 
+    /// Change the attributes of the node, the fields of `attr` set to `None`
+    /// are left unchanged.
+    fn set_attr(&self, _attr: &VfsSetAttr) -> VfsResult {
+        ax_err!(Unsupported)
+    }
+
     // file operations:
 
     /// Read data from the file at the given offset.
@@ -122,6 +142,16 @@ pub trait VfsNodeOps: Send + Sync {
         ax_err!(InvalidInput)
     }
 
+    // symlink operations:
+
+    /// Read the target path of the symbolic link into `buf`.
+    ///
+    /// Return the number of bytes read. The target is truncated if `buf` is
+    /// too small.
+    fn readlink(&self, _buf: &mut [u8]) -> VfsResult<usize> {
+        ax_err!(InvalidInput)
+    }
+
     // directory operations:
 
     /// Get the parent directory of this directory.
@@ -160,6 +190,23 @@ pub trait VfsNodeOps: Send + Sync {
         ax_err!(Unsupported)
     }
 
+    /// Create a symbolic link with the given `path` in the directory, which
+    /// points to `target`.
+    ///
+    /// The `target` is stored as is, it will not be checked or resolved.
+    fn symlink(&self, _path: &str, _target: &str) -> VfsResult {
+        ax_err!(Unsupported)
+    }
+
+    /// Create a hard link with the given `path` in the directory, which
+    /// refers to the existing `node`.
+    ///
+    /// Return [`CrossesDevices`](VfsError::CrossesDevices) if `node` does not
+    /// belong to the same filesystem.
+    fn link(&self, _path: &str, _node: VfsNodeRef) -> VfsResult {
+        ax_err!(Unsupported)
+    }
+
     /// Convert `&self` to [`&dyn Any`][1] that can use
     /// [`Any::downcast_ref`][2].
     ///
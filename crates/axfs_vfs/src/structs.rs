@@ -1,8 +1,32 @@
-/// Filesystem attributes.
+use core::time::Duration;
+
+/// The user ID that owns a node unless the filesystem records another one.
+pub const DEFAULT_UID: u32 = 1000;
+/// The group ID that owns a node unless the filesystem records another one.
+pub const DEFAULT_GID: u32 = 1000;
+
+/// Filesystem attributes, returned by [`VfsOps::statfs`].
 ///
-/// Currently not used.
-#[non_exhaustive]
-pub struct FileSystemInfo;
+/// [`VfsOps::statfs`]: crate::VfsOps::statfs
+#[derive(Debug, Clone, Copy)]
+pub struct FileSystemInfo {
+    /// Type of the filesystem, the magic number as in Linux `statfs`.
+    fs_type: u64,
+    /// Size of a block, in bytes.
+    block_size: u64,
+    /// Total number of blocks.
+    blocks: u64,
+    /// Number of free blocks.
+    blocks_free: u64,
+    /// Number of free blocks available to unprivileged users.
+    blocks_avail: u64,
+    /// Total number of inodes.
+    files: u64,
+    /// Number of free inodes.
+    files_free: u64,
+    /// Maximum length of file names.
+    name_len: u64,
+}
 
 /// Node (file/directory) attributes.
 #[allow(dead_code)]
@@ -16,6 +40,39 @@ pub struct VfsNodeAttr {
     size: u64,
     /// Number of 512B blocks allocated.
     blocks: u64,
+    /// Inode number, unique in the filesystem.
+    ino: u64,
+    /// Number of hard links.
+    nlink: u64,
+    /// User ID of the owner.
+    uid: u32,
+    /// Group ID of the owner.
+    gid: u32,
+    /// Time of last access, since the Unix epoch.
+    atime: Duration,
+    /// Time of last modification, since the Unix epoch.
+    mtime: Duration,
+    /// Time of last status change, since the Unix epoch.
+    ctime: Duration,
+}
+
+/// Node attributes to change by [`VfsNodeOps::set_attr`].
+///
+/// The attributes set to `None` are left unchanged.
+///
+/// [`VfsNodeOps::set_attr`]: crate::VfsNodeOps::set_attr
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VfsSetAttr {
+    /// New permission mode (`chmod`).
+    pub mode: Option<VfsNodePerm>,
+    /// New user ID of the owner (`chown`).
+    pub uid: Option<u32>,
+    /// New group ID of the owner (`chown`).
+    pub gid: Option<u32>,
+    /// New time of last access (`utimens`).
+    pub atime: Option<Duration>,
+    /// New time of last modification (`utimens`).
+    pub mtime: Option<Duration>,
 }
 
 bitflags::bitflags! {
@@ -71,6 +128,82 @@ pub struct VfsDirEntry {
     d_name: [u8; 63],
 }
 
+impl FileSystemInfo {
+    /// Creates a new `FileSystemInfo` with the given filesystem type, block
+    /// size and maximum length of file names.
+    ///
+    /// The numbers of blocks and inodes are zero, use
+    /// [`with_blocks`](Self::with_blocks) and [`with_files`](Self::with_files)
+    /// to set them.
+    pub const fn new(fs_type: u64, block_size: u64, name_len: u64) -> Self {
+        Self {
+            fs_type,
+            block_size,
+            blocks: 0,
+            blocks_free: 0,
+            blocks_avail: 0,
+            files: 0,
+            files_free: 0,
+            name_len,
+        }
+    }
+
+    /// Sets the total, free and available number of blocks.
+    pub const fn with_blocks(mut self, total: u64, free: u64, avail: u64) -> Self {
+        self.blocks = total;
+        self.blocks_free = free;
+        self.blocks_avail = avail;
+        self
+    }
+
+    /// Sets the total and free number of inodes.
+    pub const fn with_files(mut self, total: u64, free: u64) -> Self {
+        self.files = total;
+        self.files_free = free;
+        self
+    }
+
+    /// Returns the type (magic number) of the filesystem.
+    pub const fn fs_type(&self) -> u64 {
+        self.fs_type
+    }
+
+    /// Returns the size of a block, in bytes.
+    pub const fn block_size(&self) -> u64 {
+        self.block_size
+    }
+
+    /// Returns the total number of blocks.
+    pub const fn blocks(&self) -> u64 {
+        self.blocks
+    }
+
+    /// Returns the number of free blocks.
+    pub const fn blocks_free(&self) -> u64 {
+        self.blocks_free
+    }
+
+    /// Returns the number of free blocks available to unprivileged users.
+    pub const fn blocks_avail(&self) -> u64 {
+        self.blocks_avail
+    }
+
+    /// Returns the total number of inodes.
+    pub const fn files(&self) -> u64 {
+        self.files
+    }
+
+    /// Returns the number of free inodes.
+    pub const fn files_free(&self) -> u64 {
+        self.files_free
+    }
+
+    /// Returns the maximum length of file names.
+    pub const fn name_len(&self) -> u64 {
+        self.name_len
+    }
+}
+
 impl VfsNodePerm {
     /// Returns the default permission for a file.
     ///
@@ -199,12 +332,22 @@ impl VfsNodeType {
 impl VfsNodeAttr {
     /// Creates a new `VfsNodeAttr` with the given permission mode, type, size
     /// and number of blocks.
+    ///
+    /// The node has one link, is owned by [`DEFAULT_UID`] and [`DEFAULT_GID`],
+    /// and all of its timestamps are zero. Use the `with_*` methods to set them.
     pub const fn new(mode: VfsNodePerm, ty: VfsNodeType, size: u64, blocks: u64) -> Self {
         Self {
             mode,
             ty,
             size,
             blocks,
+            ino: 0,
+            nlink: 1,
+            uid: DEFAULT_UID,
+            gid: DEFAULT_GID,
+            atime: Duration::ZERO,
+            mtime: Duration::ZERO,
+            ctime: Duration::ZERO,
         }
     }
 
@@ -215,6 +358,13 @@ impl VfsNodeAttr {
             ty: VfsNodeType::File,
             size,
             blocks,
+            ino: 0,
+            nlink: 1,
+            uid: DEFAULT_UID,
+            gid: DEFAULT_GID,
+            atime: Duration::ZERO,
+            mtime: Duration::ZERO,
+            ctime: Duration::ZERO,
         }
     }
 
@@ -226,9 +376,63 @@ impl VfsNodeAttr {
             ty: VfsNodeType::Dir,
             size,
             blocks,
+            ino: 0,
+            nlink: 1,
+            uid: DEFAULT_UID,
+            gid: DEFAULT_GID,
+            atime: Duration::ZERO,
+            mtime: Duration::ZERO,
+            ctime: Duration::ZERO,
         }
     }
 
+    /// Creates a new `VfsNodeAttr` for a symbolic link, whose size is the
+    /// length of the target path.
+    ///
+    /// Symbolic links always have the permission `0o777`.
+    pub const fn new_symlink(size: u64) -> Self {
+        Self {
+            mode: VfsNodePerm::from_bits_truncate(0o777),
+            ty: VfsNodeType::SymLink,
+            size,
+            blocks: 0,
+            ino: 0,
+            nlink: 1,
+            uid: DEFAULT_UID,
+            gid: DEFAULT_GID,
+            atime: Duration::ZERO,
+            mtime: Duration::ZERO,
+            ctime: Duration::ZERO,
+        }
+    }
+
+    /// Sets the inode number of the node.
+    pub const fn with_ino(mut self, ino: u64) -> Self {
+        self.ino = ino;
+        self
+    }
+
+    /// Sets the number of hard links to the node.
+    pub const fn with_nlink(mut self, nlink: u64) -> Self {
+        self.nlink = nlink;
+        self
+    }
+
+    /// Sets the user ID and group ID of the owner.
+    pub const fn with_owner(mut self, uid: u32, gid: u32) -> Self {
+        self.uid = uid;
+        self.gid = gid;
+        self
+    }
+
+    /// Sets the access, modification and status change time of the node.
+    pub const fn with_times(mut self, atime: Duration, mtime: Duration, ctime: Duration) -> Self {
+        self.atime = atime;
+        self.mtime = mtime;
+        self.ctime = ctime;
+        self
+    }
+
     /// Returns the size of the node.
     pub const fn size(&self) -> u64 {
         self.size
@@ -249,6 +453,41 @@ impl VfsNodeAttr {
         self.mode = perm
     }
 
+    /// Returns the inode number of the node.
+    pub const fn ino(&self) -> u64 {
+        self.ino
+    }
+
+    /// Returns the number of hard links to the node.
+    pub const fn nlink(&self) -> u64 {
+        self.nlink
+    }
+
+    /// Returns the user ID of the owner.
+    pub const fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    /// Returns the group ID of the owner.
+    pub const fn gid(&self) -> u32 {
+        self.gid
+    }
+
+    /// Returns the time of last access.
+    pub const fn atime(&self) -> Duration {
+        self.atime
+    }
+
+    /// Returns the time of last modification.
+    pub const fn mtime(&self) -> Duration {
+        self.mtime
+    }
+
+    /// Returns the time of last status change.
+    pub const fn ctime(&self) -> Duration {
+        self.ctime
+    }
+
     /// Returns the type of the node.
     pub const fn file_type(&self) -> VfsNodeType {
         self.ty
@@ -263,6 +502,11 @@ impl VfsNodeAttr {
     pub const fn is_dir(&self) -> bool {
         self.ty.is_dir()
     }
+
+    /// Whether the node is a symbolic link.
+    pub const fn is_symlink(&self) -> bool {
+        self.ty.is_symlink()
+    }
 }
 
 impl VfsDirEntry {
@@ -257,4 +257,6 @@ pub struct PollState {
     pub readable: bool,
     /// Object can be writen now.
     pub writable: bool,
+    /// The peer has closed its end, e.g., the write end of a pipe.
+    pub hangup: bool,
 }
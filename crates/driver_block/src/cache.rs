@@ -0,0 +1,391 @@
+//! A write-back block cache that wraps any block device.
+
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::BlockDriverOps;
+use driver_common::{BaseDriverOps, DevError, DevResult, DeviceType};
+
+struct CachedBlock {
+    data: Box<[u8]>,
+    dirty: bool,
+    /// The time of the last access, used to find the least recently used
+    /// block.
+    stamp: u64,
+}
+
+/// A block device with an LRU cache of its blocks.
+///
+/// Reads are served from the cache if possible, and writes only modify the
+/// cached blocks, which are written back to the device when they are evicted
+/// or when [`flush`](BlockDriverOps::flush) is called. It implements
+/// [`BlockDriverOps`] itself, so it can be used in place of the inner device.
+///
+/// A cache with a capacity of zero passes all operations to the inner device.
+pub struct BlockCache<D: BlockDriverOps> {
+    dev: D,
+    capacity: usize,
+    blocks: BTreeMap<u64, CachedBlock>,
+    /// Maps the access time to the block ID, in the order of recency.
+    lru: BTreeMap<u64, u64>,
+    clock: u64,
+}
+
+impl<D: BlockDriverOps> BlockCache<D> {
+    /// Creates a cache of at most `capacity` blocks for the device `dev`.
+    pub fn new(dev: D, capacity: usize) -> Self {
+        Self {
+            dev,
+            capacity,
+            blocks: BTreeMap::new(),
+            lru: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+
+    /// The maximum number of cached blocks.
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of blocks that are modified but not written back.
+    pub fn dirty_blocks(&self) -> usize {
+        self.blocks.values().filter(|b| b.dirty).count()
+    }
+
+    /// Returns a reference to the inner device.
+    pub const fn inner(&self) -> &D {
+        &self.dev
+    }
+
+    /// Writes back all dirty blocks, and returns the inner device.
+    pub fn into_inner(mut self) -> DevResult<D> {
+        self.write_back()?;
+        Ok(self.dev)
+    }
+
+    /// Checks that `len` bytes from `block_id` are whole blocks within the
+    /// device, returns the number of blocks.
+    fn check_range(&self, block_id: u64, len: usize) -> DevResult<usize> {
+        let block_size = self.dev.block_size();
+        if len % block_size != 0 {
+            return Err(DevError::InvalidParam);
+        }
+        let count = len / block_size;
+        if block_id + count as u64 > self.dev.num_blocks() {
+            return Err(DevError::Io);
+        }
+        Ok(count)
+    }
+
+    /// Marks the cached block as the most recently used one.
+    fn touch(&mut self, block_id: u64) {
+        self.clock += 1;
+        let block = self.blocks.get_mut(&block_id).unwrap();
+        self.lru.remove(&block.stamp);
+        block.stamp = self.clock;
+        self.lru.insert(self.clock, block_id);
+    }
+
+    /// Adds a block to the cache, evicts the least recently used block if
+    /// the cache is full.
+    ///
+    /// Returns `false` without adding the block if the evicted block is dirty
+    /// and fails to be written back. It is kept dirty in the cache, and the
+    /// error is reported by the next [`flush`](BlockDriverOps::flush).
+    fn insert(&mut self, block_id: u64, data: &[u8], dirty: bool) -> bool {
+        while self.blocks.len() >= self.capacity {
+            let (&stamp, &victim) = self.lru.first_key_value().unwrap();
+            let block = &self.blocks[&victim];
+            if block.dirty && self.dev.write_block(victim, &block.data).is_err() {
+                return false;
+            }
+            self.lru.remove(&stamp);
+            self.blocks.remove(&victim);
+        }
+        self.clock += 1;
+        let block = CachedBlock {
+            data: data.into(),
+            dirty,
+            stamp: self.clock,
+        };
+        self.blocks.insert(block_id, block);
+        self.lru.insert(self.clock, block_id);
+        true
+    }
+
+    /// Writes all dirty blocks to the device, contiguous blocks are written
+    /// in one request.
+    ///
+    /// A block stays dirty until the request that contains it succeeds.
+    fn write_back(&mut self) -> DevResult {
+        let dirty: Vec<u64> = self
+            .blocks
+            .iter()
+            .filter(|(_, b)| b.dirty)
+            .map(|(&id, _)| id)
+            .collect();
+        let mut run: Vec<u8> = Vec::new();
+        let mut run_start = 0;
+        let mut run_end = 0;
+        for block_id in dirty {
+            if !run.is_empty() && block_id != run_end {
+                self.write_run(run_start, run_end, &run)?;
+                run.clear();
+            }
+            if run.is_empty() {
+                run_start = block_id;
+            }
+            run.extend_from_slice(&self.blocks[&block_id].data);
+            run_end = block_id + 1;
+        }
+        if !run.is_empty() {
+            self.write_run(run_start, run_end, &run)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the contiguous blocks from `start` to `end` (exclusive) to the
+    /// device, and marks them clean if it succeeds.
+    fn write_run(&mut self, start: u64, end: u64, data: &[u8]) -> DevResult {
+        self.dev.write_block(start, data)?;
+        for block_id in start..end {
+            self.blocks.get_mut(&block_id).unwrap().dirty = false;
+        }
+        Ok(())
+    }
+}
+
+impl<D: BlockDriverOps> BaseDriverOps for BlockCache<D> {
+    fn device_name(&self) -> &str {
+        self.dev.device_name()
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::Block
+    }
+}
+
+impl<D: BlockDriverOps> BlockDriverOps for BlockCache<D> {
+    #[inline]
+    fn num_blocks(&self) -> u64 {
+        self.dev.num_blocks()
+    }
+
+    #[inline]
+    fn block_size(&self) -> usize {
+        self.dev.block_size()
+    }
+
+    fn read_block(&mut self, block_id: u64, buf: &mut [u8]) -> DevResult {
+        if self.capacity == 0 {
+            return self.dev.read_block(block_id, buf);
+        }
+        let count = self.check_range(block_id, buf.len())?;
+        let block_size = self.dev.block_size();
+        let mut i = 0;
+        while i < count {
+            let id = block_id + i as u64;
+            if let Some(block) = self.blocks.get(&id) {
+                buf[i * block_size..(i + 1) * block_size].copy_from_slice(&block.data);
+                self.touch(id);
+                i += 1;
+                continue;
+            }
+            // read the following blocks that are not cached in one request
+            let end = (i + 1..count)
+                .find(|&j| self.blocks.contains_key(&(block_id + j as u64)))
+                .unwrap_or(count);
+            let missed = &mut buf[i * block_size..end * block_size];
+            self.dev.read_block(id, missed)?;
+            for (j, data) in missed.chunks(block_size).enumerate() {
+                // the data is read anyway if it can not be cached
+                self.insert(id + j as u64, data, false);
+            }
+            i = end;
+        }
+        Ok(())
+    }
+
+    fn write_block(&mut self, block_id: u64, buf: &[u8]) -> DevResult {
+        if self.capacity == 0 {
+            return self.dev.write_block(block_id, buf);
+        }
+        self.check_range(block_id, buf.len())?;
+        let block_size = self.dev.block_size();
+        for (i, data) in buf.chunks(block_size).enumerate() {
+            let id = block_id + i as u64;
+            if let Some(block) = self.blocks.get_mut(&id) {
+                block.data.copy_from_slice(data);
+                block.dirty = true;
+                self.touch(id);
+            } else if !self.insert(id, data, true) {
+                // write through if it can not be cached
+                self.dev.write_block(id, data)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> DevResult {
+        self.write_back()?;
+        self.dev.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const BLOCK_SIZE: usize = 512;
+
+    /// A device that counts the requests.
+    struct MockDisk {
+        data: Vec<u8>,
+        reads: usize,
+        writes: usize,
+        /// Whether writes fail with an I/O error.
+        broken: bool,
+    }
+
+    impl MockDisk {
+        fn new(num_blocks: usize) -> Self {
+            Self {
+                data: vec![0; num_blocks * BLOCK_SIZE],
+                reads: 0,
+                writes: 0,
+                broken: false,
+            }
+        }
+    }
+
+    impl BaseDriverOps for MockDisk {
+        fn device_name(&self) -> &str {
+            "mock"
+        }
+
+        fn device_type(&self) -> DeviceType {
+            DeviceType::Block
+        }
+    }
+
+    impl BlockDriverOps for MockDisk {
+        fn num_blocks(&self) -> u64 {
+            (self.data.len() / BLOCK_SIZE) as u64
+        }
+
+        fn block_size(&self) -> usize {
+            BLOCK_SIZE
+        }
+
+        fn read_block(&mut self, block_id: u64, buf: &mut [u8]) -> DevResult {
+            let offset = block_id as usize * BLOCK_SIZE;
+            buf.copy_from_slice(&self.data[offset..offset + buf.len()]);
+            self.reads += 1;
+            Ok(())
+        }
+
+        fn write_block(&mut self, block_id: u64, buf: &[u8]) -> DevResult {
+            if self.broken {
+                return Err(DevError::Io);
+            }
+            let offset = block_id as usize * BLOCK_SIZE;
+            self.data[offset..offset + buf.len()].copy_from_slice(buf);
+            self.writes += 1;
+            Ok(())
+        }
+
+        fn flush(&mut self) -> DevResult {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_write_back() {
+        let mut cache = BlockCache::new(MockDisk::new(16), 4);
+        for i in 0..4 {
+            cache.write_block(i, &[i as u8 + 1; BLOCK_SIZE]).unwrap();
+        }
+        assert_eq!(cache.inner().writes, 0);
+        assert_eq!(cache.dirty_blocks(), 4);
+
+        let mut buf = [0; BLOCK_SIZE * 4];
+        cache.read_block(0, &mut buf).unwrap();
+        assert_eq!(cache.inner().reads, 0);
+        assert_eq!(buf[BLOCK_SIZE * 3], 4);
+
+        // contiguous dirty blocks are written in one request
+        cache.flush().unwrap();
+        assert_eq!(cache.inner().writes, 1);
+        assert_eq!(cache.dirty_blocks(), 0);
+        assert_eq!(cache.inner().data[BLOCK_SIZE * 2], 3);
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut cache = BlockCache::new(MockDisk::new(16), 2);
+        let mut buf = [0; BLOCK_SIZE];
+        cache.write_block(0, &[1; BLOCK_SIZE]).unwrap();
+        cache.read_block(1, &mut buf).unwrap();
+        cache.read_block(0, &mut buf).unwrap(); // block 1 is now the LRU
+        assert_eq!(cache.inner().reads, 1);
+
+        cache.read_block(2, &mut buf).unwrap(); // evicts block 1
+        assert_eq!(cache.inner().writes, 0);
+        cache.read_block(3, &mut buf).unwrap(); // evicts dirty block 0
+        assert_eq!(cache.inner().writes, 1);
+        assert_eq!(cache.inner().data[0], 1);
+
+        cache.read_block(0, &mut buf).unwrap();
+        assert_eq!(buf, [1; BLOCK_SIZE]);
+        assert_eq!(cache.inner().reads, 4);
+    }
+
+    #[test]
+    fn test_write_error() {
+        let mut cache = BlockCache::new(MockDisk::new(16), 2);
+        let mut buf = [0; BLOCK_SIZE];
+        cache.write_block(0, &[1; BLOCK_SIZE]).unwrap();
+        cache.write_block(1, &[2; BLOCK_SIZE]).unwrap();
+
+        // failed writes keep the blocks dirty and cached
+        cache.dev.broken = true;
+        assert!(matches!(cache.flush(), Err(DevError::Io)));
+        assert_eq!(cache.dirty_blocks(), 2);
+        // other blocks are read and written without being cached
+        cache.dev.data[BLOCK_SIZE * 2] = 3;
+        cache.read_block(2, &mut buf).unwrap();
+        assert_eq!(buf[0], 3);
+        assert_eq!(cache.dirty_blocks(), 2);
+        assert!(matches!(cache.write_block(3, &buf), Err(DevError::Io)));
+        assert_eq!(cache.dirty_blocks(), 2);
+        cache.read_block(0, &mut buf).unwrap();
+        assert_eq!(buf, [1; BLOCK_SIZE]);
+        assert_eq!(cache.inner().reads, 1);
+
+        cache.dev.broken = false;
+        cache.flush().unwrap();
+        assert_eq!(cache.dirty_blocks(), 0);
+        assert_eq!(cache.inner().data[BLOCK_SIZE], 2);
+    }
+
+    #[test]
+    fn test_no_cache() {
+        let mut cache = BlockCache::new(MockDisk::new(4), 0);
+        cache.write_block(1, &[1; BLOCK_SIZE]).unwrap();
+        assert_eq!(cache.inner().writes, 1);
+        let disk = cache.into_inner().unwrap();
+        assert_eq!(disk.data[BLOCK_SIZE], 1);
+    }
+
+    #[test]
+    fn test_invalid_range() {
+        let mut cache = BlockCache::new(MockDisk::new(4), 4);
+        let mut buf = [0; BLOCK_SIZE * 2];
+        assert!(cache.read_block(3, &mut buf).is_err());
+        assert!(cache.write_block(0, &buf[..100]).is_err());
+    }
+}
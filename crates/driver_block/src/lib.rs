@@ -1,12 +1,18 @@
 //! Common traits and types for block storage device drivers (i.e. disk).
 
-#![no_std]
+#![cfg_attr(not(test), no_std)]
 #![feature(doc_auto_cfg)]
 #![feature(const_trait_impl)]
 
 #[cfg(feature = "ramdisk")]
 pub mod ramdisk;
 
+#[cfg(feature = "cache")]
+pub mod cache;
+
+#[cfg(feature = "partition")]
+pub mod partition;
+
 #[cfg(feature = "bcm2835-sdhci")]
 pub mod bcm2835sdhci;
 
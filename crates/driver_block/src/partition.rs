@@ -0,0 +1,639 @@
+//! Partition tables (MBR and GPT), and block devices of partitions.
+//!
+//! A block device is shared by its partitions through [`SharedDevice`], and
+//! each [`Partition`] is a block device that only covers its range of blocks.
+
+extern crate alloc;
+
+use alloc::{string::String, sync::Arc, vec, vec::Vec};
+use core::fmt;
+use core::str::FromStr;
+
+use crate::BlockDriverOps;
+use driver_common::{BaseDriverOps, DevError, DevResult, DeviceType};
+use spin::{Mutex, MutexGuard};
+
+const MBR_SIGNATURE: u16 = 0xaa55;
+const MBR_ENTRY_OFFSET: usize = 446;
+const MBR_ENTRY_SIZE: usize = 16;
+const MBR_TYPE_GPT_PROTECTIVE: u8 = 0xee;
+const MBR_TYPE_EXTENDED: [u8; 3] = [0x05, 0x0f, 0x85];
+/// Maximum number of logical partitions in an extended partition.
+const MAX_LOGICAL_PARTITIONS: usize = 128;
+
+const GPT_SIGNATURE: &[u8; 8] = b"EFI PART";
+const GPT_MIN_ENTRY_SIZE: usize = 128;
+/// Maximum number of entries in a GPT, to limit the memory used by corrupted
+/// tables.
+const GPT_MAX_ENTRIES: usize = 1024;
+/// Maximum length of a GPT partition name in UTF-16 code units.
+const GPT_NAME_LEN: usize = 36;
+
+/// A GUID, which identifies GPT partitions and their types.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub struct Guid([u8; 16]);
+
+/// The type of a partition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionType {
+    /// The system ID of an MBR partition.
+    Mbr(u8),
+    /// The partition type GUID of a GPT partition.
+    Gpt(Guid),
+}
+
+/// A partition found in the partition table.
+#[derive(Debug, Clone)]
+pub struct PartitionInfo {
+    /// Index of the partition in the order of the partition table, starting
+    /// from 0. Empty entries are skipped, and logical partitions of MBR
+    /// follow the primary ones.
+    pub index: usize,
+    /// The first block of the partition.
+    pub start_block: u64,
+    /// The number of blocks of the partition.
+    pub num_blocks: u64,
+    /// The type of the partition.
+    pub kind: PartitionType,
+    /// The unique GUID of the partition, only for GPT.
+    pub guid: Option<Guid>,
+    /// The name of the partition, empty for MBR.
+    pub label: String,
+}
+
+/// A block device shared by multiple owners, such as its partitions.
+pub struct SharedDevice<D>(Arc<Mutex<D>>);
+
+/// A partition of a block device.
+///
+/// It implements [`BlockDriverOps`], block IDs are relative to the start of
+/// the partition, and accesses beyond the partition fail.
+pub struct Partition<D: BlockDriverOps> {
+    dev: SharedDevice<D>,
+    name: String,
+    start_block: u64,
+    num_blocks: u64,
+    block_size: usize,
+}
+
+impl Guid {
+    /// Creates a GUID from its on-disk bytes, where the first three fields
+    /// are little-endian.
+    pub const fn from_bytes(bytes: [u8; 16]) -> Self {
+        Self(bytes)
+    }
+
+    /// The on-disk bytes of the GUID.
+    pub const fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    /// Whether all bytes are zero, which marks an unused GPT entry.
+    pub fn is_zero(&self) -> bool {
+        self.0.iter().all(|&b| b == 0)
+    }
+}
+
+impl fmt::Display for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let b = &self.0;
+        write!(
+            f,
+            "{:08x}-{:04x}-{:04x}-{:02x}{:02x}-",
+            u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            u16::from_le_bytes([b[4], b[5]]),
+            u16::from_le_bytes([b[6], b[7]]),
+            b[8],
+            b[9],
+        )?;
+        b[10..].iter().try_for_each(|b| write!(f, "{:02x}", b))
+    }
+}
+
+impl fmt::Debug for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl FromStr for Guid {
+    type Err = DevError;
+
+    /// Parses a GUID in the form of `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx`,
+    /// case-insensitive.
+    fn from_str(s: &str) -> DevResult<Self> {
+        let fields: Vec<&str> = s.split('-').collect();
+        let lens = [8, 4, 4, 4, 12];
+        if fields.len() != lens.len() || fields.iter().zip(lens).any(|(f, l)| f.len() != l) {
+            return Err(DevError::InvalidParam);
+        }
+        let parse = |s: &str| u64::from_str_radix(s, 16).map_err(|_| DevError::InvalidParam);
+        let mut bytes = [0; 16];
+        bytes[..4].copy_from_slice(&(parse(fields[0])? as u32).to_le_bytes());
+        bytes[4..6].copy_from_slice(&(parse(fields[1])? as u16).to_le_bytes());
+        bytes[6..8].copy_from_slice(&(parse(fields[2])? as u16).to_le_bytes());
+        bytes[8..10].copy_from_slice(&(parse(fields[3])? as u16).to_be_bytes());
+        bytes[10..].copy_from_slice(&parse(fields[4])?.to_be_bytes()[2..]);
+        Ok(Self(bytes))
+    }
+}
+
+impl<D> SharedDevice<D> {
+    /// Wraps the device to share it.
+    pub fn new(dev: D) -> Self {
+        Self(Arc::new(Mutex::new(dev)))
+    }
+
+    /// Locks the device for exclusive access.
+    pub fn lock(&self) -> MutexGuard<'_, D> {
+        self.0.lock()
+    }
+}
+
+impl<D> Clone for SharedDevice<D> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<D: BlockDriverOps> Partition<D> {
+    /// Creates the block device of the partition `info` of `dev`.
+    pub fn new(dev: SharedDevice<D>, info: &PartitionInfo) -> Self {
+        let (name, block_size) = {
+            let dev = dev.lock();
+            let name = alloc::format!("{}p{}", dev.device_name(), info.index + 1);
+            (name, dev.block_size())
+        };
+        Self {
+            dev,
+            name,
+            start_block: info.start_block,
+            num_blocks: info.num_blocks,
+            block_size,
+        }
+    }
+
+    /// Creates a block device that covers the whole `dev`.
+    pub fn whole(dev: SharedDevice<D>) -> Self {
+        let (name, num_blocks, block_size) = {
+            let dev = dev.lock();
+            (dev.device_name().into(), dev.num_blocks(), dev.block_size())
+        };
+        Self {
+            dev,
+            name,
+            start_block: 0,
+            num_blocks,
+            block_size,
+        }
+    }
+
+    /// The whole device that the partition is on.
+    pub const fn device(&self) -> &SharedDevice<D> {
+        &self.dev
+    }
+
+    /// The first block of the partition in the whole device.
+    pub const fn start_block(&self) -> u64 {
+        self.start_block
+    }
+
+    /// Converts the block range to the blocks of the whole device.
+    fn map(&self, block_id: u64, len: usize) -> DevResult<u64> {
+        let count = (len / self.block_size) as u64;
+        match block_id.checked_add(count) {
+            Some(end) if end <= self.num_blocks => Ok(self.start_block + block_id),
+            _ => Err(DevError::Io),
+        }
+    }
+}
+
+impl<D: BlockDriverOps> Clone for Partition<D> {
+    fn clone(&self) -> Self {
+        Self {
+            dev: self.dev.clone(),
+            name: self.name.clone(),
+            start_block: self.start_block,
+            num_blocks: self.num_blocks,
+            block_size: self.block_size,
+        }
+    }
+}
+
+impl<D: BlockDriverOps> BaseDriverOps for Partition<D> {
+    fn device_name(&self) -> &str {
+        &self.name
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::Block
+    }
+}
+
+impl<D: BlockDriverOps> BlockDriverOps for Partition<D> {
+    #[inline]
+    fn num_blocks(&self) -> u64 {
+        self.num_blocks
+    }
+
+    #[inline]
+    fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    fn read_block(&mut self, block_id: u64, buf: &mut [u8]) -> DevResult {
+        let block_id = self.map(block_id, buf.len())?;
+        self.dev.lock().read_block(block_id, buf)
+    }
+
+    fn write_block(&mut self, block_id: u64, buf: &[u8]) -> DevResult {
+        let block_id = self.map(block_id, buf.len())?;
+        self.dev.lock().write_block(block_id, buf)
+    }
+
+    fn flush(&mut self) -> DevResult {
+        self.dev.lock().flush()
+    }
+}
+
+/// Reads the partition table of the device, returns an empty list if there
+/// is no partition table.
+///
+/// GPT is used if the MBR is a protective MBR, and the backup GPT at the end
+/// of the device is used if the primary one is corrupted.
+pub fn scan_partitions<D: BlockDriverOps>(dev: &mut D) -> DevResult<Vec<PartitionInfo>> {
+    let block_size = dev.block_size();
+    if block_size < 512 {
+        return Err(DevError::Unsupported);
+    }
+    let mut mbr = vec![0; block_size];
+    dev.read_block(0, &mut mbr)?;
+    if u16::from_le_bytes([mbr[510], mbr[511]]) != MBR_SIGNATURE {
+        return Ok(Vec::new());
+    }
+    let entries = mbr_entries(&mbr);
+    if entries.iter().any(|e| e.kind == MBR_TYPE_GPT_PROTECTIVE) {
+        return scan_gpt(dev);
+    }
+    // a FAT boot sector also ends with the MBR signature, but its partition
+    // entries are not valid
+    let num_blocks = dev.num_blocks();
+    let invalid = |e: &MbrEntry| {
+        e.status & 0x7f != 0
+            || (e.kind != 0 && (e.start_block == 0 || e.start_block + e.num_blocks > num_blocks))
+    };
+    if entries.iter().any(invalid) {
+        return Ok(Vec::new());
+    }
+
+    let mut partitions = Vec::new();
+    let mut extended = None;
+    for entry in entries.iter().filter(|e| e.kind != 0 && e.num_blocks != 0) {
+        if MBR_TYPE_EXTENDED.contains(&entry.kind) {
+            extended.get_or_insert(entry.start_block);
+        } else {
+            push_mbr_partition(
+                &mut partitions,
+                entry.kind,
+                entry.start_block,
+                entry.num_blocks,
+            );
+        }
+    }
+    if let Some(ext_start) = extended {
+        // logical partitions are in a chain of extended boot records
+        let mut ebr_block = ext_start;
+        for _ in 0..MAX_LOGICAL_PARTITIONS {
+            dev.read_block(ebr_block, &mut mbr)?;
+            if u16::from_le_bytes([mbr[510], mbr[511]]) != MBR_SIGNATURE {
+                break;
+            }
+            let [logical, next, ..] = mbr_entries(&mbr);
+            if logical.kind != 0 && logical.num_blocks != 0 {
+                let start = ebr_block + logical.start_block;
+                push_mbr_partition(&mut partitions, logical.kind, start, logical.num_blocks);
+            }
+            if next.kind == 0 || next.start_block == 0 {
+                break;
+            }
+            ebr_block = ext_start + next.start_block;
+        }
+    }
+    Ok(partitions)
+}
+
+struct MbrEntry {
+    status: u8,
+    kind: u8,
+    start_block: u64,
+    num_blocks: u64,
+}
+
+fn mbr_entries(mbr: &[u8]) -> [MbrEntry; 4] {
+    core::array::from_fn(|i| {
+        let e = &mbr[MBR_ENTRY_OFFSET + i * MBR_ENTRY_SIZE..][..MBR_ENTRY_SIZE];
+        MbrEntry {
+            status: e[0],
+            kind: e[4],
+            start_block: read_u32(e, 8) as u64,
+            num_blocks: read_u32(e, 12) as u64,
+        }
+    })
+}
+
+fn push_mbr_partition(list: &mut Vec<PartitionInfo>, kind: u8, start: u64, len: u64) {
+    list.push(PartitionInfo {
+        index: list.len(),
+        start_block: start,
+        num_blocks: len,
+        kind: PartitionType::Mbr(kind),
+        guid: None,
+        label: String::new(),
+    });
+}
+
+fn scan_gpt<D: BlockDriverOps>(dev: &mut D) -> DevResult<Vec<PartitionInfo>> {
+    match read_gpt(dev, 1) {
+        Ok(partitions) => Ok(partitions),
+        Err(_) => {
+            log::warn!("primary GPT is corrupted, try the backup one");
+            let last = dev.num_blocks().checked_sub(1).ok_or(DevError::Io)?;
+            read_gpt(dev, last)
+        }
+    }
+}
+
+/// Reads the GPT whose header is at `header_block`.
+fn read_gpt<D: BlockDriverOps>(dev: &mut D, header_block: u64) -> DevResult<Vec<PartitionInfo>> {
+    let block_size = dev.block_size();
+    let mut header = vec![0; block_size];
+    dev.read_block(header_block, &mut header)?;
+    let header_size = read_u32(&header, 12) as usize;
+    if &header[..8] != GPT_SIGNATURE || !(92..=block_size).contains(&header_size) {
+        return Err(DevError::Io);
+    }
+    let header_crc = read_u32(&header, 16);
+    header[16..20].fill(0);
+    if crc32(&header[..header_size]) != header_crc {
+        return Err(DevError::Io);
+    }
+
+    let entries_block = read_u64(&header, 72);
+    let num_entries = read_u32(&header, 80) as usize;
+    let entry_size = read_u32(&header, 84) as usize;
+    if num_entries > GPT_MAX_ENTRIES
+        || entry_size < GPT_MIN_ENTRY_SIZE
+        || entry_size > block_size
+        || entry_size % 8 != 0
+    {
+        return Err(DevError::Io);
+    }
+    let table_size = num_entries * entry_size;
+    let mut table = vec![0; table_size.div_ceil(block_size) * block_size];
+    dev.read_block(entries_block, &mut table)?;
+    if crc32(&table[..table_size]) != read_u32(&header, 88) {
+        return Err(DevError::Io);
+    }
+
+    let mut partitions = Vec::new();
+    for entry in table[..table_size].chunks(entry_size) {
+        let kind = Guid(entry[..16].try_into().unwrap());
+        if kind.is_zero() {
+            continue;
+        }
+        let first = read_u64(entry, 32);
+        let last = read_u64(entry, 40);
+        if last < first {
+            return Err(DevError::Io);
+        }
+        let name: Vec<u16> = entry[56..56 + GPT_NAME_LEN * 2]
+            .chunks(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .take_while(|&c| c != 0)
+            .collect();
+        partitions.push(PartitionInfo {
+            index: partitions.len(),
+            start_block: first,
+            num_blocks: last - first + 1,
+            kind: PartitionType::Gpt(kind),
+            guid: Some(Guid(entry[16..32].try_into().unwrap())),
+            label: String::from_utf16_lossy(&name),
+        });
+    }
+    Ok(partitions)
+}
+
+fn read_u32(buf: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(buf[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(buf: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(buf[offset..offset + 8].try_into().unwrap())
+}
+
+/// CRC-32 (IEEE 802.3) used by GPT.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCK_SIZE: usize = 512;
+
+    /// A device of the data with the block size.
+    struct MockDisk(Vec<u8>, usize);
+
+    impl BaseDriverOps for MockDisk {
+        fn device_name(&self) -> &str {
+            "mock"
+        }
+
+        fn device_type(&self) -> DeviceType {
+            DeviceType::Block
+        }
+    }
+
+    impl BlockDriverOps for MockDisk {
+        fn num_blocks(&self) -> u64 {
+            (self.0.len() / self.1) as u64
+        }
+
+        fn block_size(&self) -> usize {
+            self.1
+        }
+
+        fn read_block(&mut self, block_id: u64, buf: &mut [u8]) -> DevResult {
+            let offset = block_id as usize * self.1;
+            buf.copy_from_slice(&self.0[offset..offset + buf.len()]);
+            Ok(())
+        }
+
+        fn write_block(&mut self, block_id: u64, buf: &[u8]) -> DevResult {
+            let offset = block_id as usize * self.1;
+            self.0[offset..offset + buf.len()].copy_from_slice(buf);
+            Ok(())
+        }
+
+        fn flush(&mut self) -> DevResult {
+            Ok(())
+        }
+    }
+
+    fn set_mbr_entry(disk: &mut [u8], block: u64, slot: usize, kind: u8, start: u32, len: u32) {
+        let sector = &mut disk[block as usize * BLOCK_SIZE..][..512];
+        let e = &mut sector[MBR_ENTRY_OFFSET + slot * MBR_ENTRY_SIZE..][..MBR_ENTRY_SIZE];
+        e[4] = kind;
+        e[8..12].copy_from_slice(&start.to_le_bytes());
+        e[12..16].copy_from_slice(&len.to_le_bytes());
+        sector[510..].copy_from_slice(&MBR_SIGNATURE.to_le_bytes());
+    }
+
+    /// Writes a GPT header at `header_block` and its entries at
+    /// `entries_block` on a disk with blocks of `bs` bytes, with 4 entries of
+    /// 128 bytes.
+    fn write_gpt(
+        disk: &mut [u8],
+        bs: usize,
+        header_block: u64,
+        entries_block: u64,
+        parts: &[(u64, u64, &str)],
+    ) {
+        let mut table = vec![0u8; 4 * 128];
+        for (i, &(first, last, name)) in parts.iter().enumerate() {
+            let e = &mut table[i * 128..(i + 1) * 128];
+            e[..16].fill(0xaa); // type GUID
+            e[16..32].fill(i as u8 + 1); // unique GUID
+            e[32..40].copy_from_slice(&first.to_le_bytes());
+            e[40..48].copy_from_slice(&last.to_le_bytes());
+            for (j, c) in name.encode_utf16().enumerate() {
+                e[56 + j * 2..58 + j * 2].copy_from_slice(&c.to_le_bytes());
+            }
+        }
+        let offset = entries_block as usize * bs;
+        disk[offset..offset + table.len()].copy_from_slice(&table);
+
+        let h = &mut disk[header_block as usize * bs..][..bs];
+        h[..8].copy_from_slice(GPT_SIGNATURE);
+        h[12..16].copy_from_slice(&92u32.to_le_bytes());
+        h[72..80].copy_from_slice(&entries_block.to_le_bytes());
+        h[80..84].copy_from_slice(&4u32.to_le_bytes());
+        h[84..88].copy_from_slice(&128u32.to_le_bytes());
+        h[88..92].copy_from_slice(&crc32(&table).to_le_bytes());
+        let crc = crc32(&h[..92]);
+        h[16..20].copy_from_slice(&crc.to_le_bytes());
+    }
+
+    #[test]
+    fn test_mbr() {
+        let mut disk = vec![0; 256 * BLOCK_SIZE];
+        set_mbr_entry(&mut disk, 0, 0, 0x0c, 8, 32);
+        set_mbr_entry(&mut disk, 0, 2, 0x83, 40, 64);
+        set_mbr_entry(&mut disk, 0, 3, 0x05, 128, 128);
+        // two logical partitions in the extended partition
+        set_mbr_entry(&mut disk, 128, 0, 0x83, 2, 30);
+        set_mbr_entry(&mut disk, 128, 1, 0x05, 64, 64);
+        set_mbr_entry(&mut disk, 192, 0, 0x82, 2, 62);
+
+        let parts = scan_partitions(&mut MockDisk(disk, BLOCK_SIZE)).unwrap();
+        let ranges: Vec<_> = parts
+            .iter()
+            .map(|p| (p.start_block, p.num_blocks))
+            .collect();
+        assert_eq!(ranges, [(8, 32), (40, 64), (130, 30), (194, 62)]);
+        assert_eq!(parts[3].index, 3);
+        assert_eq!(parts[1].kind, PartitionType::Mbr(0x83));
+        assert!(parts[0].guid.is_none());
+    }
+
+    #[test]
+    fn test_no_table() {
+        let mut disk = MockDisk(vec![0; 16 * BLOCK_SIZE], BLOCK_SIZE);
+        assert!(scan_partitions(&mut disk).unwrap().is_empty());
+        // a FAT boot sector has the MBR signature, but invalid entries
+        disk.0[MBR_ENTRY_OFFSET] = 0x33;
+        disk.0[510..512].copy_from_slice(&MBR_SIGNATURE.to_le_bytes());
+        assert!(scan_partitions(&mut disk).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_gpt() {
+        let mut disk = vec![0; 128 * BLOCK_SIZE];
+        set_mbr_entry(&mut disk, 0, 0, MBR_TYPE_GPT_PROTECTIVE, 1, 127);
+        let parts = [(34, 63, "boot"), (64, 95, "data")];
+        write_gpt(&mut disk, BLOCK_SIZE, 1, 2, &parts);
+        write_gpt(&mut disk, BLOCK_SIZE, 127, 96, &parts);
+
+        let mut disk = MockDisk(disk, BLOCK_SIZE);
+        let found = scan_partitions(&mut disk).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[1].label, "data");
+        assert_eq!((found[1].start_block, found[1].num_blocks), (64, 32));
+        assert_eq!(found[0].guid, Some(Guid::from_bytes([1; 16])));
+
+        // falls back to the backup GPT
+        disk.0[2 * BLOCK_SIZE + 40] ^= 1;
+        let found = scan_partitions(&mut disk).unwrap();
+        assert_eq!(found[0].label, "boot");
+        assert_eq!((found[0].start_block, found[0].num_blocks), (34, 30));
+    }
+
+    #[test]
+    fn test_gpt_4k() {
+        const BS: usize = 4096;
+        let mut disk = vec![0; 64 * BS];
+        set_mbr_entry(&mut disk, 0, 0, MBR_TYPE_GPT_PROTECTIVE, 1, 63);
+        write_gpt(&mut disk, BS, 1, 2, &[(6, 37, "data")]);
+
+        let mut disk = MockDisk(disk, BS);
+        let info = &scan_partitions(&mut disk).unwrap()[0];
+        assert_eq!((info.start_block, info.num_blocks), (6, 32));
+
+        let mut part = Partition::new(SharedDevice::new(disk), info);
+        assert_eq!(part.block_size(), BS);
+        part.write_block(30, &[1; BS * 2]).unwrap();
+        assert!(part.write_block(31, &[1; BS * 2]).is_err());
+    }
+
+    #[test]
+    fn test_partition_io() {
+        let mut disk = vec![0; 64 * BLOCK_SIZE];
+        set_mbr_entry(&mut disk, 0, 0, 0x83, 16, 8);
+        let mut disk = MockDisk(disk, BLOCK_SIZE);
+        let info = &scan_partitions(&mut disk).unwrap()[0];
+
+        let shared = SharedDevice::new(disk);
+        let mut part = Partition::new(shared.clone(), info);
+        assert_eq!(part.device_name(), "mockp1");
+        assert_eq!(part.num_blocks(), 8);
+        part.write_block(7, &[1; BLOCK_SIZE]).unwrap();
+        assert_eq!(shared.lock().0[23 * BLOCK_SIZE], 1);
+        assert!(part.write_block(8, &[1; BLOCK_SIZE]).is_err());
+        assert!(part.read_block(6, &mut [0; BLOCK_SIZE * 3]).is_err());
+
+        let mut whole = Partition::whole(shared);
+        let mut buf = [0; BLOCK_SIZE];
+        whole.read_block(23, &mut buf).unwrap();
+        assert_eq!(buf, [1; BLOCK_SIZE]);
+    }
+
+    #[test]
+    fn test_guid() {
+        let s = "c12a7328-f81f-11d2-ba4b-00a0c93ec93b"; // EFI system partition
+        let guid: Guid = s.to_uppercase().parse().unwrap();
+        assert_eq!(guid.as_bytes()[..4], [0x28, 0x73, 0x2a, 0xc1]);
+        assert_eq!(alloc::format!("{}", guid), s);
+        assert!("c12a7328-f81f-11d2-ba4b".parse::<Guid>().is_err());
+        assert!("c12a7328-f81f-11d2-ba4b-00a0c93ec93g"
+            .parse::<Guid>()
+            .is_err());
+    }
+}
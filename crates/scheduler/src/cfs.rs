@@ -64,10 +64,15 @@ impl<T> CFSTask<T> {
 
     fn set_vruntime(&self, v: isize) {
         self.init_vruntime.store(v, Ordering::Release);
+        self.delta.store(0, Ordering::Release);
+    }
+
+    fn get_nice(&self) -> isize {
+        self.nice.load(Ordering::Acquire)
     }
 
-    // Simple Implementation: no change in vruntime.
-    // Only modifying priority of current process is supported currently.
+    // The vruntime so far is kept, and the new weight applies to the runtime
+    // afterwards. So the key of a ready task does not change.
     fn set_priority(&self, nice: isize) {
         let current_init_vruntime = self.get_vruntime();
         self.init_vruntime
@@ -119,6 +124,14 @@ impl<T> CFScheduler<T> {
     pub fn scheduler_name() -> &'static str {
         "Completely Fair"
     }
+
+    fn update_min_vruntime(&mut self) {
+        if let Some(((min_vruntime, _), _)) = self.ready_queue.first_key_value() {
+            self.min_vruntime = Some(AtomicIsize::new(*min_vruntime));
+        } else {
+            self.min_vruntime = None;
+        }
+    }
 }
 
 impl<T> BaseScheduler for CFScheduler<T> {
@@ -135,11 +148,7 @@ impl<T> BaseScheduler for CFScheduler<T> {
         task.set_vruntime(vruntime);
         task.set_id(taskid);
         self.ready_queue.insert((vruntime, taskid), task);
-        if let Some(((min_vruntime, _), _)) = self.ready_queue.first_key_value() {
-            self.min_vruntime = Some(AtomicIsize::new(*min_vruntime));
-        } else {
-            self.min_vruntime = None;
-        }
+        self.update_min_vruntime();
     }
 
     fn remove_task(&mut self, task: &Self::SchedItem) -> Option<Self::SchedItem> {
@@ -147,11 +156,7 @@ impl<T> BaseScheduler for CFScheduler<T> {
             .ready_queue
             .remove_entry(&(task.clone().get_vruntime(), task.clone().get_id()))
         {
-            if let Some(((min_vruntime, _), _)) = self.ready_queue.first_key_value() {
-                self.min_vruntime = Some(AtomicIsize::new(*min_vruntime));
-            } else {
-                self.min_vruntime = None;
-            }
+            self.update_min_vruntime();
             Some(tmp)
         } else {
             None
@@ -166,6 +171,16 @@ impl<T> BaseScheduler for CFScheduler<T> {
         }
     }
 
+    fn pick_next_task_if<F>(&mut self, mut pred: F) -> Option<Self::SchedItem>
+    where
+        F: FnMut(&Self::SchedItem) -> bool,
+    {
+        let key = *self.ready_queue.iter().find(|(_, t)| pred(t))?.0;
+        let task = self.ready_queue.remove(&key);
+        self.update_min_vruntime();
+        task
+    }
+
     fn put_prev_task(&mut self, prev: Self::SchedItem, _preempt: bool) {
         let taskid = self.id_pool.fetch_add(1, Ordering::Release);
         prev.set_id(taskid);
@@ -181,10 +196,20 @@ impl<T> BaseScheduler for CFScheduler<T> {
 
     fn set_priority(&mut self, task: &Self::SchedItem, prio: isize) -> bool {
         if (-20..=19).contains(&prio) {
+            let removed = self.remove_task(task);
             task.set_priority(prio);
+            if let Some(task) = removed {
+                let key = (task.get_vruntime(), task.get_id());
+                self.ready_queue.insert(key, task);
+                self.update_min_vruntime();
+            }
             true
         } else {
             false
         }
     }
+
+    fn get_priority(&self, task: &Self::SchedItem) -> isize {
+        task.get_nice()
+    }
 }
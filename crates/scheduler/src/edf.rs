@@ -0,0 +1,177 @@
+use alloc::{collections::BTreeMap, sync::Arc};
+use core::ops::Deref;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::BaseScheduler;
+
+/// A task wrapper for the [`EDFScheduler`].
+///
+/// It adds the relative deadline of the task, and the absolute deadline of
+/// its current job, both in timer ticks.
+pub struct EDFTask<T> {
+    inner: T,
+    rel_deadline: AtomicU64,
+    deadline: AtomicU64,
+    seq: AtomicU64,
+}
+
+impl<T> EDFTask<T> {
+    /// Creates a new [`EDFTask`] from the inner task struct. It has no
+    /// deadline.
+    pub const fn new(inner: T) -> Self {
+        Self {
+            inner,
+            rel_deadline: AtomicU64::new(0),
+            deadline: AtomicU64::new(u64::MAX),
+            seq: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the relative deadline of the task in ticks, or `0` if it has
+    /// no deadline.
+    pub fn relative_deadline(&self) -> u64 {
+        self.rel_deadline.load(Ordering::Acquire)
+    }
+
+    /// Returns the absolute deadline of the current job in ticks, or
+    /// `u64::MAX` if the task has no deadline.
+    pub fn deadline(&self) -> u64 {
+        self.deadline.load(Ordering::Acquire)
+    }
+
+    /// Starts a new job at `now`.
+    fn release(&self, now: u64) {
+        let rel_deadline = self.relative_deadline();
+        let deadline = if rel_deadline > 0 {
+            now.saturating_add(rel_deadline)
+        } else {
+            u64::MAX
+        };
+        self.deadline.store(deadline, Ordering::Release);
+    }
+
+    fn key(&self) -> (u64, u64) {
+        (self.deadline(), self.seq.load(Ordering::Acquire))
+    }
+
+    /// Returns a reference to the inner task struct.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> Deref for EDFTask<T> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+/// An [Earliest Deadline First][1] (EDF) preemptive scheduler.
+///
+/// Each task has a relative deadline, set by [`BaseScheduler::set_priority`]
+/// in timer ticks. A job of the task is released when it is added to the
+/// scheduler (e.g., woken up) or yields, and its absolute deadline is the
+/// release time plus the relative deadline. The ready task with the earliest
+/// absolute deadline runs first, and tasks without deadlines run only if no
+/// task with a deadline is ready.
+///
+/// The current time is read from a clock given by [`EDFScheduler::with_clock`],
+/// which should be global and monotonic, so that deadlines are comparable
+/// across CPUs and keep advancing while the timer interrupt is off.
+///
+/// [1]: https://en.wikipedia.org/wiki/Earliest_deadline_first_scheduling
+pub struct EDFScheduler<T> {
+    ready_queue: BTreeMap<(u64, u64), Arc<EDFTask<T>>>, // (deadline, seq)
+    next_seq: u64,
+    clock: fn() -> u64,
+}
+
+impl<T> EDFScheduler<T> {
+    /// Creates a new empty [`EDFScheduler`] whose clock is always `0`, so
+    /// every job is released at time `0`.
+    pub const fn new() -> Self {
+        Self::with_clock(|| 0)
+    }
+
+    /// Creates a new empty [`EDFScheduler`] that reads the current time in
+    /// ticks from `clock`.
+    pub const fn with_clock(clock: fn() -> u64) -> Self {
+        Self {
+            ready_queue: BTreeMap::new(),
+            next_seq: 0,
+            clock,
+        }
+    }
+
+    /// get the name of scheduler
+    pub fn scheduler_name() -> &'static str {
+        "Earliest Deadline First"
+    }
+
+    fn enqueue(&mut self, task: Arc<EDFTask<T>>) {
+        task.seq.store(self.next_seq, Ordering::Release);
+        self.next_seq += 1;
+        self.ready_queue.insert(task.key(), task);
+    }
+}
+
+impl<T> BaseScheduler for EDFScheduler<T> {
+    type SchedItem = Arc<EDFTask<T>>;
+
+    fn init(&mut self) {}
+
+    fn add_task(&mut self, task: Self::SchedItem) {
+        task.release((self.clock)());
+        self.enqueue(task);
+    }
+
+    fn remove_task(&mut self, task: &Self::SchedItem) -> Option<Self::SchedItem> {
+        self.ready_queue.remove(&task.key())
+    }
+
+    fn pick_next_task(&mut self) -> Option<Self::SchedItem> {
+        self.ready_queue.pop_first().map(|(_, task)| task)
+    }
+
+    fn pick_next_task_if<F>(&mut self, mut pred: F) -> Option<Self::SchedItem>
+    where
+        F: FnMut(&Self::SchedItem) -> bool,
+    {
+        let key = *self.ready_queue.iter().find(|(_, t)| pred(t))?.0;
+        self.ready_queue.remove(&key)
+    }
+
+    fn put_prev_task(&mut self, prev: Self::SchedItem, preempt: bool) {
+        // a preempted job continues, while yielding finishes the job
+        if !preempt {
+            prev.release((self.clock)());
+        }
+        self.enqueue(prev);
+    }
+
+    fn task_tick(&mut self, current: &Self::SchedItem) -> bool {
+        match self.ready_queue.first_key_value() {
+            Some(((deadline, _), _)) => *deadline < current.deadline(),
+            None => false,
+        }
+    }
+
+    fn set_priority(&mut self, task: &Self::SchedItem, prio: isize) -> bool {
+        if prio < 0 {
+            return false;
+        }
+        let removed = self.remove_task(task);
+        task.rel_deadline.store(prio as u64, Ordering::Release);
+        task.release((self.clock)());
+        if let Some(task) = removed {
+            self.enqueue(task);
+        }
+        true
+    }
+
+    fn get_priority(&self, task: &Self::SchedItem) -> isize {
+        task.relative_deadline() as isize
+    }
+}
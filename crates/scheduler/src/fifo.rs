@@ -88,6 +88,25 @@ impl<T> BaseScheduler for FifoScheduler<T> {
         self.ready_queue.pop_front()
     }
 
+    fn pick_next_task_if<F>(&mut self, mut pred: F) -> Option<Self::SchedItem>
+    where
+        F: FnMut(&Self::SchedItem) -> bool,
+    {
+        // the list has no cursor to remove entries, so move other tasks to a
+        // new list in order
+        let mut rest = List::new();
+        let mut picked = None;
+        while let Some(task) = self.ready_queue.pop_front() {
+            if picked.is_none() && pred(&task) {
+                picked = Some(task);
+            } else {
+                rest.push_back(task);
+            }
+        }
+        self.ready_queue = rest;
+        picked
+    }
+
     fn put_prev_task(&mut self, prev: Self::SchedItem, _preempt: bool) {
         self.ready_queue.push_back(prev);
     }
@@ -99,4 +118,8 @@ impl<T> BaseScheduler for FifoScheduler<T> {
     fn set_priority(&mut self, _task: &Self::SchedItem, _prio: isize) -> bool {
         false
     }
+
+    fn get_priority(&self, _task: &Self::SchedItem) -> isize {
+        0
+    }
 }
@@ -5,13 +5,19 @@
 //! - [`FifoScheduler`]: FIFO (First-In-First-Out) scheduler (cooperative).
 //! - [`RRScheduler`]: Round-robin scheduler (preemptive).
 //! - [`CFScheduler`]: Completely Fair Scheduler (preemptive).
+//! - [`RTScheduler`]: Real-time scheduler with fixed priorities (preemptive).
+//! - [`RTCFScheduler`]: Real-time scheduler with a fair class for non-real-time
+//!   tasks (preemptive).
+//! - [`EDFScheduler`]: Earliest Deadline First scheduler (preemptive).
 
 #![cfg_attr(not(test), no_std)]
 #![feature(const_mut_refs)]
 
 mod cfs;
+mod edf;
 mod fifo;
 mod round_robin;
+mod rt;
 
 #[cfg(test)]
 mod tests;
@@ -19,8 +25,10 @@ mod tests;
 extern crate alloc;
 
 pub use cfs::{CFSTask, CFScheduler};
+pub use edf::{EDFScheduler, EDFTask};
 pub use fifo::{FifoScheduler, FifoTask};
 pub use round_robin::{RRScheduler, RRTask};
+pub use rt::{RTCFScheduler, RTPolicy, RTScheduler, RTTask, RT_PRIO_LEVELS};
 
 /// The base scheduler trait that all schedulers should implement.
 ///
@@ -49,6 +57,13 @@ pub trait BaseScheduler {
     /// Returns [`None`] if there is not runnable task.
     fn pick_next_task(&mut self) -> Option<Self::SchedItem>;
 
+    /// Picks the first task that satisfies `pred`, in the order of
+    /// [`pick_next_task`](BaseScheduler::pick_next_task), and removes it
+    /// from the scheduler. The order of other tasks is not changed.
+    fn pick_next_task_if<F>(&mut self, pred: F) -> Option<Self::SchedItem>
+    where
+        F: FnMut(&Self::SchedItem) -> bool;
+
     /// Puts the previous task back to the scheduler. The previous task is
     /// usually placed at the end of the ready queue, making it less likely
     /// to be re-scheduled.
@@ -65,5 +80,13 @@ pub trait BaseScheduler {
     fn task_tick(&mut self, current: &Self::SchedItem) -> bool;
 
     /// set priority for a task
+    ///
+    /// The task may be in the scheduler, running, or sleeping. If it is in the
+    /// scheduler, it is moved to the right place for the new priority.
     fn set_priority(&mut self, task: &Self::SchedItem, prio: isize) -> bool;
+
+    /// get priority of a task, in the same form as [`set_priority`].
+    ///
+    /// [`set_priority`]: BaseScheduler::set_priority
+    fn get_priority(&self, task: &Self::SchedItem) -> isize;
 }
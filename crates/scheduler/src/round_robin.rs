@@ -93,6 +93,14 @@ impl<T, const S: usize> BaseScheduler for RRScheduler<T, S> {
         self.ready_queue.pop_front()
     }
 
+    fn pick_next_task_if<F>(&mut self, pred: F) -> Option<Self::SchedItem>
+    where
+        F: FnMut(&Self::SchedItem) -> bool,
+    {
+        let idx = self.ready_queue.iter().position(pred)?;
+        self.ready_queue.remove(idx)
+    }
+
     fn put_prev_task(&mut self, prev: Self::SchedItem, preempt: bool) {
         if prev.time_slice() > 0 && preempt {
             self.ready_queue.push_front(prev)
@@ -110,4 +118,8 @@ impl<T, const S: usize> BaseScheduler for RRScheduler<T, S> {
     fn set_priority(&mut self, _task: &Self::SchedItem, _prio: isize) -> bool {
         false
     }
+
+    fn get_priority(&self, _task: &Self::SchedItem) -> isize {
+        0
+    }
 }
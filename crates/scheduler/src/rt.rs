@@ -0,0 +1,444 @@
+use alloc::{collections::VecDeque, sync::Arc};
+use core::ops::Deref;
+use core::sync::atomic::{AtomicIsize, AtomicU8, AtomicUsize, Ordering};
+
+use crate::{BaseScheduler, CFSTask, CFScheduler};
+
+/// The number of real-time priority levels.
+///
+/// Real-time priorities range from 0 to `RT_PRIO_LEVELS - 1`, and a larger
+/// value means a higher priority, as `sched_priority` in POSIX.
+pub const RT_PRIO_LEVELS: usize = 100;
+
+/// Scheduling policies of tasks in the [`RTScheduler`] and [`RTCFScheduler`].
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RTPolicy {
+    /// Not a real-time task. It runs only if there are no ready real-time
+    /// tasks, and is scheduled by the non-real-time class of the scheduler.
+    Normal = 0,
+    /// First-in-first-out real-time policy (`SCHED_FIFO`). The task runs until
+    /// it blocks, yields, or is preempted by a higher priority task.
+    Fifo = 1,
+    /// Round-robin real-time policy (`SCHED_RR`). Like [`RTPolicy::Fifo`], but
+    /// the task is also moved to the end of its priority level when its time
+    /// slice is used up.
+    RoundRobin = 2,
+}
+
+impl RTPolicy {
+    const fn from_u8(policy: u8) -> Self {
+        match policy {
+            1 => Self::Fifo,
+            2 => Self::RoundRobin,
+            _ => Self::Normal,
+        }
+    }
+
+    /// Whether it is a real-time policy.
+    pub const fn is_rt(&self) -> bool {
+        !matches!(self, Self::Normal)
+    }
+}
+
+/// A task wrapper for the [`RTScheduler`] and [`RTCFScheduler`].
+///
+/// It adds the scheduling policy, the real-time priority and a time slice
+/// counter. New tasks are [`RTPolicy::Normal`] ones.
+pub struct RTTask<T, const MAX_TIME_SLICE: usize> {
+    inner: T,
+    policy: AtomicU8,
+    prio: AtomicUsize,
+    time_slice: AtomicIsize,
+}
+
+impl<T, const S: usize> RTTask<T, S> {
+    /// Creates a new [`RTTask`] from the inner task struct.
+    pub const fn new(inner: T) -> Self {
+        Self {
+            inner,
+            policy: AtomicU8::new(RTPolicy::Normal as u8),
+            prio: AtomicUsize::new(0),
+            time_slice: AtomicIsize::new(S as isize),
+        }
+    }
+
+    /// Returns the scheduling policy of the task.
+    pub fn policy(&self) -> RTPolicy {
+        RTPolicy::from_u8(self.policy.load(Ordering::Acquire))
+    }
+
+    /// Returns the real-time priority of the task. It is meaningless for
+    /// [`RTPolicy::Normal`] tasks.
+    pub fn rt_priority(&self) -> usize {
+        self.prio.load(Ordering::Acquire)
+    }
+
+    fn is_rt(&self) -> bool {
+        self.policy().is_rt()
+    }
+
+    fn set_policy(&self, policy: RTPolicy, prio: usize) {
+        self.policy.store(policy as u8, Ordering::Release);
+        self.prio.store(prio, Ordering::Release);
+    }
+
+    fn time_slice(&self) -> isize {
+        self.time_slice.load(Ordering::Acquire)
+    }
+
+    fn reset_time_slice(&self) {
+        self.time_slice.store(S as isize, Ordering::Release);
+    }
+
+    /// Decrements the time slice, returns `true` if it is used up.
+    fn tick_time_slice(&self) -> bool {
+        self.policy() != RTPolicy::Fifo && self.time_slice.fetch_sub(1, Ordering::Release) <= 1
+    }
+
+    /// Returns a reference to the inner task struct.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T, const S: usize> From<T> for RTTask<T, S> {
+    fn from(inner: T) -> Self {
+        Self::new(inner)
+    }
+}
+
+impl<T, const S: usize> Deref for RTTask<T, S> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+/// Ready queues of all real-time priority levels.
+struct RTReadyQueue<E> {
+    levels: [VecDeque<Arc<E>>; RT_PRIO_LEVELS],
+    /// Bit `i` is set if the level `i` is not empty.
+    bitmap: u128,
+}
+
+impl<E> RTReadyQueue<E> {
+    const fn new() -> Self {
+        Self {
+            levels: [const { VecDeque::new() }; RT_PRIO_LEVELS],
+            bitmap: 0,
+        }
+    }
+
+    fn highest_prio(&self) -> Option<usize> {
+        if self.bitmap == 0 {
+            None
+        } else {
+            Some(127 - self.bitmap.leading_zeros() as usize)
+        }
+    }
+
+    fn push(&mut self, prio: usize, task: Arc<E>, front: bool) {
+        if front {
+            self.levels[prio].push_front(task);
+        } else {
+            self.levels[prio].push_back(task);
+        }
+        self.bitmap |= 1 << prio;
+    }
+
+    fn pop(&mut self) -> Option<Arc<E>> {
+        let prio = self.highest_prio()?;
+        let task = self.levels[prio].pop_front();
+        if self.levels[prio].is_empty() {
+            self.bitmap &= !(1 << prio);
+        }
+        task
+    }
+
+    /// Removes the first task that satisfies `pred` in the order of `pop`.
+    fn pop_if<F>(&mut self, pred: &mut F) -> Option<Arc<E>>
+    where
+        F: FnMut(&Arc<E>) -> bool,
+    {
+        let mut bitmap = self.bitmap;
+        while bitmap != 0 {
+            let prio = 127 - bitmap.leading_zeros() as usize;
+            bitmap &= !(1 << prio);
+            let level = &mut self.levels[prio];
+            if let Some(idx) = level.iter().position(&mut *pred) {
+                let task = level.remove(idx);
+                if level.is_empty() {
+                    self.bitmap &= !(1 << prio);
+                }
+                return task;
+            }
+        }
+        None
+    }
+
+    fn remove(&mut self, prio: usize, task: &Arc<E>) -> Option<Arc<E>> {
+        let level = &mut self.levels[prio];
+        let task = level
+            .iter()
+            .position(|t| Arc::ptr_eq(t, task))
+            .and_then(|idx| level.remove(idx));
+        if level.is_empty() {
+            self.bitmap &= !(1 << prio);
+        }
+        task
+    }
+}
+
+/// A real-time preemptive scheduler with fixed priorities.
+///
+/// Real-time tasks have priorities from 0 to 99 (see [`RT_PRIO_LEVELS`]), and
+/// the highest priority ready task always runs first. Tasks of the same
+/// priority are scheduled by their policies, [`RTPolicy::Fifo`] or
+/// [`RTPolicy::RoundRobin`], like `SCHED_FIFO` and `SCHED_RR` in Linux.
+///
+/// [`RTPolicy::Normal`] tasks run only if there are no ready real-time tasks,
+/// and they are scheduled in round-robin among themselves. Use
+/// [`RTCFScheduler`] to schedule them fairly instead.
+///
+/// Use [`RTScheduler::set_policy`] to change the policy of a task, and
+/// [`BaseScheduler::set_priority`] to change the priority of a real-time task.
+pub struct RTScheduler<T, const MAX_TIME_SLICE: usize> {
+    rt_queue: RTReadyQueue<RTTask<T, MAX_TIME_SLICE>>,
+    normal_queue: VecDeque<Arc<RTTask<T, MAX_TIME_SLICE>>>,
+}
+
+impl<T, const S: usize> RTScheduler<T, S> {
+    /// Creates a new empty [`RTScheduler`].
+    pub const fn new() -> Self {
+        Self {
+            rt_queue: RTReadyQueue::new(),
+            normal_queue: VecDeque::new(),
+        }
+    }
+
+    /// get the name of scheduler
+    pub fn scheduler_name() -> &'static str {
+        "Real-time"
+    }
+
+    /// Sets the scheduling policy and the real-time priority of a task, and
+    /// moves it to the new ready queue if it is ready.
+    ///
+    /// `prio` is ignored for [`RTPolicy::Normal`]. Returns `false` if `prio`
+    /// is out of range.
+    pub fn set_policy(&mut self, task: &Arc<RTTask<T, S>>, policy: RTPolicy, prio: usize) -> bool {
+        if policy.is_rt() && prio >= RT_PRIO_LEVELS {
+            return false;
+        }
+        let removed = self.remove_task(task);
+        task.set_policy(policy, if policy.is_rt() { prio } else { 0 });
+        task.reset_time_slice();
+        if let Some(task) = removed {
+            self.add_task(task);
+        }
+        true
+    }
+}
+
+impl<T, const S: usize> BaseScheduler for RTScheduler<T, S> {
+    type SchedItem = Arc<RTTask<T, S>>;
+
+    fn init(&mut self) {}
+
+    fn add_task(&mut self, task: Self::SchedItem) {
+        if task.is_rt() {
+            self.rt_queue.push(task.rt_priority(), task, false);
+        } else {
+            self.normal_queue.push_back(task);
+        }
+    }
+
+    fn remove_task(&mut self, task: &Self::SchedItem) -> Option<Self::SchedItem> {
+        if task.is_rt() {
+            self.rt_queue.remove(task.rt_priority(), task)
+        } else {
+            self.normal_queue
+                .iter()
+                .position(|t| Arc::ptr_eq(t, task))
+                .and_then(|idx| self.normal_queue.remove(idx))
+        }
+    }
+
+    fn pick_next_task(&mut self) -> Option<Self::SchedItem> {
+        self.rt_queue
+            .pop()
+            .or_else(|| self.normal_queue.pop_front())
+    }
+
+    fn pick_next_task_if<F>(&mut self, mut pred: F) -> Option<Self::SchedItem>
+    where
+        F: FnMut(&Self::SchedItem) -> bool,
+    {
+        self.rt_queue.pop_if(&mut pred).or_else(|| {
+            let idx = self.normal_queue.iter().position(pred)?;
+            self.normal_queue.remove(idx)
+        })
+    }
+
+    fn put_prev_task(&mut self, prev: Self::SchedItem, preempt: bool) {
+        // a preempted task keeps its place at the head of its level
+        let front = preempt && prev.time_slice() > 0;
+        if !front {
+            prev.reset_time_slice();
+        }
+        if prev.is_rt() {
+            self.rt_queue.push(prev.rt_priority(), prev, front);
+        } else if front {
+            self.normal_queue.push_front(prev);
+        } else {
+            self.normal_queue.push_back(prev);
+        }
+    }
+
+    fn task_tick(&mut self, current: &Self::SchedItem) -> bool {
+        let expired = current.tick_time_slice();
+        match self.rt_queue.highest_prio() {
+            Some(prio) if !current.is_rt() || prio > current.rt_priority() => true,
+            _ => expired,
+        }
+    }
+
+    fn set_priority(&mut self, task: &Self::SchedItem, prio: isize) -> bool {
+        let policy = task.policy();
+        policy.is_rt() && prio >= 0 && self.set_policy(task, policy, prio as usize)
+    }
+
+    fn get_priority(&self, task: &Self::SchedItem) -> isize {
+        task.rt_priority() as isize
+    }
+}
+
+/// A scheduler with a real-time class and a fair class.
+///
+/// Real-time tasks are scheduled as in [`RTScheduler`], and always run
+/// before [`RTPolicy::Normal`] tasks, which are scheduled by the
+/// [`CFScheduler`].
+///
+/// [`BaseScheduler::set_priority`] sets the real-time priority of real-time
+/// tasks, and the nice value of normal tasks.
+pub struct RTCFScheduler<T, const MAX_TIME_SLICE: usize> {
+    rt_queue: RTReadyQueue<CFSTask<RTTask<T, MAX_TIME_SLICE>>>,
+    cfs: CFScheduler<RTTask<T, MAX_TIME_SLICE>>,
+}
+
+impl<T, const S: usize> RTCFScheduler<T, S> {
+    /// Creates a new empty [`RTCFScheduler`].
+    pub const fn new() -> Self {
+        Self {
+            rt_queue: RTReadyQueue::new(),
+            cfs: CFScheduler::new(),
+        }
+    }
+
+    /// get the name of scheduler
+    pub fn scheduler_name() -> &'static str {
+        "Real-time + Completely Fair"
+    }
+
+    /// Sets the scheduling policy and the real-time priority of a task, and
+    /// moves it to the new ready queue if it is ready.
+    ///
+    /// `prio` is ignored for [`RTPolicy::Normal`]. Returns `false` if `prio`
+    /// is out of range.
+    pub fn set_policy(
+        &mut self,
+        task: &Arc<CFSTask<RTTask<T, S>>>,
+        policy: RTPolicy,
+        prio: usize,
+    ) -> bool {
+        if policy.is_rt() && prio >= RT_PRIO_LEVELS {
+            return false;
+        }
+        let removed = self.remove_task(task);
+        task.set_policy(policy, if policy.is_rt() { prio } else { 0 });
+        task.reset_time_slice();
+        if let Some(task) = removed {
+            self.add_task(task);
+        }
+        true
+    }
+}
+
+impl<T, const S: usize> BaseScheduler for RTCFScheduler<T, S> {
+    type SchedItem = Arc<CFSTask<RTTask<T, S>>>;
+
+    fn init(&mut self) {
+        self.cfs.init();
+    }
+
+    fn add_task(&mut self, task: Self::SchedItem) {
+        if task.is_rt() {
+            self.rt_queue.push(task.rt_priority(), task, false);
+        } else {
+            self.cfs.add_task(task);
+        }
+    }
+
+    fn remove_task(&mut self, task: &Self::SchedItem) -> Option<Self::SchedItem> {
+        if task.is_rt() {
+            self.rt_queue.remove(task.rt_priority(), task)
+        } else {
+            self.cfs.remove_task(task)
+        }
+    }
+
+    fn pick_next_task(&mut self) -> Option<Self::SchedItem> {
+        self.rt_queue.pop().or_else(|| self.cfs.pick_next_task())
+    }
+
+    fn pick_next_task_if<F>(&mut self, mut pred: F) -> Option<Self::SchedItem>
+    where
+        F: FnMut(&Self::SchedItem) -> bool,
+    {
+        self.rt_queue
+            .pop_if(&mut pred)
+            .or_else(|| self.cfs.pick_next_task_if(pred))
+    }
+
+    fn put_prev_task(&mut self, prev: Self::SchedItem, preempt: bool) {
+        if prev.is_rt() {
+            let front = preempt && prev.time_slice() > 0;
+            if !front {
+                prev.reset_time_slice();
+            }
+            self.rt_queue.push(prev.rt_priority(), prev, front);
+        } else {
+            self.cfs.put_prev_task(prev, preempt);
+        }
+    }
+
+    fn task_tick(&mut self, current: &Self::SchedItem) -> bool {
+        let highest_rt = self.rt_queue.highest_prio();
+        if current.is_rt() {
+            let expired = current.tick_time_slice();
+            matches!(highest_rt, Some(prio) if prio > current.rt_priority()) || expired
+        } else {
+            // always tick the fair class to account the runtime
+            self.cfs.task_tick(current) || highest_rt.is_some()
+        }
+    }
+
+    fn set_priority(&mut self, task: &Self::SchedItem, prio: isize) -> bool {
+        let policy = task.policy();
+        if policy.is_rt() {
+            prio >= 0 && self.set_policy(task, policy, prio as usize)
+        } else {
+            self.cfs.set_priority(task, prio)
+        }
+    }
+
+    fn get_priority(&self, task: &Self::SchedItem) -> isize {
+        if task.is_rt() {
+            task.rt_priority() as isize
+        } else {
+            self.cfs.get_priority(task)
+        }
+    }
+}
@@ -28,6 +28,29 @@ macro_rules! def_test_sched {
                 assert_eq!(n, NUM_TASKS);
             }
 
+            #[test]
+            fn test_pick_next_task_if() {
+                const NUM_TASKS: usize = 11;
+
+                let mut scheduler = <$scheduler>::new();
+                for i in 0..NUM_TASKS {
+                    scheduler.add_task(Arc::new(<$task>::new(i)));
+                }
+
+                let picked = scheduler.pick_next_task_if(|t| *t.inner() % 5 == 4);
+                assert_eq!(*picked.unwrap().inner(), 4);
+                assert!(scheduler
+                    .pick_next_task_if(|t| *t.inner() > NUM_TASKS)
+                    .is_none());
+
+                // the order of other tasks is kept
+                let order: Vec<_> = core::iter::from_fn(|| scheduler.pick_next_task())
+                    .map(|t| *t.inner())
+                    .collect();
+                let expected: Vec<_> = (0..NUM_TASKS).filter(|i| *i != 4).collect();
+                assert_eq!(order, expected);
+            }
+
             #[test]
             fn bench_yield() {
                 const NUM_TASKS: usize = 1_000_000;
@@ -82,3 +105,141 @@ macro_rules! def_test_sched {
 def_test_sched!(fifo, FifoScheduler::<usize>, FifoTask::<usize>);
 def_test_sched!(rr, RRScheduler::<usize, 5>, RRTask::<usize, 5>);
 def_test_sched!(cfs, CFScheduler::<usize>, CFSTask::<usize>);
+def_test_sched!(rt, RTScheduler::<usize, 5>, RTTask::<usize, 5>);
+def_test_sched!(edf, EDFScheduler::<usize>, EDFTask::<usize>);
+
+mod rt_prio {
+    use crate::*;
+    use alloc::sync::Arc;
+
+    #[test]
+    fn test_rt_priority() {
+        let mut scheduler = RTScheduler::<usize, 5>::new();
+        let tasks: Vec<_> = (0..4).map(|i| Arc::new(RTTask::new(i))).collect();
+        for t in &tasks {
+            scheduler.add_task(t.clone());
+        }
+        // task 0 stays normal, others are real-time ones of rising priorities
+        assert!(scheduler.set_policy(&tasks[1], RTPolicy::Fifo, 10));
+        assert!(scheduler.set_policy(&tasks[2], RTPolicy::RoundRobin, 50));
+        assert!(scheduler.set_policy(&tasks[3], RTPolicy::Fifo, 50));
+        assert!(!scheduler.set_policy(&tasks[3], RTPolicy::Fifo, RT_PRIO_LEVELS));
+        assert!(!scheduler.set_priority(&tasks[0], 1));
+
+        let order: Vec<_> = core::iter::from_fn(|| scheduler.pick_next_task())
+            .map(|t| *t.inner())
+            .collect();
+        assert_eq!(order, [2, 3, 1, 0]);
+
+        // a lower priority task is preempted at the tick if a higher one is ready
+        scheduler.add_task(tasks[2].clone());
+        assert!(scheduler.task_tick(&tasks[1]));
+        assert!(!scheduler.task_tick(&tasks[3]));
+
+        // FIFO tasks never expire, round-robin ones do
+        for _ in 0..10 {
+            assert!(!scheduler.task_tick(&tasks[3]));
+        }
+        let rr = scheduler.pick_next_task().unwrap();
+        assert_eq!(*rr.inner(), 2);
+        assert!((0..5).any(|_| scheduler.task_tick(&rr)));
+    }
+
+    #[test]
+    fn test_rt_cfs() {
+        let mut scheduler = RTCFScheduler::<usize, 5>::new();
+        let normal = Arc::new(CFSTask::new(RTTask::new(0)));
+        let rt = Arc::new(CFSTask::new(RTTask::new(1)));
+        scheduler.add_task(normal.clone());
+        scheduler.add_task(rt.clone());
+        assert!(scheduler.set_policy(&rt, RTPolicy::RoundRobin, 1));
+        assert!(scheduler.set_priority(&normal, -5));
+
+        let next = scheduler.pick_next_task().unwrap();
+        assert!(Arc::ptr_eq(&next, &rt));
+        scheduler.put_prev_task(next, false);
+        // the normal task is preempted by the ready real-time task
+        assert!(scheduler.task_tick(&normal));
+    }
+
+    #[test]
+    fn test_edf() {
+        use core::sync::atomic::{AtomicU64, Ordering};
+        static NOW: AtomicU64 = AtomicU64::new(0);
+
+        let mut scheduler = EDFScheduler::<usize>::with_clock(|| NOW.load(Ordering::Relaxed));
+        let tasks: Vec<_> = (0..3).map(|i| Arc::new(EDFTask::new(i))).collect();
+        assert!(scheduler.set_priority(&tasks[1], 20));
+        assert!(scheduler.set_priority(&tasks[2], 10));
+        assert!(!scheduler.set_priority(&tasks[0], -1));
+        for t in &tasks {
+            scheduler.add_task(t.clone());
+        }
+
+        let next = scheduler.pick_next_task().unwrap();
+        assert_eq!(*next.inner(), 2);
+        for _ in 0..5 {
+            NOW.fetch_add(1, Ordering::Relaxed);
+            assert!(!scheduler.task_tick(&next));
+        }
+        // yielding finishes the job, the next one is due at tick 15
+        scheduler.put_prev_task(next, false);
+        assert_eq!(tasks[2].deadline(), 15);
+        assert_eq!(*scheduler.pick_next_task().unwrap().inner(), 2);
+
+        // task 1 (deadline 20) preempts the task without a deadline
+        assert_eq!(*scheduler.pick_next_task().unwrap().inner(), 1);
+        scheduler.add_task(tasks[1].clone());
+        assert!(scheduler.task_tick(&tasks[0]));
+    }
+}
+
+mod set_prio {
+    use crate::*;
+    use alloc::sync::Arc;
+
+    #[test]
+    fn test_cfs_set_priority() {
+        let mut scheduler = CFScheduler::<usize>::new();
+        let tasks: Vec<_> = (0..3).map(|i| Arc::new(CFSTask::new(i))).collect();
+        for t in &tasks {
+            scheduler.add_task(t.clone());
+        }
+
+        // task 0 runs for a while, then sleeps and wakes up again
+        let next = scheduler.pick_next_task().unwrap();
+        for _ in 0..10 {
+            scheduler.task_tick(&next);
+        }
+        scheduler.add_task(next);
+
+        // ready tasks are still found after changing priorities
+        assert!(scheduler.set_priority(&tasks[0], 5));
+        assert!(scheduler.set_priority(&tasks[1], -10));
+        assert!(!scheduler.set_priority(&tasks[2], 20));
+        assert_eq!(scheduler.get_priority(&tasks[0]), 5);
+        assert_eq!(scheduler.get_priority(&tasks[1]), -10);
+        assert_eq!(scheduler.get_priority(&tasks[2]), 0);
+        for t in &tasks {
+            assert!(scheduler.remove_task(t).is_some());
+        }
+        assert!(scheduler.pick_next_task().is_none());
+    }
+
+    #[test]
+    fn test_set_priority_not_ready() {
+        let mut scheduler = RTScheduler::<usize, 5>::new();
+        let running = Arc::new(RTTask::new(0));
+        assert!(scheduler.set_policy(&running, RTPolicy::Fifo, 10));
+        assert!(scheduler.set_priority(&running, 20));
+        assert_eq!(scheduler.get_priority(&running), 20);
+        // the task is not put into the scheduler
+        assert!(scheduler.pick_next_task().is_none());
+
+        let mut scheduler = EDFScheduler::<usize>::new();
+        let running = Arc::new(EDFTask::new(0));
+        assert!(scheduler.set_priority(&running, 10));
+        assert_eq!(scheduler.get_priority(&running), 10);
+        assert!(scheduler.pick_next_task().is_none());
+    }
+}
@@ -0,0 +1,54 @@
+use axfs_vfs::{VfsNodeAttr, VfsNodeOps, VfsNodePerm, VfsNodeType, VfsResult};
+
+/// The framebuffer device `/dev/fb0`.
+///
+/// It reads and writes the framebuffer of the main display directly, and the
+/// screen is flushed after each write.
+pub(crate) struct FrameBufferDev;
+
+impl FrameBufferDev {
+    /// Returns the part of the framebuffer starting at `offset`, with at most
+    /// `len` bytes.
+    fn framebuffer(offset: u64, len: usize) -> &'static mut [u8] {
+        let info = crate::framebuffer_info();
+        let offset = (offset as usize).min(info.fb_size);
+        let len = len.min(info.fb_size - offset);
+        // SAFETY: the framebuffer is mapped during the lifetime of the device
+        unsafe { core::slice::from_raw_parts_mut((info.fb_base_vaddr + offset) as *mut u8, len) }
+    }
+}
+
+impl VfsNodeOps for FrameBufferDev {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        let size = crate::framebuffer_info().fb_size as u64;
+        let perm = VfsNodePerm::from_bits_truncate(0o660);
+        Ok(VfsNodeAttr::new(perm, VfsNodeType::CharDevice, size, 0))
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> VfsResult<usize> {
+        let fb = Self::framebuffer(offset, buf.len());
+        buf[..fb.len()].copy_from_slice(fb);
+        Ok(fb.len())
+    }
+
+    fn write_at(&self, offset: u64, buf: &[u8]) -> VfsResult<usize> {
+        let fb = Self::framebuffer(offset, buf.len());
+        if fb.is_empty() && !buf.is_empty() {
+            return Err(axfs_vfs::VfsError::StorageFull);
+        }
+        fb.copy_from_slice(&buf[..fb.len()]);
+        crate::framebuffer_flush();
+        Ok(fb.len())
+    }
+
+    fn fsync(&self) -> VfsResult {
+        crate::framebuffer_flush();
+        Ok(())
+    }
+
+    fn truncate(&self, _size: u64) -> VfsResult {
+        Ok(())
+    }
+
+    axfs_vfs::impl_vfs_non_dir_default! {}
+}
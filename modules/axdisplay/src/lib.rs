@@ -1,12 +1,22 @@
 //! [ArceOS](https://github.com/rcore-os/arceos) graphics module.
 //!
 //! Currently only supports direct writing to the framebuffer.
+//!
+//! # Cargo Features
+//!
+//! - `devfs`: Register the framebuffer as `/dev/fb0` in the device filesystem.
 
 #![no_std]
 
 #[macro_use]
 extern crate log;
 
+#[cfg(feature = "devfs")]
+extern crate alloc;
+
+#[cfg(feature = "devfs")]
+mod fbdev;
+
 #[doc(no_inline)]
 pub use driver_display::DisplayInfo;
 
@@ -23,6 +33,9 @@ pub fn init_display(mut display_devs: AxDeviceContainer<AxDisplayDevice>) {
     let dev = display_devs.take_one().expect("No graphics device found!");
     info!("  use graphics device 0: {:?}", dev.device_name());
     MAIN_DISPLAY.init_by(Mutex::new(dev));
+
+    #[cfg(feature = "devfs")]
+    axdriver::devfs::register_node("fb0", alloc::sync::Arc::new(fbdev::FrameBufferDev));
 }
 
 /// Gets the framebuffer information.
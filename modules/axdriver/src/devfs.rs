@@ -0,0 +1,48 @@
+//! Device nodes registered by drivers.
+//!
+//! Drivers register nodes of their devices by [`register_node`], usually at
+//! probe time. The device filesystem receives them by [`on_register`], no
+//! matter whether they are registered before or after it is mounted.
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+use axfs_vfs::VfsNodeRef;
+use spin::Mutex;
+
+/// The function called for each registered device node with its path.
+pub type NodeHandler = Box<dyn Fn(&str, VfsNodeRef) + Send + Sync>;
+
+struct Registry {
+    nodes: Vec<(String, VfsNodeRef)>,
+    handlers: Vec<NodeHandler>,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    nodes: Vec::new(),
+    handlers: Vec::new(),
+});
+
+/// Registers a device node at `path` relative to `/dev`, such as `fb0` or
+/// `input/event0`.
+///
+/// The node is usually a character or block device, whose type is returned
+/// by [`VfsNodeOps::get_attr`](axfs_vfs::VfsNodeOps::get_attr).
+pub fn register_node(path: &str, node: VfsNodeRef) {
+    debug!("register device node: /dev/{}", path);
+    let mut registry = REGISTRY.lock();
+    for handler in &registry.handlers {
+        handler(path, node.clone());
+    }
+    registry.nodes.push((path.into(), node));
+}
+
+/// Calls `handler` for every device node registered so far, and for all
+/// nodes registered later.
+///
+/// The handler must not register device nodes itself.
+pub fn on_register(handler: NodeHandler) {
+    let mut registry = REGISTRY.lock();
+    for (path, node) in &registry.nodes {
+        handler(path, node.clone());
+    }
+    registry.handlers.push(handler);
+}
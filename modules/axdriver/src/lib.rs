@@ -48,6 +48,8 @@
 //!    features, a dummy struct is used for [`AxNetDevice`].
 //! - `block`: use block storage devices. Similar to the `net` feature.
 //! - `display`: use graphics display devices. Similar to the `net` feature.
+//! - `devfs`: allow drivers to register nodes of their devices in `/dev`, see
+//!    the [`devfs`] module.
 //!
 //! [`VirtioNetDev`]: driver_virtio::VirtIoNetDev
 //! [`Box<dyn NetDriverOps>`]: driver_net::NetDriverOps
@@ -61,7 +63,7 @@
 #[macro_use]
 extern crate log;
 
-#[cfg(feature = "dyn")]
+#[cfg(any(feature = "dyn", feature = "devfs"))]
 extern crate alloc;
 
 #[macro_use]
@@ -78,6 +80,9 @@ mod virtio;
 #[cfg(feature = "ixgbe")]
 mod ixgbe;
 
+#[cfg(feature = "devfs")]
+pub mod devfs;
+
 pub mod prelude;
 
 #[allow(unused_imports)]
@@ -28,3 +28,21 @@ create_test_img() {
 
 create_test_img "$CUR_DIR/fat16.img" 2500 16
 create_test_img "$CUR_DIR/fat32.img" 34000 32
+
+create_ext2_img() {
+	local name=$1
+	local blkcount=$2
+	local blksize=$3
+	mkdir -p ext2_root/very/long/path ext2_root/very-long-dir-name
+	for i in $(seq 1 1000); do
+	  echo "Rust is cool!" >>"ext2_root/long.txt"
+	done
+	echo "Rust is cool!" >>"ext2_root/short.txt"
+	echo "Rust is cool!" >>"ext2_root/very/long/path/test.txt"
+	echo "Rust is cool!" >>"ext2_root/very-long-dir-name/very-long-file-name.txt"
+	mke2fs -t ext2 -b $blksize -L "Test!" -U 12345678-1234-1234-1234-123456789abc \
+		-E root_owner=0:0 -d ext2_root -F "$name" $blkcount
+	rm -rf ext2_root
+}
+
+create_ext2_img "$CUR_DIR/ext2.img" 2048 1024
@@ -71,6 +71,15 @@ impl OpenOptions {
 }
 
 impl Metadata {
+    pub(super) const fn new(attr: fops::FileAttr) -> Self {
+        Self(attr)
+    }
+
+    /// Returns the underlying raw file attributes.
+    pub const fn raw_metadata(&self) -> &fops::FileAttr {
+        &self.0
+    }
+
     /// Returns the file type for this metadata.
     pub const fn file_type(&self) -> FileType {
         self.0.file_type()
@@ -90,6 +99,11 @@ impl Metadata {
         self.0.is_file()
     }
 
+    /// Returns `true` if this metadata is for a symbolic link.
+    pub const fn is_symlink(&self) -> bool {
+        self.0.is_symlink()
+    }
+
     /// Returns the size of the file, in bytes, this metadata is for.
     #[allow(clippy::len_without_is_empty)]
     pub const fn len(&self) -> u64 {
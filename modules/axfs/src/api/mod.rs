@@ -6,8 +6,12 @@ mod file;
 pub use self::dir::{DirBuilder, DirEntry, ReadDir};
 pub use self::file::{File, FileType, Metadata, OpenOptions, Permissions};
 
-use alloc::{string::String, vec::Vec};
+use alloc::{string::String, sync::Arc, vec::Vec};
+use axfs_vfs::VfsOps;
 use axio::{self as io, prelude::*};
+use core::time::Duration;
+
+use crate::fops::FileSetAttr;
 
 /// Returns an iterator over the entries within a directory.
 pub fn read_dir(path: &str) -> io::Result<ReadDir> {
@@ -59,6 +63,88 @@ pub fn metadata(path: &str) -> io::Result<Metadata> {
     File::open(path)?.metadata()
 }
 
+/// Query the metadata about a file without following symbolic links.
+pub fn symlink_metadata(path: &str) -> io::Result<Metadata> {
+    let node = crate::root::lookup_no_follow(None, path)?;
+    node.get_attr().map(Metadata::new)
+}
+
+/// Reads a symbolic link, returning the path that the link points to.
+pub fn read_link(path: &str) -> io::Result<String> {
+    crate::root::read_link(None, path)
+}
+
+/// Creates a new symbolic link `link` on the filesystem, which points to
+/// `original`.
+pub fn symlink(original: &str, link: &str) -> io::Result<()> {
+    crate::root::create_symlink(None, original, link)
+}
+
+/// Creates a new hard link `link` on the filesystem, which refers to the same
+/// node as `original`.
+pub fn hard_link(original: &str, link: &str) -> io::Result<()> {
+    crate::root::create_link(None, original, link)
+}
+
+/// Changes the permissions found on a file or a directory.
+pub fn set_permissions(path: &str, perm: Permissions) -> io::Result<()> {
+    let attr = FileSetAttr {
+        mode: Some(perm),
+        ..Default::default()
+    };
+    crate::root::set_attr(None, path, &attr, false)
+}
+
+/// Changes the owner and group of a file or a directory.
+///
+/// A `None` leaves the corresponding id unchanged.
+pub fn set_owner(path: &str, uid: Option<u32>, gid: Option<u32>) -> io::Result<()> {
+    let attr = FileSetAttr {
+        uid,
+        gid,
+        ..Default::default()
+    };
+    crate::root::set_attr(None, path, &attr, false)
+}
+
+/// Changes the owner and group of a file, a directory or a symbolic link,
+/// without following it.
+pub fn set_symlink_owner(path: &str, uid: Option<u32>, gid: Option<u32>) -> io::Result<()> {
+    let attr = FileSetAttr {
+        uid,
+        gid,
+        ..Default::default()
+    };
+    crate::root::set_attr(None, path, &attr, true)
+}
+
+/// Changes the last access and modification times of a file or a directory.
+///
+/// A `None` leaves the corresponding time unchanged.
+pub fn set_times(path: &str, atime: Option<Duration>, mtime: Option<Duration>) -> io::Result<()> {
+    let attr = FileSetAttr {
+        atime,
+        mtime,
+        ..Default::default()
+    };
+    crate::root::set_attr(None, path, &attr, false)
+}
+
+/// Changes the last access and modification times of a file, a directory or
+/// a symbolic link, without following it.
+pub fn set_symlink_times(
+    path: &str,
+    atime: Option<Duration>,
+    mtime: Option<Duration>,
+) -> io::Result<()> {
+    let attr = FileSetAttr {
+        atime,
+        mtime,
+        ..Default::default()
+    };
+    crate::root::set_attr(None, path, &attr, true)
+}
+
 /// Creates a new, empty directory at the provided path.
 pub fn create_dir(path: &str) -> io::Result<()> {
     DirBuilder::new().create(path)
@@ -87,3 +173,61 @@ pub fn remove_file(path: &str) -> io::Result<()> {
 pub fn rename(old: &str, new: &str) -> io::Result<()> {
     crate::root::rename(old, new)
 }
+
+/// Mounts the filesystem `fs` at the directory `path`.
+///
+/// The mount point is created if it does not exist. It can be in another
+/// mounted filesystem.
+pub fn mount(path: &str, fs: Arc<dyn VfsOps>) -> io::Result<()> {
+    crate::root::mount(path, fs)
+}
+
+/// Mounts a partition of the first block device at the directory `path`.
+///
+/// It is the same as [`mount_device`] on `vda`.
+pub fn mount_partition(path: &str, part: &crate::PartitionSelector) -> io::Result<()> {
+    mount_device(path, "vda", Some(part))
+}
+
+/// Mounts a partition of the block device `device` at the directory `path`,
+/// or the whole device if `part` is `None`.
+///
+/// The device is named like `vdb` or `/dev/vdb`. The filesystem on it is of
+/// the same type as the one mounted on `/`. It fails with
+/// [`ResourceBusy`](axerrno::AxError::ResourceBusy) if the partition is
+/// already mounted.
+pub fn mount_device(
+    path: &str,
+    device: &str,
+    part: Option<&crate::PartitionSelector>,
+) -> io::Result<()> {
+    let fs = crate::dev::find_block_device(device)?.filesystem(part)?;
+    if crate::root::is_mounted(&fs) {
+        return Err(io::Error::ResourceBusy);
+    }
+    crate::root::mount(path, fs)
+}
+
+/// Unmounts the filesystem mounted at `path`, and calls [`VfsOps::umount`] on
+/// it.
+///
+/// It fails if other filesystems are mounted under `path`, or the current
+/// working directory is in it.
+pub fn umount(path: &str) -> io::Result<()> {
+    crate::root::umount(path)
+}
+
+/// Returns the paths of all mount points, starting with the root `/`.
+pub fn mount_points() -> Vec<String> {
+    crate::root::mount_points()
+}
+
+/// Writes the cached data of all mounted filesystems to the storage.
+pub fn sync() -> io::Result<()> {
+    crate::root::sync()
+}
+
+/// Returns the statistics of the filesystem that contains `path`.
+pub fn statfs(path: &str) -> io::Result<crate::fops::FileSystemInfo> {
+    crate::root::filesystem_of(None, None, path)?.statfs()
+}
@@ -0,0 +1,54 @@
+use axfs_vfs::{VfsNodeAttr, VfsNodeOps, VfsNodePerm, VfsNodeType, VfsResult};
+
+/// The console device behaves like `/dev/tty` and `/dev/console`.
+///
+/// It reads from and writes to the console of [`axhal`]. Reads block until
+/// at least one byte is available.
+pub(crate) struct ConsoleDev;
+
+impl ConsoleDev {
+    fn getchar() -> Option<u8> {
+        axhal::console::getchar().map(|c| if c == b'\r' { b'\n' } else { c })
+    }
+}
+
+impl VfsNodeOps for ConsoleDev {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        let perm = VfsNodePerm::from_bits_truncate(0o620);
+        let ino = self as *const _ as usize as u64;
+        Ok(VfsNodeAttr::new(perm, VfsNodeType::CharDevice, 0, 0).with_ino(ino))
+    }
+
+    fn read_at(&self, _offset: u64, buf: &mut [u8]) -> VfsResult<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let c = loop {
+            match Self::getchar() {
+                Some(c) => break c,
+                None => axtask::yield_now(),
+            }
+        };
+        buf[0] = c;
+        let mut read_len = 1;
+        while read_len < buf.len() {
+            match Self::getchar() {
+                Some(c) => buf[read_len] = c,
+                None => break,
+            }
+            read_len += 1;
+        }
+        Ok(read_len)
+    }
+
+    fn write_at(&self, _offset: u64, buf: &[u8]) -> VfsResult<usize> {
+        axhal::console::write_bytes(buf);
+        Ok(buf.len())
+    }
+
+    fn truncate(&self, _size: u64) -> VfsResult {
+        Ok(())
+    }
+
+    axfs_vfs::impl_vfs_non_dir_default! {}
+}
@@ -1,92 +1,447 @@
-use axdriver::prelude::*;
+use alloc::{boxed::Box, format, string::String, sync::Arc, sync::Weak, vec, vec::Vec};
+use core::str::FromStr;
 
-const BLOCK_SIZE: usize = 512;
+use axdriver::{prelude::*, AxDeviceContainer};
+use axerrno::{ax_err, AxError, AxResult};
+use axfs_vfs::{VfsNodeAttr, VfsNodeOps, VfsNodePerm, VfsNodeType, VfsOps, VfsResult};
+use axsync::Mutex;
+use driver_block::cache::BlockCache;
+use driver_block::partition::{self, Guid, Partition, PartitionInfo, SharedDevice};
+use lazy_init::LazyInit;
+
+use crate::fs;
+
+pub(crate) type CachedDevice = BlockCache<DriverDevice>;
+
+static BLOCK_DEVICES: LazyInit<Vec<BlockDevice>> = LazyInit::new();
+
+/// Selects a partition of the block device.
+///
+/// It can be parsed from a string like the `root=` parameter of Linux: an
+/// index, `PARTUUID=<guid>` or `PARTLABEL=<name>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PartitionSelector {
+    /// The partition at the index of the partition table, starting from 0.
+    Index(usize),
+    /// The GPT partition with the unique GUID.
+    Guid(Guid),
+    /// The GPT partition with the name.
+    Label(String),
+}
+
+/// The block device of the driver, which is a trait object if the feature
+/// `dyn` of [`axdriver`] is enabled.
+pub(crate) struct DriverDevice(AxBlockDevice);
+
+/// A block device and its partitions.
+///
+/// All blocks of the device are cached in one cache, which is shared by the
+/// filesystems on its partitions and the device node.
+pub(crate) struct BlockDevice {
+    /// Name of the device node in devfs, like `vda`.
+    name: String,
+    dev: SharedDevice<CachedDevice>,
+    partitions: Vec<PartitionInfo>,
+    /// Filesystems created on the partitions, the last one is on the whole
+    /// device.
+    filesystems: Mutex<Vec<Option<Weak<dyn VfsOps>>>>,
+}
 
 /// A disk device with a cursor.
+///
+/// It works with any block size of the device. Blocks of the device are
+/// cached with write-back, the size of the cache is set by
+/// [`axconfig::BLOCK_CACHE_SIZE`]. Modified blocks are written to the
+/// device when they are evicted or [`Disk::flush`] is called.
 pub struct Disk {
     block_id: u64,
     offset: usize,
-    dev: AxBlockDevice,
+    block_size: usize,
+    /// Buffer of one block for partial reads and writes.
+    block_buf: Box<[u8]>,
+    dev: Partition<CachedDevice>,
+}
+
+/// The node of a block device in devfs, which reads and writes the whole
+/// device or one of its partitions.
+pub(crate) struct BlockDeviceNode {
+    disk: Mutex<Disk>,
+}
+
+impl PartitionSelector {
+    fn matches(&self, info: &PartitionInfo) -> bool {
+        match self {
+            Self::Index(index) => info.index == *index,
+            Self::Guid(guid) => info.guid == Some(*guid),
+            Self::Label(label) => info.label == *label,
+        }
+    }
+}
+
+impl FromStr for PartitionSelector {
+    type Err = AxError;
+
+    fn from_str(s: &str) -> AxResult<Self> {
+        if let Some(guid) = s.strip_prefix("PARTUUID=") {
+            let guid = guid.parse().map_err(|_| AxError::InvalidInput)?;
+            Ok(Self::Guid(guid))
+        } else if let Some(label) = s.strip_prefix("PARTLABEL=") {
+            Ok(Self::Label(label.into()))
+        } else {
+            let index = s.parse().map_err(|_| AxError::InvalidInput)?;
+            Ok(Self::Index(index))
+        }
+    }
+}
+
+impl BaseDriverOps for DriverDevice {
+    fn device_name(&self) -> &str {
+        self.0.device_name()
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::Block
+    }
+}
+
+impl BlockDriverOps for DriverDevice {
+    fn num_blocks(&self) -> u64 {
+        self.0.num_blocks()
+    }
+
+    fn block_size(&self) -> usize {
+        self.0.block_size()
+    }
+
+    fn read_block(&mut self, block_id: u64, buf: &mut [u8]) -> DevResult {
+        self.0.read_block(block_id, buf)
+    }
+
+    fn write_block(&mut self, block_id: u64, buf: &[u8]) -> DevResult {
+        self.0.write_block(block_id, buf)
+    }
+
+    fn flush(&mut self) -> DevResult {
+        self.0.flush()
+    }
+}
+
+impl BlockDevice {
+    fn new(name: String, dev: AxBlockDevice) -> Self {
+        let capacity = axconfig::BLOCK_CACHE_SIZE / dev.block_size();
+        let mut dev = BlockCache::new(DriverDevice(dev), capacity);
+        let partitions = partition::scan_partitions(&mut dev).unwrap_or_else(|e| {
+            warn!("failed to read the partition table of {}: {:?}", name, e);
+            Vec::new()
+        });
+        for part in &partitions {
+            info!(
+                "  partition {}: blocks {}..{}, {:?} {:?}",
+                part.index,
+                part.start_block,
+                part.start_block + part.num_blocks,
+                part.guid,
+                part.label
+            );
+        }
+        let filesystems = (0..=partitions.len()).map(|_| None).collect();
+        Self {
+            name,
+            dev: SharedDevice::new(dev),
+            partitions,
+            filesystems: Mutex::new(filesystems),
+        }
+    }
+
+    /// The name of the device node.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The name of the device node of the partition at `index`, like `vda1`
+    /// for the first one.
+    pub fn partition_name(&self, index: usize) -> String {
+        format!("{}{}", self.name, index + 1)
+    }
+
+    /// Creates the device node of the whole device.
+    pub fn node(&self) -> Arc<BlockDeviceNode> {
+        BlockDeviceNode::new(Partition::whole(self.dev.clone()))
+    }
+
+    /// Creates the device nodes of the partitions, with their names.
+    pub fn partition_nodes(&self) -> Vec<(String, Arc<BlockDeviceNode>)> {
+        self.partitions
+            .iter()
+            .map(|info| {
+                let node = BlockDeviceNode::new(Partition::new(self.dev.clone(), info));
+                (self.partition_name(info.index), node)
+            })
+            .collect()
+    }
+
+    /// Whether the device has a partition table.
+    pub fn is_partitioned(&self) -> bool {
+        !self.partitions.is_empty()
+    }
+
+    /// Returns the filesystem on the selected partition, or on the whole
+    /// device if `part` is `None`.
+    ///
+    /// The filesystem is created by [`fs::new_filesystem`] if there is none,
+    /// so that a partition is never accessed by two instances of filesystems
+    /// at the same time.
+    pub fn filesystem(&self, part: Option<&PartitionSelector>) -> AxResult<Arc<dyn VfsOps>> {
+        let index = match part {
+            Some(sel) => match self.partitions.iter().find(|p| sel.matches(p)) {
+                Some(info) => info.index,
+                None => return ax_err!(NotFound, "partition not found"),
+            },
+            None => self.partitions.len(),
+        };
+        let mut filesystems = self.filesystems.lock();
+        if let Some(fs) = filesystems[index].as_ref().and_then(Weak::upgrade) {
+            return Ok(fs);
+        }
+        let dev = match part {
+            Some(_) => Partition::new(self.dev.clone(), &self.partitions[index]),
+            None => Partition::whole(self.dev.clone()),
+        };
+        let fs = fs::new_filesystem(Disk::new(dev))?;
+        filesystems[index] = Some(Arc::downgrade(&fs));
+        Ok(fs)
+    }
 }
 
 impl Disk {
-    /// Create a new disk.
-    pub fn new(dev: AxBlockDevice) -> Self {
-        assert_eq!(BLOCK_SIZE, dev.block_size());
+    /// Create a new disk on a partition, or the whole device.
+    pub(crate) fn new(dev: Partition<CachedDevice>) -> Self {
+        let block_size = dev.block_size();
         Self {
             block_id: 0,
             offset: 0,
+            block_size,
+            block_buf: vec![0; block_size].into_boxed_slice(),
             dev,
         }
     }
 
+    /// Create another cursor on the same disk.
+    pub fn share(&self) -> Self {
+        Self {
+            block_id: 0,
+            offset: 0,
+            block_size: self.block_size,
+            block_buf: vec![0; self.block_size].into_boxed_slice(),
+            dev: self.dev.clone(),
+        }
+    }
+
+    /// Get the size of each block of the disk.
+    pub const fn block_size(&self) -> usize {
+        self.block_size
+    }
+
     /// Get the size of the disk.
     pub fn size(&self) -> u64 {
-        self.dev.num_blocks() * BLOCK_SIZE as u64
+        self.dev.num_blocks() * self.block_size as u64
     }
 
     /// Get the position of the cursor.
     pub fn position(&self) -> u64 {
-        self.block_id * BLOCK_SIZE as u64 + self.offset as u64
+        self.block_id * self.block_size as u64 + self.offset as u64
     }
 
     /// Set the position of the cursor.
     pub fn set_position(&mut self, pos: u64) {
-        self.block_id = pos / BLOCK_SIZE as u64;
-        self.offset = pos as usize % BLOCK_SIZE;
+        self.block_id = pos / self.block_size as u64;
+        self.offset = (pos % self.block_size as u64) as usize;
     }
 
-    /// Read within one block, returns the number of bytes read.
+    /// Read within one block, or read whole blocks in one request if the
+    /// cursor is aligned, returns the number of bytes read.
     pub fn read_one(&mut self, buf: &mut [u8]) -> DevResult<usize> {
-        let read_size = if self.offset == 0 && buf.len() >= BLOCK_SIZE {
-            // whole block
-            self.dev
-                .read_block(self.block_id, &mut buf[0..BLOCK_SIZE])?;
-            self.block_id += 1;
-            BLOCK_SIZE
+        let block_size = self.block_size;
+        let dev = &mut self.dev;
+        let read_size = if self.offset == 0 && buf.len() >= block_size {
+            // whole blocks
+            let count = buf.len() / block_size;
+            dev.read_block(self.block_id, &mut buf[..count * block_size])?;
+            self.block_id += count as u64;
+            count * block_size
         } else {
             // partial block
-            let mut data = [0u8; BLOCK_SIZE];
+            let data = &mut self.block_buf;
             let start = self.offset;
-            let count = buf.len().min(BLOCK_SIZE - self.offset);
+            let count = buf.len().min(block_size - self.offset);
 
-            self.dev.read_block(self.block_id, &mut data)?;
+            dev.read_block(self.block_id, data)?;
             buf[..count].copy_from_slice(&data[start..start + count]);
 
             self.offset += count;
-            if self.offset >= BLOCK_SIZE {
+            if self.offset >= block_size {
                 self.block_id += 1;
-                self.offset -= BLOCK_SIZE;
+                self.offset -= block_size;
             }
             count
         };
         Ok(read_size)
     }
 
-    /// Write within one block, returns the number of bytes written.
+    /// Write within one block, or write whole blocks in one request if the
+    /// cursor is aligned, returns the number of bytes written.
     pub fn write_one(&mut self, buf: &[u8]) -> DevResult<usize> {
-        let write_size = if self.offset == 0 && buf.len() >= BLOCK_SIZE {
-            // whole block
-            self.dev.write_block(self.block_id, &buf[0..BLOCK_SIZE])?;
-            self.block_id += 1;
-            BLOCK_SIZE
+        let block_size = self.block_size;
+        let dev = &mut self.dev;
+        let write_size = if self.offset == 0 && buf.len() >= block_size {
+            // whole blocks
+            let count = buf.len() / block_size;
+            dev.write_block(self.block_id, &buf[..count * block_size])?;
+            self.block_id += count as u64;
+            count * block_size
         } else {
             // partial block
-            let mut data = [0u8; BLOCK_SIZE];
+            let data = &mut self.block_buf;
             let start = self.offset;
-            let count = buf.len().min(BLOCK_SIZE - self.offset);
+            let count = buf.len().min(block_size - self.offset);
 
-            self.dev.read_block(self.block_id, &mut data)?;
+            dev.read_block(self.block_id, data)?;
             data[start..start + count].copy_from_slice(&buf[..count]);
-            self.dev.write_block(self.block_id, &data)?;
+            dev.write_block(self.block_id, data)?;
 
             self.offset += count;
-            if self.offset >= BLOCK_SIZE {
+            if self.offset >= block_size {
                 self.block_id += 1;
-                self.offset -= BLOCK_SIZE;
+                self.offset -= block_size;
             }
             count
         };
         Ok(write_size)
     }
+
+    /// Write all cached blocks that are modified to the device.
+    ///
+    /// Blocks of other partitions on the same device are written as well.
+    pub fn flush(&self) -> DevResult {
+        self.dev.device().lock().flush()
+    }
+}
+
+impl BlockDeviceNode {
+    fn new(dev: Partition<CachedDevice>) -> Arc<Self> {
+        Arc::new(Self {
+            disk: Mutex::new(Disk::new(dev)),
+        })
+    }
+}
+
+impl VfsNodeOps for BlockDeviceNode {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        let size = self.disk.lock().size();
+        let perm = VfsNodePerm::from_bits_truncate(0o660);
+        let ino = self as *const _ as usize as u64;
+        Ok(VfsNodeAttr::new(perm, VfsNodeType::BlockDevice, size, 0).with_ino(ino))
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> VfsResult<usize> {
+        let mut disk = self.disk.lock();
+        let len = disk.size().saturating_sub(offset).min(buf.len() as u64) as usize;
+        disk.set_position(offset);
+        let mut done = 0;
+        while done < len {
+            done += disk.read_one(&mut buf[done..len]).map_err(dev_err)?;
+        }
+        Ok(len)
+    }
+
+    fn write_at(&self, offset: u64, buf: &[u8]) -> VfsResult<usize> {
+        let mut disk = self.disk.lock();
+        let len = disk.size().saturating_sub(offset).min(buf.len() as u64) as usize;
+        if len == 0 && !buf.is_empty() {
+            return ax_err!(StorageFull);
+        }
+        disk.set_position(offset);
+        let mut done = 0;
+        while done < len {
+            done += disk.write_one(&buf[done..len]).map_err(dev_err)?;
+        }
+        Ok(len)
+    }
+
+    fn fsync(&self) -> VfsResult {
+        self.disk.lock().flush().map_err(dev_err)
+    }
+
+    fn truncate(&self, _size: u64) -> VfsResult {
+        Ok(()) // the size of a device cannot be changed
+    }
+
+    axfs_vfs::impl_vfs_non_dir_default! {}
+}
+
+const fn dev_err(_err: DevError) -> AxError {
+    AxError::Io
+}
+
+/// Names the block device at `index`, like `vda`, `vdb` ... `vdz`, `vdaa`.
+fn device_name(mut index: usize) -> String {
+    let mut suffix = Vec::new();
+    loop {
+        suffix.push(b'a' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    suffix.reverse();
+    format!("vd{}", String::from_utf8(suffix).unwrap())
+}
+
+/// Reads the partition tables of all block devices, and keeps them for
+/// mounting their partitions later.
+pub(crate) fn init_block_devices(
+    mut devs: AxDeviceContainer<AxBlockDevice>,
+) -> &'static [BlockDevice] {
+    let mut block_devices = Vec::new();
+    while let Some(dev) = devs.take_one() {
+        let name = device_name(block_devices.len());
+        info!("  block device {}: {:?}", name, dev.device_name());
+        block_devices.push(BlockDevice::new(name, dev));
+    }
+    BLOCK_DEVICES.init_by(block_devices);
+    &BLOCK_DEVICES
+}
+
+/// Returns all block devices, the first one contains the root filesystem.
+pub(crate) fn block_devices() -> &'static [BlockDevice] {
+    BLOCK_DEVICES.try_get().map_or(&[], |devs| devs)
+}
+
+/// Returns the name of the device node that `fs` is on, like `vda` for a
+/// whole device or `vda1` for its first partition.
+pub(crate) fn device_of(fs: &Arc<dyn VfsOps>) -> Option<String> {
+    for dev in block_devices() {
+        let filesystems = dev.filesystems.lock();
+        let found = filesystems.iter().position(|f| {
+            f.as_ref()
+                .and_then(Weak::upgrade)
+                .is_some_and(|f| Arc::ptr_eq(&f, fs))
+        });
+        match found {
+            Some(index) if index == dev.partitions.len() => return Some(dev.name.clone()),
+            Some(index) => return Some(dev.partition_name(index)),
+            None => {}
+        }
+    }
+    None
+}
+
+/// Returns the block device with the name, with or without the `/dev/`
+/// prefix.
+pub(crate) fn find_block_device(name: &str) -> AxResult<&'static BlockDevice> {
+    let name = name.strip_prefix("/dev/").unwrap_or(name);
+    match block_devices().iter().find(|dev| dev.name == name) {
+        Some(dev) => Ok(dev),
+        None => ax_err!(NotFound, "block device not found"),
+    }
 }
@@ -1,7 +1,8 @@
 //! Low-level filesystem operations.
 
+use alloc::{string::String, sync::Arc};
 use axerrno::{ax_err, ax_err_type, AxResult};
-use axfs_vfs::{VfsError, VfsNodeRef};
+use axfs_vfs::{VfsError, VfsNodeRef, VfsOps};
 use axio::SeekFrom;
 use capability::{Cap, WithCap};
 use core::fmt;
@@ -19,10 +20,15 @@ pub type DirEntry = axfs_vfs::VfsDirEntry;
 pub type FileAttr = axfs_vfs::VfsNodeAttr;
 /// Alias of [`axfs_vfs::VfsNodePerm`].
 pub type FilePerm = axfs_vfs::VfsNodePerm;
+/// Alias of [`axfs_vfs::VfsSetAttr`].
+pub type FileSetAttr = axfs_vfs::VfsSetAttr;
+/// Alias of [`axfs_vfs::FileSystemInfo`].
+pub type FileSystemInfo = axfs_vfs::FileSystemInfo;
 
 /// An opened file object, with open permissions and a cursor.
 pub struct File {
     node: WithCap<VfsNodeRef>,
+    fs: Arc<dyn VfsOps>,
     is_append: bool,
     offset: u64,
 }
@@ -31,6 +37,7 @@ pub struct File {
 /// [`read_dir`](Directory::read_dir).
 pub struct Directory {
     node: WithCap<VfsNodeRef>,
+    fs: Arc<dyn VfsOps>,
     entry_idx: usize,
 }
 
@@ -45,6 +52,7 @@ pub struct OpenOptions {
     create: bool,
     create_new: bool,
     // system-specific
+    no_follow: bool,
     _custom_flags: i32,
     _mode: u32,
 }
@@ -61,6 +69,7 @@ impl OpenOptions {
             create: false,
             create_new: false,
             // system-specific
+            no_follow: false,
             _custom_flags: 0,
             _mode: 0o666,
         }
@@ -89,6 +98,11 @@ impl OpenOptions {
     pub fn create_new(&mut self, create_new: bool) {
         self.create_new = create_new;
     }
+    /// Sets the option to fail if the last component of the path is a
+    /// symbolic link (like `O_NOFOLLOW`).
+    pub fn no_follow(&mut self, no_follow: bool) {
+        self.no_follow = no_follow;
+    }
 
     const fn is_valid(&self) -> bool {
         if !self.read && !self.write && !self.append {
@@ -112,13 +126,22 @@ impl OpenOptions {
 }
 
 impl File {
-    fn _open_at(dir: Option<&VfsNodeRef>, path: &str, opts: &OpenOptions) -> AxResult<Self> {
+    fn _open_at(
+        dir: Option<&VfsNodeRef>,
+        dir_fs: Option<&Arc<dyn VfsOps>>,
+        path: &str,
+        opts: &OpenOptions,
+    ) -> AxResult<Self> {
         debug!("open file: {} {:?}", path, opts);
         if !opts.is_valid() {
             return ax_err!(InvalidInput);
         }
 
-        let node_option = crate::root::lookup(dir, path);
+        let node_option = if opts.no_follow {
+            crate::root::lookup_no_follow(dir, path)
+        } else {
+            crate::root::lookup(dir, path)
+        };
         let node = if opts.create || opts.create_new {
             match node_option {
                 Ok(node) => {
@@ -138,6 +161,9 @@ impl File {
         };
 
         let attr = node.get_attr()?;
+        if attr.is_symlink() {
+            return ax_err!(FilesystemLoop); // only happens with `no_follow`
+        }
         if attr.is_dir()
             && (opts.create || opts.create_new || opts.write || opts.append || opts.truncate)
         {
@@ -148,12 +174,14 @@ impl File {
             return ax_err!(PermissionDenied);
         }
 
+        let fs = crate::root::filesystem_of(dir, dir_fs, path)?;
         node.open()?;
         if opts.truncate {
             node.truncate(0)?;
         }
         Ok(Self {
             node: WithCap::new(node, access_cap),
+            fs,
             is_append: opts.append,
             offset: 0,
         })
@@ -162,7 +190,7 @@ impl File {
     /// Opens a file at the path relative to the current directory. Returns a
     /// [`File`] object.
     pub fn open(path: &str, opts: &OpenOptions) -> AxResult<Self> {
-        Self::_open_at(None, path, opts)
+        Self::_open_at(None, None, path, opts)
     }
 
     /// Truncates the file to the specified size.
@@ -240,10 +268,25 @@ impl File {
     pub fn get_attr(&self) -> AxResult<FileAttr> {
         self.node.access(Cap::empty())?.get_attr()
     }
+
+    /// Changes the permission, owner or timestamps of the file.
+    pub fn set_attr(&self, attr: &FileSetAttr) -> AxResult {
+        self.node.access(Cap::empty())?.set_attr(attr)
+    }
+
+    /// Gets the statistics of the filesystem that contains the file.
+    pub fn statfs(&self) -> AxResult<FileSystemInfo> {
+        self.fs.statfs()
+    }
 }
 
 impl Directory {
-    fn _open_dir_at(dir: Option<&VfsNodeRef>, path: &str, opts: &OpenOptions) -> AxResult<Self> {
+    fn _open_dir_at(
+        dir: Option<&VfsNodeRef>,
+        dir_fs: Option<&Arc<dyn VfsOps>>,
+        path: &str,
+        opts: &OpenOptions,
+    ) -> AxResult<Self> {
         debug!("open dir: {}", path);
         if !opts.read {
             return ax_err!(InvalidInput);
@@ -262,9 +305,11 @@ impl Directory {
             return ax_err!(PermissionDenied);
         }
 
+        let fs = crate::root::filesystem_of(dir, dir_fs, path)?;
         node.open()?;
         Ok(Self {
             node: WithCap::new(node, access_cap),
+            fs,
             entry_idx: 0,
         })
     }
@@ -280,19 +325,19 @@ impl Directory {
     /// Opens a directory at the path relative to the current directory.
     /// Returns a [`Directory`] object.
     pub fn open_dir(path: &str, opts: &OpenOptions) -> AxResult<Self> {
-        Self::_open_dir_at(None, path, opts)
+        Self::_open_dir_at(None, None, path, opts)
     }
 
     /// Opens a directory at the path relative to this directory. Returns a
     /// [`Directory`] object.
     pub fn open_dir_at(&self, path: &str, opts: &OpenOptions) -> AxResult<Self> {
-        Self::_open_dir_at(self.access_at(path)?, path, opts)
+        Self::_open_dir_at(self.access_at(path)?, Some(&self.fs), path, opts)
     }
 
     /// Opens a file at the path relative to this directory. Returns a [`File`]
     /// object.
     pub fn open_file_at(&self, path: &str, opts: &OpenOptions) -> AxResult<File> {
-        File::_open_at(self.access_at(path)?, path, opts)
+        File::_open_at(self.access_at(path)?, Some(&self.fs), path, opts)
     }
 
     /// Creates an empty file at the path relative to this directory.
@@ -315,6 +360,38 @@ impl Directory {
         crate::root::remove_dir(self.access_at(path)?, path)
     }
 
+    /// Creates a symbolic link at the path relative to this directory, which
+    /// points to `target`.
+    pub fn create_symlink(&self, target: &str, path: &str) -> AxResult {
+        crate::root::create_symlink(self.access_at(path)?, target, path)
+    }
+
+    /// Reads the target of the symbolic link at the path relative to this
+    /// directory.
+    pub fn read_link(&self, path: &str) -> AxResult<String> {
+        crate::root::read_link(self.access_at(path)?, path)
+    }
+
+    /// Gets the file attributes of the node at the path relative to this
+    /// directory. The last component is not followed if it is a symbolic link
+    /// and `no_follow` is `true`.
+    pub fn get_attr_at(&self, path: &str, no_follow: bool) -> AxResult<FileAttr> {
+        let dir = self.access_at(path)?;
+        let node = if no_follow {
+            crate::root::lookup_no_follow(dir, path)?
+        } else {
+            crate::root::lookup(dir, path)?
+        };
+        node.get_attr()
+    }
+
+    /// Changes the permission, owner or timestamps of the node at the path
+    /// relative to this directory. The last component is not followed if it
+    /// is a symbolic link and `no_follow` is `true`.
+    pub fn set_attr_at(&self, path: &str, attr: &FileSetAttr, no_follow: bool) -> AxResult {
+        crate::root::set_attr(self.access_at(path)?, path, attr, no_follow)
+    }
+
     /// Reads directory entries starts from the current position into the
     /// given buffer. Returns the number of entries read.
     ///
@@ -329,6 +406,33 @@ impl Directory {
         Ok(n)
     }
 
+    /// Returns the index of the next entry to be read by
+    /// [`read_dir`](Self::read_dir).
+    pub fn position(&self) -> usize {
+        self.entry_idx
+    }
+
+    /// Moves the cursor of [`read_dir`](Self::read_dir) to the entry at the
+    /// given index.
+    pub fn set_position(&mut self, entry_idx: usize) {
+        self.entry_idx = entry_idx;
+    }
+
+    /// Gets the file attributes of the directory.
+    pub fn get_attr(&self) -> AxResult<FileAttr> {
+        self.node.access(Cap::empty())?.get_attr()
+    }
+
+    /// Changes the permission, owner or timestamps of the directory.
+    pub fn set_attr(&self, attr: &FileSetAttr) -> AxResult {
+        self.node.access(Cap::empty())?.set_attr(attr)
+    }
+
+    /// Gets the statistics of the filesystem that contains the directory.
+    pub fn statfs(&self) -> AxResult<FileSystemInfo> {
+        self.fs.statfs()
+    }
+
     /// Rename a file or directory to a new name.
     /// Delete the original file if `old` already exists.
     ///
@@ -371,6 +475,7 @@ impl fmt::Debug for OpenOptions {
         fmt_opt!(truncate, "TRUNC");
         fmt_opt!(create, "CREATE");
         fmt_opt!(create_new, "CREATE_NEW");
+        fmt_opt!(no_follow, "NOFOLLOW");
         Ok(())
     }
 }
@@ -0,0 +1,565 @@
+//! On-disk structures of ext2, and the ext4 extensions that can be read.
+//!
+//! All structures keep their raw bytes, and fields are accessed at their
+//! offsets in little-endian, so that fields not used by us are preserved when
+//! written back.
+
+use core::time::Duration;
+
+use axfs_vfs::VfsNodeType;
+
+pub const EXT2_MAGIC: u16 = 0xef53;
+pub const SUPERBLOCK_OFFSET: u64 = 1024;
+pub const SUPERBLOCK_SIZE: usize = 1024;
+pub const ROOT_INO: u32 = 2;
+pub const MAX_NAME_LEN: usize = 255;
+
+/// Number of direct block pointers in an inode.
+pub const DIRECT_BLOCKS: usize = 12;
+/// Size of the inode fields that are used, the rest of a larger inode is
+/// left untouched.
+pub const INODE_BASE_SIZE: usize = 128;
+/// Size of the block pointer area in an inode.
+pub const INODE_BLOCK_AREA: usize = 60;
+
+pub const INCOMPAT_FILETYPE: u32 = 0x2;
+pub const INCOMPAT_EXTENTS: u32 = 0x40;
+pub const INCOMPAT_64BIT: u32 = 0x80;
+pub const INCOMPAT_MMP: u32 = 0x100;
+pub const INCOMPAT_FLEX_BG: u32 = 0x200;
+pub const INCOMPAT_CSUM_SEED: u32 = 0x2000;
+pub const INCOMPAT_LARGEDIR: u32 = 0x4000;
+pub const RO_COMPAT_SPARSE_SUPER: u32 = 0x1;
+pub const RO_COMPAT_LARGE_FILE: u32 = 0x2;
+
+/// Incompatible features that can be read.
+pub const INCOMPAT_READ: u32 = INCOMPAT_FILETYPE
+    | INCOMPAT_EXTENTS
+    | INCOMPAT_64BIT
+    | INCOMPAT_MMP
+    | INCOMPAT_FLEX_BG
+    | INCOMPAT_CSUM_SEED
+    | INCOMPAT_LARGEDIR;
+/// Incompatible features that can be written.
+pub const INCOMPAT_WRITE: u32 = INCOMPAT_FILETYPE;
+/// Read-only compatible features that can be written.
+pub const RO_COMPAT_WRITE: u32 = RO_COMPAT_SPARSE_SUPER | RO_COMPAT_LARGE_FILE;
+
+pub const INODE_FLAG_INDEX: u32 = 0x1000;
+pub const INODE_FLAG_EXTENTS: u32 = 0x8_0000;
+
+const EXTENT_MAGIC: u16 = 0xf30a;
+
+fn get_u16(raw: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([raw[offset], raw[offset + 1]])
+}
+
+fn get_u32(raw: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(raw[offset..offset + 4].try_into().unwrap())
+}
+
+fn set_u16(raw: &mut [u8], offset: usize, value: u16) {
+    raw[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+fn set_u32(raw: &mut [u8], offset: usize, value: u32) {
+    raw[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+pub fn read_u32(raw: &[u8], index: usize) -> u32 {
+    get_u32(raw, index * 4)
+}
+
+pub fn write_u32(raw: &mut [u8], index: usize, value: u32) {
+    set_u32(raw, index * 4, value)
+}
+
+/// The superblock, which is always at byte offset 1024.
+pub struct SuperBlock {
+    raw: [u8; SUPERBLOCK_SIZE],
+}
+
+impl SuperBlock {
+    pub fn new(raw: [u8; SUPERBLOCK_SIZE]) -> Self {
+        Self { raw }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.raw
+    }
+
+    pub fn inodes_count(&self) -> u32 {
+        get_u32(&self.raw, 0)
+    }
+
+    pub fn blocks_count(&self) -> u64 {
+        get_u32(&self.raw, 4) as u64
+    }
+
+    pub fn reserved_blocks_count(&self) -> u64 {
+        get_u32(&self.raw, 8) as u64
+    }
+
+    pub fn free_blocks_count(&self) -> u64 {
+        get_u32(&self.raw, 12) as u64
+    }
+
+    pub fn set_free_blocks_count(&mut self, count: u64) {
+        set_u32(&mut self.raw, 12, count as u32)
+    }
+
+    pub fn free_inodes_count(&self) -> u32 {
+        get_u32(&self.raw, 16)
+    }
+
+    pub fn set_free_inodes_count(&mut self, count: u32) {
+        set_u32(&mut self.raw, 16, count)
+    }
+
+    pub fn first_data_block(&self) -> u64 {
+        get_u32(&self.raw, 20) as u64
+    }
+
+    pub fn log_block_size(&self) -> u32 {
+        get_u32(&self.raw, 24)
+    }
+
+    pub fn blocks_per_group(&self) -> u32 {
+        get_u32(&self.raw, 32)
+    }
+
+    pub fn inodes_per_group(&self) -> u32 {
+        get_u32(&self.raw, 40)
+    }
+
+    pub fn magic(&self) -> u16 {
+        get_u16(&self.raw, 56)
+    }
+
+    pub fn rev_level(&self) -> u32 {
+        get_u32(&self.raw, 76)
+    }
+
+    /// The first inode that is not reserved.
+    pub fn first_ino(&self) -> u32 {
+        if self.rev_level() == 0 {
+            11
+        } else {
+            get_u32(&self.raw, 84)
+        }
+    }
+
+    pub fn inode_size(&self) -> usize {
+        if self.rev_level() == 0 {
+            INODE_BASE_SIZE
+        } else {
+            get_u16(&self.raw, 88) as usize
+        }
+    }
+
+    pub fn feature_incompat(&self) -> u32 {
+        get_u32(&self.raw, 96)
+    }
+
+    pub fn feature_ro_compat(&self) -> u32 {
+        get_u32(&self.raw, 100)
+    }
+
+    /// The size of a group descriptor.
+    pub fn desc_size(&self) -> usize {
+        if self.feature_incompat() & INCOMPAT_64BIT != 0 {
+            (get_u16(&self.raw, 254) as usize).max(GroupDesc::BASE_SIZE)
+        } else {
+            GroupDesc::BASE_SIZE
+        }
+    }
+}
+
+/// A block group descriptor.
+pub struct GroupDesc {
+    raw: [u8; GroupDesc::BASE_SIZE],
+    hi: Option<[u8; GroupDesc::BASE_SIZE]>,
+}
+
+impl GroupDesc {
+    pub const BASE_SIZE: usize = 32;
+
+    /// Parses a group descriptor of `raw.len()` bytes.
+    pub fn new(raw: &[u8]) -> Self {
+        let hi = (raw.len() >= Self::BASE_SIZE * 2).then(|| {
+            raw[Self::BASE_SIZE..Self::BASE_SIZE * 2]
+                .try_into()
+                .unwrap()
+        });
+        Self {
+            raw: raw[..Self::BASE_SIZE].try_into().unwrap(),
+            hi,
+        }
+    }
+
+    /// The raw bytes of the fields that can be changed.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.raw
+    }
+
+    fn get_lo_hi(&self, offset: usize) -> u64 {
+        let hi = self.hi.as_ref().map_or(0, |hi| get_u32(hi, offset));
+        get_u32(&self.raw, offset) as u64 | (hi as u64) << 32
+    }
+
+    pub fn block_bitmap(&self) -> u64 {
+        self.get_lo_hi(0)
+    }
+
+    pub fn inode_bitmap(&self) -> u64 {
+        self.get_lo_hi(4)
+    }
+
+    pub fn inode_table(&self) -> u64 {
+        self.get_lo_hi(8)
+    }
+
+    pub fn free_blocks_count(&self) -> u16 {
+        get_u16(&self.raw, 12)
+    }
+
+    pub fn set_free_blocks_count(&mut self, count: u16) {
+        set_u16(&mut self.raw, 12, count)
+    }
+
+    pub fn free_inodes_count(&self) -> u16 {
+        get_u16(&self.raw, 14)
+    }
+
+    pub fn set_free_inodes_count(&mut self, count: u16) {
+        set_u16(&mut self.raw, 14, count)
+    }
+
+    pub fn used_dirs_count(&self) -> u16 {
+        get_u16(&self.raw, 16)
+    }
+
+    pub fn set_used_dirs_count(&mut self, count: u16) {
+        set_u16(&mut self.raw, 16, count)
+    }
+}
+
+/// The first 128 bytes of an inode.
+#[derive(Clone)]
+pub struct Inode {
+    raw: [u8; INODE_BASE_SIZE],
+}
+
+impl Inode {
+    const MODE_TYPE_MASK: u16 = 0o170000;
+
+    pub fn new(raw: [u8; INODE_BASE_SIZE]) -> Self {
+        Self { raw }
+    }
+
+    /// Creates an empty inode of the given type and permission.
+    pub fn empty(ty: VfsNodeType, perm: u16, now: Duration) -> Self {
+        let mut inode = Self {
+            raw: [0; INODE_BASE_SIZE],
+        };
+        inode.set_mode(((ty as u16) << 12) | perm);
+        inode.set_times(Some(now), Some(now), now);
+        inode
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.raw
+    }
+
+    pub fn mode(&self) -> u16 {
+        get_u16(&self.raw, 0)
+    }
+
+    pub fn set_mode(&mut self, mode: u16) {
+        set_u16(&mut self.raw, 0, mode)
+    }
+
+    /// The permission bits of the mode, including setuid, setgid and sticky.
+    pub fn perm(&self) -> u16 {
+        self.mode() & !Self::MODE_TYPE_MASK
+    }
+
+    pub fn set_perm(&mut self, perm: u16) {
+        let ty = self.mode() & Self::MODE_TYPE_MASK;
+        self.set_mode(ty | (perm & !Self::MODE_TYPE_MASK));
+    }
+
+    pub fn node_type(&self) -> VfsNodeType {
+        match self.mode() >> 12 {
+            0o1 => VfsNodeType::Fifo,
+            0o2 => VfsNodeType::CharDevice,
+            0o4 => VfsNodeType::Dir,
+            0o6 => VfsNodeType::BlockDevice,
+            0o12 => VfsNodeType::SymLink,
+            0o14 => VfsNodeType::Socket,
+            _ => VfsNodeType::File,
+        }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.node_type() == VfsNodeType::Dir
+    }
+
+    pub fn uid(&self) -> u32 {
+        get_u16(&self.raw, 2) as u32 | (get_u16(&self.raw, 120) as u32) << 16
+    }
+
+    pub fn gid(&self) -> u32 {
+        get_u16(&self.raw, 24) as u32 | (get_u16(&self.raw, 122) as u32) << 16
+    }
+
+    pub fn set_owner(&mut self, uid: u32, gid: u32) {
+        set_u16(&mut self.raw, 2, uid as u16);
+        set_u16(&mut self.raw, 120, (uid >> 16) as u16);
+        set_u16(&mut self.raw, 24, gid as u16);
+        set_u16(&mut self.raw, 122, (gid >> 16) as u16);
+    }
+
+    /// The size in bytes, the high 32 bits are only used by regular files in
+    /// ext2.
+    pub fn size(&self) -> u64 {
+        let lo = get_u32(&self.raw, 4) as u64;
+        if self.node_type() == VfsNodeType::File {
+            lo | (get_u32(&self.raw, 108) as u64) << 32
+        } else {
+            lo
+        }
+    }
+
+    pub fn set_size(&mut self, size: u64) {
+        set_u32(&mut self.raw, 4, size as u32);
+        if self.node_type() == VfsNodeType::File {
+            set_u32(&mut self.raw, 108, (size >> 32) as u32);
+        }
+    }
+
+    pub fn atime(&self) -> Duration {
+        Duration::from_secs(get_u32(&self.raw, 8) as u64)
+    }
+
+    pub fn ctime(&self) -> Duration {
+        Duration::from_secs(get_u32(&self.raw, 12) as u64)
+    }
+
+    pub fn mtime(&self) -> Duration {
+        Duration::from_secs(get_u32(&self.raw, 16) as u64)
+    }
+
+    /// Sets the access and modification time if given, and the status change
+    /// time.
+    pub fn set_times(&mut self, atime: Option<Duration>, mtime: Option<Duration>, ctime: Duration) {
+        if let Some(atime) = atime {
+            set_u32(&mut self.raw, 8, atime.as_secs() as u32);
+        }
+        if let Some(mtime) = mtime {
+            set_u32(&mut self.raw, 16, mtime.as_secs() as u32);
+        }
+        set_u32(&mut self.raw, 12, ctime.as_secs() as u32);
+    }
+
+    pub fn set_dtime(&mut self, dtime: Duration) {
+        // a zero `dtime` means the inode is in use, so it is at least 1
+        set_u32(&mut self.raw, 20, dtime.as_secs().max(1) as u32);
+    }
+
+    pub fn links_count(&self) -> u16 {
+        get_u16(&self.raw, 26)
+    }
+
+    pub fn set_links_count(&mut self, count: u16) {
+        set_u16(&mut self.raw, 26, count)
+    }
+
+    /// The number of 512-byte sectors allocated to the inode.
+    pub fn sectors(&self) -> u64 {
+        get_u32(&self.raw, 28) as u64
+    }
+
+    pub fn set_sectors(&mut self, sectors: u64) {
+        set_u32(&mut self.raw, 28, sectors as u32)
+    }
+
+    pub fn flags(&self) -> u32 {
+        get_u32(&self.raw, 32)
+    }
+
+    pub fn set_flags(&mut self, flags: u32) {
+        set_u32(&mut self.raw, 32, flags)
+    }
+
+    /// The block of extended attributes.
+    pub fn file_acl(&self) -> u32 {
+        get_u32(&self.raw, 104)
+    }
+
+    /// The block pointers, or the root of the extent tree.
+    pub fn block_area(&self) -> &[u8] {
+        &self.raw[40..40 + INODE_BLOCK_AREA]
+    }
+
+    pub fn block_area_mut(&mut self) -> &mut [u8] {
+        &mut self.raw[40..40 + INODE_BLOCK_AREA]
+    }
+
+    pub fn block(&self, index: usize) -> u32 {
+        read_u32(self.block_area(), index)
+    }
+
+    pub fn set_block(&mut self, index: usize, block: u32) {
+        write_u32(self.block_area_mut(), index, block)
+    }
+
+    /// Whether the target of the symbolic link is stored in the inode.
+    pub fn is_fast_symlink(&self, block_size: usize) -> bool {
+        let acl_sectors = if self.file_acl() != 0 {
+            block_size as u64 / 512
+        } else {
+            0
+        };
+        self.sectors() == acl_sectors && self.flags() & INODE_FLAG_EXTENTS == 0
+    }
+}
+
+/// An entry in a directory block.
+pub struct DirEntry<'a> {
+    pub ino: u32,
+    pub rec_len: usize,
+    pub name: &'a [u8],
+    pub file_type: u8,
+}
+
+impl<'a> DirEntry<'a> {
+    pub const HEADER_SIZE: usize = 8;
+
+    /// Parses the entry at `offset` of a directory block, returns `None` if it
+    /// is corrupted.
+    pub fn parse(block: &'a [u8], offset: usize, has_file_type: bool) -> Option<Self> {
+        if offset + Self::HEADER_SIZE > block.len() {
+            return None;
+        }
+        let rec_len = get_u16(block, offset + 4) as usize;
+        let (name_len, file_type) = if has_file_type {
+            (block[offset + 6] as usize, block[offset + 7])
+        } else {
+            (get_u16(block, offset + 6) as usize, 0)
+        };
+        if rec_len < Self::HEADER_SIZE
+            || rec_len % 4 != 0
+            || offset + rec_len > block.len()
+            || Self::HEADER_SIZE + name_len > rec_len
+        {
+            return None;
+        }
+        let name_start = offset + Self::HEADER_SIZE;
+        Some(Self {
+            ino: get_u32(block, offset),
+            rec_len,
+            name: &block[name_start..name_start + name_len],
+            file_type,
+        })
+    }
+
+    /// The space used by an entry with a name of `name_len` bytes.
+    pub const fn min_rec_len(name_len: usize) -> usize {
+        (Self::HEADER_SIZE + name_len + 3) & !3
+    }
+
+    /// Writes an entry at `offset` of a directory block.
+    pub fn write(block: &mut [u8], offset: usize, ino: u32, rec_len: usize, name: &[u8], ty: u8) {
+        set_u32(block, offset, ino);
+        set_u16(block, offset + 4, rec_len as u16);
+        block[offset + 6] = name.len() as u8;
+        block[offset + 7] = ty;
+        let name_start = offset + Self::HEADER_SIZE;
+        block[name_start..name_start + name.len()].copy_from_slice(name);
+    }
+
+    pub fn set_ino(block: &mut [u8], offset: usize, ino: u32) {
+        set_u32(block, offset, ino)
+    }
+
+    pub fn set_rec_len(block: &mut [u8], offset: usize, rec_len: usize) {
+        set_u16(block, offset + 4, rec_len as u16)
+    }
+
+    pub fn set_file_type(block: &mut [u8], offset: usize, ty: u8) {
+        block[offset + 7] = ty;
+    }
+}
+
+/// Converts the node type to the file type in directory entries.
+pub const fn file_type_of(ty: VfsNodeType) -> u8 {
+    match ty {
+        VfsNodeType::File => 1,
+        VfsNodeType::Dir => 2,
+        VfsNodeType::CharDevice => 3,
+        VfsNodeType::BlockDevice => 4,
+        VfsNodeType::Fifo => 5,
+        VfsNodeType::Socket => 6,
+        VfsNodeType::SymLink => 7,
+    }
+}
+
+/// A node in the extent tree of ext4.
+pub enum ExtentNode<'a> {
+    /// Leaf entries, maps `len` blocks from the logical block `start` to the
+    /// physical block `phys`.
+    Leaf(&'a [u8]),
+    /// Index entries, points to the child nodes that cover the logical
+    /// blocks from `start`.
+    Index(&'a [u8]),
+}
+
+impl<'a> ExtentNode<'a> {
+    const HEADER_SIZE: usize = 12;
+    const ENTRY_SIZE: usize = 12;
+    /// Extents longer than this are uninitialized, and read as zeros.
+    const MAX_INIT_LEN: u16 = 32768;
+
+    /// Parses the extent node in `raw`, returns `None` if it is corrupted.
+    pub fn parse(raw: &'a [u8]) -> Option<Self> {
+        if raw.len() < Self::HEADER_SIZE || get_u16(raw, 0) != EXTENT_MAGIC {
+            return None;
+        }
+        let entries = get_u16(raw, 2) as usize;
+        let depth = get_u16(raw, 6);
+        let end = Self::HEADER_SIZE + entries * Self::ENTRY_SIZE;
+        let entries = raw.get(Self::HEADER_SIZE..end)?;
+        Some(if depth == 0 {
+            Self::Leaf(entries)
+        } else {
+            Self::Index(entries)
+        })
+    }
+
+    /// Finds the last entry that starts at or before `block`.
+    fn find(entries: &[u8], block: u32) -> Option<&[u8]> {
+        entries
+            .chunks(Self::ENTRY_SIZE)
+            .take_while(|entry| get_u32(entry, 0) <= block)
+            .last()
+    }
+
+    /// For leaf nodes, returns the physical block of the logical `block`, or
+    /// 0 if it is a hole. For index nodes, returns the child node to search.
+    pub fn lookup(&self, block: u32) -> u64 {
+        match self {
+            Self::Leaf(entries) => Self::find(entries, block).map_or(0, |entry| {
+                let (start, len) = (get_u32(entry, 0), get_u16(entry, 4));
+                if len > Self::MAX_INIT_LEN || block - start >= len as u32 {
+                    return 0;
+                }
+                let phys = get_u32(entry, 8) as u64 | (get_u16(entry, 6) as u64) << 32;
+                phys + (block - start) as u64
+            }),
+            Self::Index(entries) => Self::find(entries, block).map_or(0, |entry| {
+                get_u32(entry, 4) as u64 | (get_u16(entry, 8) as u64) << 32
+            }),
+        }
+    }
+}
@@ -0,0 +1,1244 @@
+//! Native [ext2] filesystem, which can also read ext4 filesystems whose files
+//! are mapped by extents.
+//!
+//! Filesystems with features that can be read but not written, such as
+//! extents, 64-bit block numbers or metadata checksums, are mounted as
+//! read-only. Write operations on them return
+//! [`PermissionDenied`](VfsError::PermissionDenied).
+//!
+//! All on-disk structures are protected by one lock, and written to the disk
+//! when they are changed. The disk caches blocks with write-back, they reach
+//! the device on `fsync` or when the filesystem is synchronized or unmounted.
+//!
+//! [ext2]: https://www.nongnu.org/ext2-doc/ext2.html
+
+mod layout;
+mod node;
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::{string::String, sync::Arc, vec, vec::Vec};
+use core::time::Duration;
+
+use axfs_vfs::{FileSystemInfo, VfsDirEntry, VfsError, VfsNodeAttr, VfsNodePerm};
+use axfs_vfs::{VfsNodeRef, VfsNodeType, VfsOps, VfsResult, VfsSetAttr};
+use axsync::Mutex;
+
+pub use self::layout::EXT2_MAGIC;
+use self::layout::*;
+use self::node::{Ext2Dir, Ext2File};
+use crate::dev::Disk;
+
+/// Maximum depth of the extent tree.
+const MAX_EXTENT_DEPTH: usize = 5;
+
+/// The ext2 filesystem that implements [`axfs_vfs::VfsOps`].
+pub struct Ext2FileSystem {
+    fs: Arc<Ext2Fs>,
+}
+
+/// The state shared by the filesystem and its nodes.
+struct Ext2Fs {
+    inner: Mutex<FsInner>,
+    /// Parent of the mount point, which is returned as the parent of the root
+    /// directory.
+    mount_parent: Mutex<Option<VfsNodeRef>>,
+    block_size: usize,
+    read_only: bool,
+}
+
+struct FsInner {
+    disk: Disk,
+    sb: SuperBlock,
+    groups: Vec<GroupDesc>,
+    block_size: usize,
+    has_file_type: bool,
+    /// Number of live nodes of each inode.
+    open_count: BTreeMap<u32, usize>,
+    /// Inodes that have no links but are still open, they are freed when the
+    /// last node is dropped.
+    orphans: BTreeSet<u32>,
+}
+
+/// The location of an entry in a directory.
+struct EntryPos {
+    ino: u32,
+    file_type: u8,
+    block: u64,
+    offset: usize,
+    /// Offset of the previous entry in the same block.
+    prev: Option<usize>,
+}
+
+fn now() -> Duration {
+    axhal::time::current_time()
+}
+
+fn node_type_of(file_type: u8) -> Option<VfsNodeType> {
+    Some(match file_type {
+        1 => VfsNodeType::File,
+        2 => VfsNodeType::Dir,
+        3 => VfsNodeType::CharDevice,
+        4 => VfsNodeType::BlockDevice,
+        5 => VfsNodeType::Fifo,
+        6 => VfsNodeType::Socket,
+        7 => VfsNodeType::SymLink,
+        _ => return None,
+    })
+}
+
+impl Ext2FileSystem {
+    /// Opens the ext2 filesystem on `disk`.
+    ///
+    /// Returns [`InvalidData`](VfsError::InvalidData) if `disk` does not
+    /// contain an ext2 filesystem, or [`Unsupported`](VfsError::Unsupported)
+    /// if the filesystem uses features that cannot be read.
+    pub fn new(mut disk: Disk) -> VfsResult<Self> {
+        let mut raw = [0; SUPERBLOCK_SIZE];
+        disk_read(&mut disk, SUPERBLOCK_OFFSET, &mut raw)?;
+        let sb = SuperBlock::new(raw);
+        if sb.magic() != EXT2_MAGIC {
+            warn!("ext2: bad magic number {:#x}", sb.magic());
+            return Err(VfsError::InvalidData);
+        }
+        let unsupported = sb.feature_incompat() & !INCOMPAT_READ;
+        if unsupported != 0 {
+            warn!("ext2: unsupported incompatible features {:#x}", unsupported);
+            return Err(VfsError::Unsupported);
+        }
+        if sb.log_block_size() > 6 || sb.blocks_per_group() == 0 || sb.inodes_per_group() == 0 {
+            return Err(VfsError::InvalidData);
+        }
+
+        let block_size = 1024 << sb.log_block_size();
+        // a block bitmap or an inode bitmap takes one block
+        let bits_per_block = block_size as u32 * 8;
+        if sb.blocks_per_group() > bits_per_block || sb.inodes_per_group() > bits_per_block {
+            warn!("ext2: bad number of blocks or inodes per group");
+            return Err(VfsError::InvalidData);
+        }
+        let inode_size = sb.inode_size();
+        if inode_size < INODE_BASE_SIZE || inode_size > block_size || !inode_size.is_power_of_two()
+        {
+            warn!("ext2: bad inode size {}", inode_size);
+            return Err(VfsError::InvalidData);
+        }
+        let data_blocks = match sb.blocks_count().checked_sub(sb.first_data_block()) {
+            Some(n) if n > 0 => n,
+            _ => {
+                warn!("ext2: bad number of blocks {}", sb.blocks_count());
+                return Err(VfsError::InvalidData);
+            }
+        };
+        let group_count = data_blocks.div_ceil(sb.blocks_per_group() as u64);
+        if sb.inodes_count() as u64 > group_count * sb.inodes_per_group() as u64 {
+            warn!("ext2: bad number of inodes {}", sb.inodes_count());
+            return Err(VfsError::InvalidData);
+        }
+        let group_count = group_count as usize;
+        let desc_size = sb.desc_size();
+        let mut gdt = vec![0; group_count * desc_size];
+        let gdt_pos = (sb.first_data_block() + 1) * block_size as u64;
+        disk_read(&mut disk, gdt_pos, &mut gdt)?;
+        let groups = gdt.chunks_exact(desc_size).map(GroupDesc::new).collect();
+
+        let read_only = sb.feature_incompat() & !INCOMPAT_WRITE != 0
+            || sb.feature_ro_compat() & !RO_COMPAT_WRITE != 0;
+        if read_only {
+            warn!("ext2: filesystem has features that cannot be written, mount as read-only");
+        }
+        info!(
+            "ext2: block size {}, {} blocks, {} inodes, {} groups",
+            block_size,
+            sb.blocks_count(),
+            sb.inodes_count(),
+            group_count
+        );
+
+        let inner = FsInner {
+            disk,
+            has_file_type: sb.feature_incompat() & INCOMPAT_FILETYPE != 0,
+            sb,
+            groups,
+            block_size,
+            open_count: BTreeMap::new(),
+            orphans: BTreeSet::new(),
+        };
+        Ok(Self {
+            fs: Arc::new(Ext2Fs {
+                inner: Mutex::new(inner),
+                mount_parent: Mutex::new(None),
+                block_size,
+                read_only,
+            }),
+        })
+    }
+
+    /// Whether the filesystem is mounted as read-only.
+    pub fn is_read_only(&self) -> bool {
+        self.fs.read_only
+    }
+}
+
+impl VfsOps for Ext2FileSystem {
+    fn mount(&self, _path: &str, mount_point: VfsNodeRef) -> VfsResult {
+        *self.fs.mount_parent.lock() = mount_point.parent();
+        Ok(())
+    }
+
+    fn umount(&self) -> VfsResult {
+        self.fs.mount_parent.lock().take();
+        self.sync()
+    }
+
+    fn sync(&self) -> VfsResult {
+        self.fs.sync()
+    }
+
+    fn statfs(&self) -> VfsResult<FileSystemInfo> {
+        let inner = self.fs.inner.lock();
+        let sb = &inner.sb;
+        let free = sb.free_blocks_count();
+        let avail = free.saturating_sub(sb.reserved_blocks_count());
+        Ok(FileSystemInfo::new(
+            EXT2_MAGIC as u64,
+            inner.block_size as u64,
+            MAX_NAME_LEN as u64,
+        )
+        .with_blocks(sb.blocks_count(), free, avail)
+        .with_files(sb.inodes_count() as u64, sb.free_inodes_count() as u64))
+    }
+
+    fn root_dir(&self) -> VfsNodeRef {
+        self.fs.new_node(ROOT_INO, VfsNodeType::Dir)
+    }
+}
+
+impl Ext2Fs {
+    /// Creates a node of the inode `ino`.
+    fn new_node(self: &Arc<Self>, ino: u32, ty: VfsNodeType) -> VfsNodeRef {
+        *self.inner.lock().open_count.entry(ino).or_default() += 1;
+        if ty == VfsNodeType::Dir {
+            Arc::new(Ext2Dir::new(self.clone(), ino))
+        } else {
+            Arc::new(Ext2File::new(self.clone(), ino))
+        }
+    }
+
+    /// Called when a node of the inode `ino` is dropped.
+    fn release_node(&self, ino: u32) {
+        let mut inner = self.inner.lock();
+        let count = inner.open_count.get_mut(&ino).unwrap();
+        *count -= 1;
+        if *count == 0 {
+            inner.open_count.remove(&ino);
+            if inner.orphans.remove(&ino) {
+                if let Err(e) = inner.free_inode_data(ino) {
+                    warn!("ext2: failed to free inode {}: {:?}", ino, e);
+                }
+            }
+        }
+    }
+
+    /// Writes the cached blocks of the disk to the device.
+    fn sync(&self) -> VfsResult {
+        self.inner.lock().disk.flush().map_err(|_| VfsError::Io)
+    }
+
+    fn check_writable(&self) -> VfsResult {
+        if self.read_only {
+            Err(VfsError::PermissionDenied)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn get_attr(&self, ino: u32) -> VfsResult<VfsNodeAttr> {
+        let inode = self.inner.lock().read_inode(ino)?;
+        let perm = VfsNodePerm::from_bits_truncate(inode.perm());
+        let attr = VfsNodeAttr::new(perm, inode.node_type(), inode.size(), inode.sectors());
+        Ok(attr
+            .with_ino(ino as u64)
+            .with_nlink(inode.links_count() as u64)
+            .with_owner(inode.uid(), inode.gid())
+            .with_times(inode.atime(), inode.mtime(), inode.ctime()))
+    }
+
+    fn set_attr(&self, ino: u32, attr: &VfsSetAttr) -> VfsResult {
+        self.check_writable()?;
+        let mut inner = self.inner.lock();
+        let mut inode = inner.read_inode(ino)?;
+        if let Some(mode) = attr.mode {
+            inode.set_perm(mode.bits());
+        }
+        if attr.uid.is_some() || attr.gid.is_some() {
+            let uid = attr.uid.unwrap_or(inode.uid());
+            let gid = attr.gid.unwrap_or(inode.gid());
+            inode.set_owner(uid, gid);
+        }
+        inode.set_times(attr.atime, attr.mtime, now());
+        inner.write_inode(ino, &inode)
+    }
+
+    fn read_at(&self, ino: u32, offset: u64, buf: &mut [u8]) -> VfsResult<usize> {
+        let mut inner = self.inner.lock();
+        let mut inode = inner.read_inode(ino)?;
+        let len = inner.read_data(&inode, offset, buf)?;
+        // only update the access time if it is older than the modification
+        if !self.read_only && inode.atime() <= inode.mtime().max(inode.ctime()) {
+            inode.set_times(Some(now()), None, inode.ctime());
+            inner.write_inode(ino, &inode)?;
+        }
+        Ok(len)
+    }
+
+    fn write_at(&self, ino: u32, offset: u64, buf: &[u8]) -> VfsResult<usize> {
+        self.check_writable()?;
+        let mut inner = self.inner.lock();
+        let mut inode = inner.read_inode(ino)?;
+        let res = inner.write_data(ino, &mut inode, offset, buf);
+        let now = now();
+        inode.set_times(None, Some(now), now);
+        // blocks may be allocated even if it fails
+        inner.write_inode(ino, &inode)?;
+        res
+    }
+
+    fn truncate(&self, ino: u32, size: u64) -> VfsResult {
+        self.check_writable()?;
+        let mut inner = self.inner.lock();
+        let mut inode = inner.read_inode(ino)?;
+        if size < inode.size() {
+            let block_size = self.block_size as u64;
+            inner.free_blocks_from(&mut inode, size.div_ceil(block_size))?;
+            // clear the tail of the last block, which may be read after the
+            // file is extended
+            let tail = size % block_size;
+            if tail != 0 {
+                let block = inner.block_map(&inode, size / block_size)?;
+                if block != 0 {
+                    let zeros = vec![0; (block_size - tail) as usize];
+                    disk_write(&mut inner.disk, block * block_size + tail, &zeros)?;
+                }
+            }
+        }
+        inode.set_size(size);
+        let now = now();
+        inode.set_times(None, Some(now), now);
+        inner.write_inode(ino, &inode)
+    }
+
+    fn readlink(&self, ino: u32, buf: &mut [u8]) -> VfsResult<usize> {
+        let mut inner = self.inner.lock();
+        let inode = inner.read_inode(ino)?;
+        if inode.node_type() != VfsNodeType::SymLink {
+            return Err(VfsError::InvalidInput);
+        }
+        if inode.is_fast_symlink(self.block_size) {
+            let len = (inode.size() as usize).min(INODE_BLOCK_AREA).min(buf.len());
+            buf[..len].copy_from_slice(&inode.block_area()[..len]);
+            Ok(len)
+        } else {
+            inner.read_data(&inode, 0, buf)
+        }
+    }
+
+    /// Finds the entry `name` in the directory `dir_ino`.
+    fn lookup(&self, dir_ino: u32, name: &str) -> VfsResult<(u32, VfsNodeType)> {
+        let mut inner = self.inner.lock();
+        let dir = inner.read_inode(dir_ino)?;
+        let pos = inner.find_entry(&dir, name)?.ok_or(VfsError::NotFound)?;
+        Ok((pos.ino, inner.entry_type(&pos)?))
+    }
+
+    fn read_dir(
+        &self,
+        ino: u32,
+        start_idx: usize,
+        dirents: &mut [VfsDirEntry],
+    ) -> VfsResult<usize> {
+        let mut inner = self.inner.lock();
+        let dir = inner.read_inode(ino)?;
+        let mut entries = Vec::new();
+        let mut index = 0;
+        inner.scan_dir(&dir, |entry, pos| {
+            if entry.ino == 0 {
+                return None;
+            }
+            if index >= start_idx {
+                entries.push((String::from_utf8_lossy(entry.name).into_owned(), pos));
+            }
+            index += 1;
+            (entries.len() >= dirents.len()).then_some(())
+        })?;
+        for ((name, pos), ent) in entries.iter().zip(dirents.iter_mut()) {
+            *ent = VfsDirEntry::new(name, inner.entry_type(pos)?);
+        }
+        Ok(entries.len())
+    }
+
+    /// Creates a node in the directory `dir_ino`, or a symbolic link if
+    /// `target` is given.
+    fn create(&self, dir_ino: u32, name: &str, ty: VfsNodeType, target: Option<&str>) -> VfsResult {
+        self.check_writable()?;
+        if name.len() > MAX_NAME_LEN {
+            return Err(VfsError::InvalidInput);
+        }
+        let mut inner = self.inner.lock();
+        let mut dir = inner.read_inode(dir_ino)?;
+        if inner.find_entry(&dir, name)?.is_some() {
+            return Err(VfsError::AlreadyExists);
+        }
+        let perm = match ty {
+            VfsNodeType::File => VfsNodePerm::default_file().bits(),
+            VfsNodeType::Dir => VfsNodePerm::default_dir().bits(),
+            VfsNodeType::SymLink => 0o777,
+            _ => return Err(VfsError::Unsupported),
+        };
+
+        let goal = inner.group_of_ino(dir_ino);
+        let ino = inner.alloc_inode(goal, ty == VfsNodeType::Dir)?;
+        let mut inode = Inode::empty(ty, perm, now());
+        inode.set_links_count(1);
+        inner.write_new_inode(ino, &inode)?;
+        let res = inner
+            .init_node(ino, &mut inode, dir_ino, target)
+            .and_then(|_| inner.write_inode(ino, &inode))
+            .and_then(|_| inner.add_entry(dir_ino, &mut dir, name, ino, ty));
+        if let Err(e) = res {
+            inode.set_links_count(0);
+            inner.write_inode(ino, &inode)?;
+            inner.free_inode_data(ino)?;
+            return Err(e);
+        }
+
+        if ty == VfsNodeType::Dir {
+            dir.set_links_count(dir.links_count() + 1);
+        }
+        let now = now();
+        dir.set_times(None, Some(now), now);
+        inner.write_inode(dir_ino, &dir)
+    }
+
+    /// Removes the entry `name` from the directory `dir_ino`.
+    fn unlink(&self, dir_ino: u32, name: &str) -> VfsResult {
+        self.check_writable()?;
+        let mut inner = self.inner.lock();
+        let mut dir = inner.read_inode(dir_ino)?;
+        let pos = inner.find_entry(&dir, name)?.ok_or(VfsError::NotFound)?;
+        let mut inode = inner.read_inode(pos.ino)?;
+        if inode.is_dir() && !inner.is_dir_empty(&inode)? {
+            return Err(VfsError::DirectoryNotEmpty);
+        }
+        inner.remove_entry(&mut dir, name)?;
+        if inode.is_dir() {
+            dir.set_links_count(dir.links_count() - 1);
+        }
+        let now = now();
+        dir.set_times(None, Some(now), now);
+        inner.write_inode(dir_ino, &dir)?;
+        inner.drop_link(pos.ino, &mut inode)
+    }
+
+    /// Adds a hard link `name` in the directory `dir_ino` to the inode `ino`.
+    fn link(&self, dir_ino: u32, name: &str, ino: u32) -> VfsResult {
+        self.check_writable()?;
+        if name.len() > MAX_NAME_LEN {
+            return Err(VfsError::InvalidInput);
+        }
+        let mut inner = self.inner.lock();
+        let mut dir = inner.read_inode(dir_ino)?;
+        if inner.find_entry(&dir, name)?.is_some() {
+            return Err(VfsError::AlreadyExists);
+        }
+        let mut inode = inner.read_inode(ino)?;
+        if inode.links_count() == 0 {
+            return Err(VfsError::NotFound); // already removed
+        }
+        if inode.links_count() == u16::MAX {
+            return Err(VfsError::StorageFull);
+        }
+        inner.add_entry(dir_ino, &mut dir, name, ino, inode.node_type())?;
+        let now = now();
+        dir.set_times(None, Some(now), now);
+        inner.write_inode(dir_ino, &dir)?;
+        inode.set_links_count(inode.links_count() + 1);
+        inode.set_times(None, None, now);
+        inner.write_inode(ino, &inode)
+    }
+
+    /// Renames `src_path` to `dst_path`, both are relative to the directory
+    /// `dir_ino`. The destination is replaced if it exists.
+    fn rename(&self, dir_ino: u32, src_path: &str, dst_path: &str) -> VfsResult {
+        self.check_writable()?;
+        let mut inner = self.inner.lock();
+        let (src_dir_ino, src_name) = inner.resolve_parent(dir_ino, src_path)?;
+        let (dst_dir_ino, dst_name) = inner.resolve_parent(dir_ino, dst_path)?;
+        if [src_name, dst_name]
+            .iter()
+            .any(|name| matches!(*name, "" | "." | ".."))
+        {
+            return Err(VfsError::InvalidInput);
+        }
+        if dst_name.len() > MAX_NAME_LEN {
+            return Err(VfsError::InvalidInput);
+        }
+
+        let src_dir = inner.read_inode(src_dir_ino)?;
+        let src = inner
+            .find_entry(&src_dir, src_name)?
+            .ok_or(VfsError::NotFound)?;
+        let src_inode = inner.read_inode(src.ino)?;
+        let ty = src_inode.node_type();
+        let dst_dir = inner.read_inode(dst_dir_ino)?;
+        let dst = inner.find_entry(&dst_dir, dst_name)?;
+        if dst.as_ref().is_some_and(|dst| dst.ino == src.ino) {
+            return Ok(()); // the same file
+        }
+        if src_inode.is_dir() && inner.is_ancestor(src.ino, dst_dir_ino)? {
+            return Err(VfsError::InvalidInput); // move a directory into itself
+        }
+
+        let now = now();
+        if let Some(dst) = dst {
+            let mut dst_inode = inner.read_inode(dst.ino)?;
+            match (src_inode.is_dir(), dst_inode.is_dir()) {
+                (true, false) => return Err(VfsError::NotADirectory),
+                (false, true) => return Err(VfsError::IsADirectory),
+                (true, true) if !inner.is_dir_empty(&dst_inode)? => {
+                    return Err(VfsError::DirectoryNotEmpty)
+                }
+                _ => {}
+            }
+            inner.replace_entry(&dst_dir, dst_name, src.ino, ty)?;
+            inner.drop_link(dst.ino, &mut dst_inode)?;
+            let mut dst_dir = inner.read_inode(dst_dir_ino)?;
+            if dst_inode.is_dir() {
+                // the `..` of the replaced directory
+                dst_dir.set_links_count(dst_dir.links_count() - 1);
+            }
+            dst_dir.set_times(None, Some(now), now);
+            inner.write_inode(dst_dir_ino, &dst_dir)?;
+        } else {
+            let mut dst_dir = dst_dir;
+            inner.add_entry(dst_dir_ino, &mut dst_dir, dst_name, src.ino, ty)?;
+            dst_dir.set_times(None, Some(now), now);
+            inner.write_inode(dst_dir_ino, &dst_dir)?;
+        }
+
+        let mut src_dir = inner.read_inode(src_dir_ino)?;
+        inner.remove_entry(&mut src_dir, src_name)?;
+        src_dir.set_times(None, Some(now), now);
+        inner.write_inode(src_dir_ino, &src_dir)?;
+
+        if src_inode.is_dir() && src_dir_ino != dst_dir_ino {
+            inner.replace_entry(&src_inode, "..", dst_dir_ino, VfsNodeType::Dir)?;
+            let mut src_dir = inner.read_inode(src_dir_ino)?;
+            src_dir.set_links_count(src_dir.links_count() - 1);
+            inner.write_inode(src_dir_ino, &src_dir)?;
+            let mut dst_dir = inner.read_inode(dst_dir_ino)?;
+            dst_dir.set_links_count(dst_dir.links_count() + 1);
+            inner.write_inode(dst_dir_ino, &dst_dir)?;
+        }
+        let mut src_inode = inner.read_inode(src.ino)?;
+        src_inode.set_times(None, None, now);
+        inner.write_inode(src.ino, &src_inode)
+    }
+}
+
+fn disk_read(disk: &mut Disk, pos: u64, buf: &mut [u8]) -> VfsResult {
+    disk.set_position(pos);
+    let mut done = 0;
+    while done < buf.len() {
+        match disk.read_one(&mut buf[done..]) {
+            Ok(0) | Err(_) => return Err(VfsError::Io),
+            Ok(n) => done += n,
+        }
+    }
+    Ok(())
+}
+
+fn disk_write(disk: &mut Disk, pos: u64, buf: &[u8]) -> VfsResult {
+    disk.set_position(pos);
+    let mut done = 0;
+    while done < buf.len() {
+        match disk.write_one(&buf[done..]) {
+            Ok(0) | Err(_) => return Err(VfsError::Io),
+            Ok(n) => done += n,
+        }
+    }
+    Ok(())
+}
+
+impl FsInner {
+    fn read_block(&mut self, block: u64) -> VfsResult<Vec<u8>> {
+        let mut buf = vec![0; self.block_size];
+        disk_read(&mut self.disk, block * self.block_size as u64, &mut buf)?;
+        Ok(buf)
+    }
+
+    fn write_block(&mut self, block: u64, buf: &[u8]) -> VfsResult {
+        disk_write(&mut self.disk, block * self.block_size as u64, buf)
+    }
+
+    fn write_super(&mut self) -> VfsResult {
+        disk_write(&mut self.disk, SUPERBLOCK_OFFSET, self.sb.as_bytes())
+    }
+
+    fn write_group(&mut self, group: usize) -> VfsResult {
+        let gdt_pos = (self.sb.first_data_block() + 1) * self.block_size as u64;
+        let pos = gdt_pos + (group * self.sb.desc_size()) as u64;
+        disk_write(&mut self.disk, pos, self.groups[group].as_bytes())
+    }
+
+    /// Number of block pointers in an indirect block.
+    fn ptrs_per_block(&self) -> u64 {
+        self.block_size as u64 / 4
+    }
+
+    /// Number of 512-byte sectors in a block.
+    fn block_sectors(&self) -> u64 {
+        self.block_size as u64 / 512
+    }
+
+    /// The descriptor of the block group `group`, which may come from a
+    /// corrupted block or inode number.
+    fn group(&self, group: usize) -> VfsResult<&GroupDesc> {
+        self.groups.get(group).ok_or(VfsError::InvalidData)
+    }
+
+    fn group_of_ino(&self, ino: u32) -> usize {
+        ((ino - 1) / self.sb.inodes_per_group()) as usize
+    }
+
+    fn inode_pos(&self, ino: u32) -> VfsResult<u64> {
+        if ino == 0 || ino > self.sb.inodes_count() {
+            return Err(VfsError::InvalidData);
+        }
+        let group = self.group_of_ino(ino);
+        let index = ((ino - 1) % self.sb.inodes_per_group()) as u64;
+        let table = self.group(group)?.inode_table();
+        Ok(table * self.block_size as u64 + index * self.sb.inode_size() as u64)
+    }
+
+    fn read_inode(&mut self, ino: u32) -> VfsResult<Inode> {
+        let pos = self.inode_pos(ino)?;
+        let mut raw = [0; INODE_BASE_SIZE];
+        disk_read(&mut self.disk, pos, &mut raw)?;
+        Ok(Inode::new(raw))
+    }
+
+    fn write_inode(&mut self, ino: u32, inode: &Inode) -> VfsResult {
+        let pos = self.inode_pos(ino)?;
+        disk_write(&mut self.disk, pos, inode.as_bytes())
+    }
+
+    /// Writes a newly allocated inode, the extra space of a large inode is
+    /// cleared.
+    fn write_new_inode(&mut self, ino: u32, inode: &Inode) -> VfsResult {
+        let pos = self.inode_pos(ino)?;
+        let mut raw = vec![0; self.sb.inode_size()];
+        raw[..INODE_BASE_SIZE].copy_from_slice(inode.as_bytes());
+        disk_write(&mut self.disk, pos, &raw)
+    }
+
+    /// Allocates a zeroed block, prefers the block group `goal`.
+    fn alloc_block(&mut self, goal: usize) -> VfsResult<u64> {
+        let group_count = self.groups.len();
+        let blocks_per_group = self.sb.blocks_per_group() as u64;
+        for i in 0..group_count {
+            let group = (goal + i) % group_count;
+            if self.groups[group].free_blocks_count() == 0 {
+                continue;
+            }
+            let first = self.sb.first_data_block() + group as u64 * blocks_per_group;
+            let count = blocks_per_group.min(self.sb.blocks_count() - first) as usize;
+            let bitmap_block = self.groups[group].block_bitmap();
+            let mut bitmap = self.read_block(bitmap_block)?;
+            let Some(bit) = find_zero_bit(&bitmap, 0, count) else {
+                continue;
+            };
+            bitmap[bit / 8] |= 1 << (bit % 8);
+            self.write_block(bitmap_block, &bitmap)?;
+
+            let desc = &mut self.groups[group];
+            desc.set_free_blocks_count(desc.free_blocks_count() - 1);
+            self.write_group(group)?;
+            let free = self.sb.free_blocks_count();
+            self.sb.set_free_blocks_count(free.saturating_sub(1));
+            self.write_super()?;
+
+            let block = first + bit as u64;
+            self.write_block(block, &vec![0; self.block_size])?;
+            return Ok(block);
+        }
+        Err(VfsError::StorageFull)
+    }
+
+    fn free_block(&mut self, block: u64) -> VfsResult {
+        let blocks_per_group = self.sb.blocks_per_group() as u64;
+        let index = block
+            .checked_sub(self.sb.first_data_block())
+            .filter(|_| block < self.sb.blocks_count())
+            .ok_or(VfsError::InvalidData)?;
+        let group = (index / blocks_per_group) as usize;
+        let bit = (index % blocks_per_group) as usize;
+        let bitmap_block = self.group(group)?.block_bitmap();
+        let mut bitmap = self.read_block(bitmap_block)?;
+        if bitmap[bit / 8] & (1 << (bit % 8)) == 0 {
+            warn!("ext2: block {} is already free", block);
+            return Ok(());
+        }
+        bitmap[bit / 8] &= !(1 << (bit % 8));
+        self.write_block(bitmap_block, &bitmap)?;
+
+        let desc = &mut self.groups[group];
+        desc.set_free_blocks_count(desc.free_blocks_count() + 1);
+        self.write_group(group)?;
+        let free = self.sb.free_blocks_count();
+        self.sb.set_free_blocks_count(free + 1);
+        self.write_super()
+    }
+
+    /// Allocates an inode, prefers the block group `goal`.
+    fn alloc_inode(&mut self, goal: usize, is_dir: bool) -> VfsResult<u32> {
+        let group_count = self.groups.len();
+        let inodes_per_group = self.sb.inodes_per_group();
+        for i in 0..group_count {
+            let group = (goal + i) % group_count;
+            if self.groups[group].free_inodes_count() == 0 {
+                continue;
+            }
+            let bitmap_block = self.groups[group].inode_bitmap();
+            let mut bitmap = self.read_block(bitmap_block)?;
+            // skip the reserved inodes
+            let first = group as u32 * inodes_per_group + 1;
+            let skip = self.sb.first_ino().saturating_sub(first) as usize;
+            let Some(bit) = find_zero_bit(&bitmap, skip, inodes_per_group as usize) else {
+                continue;
+            };
+            bitmap[bit / 8] |= 1 << (bit % 8);
+            self.write_block(bitmap_block, &bitmap)?;
+
+            let desc = &mut self.groups[group];
+            desc.set_free_inodes_count(desc.free_inodes_count() - 1);
+            if is_dir {
+                desc.set_used_dirs_count(desc.used_dirs_count() + 1);
+            }
+            self.write_group(group)?;
+            let free = self.sb.free_inodes_count();
+            self.sb.set_free_inodes_count(free.saturating_sub(1));
+            self.write_super()?;
+            return Ok(first + bit as u32);
+        }
+        Err(VfsError::StorageFull)
+    }
+
+    fn free_inode(&mut self, ino: u32, is_dir: bool) -> VfsResult {
+        if ino == 0 || ino > self.sb.inodes_count() {
+            return Err(VfsError::InvalidData);
+        }
+        let group = self.group_of_ino(ino);
+        let bit = ((ino - 1) % self.sb.inodes_per_group()) as usize;
+        let bitmap_block = self.group(group)?.inode_bitmap();
+        let mut bitmap = self.read_block(bitmap_block)?;
+        bitmap[bit / 8] &= !(1 << (bit % 8));
+        self.write_block(bitmap_block, &bitmap)?;
+
+        let desc = &mut self.groups[group];
+        desc.set_free_inodes_count(desc.free_inodes_count() + 1);
+        if is_dir {
+            desc.set_used_dirs_count(desc.used_dirs_count().saturating_sub(1));
+        }
+        self.write_group(group)?;
+        let free = self.sb.free_inodes_count();
+        self.sb.set_free_inodes_count(free + 1);
+        self.write_super()
+    }
+
+    /// Frees the data blocks and the inode `ino`, which has no links.
+    fn free_inode_data(&mut self, ino: u32) -> VfsResult {
+        let mut inode = self.read_inode(ino)?;
+        if inode.node_type() != VfsNodeType::SymLink || !inode.is_fast_symlink(self.block_size) {
+            self.free_blocks_from(&mut inode, 0)?;
+        }
+        inode.set_size(0);
+        inode.set_dtime(now());
+        self.write_inode(ino, &inode)?;
+        self.free_inode(ino, inode.is_dir())
+    }
+
+    /// Returns the slot in the inode, the level of indirection and the index
+    /// in the indirect tree of the logical block `index`.
+    fn indirect_path(&self, index: u64) -> VfsResult<(usize, u32, u64)> {
+        let ptrs = self.ptrs_per_block();
+        let mut rest = index;
+        if rest < DIRECT_BLOCKS as u64 {
+            return Ok((rest as usize, 0, 0));
+        }
+        rest -= DIRECT_BLOCKS as u64;
+        let mut span = ptrs;
+        for level in 1..=3 {
+            if rest < span {
+                return Ok((DIRECT_BLOCKS + level as usize - 1, level, rest));
+            }
+            rest -= span;
+            span *= ptrs;
+        }
+        Err(VfsError::InvalidInput) // exceeds the maximum file size
+    }
+
+    /// Returns the physical block of the logical block `index` of the inode,
+    /// or 0 if it is a hole.
+    fn block_map(&mut self, inode: &Inode, index: u64) -> VfsResult<u64> {
+        if inode.flags() & INODE_FLAG_EXTENTS != 0 {
+            return self.extent_map(inode, index);
+        }
+        let ptrs = self.ptrs_per_block();
+        let (slot, level, mut rest) = self.indirect_path(index)?;
+        let mut block = inode.block(slot) as u64;
+        for level in (1..level + 1).rev() {
+            if block == 0 {
+                return Ok(0);
+            }
+            let span = ptrs.pow(level - 1);
+            let table = self.read_block(block)?;
+            block = read_u32(&table, (rest / span) as usize) as u64;
+            rest %= span;
+        }
+        Ok(block)
+    }
+
+    fn extent_map(&mut self, inode: &Inode, index: u64) -> VfsResult<u64> {
+        let index = u32::try_from(index).map_err(|_| VfsError::InvalidInput)?;
+        let mut raw = inode.block_area().to_vec();
+        for _ in 0..MAX_EXTENT_DEPTH {
+            let node = ExtentNode::parse(&raw).ok_or(VfsError::InvalidData)?;
+            let block = node.lookup(index);
+            match node {
+                ExtentNode::Leaf(_) => return Ok(block),
+                ExtentNode::Index(_) if block == 0 => return Ok(0),
+                ExtentNode::Index(_) => raw = self.read_block(block)?,
+            }
+        }
+        Err(VfsError::InvalidData)
+    }
+
+    /// Returns the physical block of the logical block `index` of the inode,
+    /// allocates it and the indirect blocks if they do not exist.
+    fn block_map_alloc(&mut self, inode: &mut Inode, goal: usize, index: u64) -> VfsResult<u64> {
+        if inode.flags() & INODE_FLAG_EXTENTS != 0 {
+            return Err(VfsError::Unsupported);
+        }
+        let ptrs = self.ptrs_per_block();
+        let (slot, level, mut rest) = self.indirect_path(index)?;
+        let mut block = inode.block(slot) as u64;
+        if block == 0 {
+            block = self.alloc_block(goal)?;
+            inode.set_block(slot, block as u32);
+            inode.set_sectors(inode.sectors() + self.block_sectors());
+        }
+        for level in (1..level + 1).rev() {
+            let span = ptrs.pow(level - 1);
+            let index = (rest / span) as usize;
+            rest %= span;
+            let mut table = self.read_block(block)?;
+            let mut next = read_u32(&table, index) as u64;
+            if next == 0 {
+                next = self.alloc_block(goal)?;
+                write_u32(&mut table, index, next as u32);
+                self.write_block(block, &table)?;
+                inode.set_sectors(inode.sectors() + self.block_sectors());
+            }
+            block = next;
+        }
+        Ok(block)
+    }
+
+    /// Frees all blocks of the inode from the logical block `first`.
+    fn free_blocks_from(&mut self, inode: &mut Inode, first: u64) -> VfsResult {
+        if inode.flags() & INODE_FLAG_EXTENTS != 0 {
+            return Err(VfsError::Unsupported);
+        }
+        let mut freed = 0;
+        for slot in (first as usize).min(DIRECT_BLOCKS)..DIRECT_BLOCKS {
+            let block = inode.block(slot);
+            if block != 0 {
+                self.free_block(block as u64)?;
+                inode.set_block(slot, 0);
+                freed += 1;
+            }
+        }
+        let ptrs = self.ptrs_per_block();
+        let (mut base, mut span) = (DIRECT_BLOCKS as u64, ptrs);
+        for level in 1..=3 {
+            let slot = DIRECT_BLOCKS + level as usize - 1;
+            let block = inode.block(slot) as u64;
+            if block != 0 && first < base + span {
+                let start = first.saturating_sub(base);
+                if self.free_indirect(block, level, start, &mut freed)? {
+                    self.free_block(block)?;
+                    inode.set_block(slot, 0);
+                    freed += 1;
+                }
+            }
+            base += span;
+            span *= ptrs;
+        }
+        let sectors = freed * self.block_sectors();
+        inode.set_sectors(inode.sectors().saturating_sub(sectors));
+        Ok(())
+    }
+
+    /// Frees the blocks from the index `first` in the indirect tree at
+    /// `block`, returns whether the tree becomes empty.
+    fn free_indirect(
+        &mut self,
+        block: u64,
+        level: u32,
+        first: u64,
+        freed: &mut u64,
+    ) -> VfsResult<bool> {
+        let mut table = self.read_block(block)?;
+        let span = self.ptrs_per_block().pow(level - 1);
+        let mut modified = false;
+        for index in 0..self.ptrs_per_block() as usize {
+            let child = read_u32(&table, index) as u64;
+            let start = index as u64 * span;
+            if child == 0 || start + span <= first {
+                continue;
+            }
+            let empty = level == 1
+                || self.free_indirect(child, level - 1, first.saturating_sub(start), freed)?;
+            if empty {
+                self.free_block(child)?;
+                write_u32(&mut table, index, 0);
+                *freed += 1;
+                modified = true;
+            }
+        }
+        let empty = table.iter().all(|&b| b == 0);
+        if modified && !empty {
+            self.write_block(block, &table)?;
+        }
+        Ok(empty)
+    }
+
+    /// Reads the data of the inode at `offset`, returns the number of bytes
+    /// read.
+    fn read_data(&mut self, inode: &Inode, offset: u64, buf: &mut [u8]) -> VfsResult<usize> {
+        let size = inode.size();
+        if offset >= size {
+            return Ok(0);
+        }
+        let len = buf
+            .len()
+            .min((size - offset).try_into().unwrap_or(usize::MAX));
+        let block_size = self.block_size as u64;
+        let mut done = 0;
+        while done < len {
+            let pos = offset + done as u64;
+            let start = (pos % block_size) as usize;
+            let count = (self.block_size - start).min(len - done);
+            let block = self.block_map(inode, pos / block_size)?;
+            if block == 0 {
+                buf[done..done + count].fill(0);
+            } else {
+                let buf = &mut buf[done..done + count];
+                disk_read(&mut self.disk, block * block_size + start as u64, buf)?;
+            }
+            done += count;
+        }
+        Ok(len)
+    }
+
+    /// Writes the data of the inode `ino` at `offset`, returns the number of
+    /// bytes written. The size of `inode` is extended if needed.
+    fn write_data(
+        &mut self,
+        ino: u32,
+        inode: &mut Inode,
+        offset: u64,
+        buf: &[u8],
+    ) -> VfsResult<usize> {
+        let goal = self.group_of_ino(ino);
+        let block_size = self.block_size as u64;
+        let mut done = 0;
+        while done < buf.len() {
+            let pos = offset + done as u64;
+            let start = (pos % block_size) as usize;
+            let count = (self.block_size - start).min(buf.len() - done);
+            let block = match self.block_map_alloc(inode, goal, pos / block_size) {
+                Ok(block) => block,
+                Err(_) if done > 0 => break, // return the bytes written
+                Err(e) => return Err(e),
+            };
+            let data = &buf[done..done + count];
+            disk_write(&mut self.disk, block * block_size + start as u64, data)?;
+            done += count;
+        }
+        let end = offset + done as u64;
+        if end > inode.size() {
+            inode.set_size(end);
+        }
+        Ok(done)
+    }
+
+    /// Calls `f` on each entry of the directory until it returns `Some`.
+    fn scan_dir<T>(
+        &mut self,
+        dir: &Inode,
+        mut f: impl FnMut(&DirEntry, EntryPos) -> Option<T>,
+    ) -> VfsResult<Option<T>> {
+        let blocks = dir.size() / self.block_size as u64;
+        for index in 0..blocks {
+            let block = self.block_map(dir, index)?;
+            if block == 0 {
+                continue;
+            }
+            let data = self.read_block(block)?;
+            let (mut offset, mut prev) = (0, None);
+            while offset < data.len() {
+                let entry = DirEntry::parse(&data, offset, self.has_file_type)
+                    .ok_or(VfsError::InvalidData)?;
+                let pos = EntryPos {
+                    ino: entry.ino,
+                    file_type: entry.file_type,
+                    block,
+                    offset,
+                    prev,
+                };
+                if let Some(res) = f(&entry, pos) {
+                    return Ok(Some(res));
+                }
+                prev = Some(offset);
+                offset += entry.rec_len;
+            }
+        }
+        Ok(None)
+    }
+
+    fn find_entry(&mut self, dir: &Inode, name: &str) -> VfsResult<Option<EntryPos>> {
+        if !dir.is_dir() {
+            return Err(VfsError::NotADirectory);
+        }
+        self.scan_dir(dir, |entry, pos| {
+            (entry.ino != 0 && entry.name == name.as_bytes()).then_some(pos)
+        })
+    }
+
+    /// Returns the node type of the entry, reads the inode if the directory
+    /// entry does not record it.
+    fn entry_type(&mut self, pos: &EntryPos) -> VfsResult<VfsNodeType> {
+        match node_type_of(pos.file_type) {
+            Some(ty) if self.has_file_type => Ok(ty),
+            _ => Ok(self.read_inode(pos.ino)?.node_type()),
+        }
+    }
+
+    fn is_dir_empty(&mut self, dir: &Inode) -> VfsResult<bool> {
+        let found = self.scan_dir(dir, |entry, _| {
+            (entry.ino != 0 && entry.name != b"." && entry.name != b"..").then_some(())
+        })?;
+        Ok(found.is_none())
+    }
+
+    /// Adds an entry to the directory `dir_ino`, extends the directory by one
+    /// block if there is no space.
+    fn add_entry(
+        &mut self,
+        dir_ino: u32,
+        dir: &mut Inode,
+        name: &str,
+        ino: u32,
+        ty: VfsNodeType,
+    ) -> VfsResult {
+        let file_type = if self.has_file_type {
+            file_type_of(ty)
+        } else {
+            0
+        };
+        let need = DirEntry::min_rec_len(name.len());
+        let found = self.scan_dir(dir, |entry, pos| {
+            let used = if entry.ino == 0 {
+                0
+            } else {
+                DirEntry::min_rec_len(entry.name.len())
+            };
+            (entry.rec_len - used >= need).then_some((pos, entry.rec_len, used))
+        })?;
+        // the directory is no longer indexed
+        dir.set_flags(dir.flags() & !INODE_FLAG_INDEX);
+
+        if let Some((pos, rec_len, used)) = found {
+            let mut data = self.read_block(pos.block)?;
+            if used > 0 {
+                DirEntry::set_rec_len(&mut data, pos.offset, used);
+            }
+            let offset = pos.offset + used;
+            DirEntry::write(
+                &mut data,
+                offset,
+                ino,
+                rec_len - used,
+                name.as_bytes(),
+                file_type,
+            );
+            return self.write_block(pos.block, &data);
+        }
+
+        let index = dir.size() / self.block_size as u64;
+        let block = self.block_map_alloc(dir, self.group_of_ino(dir_ino), index)?;
+        let mut data = vec![0; self.block_size];
+        DirEntry::write(
+            &mut data,
+            0,
+            ino,
+            self.block_size,
+            name.as_bytes(),
+            file_type,
+        );
+        self.write_block(block, &data)?;
+        dir.set_size(dir.size() + self.block_size as u64);
+        Ok(())
+    }
+
+    /// Removes the entry `name` from the directory, returns its inode number.
+    fn remove_entry(&mut self, dir: &mut Inode, name: &str) -> VfsResult<u32> {
+        let pos = self.find_entry(dir, name)?.ok_or(VfsError::NotFound)?;
+        let mut data = self.read_block(pos.block)?;
+        match pos.prev {
+            Some(prev) => {
+                // merge into the previous entry
+                let prev_entry = DirEntry::parse(&data, prev, self.has_file_type);
+                let rec_len = DirEntry::parse(&data, pos.offset, self.has_file_type);
+                let len = prev_entry.unwrap().rec_len + rec_len.unwrap().rec_len;
+                DirEntry::set_rec_len(&mut data, prev, len);
+            }
+            None => DirEntry::set_ino(&mut data, pos.offset, 0),
+        }
+        dir.set_flags(dir.flags() & !INODE_FLAG_INDEX);
+        self.write_block(pos.block, &data)?;
+        Ok(pos.ino)
+    }
+
+    /// Points the entry `name` of the directory to another inode.
+    fn replace_entry(&mut self, dir: &Inode, name: &str, ino: u32, ty: VfsNodeType) -> VfsResult {
+        let pos = self.find_entry(dir, name)?.ok_or(VfsError::NotFound)?;
+        let mut data = self.read_block(pos.block)?;
+        DirEntry::set_ino(&mut data, pos.offset, ino);
+        if self.has_file_type {
+            DirEntry::set_file_type(&mut data, pos.offset, file_type_of(ty));
+        }
+        self.write_block(pos.block, &data)
+    }
+
+    /// Initializes the content of a new directory or symbolic link.
+    fn init_node(
+        &mut self,
+        ino: u32,
+        inode: &mut Inode,
+        dir_ino: u32,
+        target: Option<&str>,
+    ) -> VfsResult {
+        match inode.node_type() {
+            VfsNodeType::Dir => {
+                let block = self.block_map_alloc(inode, self.group_of_ino(ino), 0)?;
+                let dir_type = if self.has_file_type {
+                    file_type_of(VfsNodeType::Dir)
+                } else {
+                    0
+                };
+                let dot_len = DirEntry::min_rec_len(1);
+                let mut data = vec![0; self.block_size];
+                DirEntry::write(&mut data, 0, ino, dot_len, b".", dir_type);
+                let rest = self.block_size - dot_len;
+                DirEntry::write(&mut data, dot_len, dir_ino, rest, b"..", dir_type);
+                self.write_block(block, &data)?;
+                inode.set_size(self.block_size as u64);
+                inode.set_links_count(2);
+            }
+            VfsNodeType::SymLink => {
+                let target = target.unwrap_or_default().as_bytes();
+                if target.len() < INODE_BLOCK_AREA {
+                    // fast symbolic link, stored in the inode
+                    inode.block_area_mut()[..target.len()].copy_from_slice(target);
+                    inode.set_size(target.len() as u64);
+                } else if self.write_data(ino, inode, 0, target)? < target.len() {
+                    return Err(VfsError::StorageFull);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Finds the parent directory of the last component of `path`, which is
+    /// relative to the directory `dir_ino`. Symbolic links are not followed.
+    fn resolve_parent<'a>(&mut self, dir_ino: u32, path: &'a str) -> VfsResult<(u32, &'a str)> {
+        let path = path.trim_end_matches('/');
+        let (parent, name) = match path.rfind('/') {
+            Some(n) => (&path[..n], &path[n + 1..]),
+            None => ("", path),
+        };
+        let mut ino = dir_ino;
+        for name in parent
+            .split('/')
+            .filter(|&name| !name.is_empty() && name != ".")
+        {
+            let dir = self.read_inode(ino)?;
+            let pos = self.find_entry(&dir, name)?.ok_or(VfsError::NotFound)?;
+            if self.entry_type(&pos)? != VfsNodeType::Dir {
+                return Err(VfsError::NotADirectory);
+            }
+            ino = pos.ino;
+        }
+        Ok((ino, name))
+    }
+
+    /// Whether the directory `ancestor` is `dir_ino` or one of its ancestors.
+    fn is_ancestor(&mut self, ancestor: u32, dir_ino: u32) -> VfsResult<bool> {
+        let mut ino = dir_ino;
+        // limit the depth in case of a corrupted loop
+        for _ in 0..self.sb.inodes_count() {
+            if ino == ancestor {
+                return Ok(true);
+            }
+            if ino == ROOT_INO {
+                return Ok(false);
+            }
+            let dir = self.read_inode(ino)?;
+            ino = self
+                .find_entry(&dir, "..")?
+                .ok_or(VfsError::InvalidData)?
+                .ino;
+        }
+        Err(VfsError::InvalidData)
+    }
+
+    /// Drops one link of the inode `ino`, or all links if it is a directory.
+    /// The inode is freed if it has no links and is not open.
+    fn drop_link(&mut self, ino: u32, inode: &mut Inode) -> VfsResult {
+        if inode.is_dir() {
+            inode.set_links_count(0);
+        } else {
+            inode.set_links_count(inode.links_count().saturating_sub(1));
+        }
+        inode.set_times(None, None, now());
+        self.write_inode(ino, inode)?;
+        if inode.links_count() == 0 {
+            if self.open_count.contains_key(&ino) {
+                self.orphans.insert(ino);
+            } else {
+                self.free_inode_data(ino)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Finds the first zero bit from `start` in the first `count` bits of the
+/// bitmap.
+fn find_zero_bit(bitmap: &[u8], start: usize, count: usize) -> Option<usize> {
+    let count = count.min(bitmap.len() * 8);
+    (start..count).find(|&bit| bitmap[bit / 8] & (1 << (bit % 8)) == 0)
+}
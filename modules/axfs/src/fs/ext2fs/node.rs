@@ -0,0 +1,192 @@
+use alloc::sync::Arc;
+
+use axfs_vfs::{VfsDirEntry, VfsError, VfsNodeAttr, VfsNodeOps, VfsNodeRef, VfsNodeType};
+use axfs_vfs::{VfsResult, VfsSetAttr};
+
+use super::{Ext2Fs, ROOT_INO};
+
+/// A regular file or a symbolic link in the ext2 filesystem.
+pub struct Ext2File {
+    fs: Arc<Ext2Fs>,
+    ino: u32,
+}
+
+/// A directory in the ext2 filesystem.
+pub struct Ext2Dir {
+    fs: Arc<Ext2Fs>,
+    ino: u32,
+}
+
+impl Ext2File {
+    pub(super) fn new(fs: Arc<Ext2Fs>, ino: u32) -> Self {
+        Self { fs, ino }
+    }
+}
+
+impl Ext2Dir {
+    pub(super) fn new(fs: Arc<Ext2Fs>, ino: u32) -> Self {
+        Self { fs, ino }
+    }
+
+    /// Returns the node of the entry `name` in this directory.
+    fn child(&self, name: &str) -> VfsResult<VfsNodeRef> {
+        match name {
+            "" | "." => Ok(self.fs.new_node(self.ino, VfsNodeType::Dir)),
+            ".." => self.parent().ok_or(VfsError::NotFound),
+            _ => {
+                let (ino, ty) = self.fs.lookup(self.ino, name)?;
+                Ok(self.fs.new_node(ino, ty))
+            }
+        }
+    }
+}
+
+impl Drop for Ext2File {
+    fn drop(&mut self) {
+        self.fs.release_node(self.ino);
+    }
+}
+
+impl Drop for Ext2Dir {
+    fn drop(&mut self) {
+        self.fs.release_node(self.ino);
+    }
+}
+
+impl VfsNodeOps for Ext2File {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        self.fs.get_attr(self.ino)
+    }
+
+    fn set_attr(&self, attr: &VfsSetAttr) -> VfsResult {
+        self.fs.set_attr(self.ino, attr)
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> VfsResult<usize> {
+        self.fs.read_at(self.ino, offset, buf)
+    }
+
+    fn write_at(&self, offset: u64, buf: &[u8]) -> VfsResult<usize> {
+        self.fs.write_at(self.ino, offset, buf)
+    }
+
+    fn fsync(&self) -> VfsResult {
+        self.fs.sync()
+    }
+
+    fn truncate(&self, size: u64) -> VfsResult {
+        self.fs.truncate(self.ino, size)
+    }
+
+    fn readlink(&self, buf: &mut [u8]) -> VfsResult<usize> {
+        self.fs.readlink(self.ino, buf)
+    }
+
+    axfs_vfs::impl_vfs_non_dir_default! {}
+}
+
+impl VfsNodeOps for Ext2Dir {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        self.fs.get_attr(self.ino)
+    }
+
+    fn set_attr(&self, attr: &VfsSetAttr) -> VfsResult {
+        self.fs.set_attr(self.ino, attr)
+    }
+
+    fn parent(&self) -> Option<VfsNodeRef> {
+        if self.ino == ROOT_INO {
+            return self.fs.mount_parent.lock().clone();
+        }
+        let (ino, _) = self.fs.lookup(self.ino, "..").ok()?;
+        Some(self.fs.new_node(ino, VfsNodeType::Dir))
+    }
+
+    fn lookup(self: Arc<Self>, path: &str) -> VfsResult<VfsNodeRef> {
+        let (name, rest) = split_path(path);
+        let node = match name {
+            "" | "." => Ok(self.clone() as VfsNodeRef),
+            _ => self.child(name),
+        }?;
+
+        if let Some(rest) = rest {
+            node.lookup(rest)
+        } else {
+            Ok(node)
+        }
+    }
+
+    fn read_dir(&self, start_idx: usize, dirents: &mut [VfsDirEntry]) -> VfsResult<usize> {
+        self.fs.read_dir(self.ino, start_idx, dirents)
+    }
+
+    fn create(&self, path: &str, ty: VfsNodeType) -> VfsResult {
+        debug!("create {:?} at ext2fs: {}", ty, path);
+        let (name, rest) = split_path(path);
+        if let Some(rest) = rest {
+            self.child(name)?.create(rest, ty)
+        } else if name.is_empty() || name == "." || name == ".." {
+            Ok(()) // already exists
+        } else {
+            self.fs.create(self.ino, name, ty, None)
+        }
+    }
+
+    fn remove(&self, path: &str) -> VfsResult {
+        debug!("remove at ext2fs: {}", path);
+        let (name, rest) = split_path(path);
+        if let Some(rest) = rest {
+            self.child(name)?.remove(rest)
+        } else if name.is_empty() || name == "." || name == ".." {
+            Err(VfsError::InvalidInput) // remove '.' or '..
+        } else {
+            self.fs.unlink(self.ino, name)
+        }
+    }
+
+    fn rename(&self, src_path: &str, dst_path: &str) -> VfsResult {
+        debug!("rename at ext2fs: {} -> {}", src_path, dst_path);
+        self.fs.rename(self.ino, src_path, dst_path)
+    }
+
+    fn symlink(&self, path: &str, target: &str) -> VfsResult {
+        debug!("symlink at ext2fs: {} -> {}", path, target);
+        let (name, rest) = split_path(path);
+        if let Some(rest) = rest {
+            self.child(name)?.symlink(rest, target)
+        } else if name.is_empty() || name == "." || name == ".." {
+            Err(VfsError::AlreadyExists)
+        } else {
+            self.fs
+                .create(self.ino, name, VfsNodeType::SymLink, Some(target))
+        }
+    }
+
+    fn link(&self, path: &str, node: VfsNodeRef) -> VfsResult {
+        debug!("link at ext2fs: {}", path);
+        let (name, rest) = split_path(path);
+        if let Some(rest) = rest {
+            self.child(name)?.link(rest, node)
+        } else if name.is_empty() || name == "." || name == ".." {
+            Err(VfsError::AlreadyExists)
+        } else if node.as_any().is::<Ext2Dir>() {
+            Err(VfsError::PermissionDenied) // hard links to directories are not allowed
+        } else {
+            match node.as_any().downcast_ref::<Ext2File>() {
+                Some(file) if Arc::ptr_eq(&file.fs, &self.fs) => {
+                    self.fs.link(self.ino, name, file.ino)
+                }
+                _ => Err(VfsError::CrossesDevices),
+            }
+        }
+    }
+
+    axfs_vfs::impl_vfs_dir_default! {}
+}
+
+fn split_path(path: &str) -> (&str, Option<&str>) {
+    let trimmed_path = path.trim_start_matches('/');
+    trimmed_path.find('/').map_or((trimmed_path, None), |n| {
+        (&trimmed_path[..n], Some(&trimmed_path[n + 1..]))
+    })
+}
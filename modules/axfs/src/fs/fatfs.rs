@@ -1,22 +1,94 @@
-use alloc::sync::Arc;
-use core::cell::UnsafeCell;
+use alloc::{
+    collections::BTreeMap,
+    sync::{Arc, Weak},
+};
+use core::time::Duration;
 
-use axfs_vfs::{VfsDirEntry, VfsError, VfsNodePerm, VfsResult};
+use axfs_vfs::{FileSystemInfo, VfsDirEntry, VfsError, VfsNodePerm, VfsResult, VfsSetAttr};
 use axfs_vfs::{VfsNodeAttr, VfsNodeOps, VfsNodeRef, VfsNodeType, VfsOps};
 use axsync::Mutex;
-use fatfs::{Dir, File, LossyOemCpConverter, NullTimeProvider, Read, Seek, SeekFrom, Write};
+use fatfs::{Date, DateTime, Time, TimeProvider};
+use fatfs::{Dir, DirEntry, File, LossyOemCpConverter, Read, Seek, SeekFrom, Write};
 
 use crate::dev::Disk;
 
-const BLOCK_SIZE: usize = 512;
+/// Magic number of FAT filesystems, the same as `MSDOS_SUPER_MAGIC` in Linux.
+pub const MSDOS_MAGIC: u64 = 0x4d44;
+/// Maximum length of long file names.
+const MAX_NAME_LEN: u64 = 255;
+/// Inode number of the root directory, which has no directory entry.
+const ROOT_INO: u64 = 1;
+
+type FatDir = Dir<'static, Disk, AxTimeProvider, LossyOemCpConverter>;
+type FatDirEntry = DirEntry<'static, Disk, AxTimeProvider, LossyOemCpConverter>;
 
 pub struct FatFileSystem {
-    inner: fatfs::FileSystem<Disk, NullTimeProvider, LossyOemCpConverter>,
-    root_dir: UnsafeCell<Option<VfsNodeRef>>,
+    inner: fatfs::FileSystem<Disk, AxTimeProvider, LossyOemCpConverter>,
+    /// Shares the cache with the disk in `inner`, used to flush it.
+    disk: Disk,
+    /// Another cursor on the disk, used to read the directory entries.
+    cursor: Mutex<Disk>,
+    layout: FatLayout,
+    /// The open files, indexed by the byte offsets of their directory
+    /// entries, so that all handles of a file share its state.
+    files: Mutex<BTreeMap<u64, Weak<FileWrapper<'static>>>>,
+    /// A weak reference to itself, which the nodes upgrade to keep `inner`
+    /// alive while they borrow it.
+    this: Weak<FatFileSystem>,
+}
+
+pub struct FileWrapper<'a> {
+    file: Mutex<File<'a, Disk, AxTimeProvider, LossyOemCpConverter>>,
+    ino: u64,
+    /// Byte offset of the short directory entry of the file on the disk.
+    entry_pos: u64,
+    times: Mutex<FatTimes>,
+    /// The filesystem that `file` borrows. It is declared last so that it is
+    /// dropped after `file`.
+    fs: Arc<FatFileSystem>,
 }
 
-pub struct FileWrapper<'a>(Mutex<File<'a, Disk, NullTimeProvider, LossyOemCpConverter>>);
-pub struct DirWrapper<'a>(Dir<'a, Disk, NullTimeProvider, LossyOemCpConverter>);
+pub struct DirWrapper<'a> {
+    dir: Dir<'a, Disk, AxTimeProvider, LossyOemCpConverter>,
+    /// The first cluster of the directory, or 0 for the root directory as in
+    /// the `..` entries.
+    cluster: u32,
+    times: FatTimes,
+    /// The filesystem that `dir` borrows. It is declared last so that it is
+    /// dropped after `dir`.
+    fs: Arc<FatFileSystem>,
+}
+
+/// Timestamps of a FAT directory entry, since the Unix epoch.
+///
+/// FAT does not record the status change time, the modification time is
+/// used instead as Linux does.
+#[derive(Clone, Copy, Default)]
+struct FatTimes {
+    atime: Duration,
+    mtime: Duration,
+}
+
+/// The layout of a FAT volume read from its boot sector, used to locate the
+/// directory entries on the disk, which [`fatfs`] does not expose.
+struct FatLayout {
+    /// Number of bits of each FAT entry: 12, 16 or 32.
+    fat_bits: u8,
+    /// Byte offset of the first FAT.
+    fat_pos: u64,
+    /// Byte offset of the fixed root directory of FAT12 and FAT16.
+    root_dir_pos: u64,
+    root_dir_size: u64,
+    /// First cluster of the root directory of FAT32, 0 for FAT12 and FAT16.
+    root_cluster: u32,
+    /// Byte offset of the data region, where cluster 2 starts.
+    data_pos: u64,
+    cluster_size: u64,
+}
+
+/// Provides the current time to [`fatfs`] from [`axhal::time`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AxTimeProvider;
 
 unsafe impl Sync for FatFileSystem {}
 unsafe impl Send for FatFileSystem {}
@@ -27,38 +99,168 @@ unsafe impl<'a> Sync for DirWrapper<'a> {}
 
 impl FatFileSystem {
     #[cfg(feature = "use-ramdisk")]
-    pub fn new(mut disk: Disk) -> Self {
-        let opts = fatfs::FormatVolumeOptions::new();
-        fatfs::format_volume(&mut disk, opts).expect("failed to format volume");
-        let inner = fatfs::FileSystem::new(disk, fatfs::FsOptions::new())
-            .expect("failed to initialize FAT filesystem");
-        Self {
-            inner,
-            root_dir: UnsafeCell::new(None),
-        }
+    pub fn new(mut disk: Disk) -> VfsResult<Arc<Self>> {
+        let opts = fatfs::FormatVolumeOptions::new().bytes_per_sector(disk.block_size() as u16);
+        fatfs::format_volume(&mut disk, opts).map_err(as_vfs_err)?;
+        Self::open(disk)
     }
 
     #[cfg(not(feature = "use-ramdisk"))]
-    pub fn new(disk: Disk) -> Self {
-        let inner = fatfs::FileSystem::new(disk, fatfs::FsOptions::new())
-            .expect("failed to initialize FAT filesystem");
-        Self {
+    pub fn new(disk: Disk) -> VfsResult<Arc<Self>> {
+        Self::open(disk)
+    }
+
+    fn open(disk: Disk) -> VfsResult<Arc<Self>> {
+        let opts = fatfs::FsOptions::new().time_provider(AxTimeProvider);
+        let shared = disk.share();
+        let mut cursor = disk.share();
+        let inner = fatfs::FileSystem::new(disk, opts).map_err(as_vfs_err)?;
+        let layout = FatLayout::read(&mut cursor)?;
+        Ok(Arc::new_cyclic(|this| Self {
             inner,
-            root_dir: UnsafeCell::new(None),
+            disk: shared,
+            cursor: Mutex::new(cursor),
+            layout,
+            files: Mutex::new(BTreeMap::new()),
+            this: this.clone(),
+        }))
+    }
+
+    /// Returns the number of 512-byte blocks allocated to a node of `size`
+    /// bytes, which occupies whole sectors of the disk.
+    fn blocks_of(&self, size: u64) -> u64 {
+        let sector_size = self.disk.block_size() as u64;
+        size.div_ceil(sector_size) * sector_size / 512
+    }
+
+    /// Returns the open file of `entry`, whose short entry is at `pos`, or
+    /// opens it if no handle of it is alive.
+    fn open_file(
+        fs: Arc<Self>,
+        entry: &FatDirEntry,
+        pos: u64,
+        times: FatTimes,
+    ) -> VfsResult<Arc<FileWrapper<'static>>> {
+        let mut files = fs.files.lock();
+        if let Some(file) = files.get(&pos).and_then(Weak::upgrade) {
+            return Ok(file);
         }
+        let ino = match fs.entry_cluster(pos)? {
+            0 => pos, // empty file
+            cluster => fs.layout.cluster_pos(cluster),
+        };
+        let file = Arc::new(FileWrapper {
+            file: Mutex::new(entry.to_file()),
+            ino,
+            entry_pos: pos,
+            times: Mutex::new(times),
+            fs: fs.clone(),
+        });
+        files.insert(pos, Arc::downgrade(&file));
+        Ok(file)
     }
 
-    pub fn init(&'static self) {
-        // must be called before later operations
-        unsafe { *self.root_dir.get() = Some(Self::new_dir(self.inner.root_dir())) }
+    /// Forgets the open file whose directory entry at `pos` has been removed
+    /// or moved, so that it is not shared with the file reusing the entry.
+    fn forget_file(&self, pos: u64) {
+        self.files.lock().remove(&pos);
     }
 
-    fn new_file(file: File<'_, Disk, NullTimeProvider, LossyOemCpConverter>) -> Arc<FileWrapper> {
-        Arc::new(FileWrapper(Mutex::new(file)))
+    fn new_dir(
+        fs: Arc<Self>,
+        dir: FatDir,
+        cluster: u32,
+        times: FatTimes,
+    ) -> Arc<DirWrapper<'static>> {
+        Arc::new(DirWrapper {
+            dir,
+            cluster,
+            times,
+            fs,
+        })
     }
 
-    fn new_dir(dir: Dir<'_, Disk, NullTimeProvider, LossyOemCpConverter>) -> Arc<DirWrapper> {
-        Arc::new(DirWrapper(dir))
+    /// Generates the inode number of the directory starting at `cluster`.
+    ///
+    /// FAT has no inodes, so the inode numbers are the byte offsets of the
+    /// first clusters of directories and files on the disk, or of the
+    /// directory entries of empty files, which have no clusters.
+    fn dir_ino(&self, cluster: u32) -> u64 {
+        if cluster == 0 || cluster == self.layout.root_cluster {
+            ROOT_INO
+        } else {
+            self.layout.cluster_pos(cluster)
+        }
+    }
+
+    /// Finds the entry `name` in `dir` starting at `cluster`, returns it with
+    /// the byte offset of its short entry on the disk.
+    fn find_entry(&self, dir: &FatDir, cluster: u32, name: &str) -> VfsResult<(FatDirEntry, u64)> {
+        for (idx, entry) in dir.iter().enumerate() {
+            let entry = entry.map_err(as_vfs_err)?;
+            if entry.file_name().eq_ignore_ascii_case(name) {
+                let pos = self.entry_pos(cluster, idx)?;
+                return Ok((entry, pos));
+            }
+        }
+        Err(VfsError::NotFound)
+    }
+
+    /// Returns the byte offset of the `idx`-th entry listed by [`Dir::iter`]
+    /// in the directory starting at `cluster`, which skips the deleted,
+    /// long name and volume label entries.
+    fn entry_pos(&self, cluster: u32, mut idx: usize) -> VfsResult<u64> {
+        let layout = &self.layout;
+        let mut cursor = self.cursor.lock();
+        let mut cluster = if cluster == 0 {
+            layout.root_cluster
+        } else {
+            cluster
+        };
+        let (mut pos, mut end) = if cluster == 0 {
+            (
+                layout.root_dir_pos,
+                layout.root_dir_pos + layout.root_dir_size,
+            )
+        } else {
+            let pos = layout.cluster_pos(cluster);
+            (pos, pos + layout.cluster_size)
+        };
+        loop {
+            while pos < end {
+                let mut entry = [0; 32];
+                read_exact_at(&mut cursor, pos, &mut entry)?;
+                match entry[0] {
+                    0 => return Err(VfsError::NotFound), // end of directory
+                    0xe5 => {}                           // deleted
+                    _ if entry[11] & 0x08 != 0 => {}     // long name or volume label
+                    _ if idx == 0 => return Ok(pos),
+                    _ => idx -= 1,
+                }
+                pos += 32;
+            }
+            if cluster == 0 {
+                return Err(VfsError::NotFound);
+            }
+            cluster = layout
+                .next_cluster(&mut cursor, cluster)?
+                .ok_or(VfsError::NotFound)?;
+            pos = layout.cluster_pos(cluster);
+            end = pos + layout.cluster_size;
+        }
+    }
+
+    /// Reads the first cluster in the short directory entry at `pos`.
+    fn entry_cluster(&self, pos: u64) -> VfsResult<u32> {
+        let mut entry = [0; 32];
+        read_exact_at(&mut self.cursor.lock(), pos, &mut entry)?;
+        let low = u16::from_le_bytes([entry[26], entry[27]]) as u32;
+        let high = u16::from_le_bytes([entry[20], entry[21]]) as u32;
+        if self.layout.fat_bits == 32 {
+            Ok(high << 16 | low)
+        } else {
+            Ok(low)
+        }
     }
 }
 
@@ -66,29 +268,99 @@ impl VfsNodeOps for FileWrapper<'static> {
     axfs_vfs::impl_vfs_non_dir_default! {}
 
     fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
-        let size = self.0.lock().seek(SeekFrom::End(0)).map_err(as_vfs_err)?;
-        let blocks = (size + BLOCK_SIZE as u64 - 1) / BLOCK_SIZE as u64;
+        let size = self
+            .file
+            .lock()
+            .seek(SeekFrom::End(0))
+            .map_err(as_vfs_err)?;
+        let blocks = self.fs.blocks_of(size);
         // FAT fs doesn't support permissions, we just set everything to 755
         let perm = VfsNodePerm::from_bits_truncate(0o755);
-        Ok(VfsNodeAttr::new(perm, VfsNodeType::File, size, blocks))
+        let times = *self.times.lock();
+        Ok(VfsNodeAttr::new(perm, VfsNodeType::File, size, blocks)
+            .with_ino(self.ino)
+            .with_times(times.atime, times.mtime, times.mtime))
+    }
+
+    fn set_attr(&self, attr: &VfsSetAttr) -> VfsResult {
+        if attr.mode.is_some() || attr.uid.is_some() || attr.gid.is_some() {
+            return Err(VfsError::Unsupported); // FAT fs doesn't support permissions and owners
+        }
+        let mut file = self.file.lock();
+        let mut times = self.times.lock();
+        if let Some(atime) = attr.atime {
+            file.set_accessed(fat_date_time(atime).date);
+            times.atime = atime;
+        }
+        if let Some(mtime) = attr.mtime {
+            file.set_modified(fat_date_time(mtime));
+            times.mtime = mtime;
+        }
+        Ok(())
     }
 
     fn read_at(&self, offset: u64, buf: &mut [u8]) -> VfsResult<usize> {
-        let mut file = self.0.lock();
+        let mut file = self.file.lock();
         file.seek(SeekFrom::Start(offset)).map_err(as_vfs_err)?; // TODO: more efficient
         file.read(buf).map_err(as_vfs_err)
     }
 
     fn write_at(&self, offset: u64, buf: &[u8]) -> VfsResult<usize> {
-        let mut file = self.0.lock();
+        let mut file = self.file.lock();
         file.seek(SeekFrom::Start(offset)).map_err(as_vfs_err)?; // TODO: more efficient
-        file.write(buf).map_err(as_vfs_err)
+        let n = file.write(buf).map_err(as_vfs_err)?;
+        self.times.lock().mtime = axhal::time::current_time();
+        Ok(n)
     }
 
     fn truncate(&self, size: u64) -> VfsResult {
-        let mut file = self.0.lock();
+        let mut file = self.file.lock();
         file.seek(SeekFrom::Start(size)).map_err(as_vfs_err)?; // TODO: more efficient
-        file.truncate().map_err(as_vfs_err)
+        file.truncate().map_err(as_vfs_err)?;
+        self.times.lock().mtime = axhal::time::current_time();
+        Ok(())
+    }
+
+    fn fsync(&self) -> VfsResult {
+        // also flushes the disk
+        self.file.lock().flush().map_err(as_vfs_err)
+    }
+}
+
+impl<'a> Drop for FileWrapper<'a> {
+    fn drop(&mut self) {
+        let mut files = self.fs.files.lock();
+        // the entry may have been forgotten and reused by another file
+        if files
+            .get(&self.entry_pos)
+            .is_some_and(|file| file.strong_count() == 0)
+        {
+            files.remove(&self.entry_pos);
+        }
+    }
+}
+
+impl DirWrapper<'static> {
+    /// Finds the entry at `path` relative to this directory, returns it with
+    /// the byte offset of its short entry on the disk.
+    fn find_entry(&self, path: &str) -> VfsResult<(FatDirEntry, u64)> {
+        let mut dir = self.dir.clone();
+        let mut cluster = self.cluster;
+        let mut names = path
+            .split('/')
+            .filter(|name| !name.is_empty() && *name != ".")
+            .peekable();
+        while let Some(name) = names.next() {
+            let (entry, pos) = self.fs.find_entry(&dir, cluster, name)?;
+            if names.peek().is_none() {
+                return Ok((entry, pos));
+            } else if !entry.is_dir() {
+                return Err(VfsError::NotADirectory);
+            }
+            cluster = self.fs.entry_cluster(pos)?;
+            dir = entry.to_dir();
+        }
+        Err(VfsError::NotFound)
     }
 }
 
@@ -97,18 +369,26 @@ impl VfsNodeOps for DirWrapper<'static> {
 
     fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
         // FAT fs doesn't support permissions, we just set everything to 755
+        let size = self.fs.disk.block_size() as u64;
         Ok(VfsNodeAttr::new(
             VfsNodePerm::from_bits_truncate(0o755),
             VfsNodeType::Dir,
-            BLOCK_SIZE as u64,
-            1,
-        ))
+            size,
+            self.fs.blocks_of(size),
+        )
+        .with_ino(self.fs.dir_ino(self.cluster))
+        .with_times(self.times.atime, self.times.mtime, self.times.mtime))
     }
 
     fn parent(&self) -> Option<VfsNodeRef> {
-        self.0
-            .open_dir("..")
-            .map_or(None, |dir| Some(FatFileSystem::new_dir(dir)))
+        let (entry, pos) = self.find_entry("..").ok()?;
+        let cluster = self.fs.entry_cluster(pos).ok()?;
+        Some(FatFileSystem::new_dir(
+            self.fs.clone(),
+            entry.to_dir(),
+            cluster,
+            FatTimes::default(),
+        ))
     }
 
     fn lookup(self: Arc<Self>, path: &str) -> VfsResult<VfsNodeRef> {
@@ -121,13 +401,23 @@ impl VfsNodeOps for DirWrapper<'static> {
             return self.lookup(rest);
         }
 
-        // TODO: use `fatfs::Dir::find_entry`, but it's not public.
-        if let Ok(file) = self.0.open_file(path) {
-            Ok(FatFileSystem::new_file(file))
-        } else if let Ok(dir) = self.0.open_dir(path) {
-            Ok(FatFileSystem::new_dir(dir))
+        let (entry, pos) = self.find_entry(path)?;
+        let times = FatTimes::of_entry(&entry);
+        if entry.is_dir() {
+            let cluster = self.fs.entry_cluster(pos)?;
+            Ok(FatFileSystem::new_dir(
+                self.fs.clone(),
+                entry.to_dir(),
+                cluster,
+                times,
+            ))
         } else {
-            Err(VfsError::NotFound)
+            Ok(FatFileSystem::open_file(
+                self.fs.clone(),
+                &entry,
+                pos,
+                times,
+            )?)
         }
     }
 
@@ -143,11 +433,11 @@ impl VfsNodeOps for DirWrapper<'static> {
 
         match ty {
             VfsNodeType::File => {
-                self.0.create_file(path).map_err(as_vfs_err)?;
+                self.dir.create_file(path).map_err(as_vfs_err)?;
                 Ok(())
             }
             VfsNodeType::Dir => {
-                self.0.create_dir(path).map_err(as_vfs_err)?;
+                self.dir.create_dir(path).map_err(as_vfs_err)?;
                 Ok(())
             }
             _ => Err(VfsError::Unsupported),
@@ -161,11 +451,14 @@ impl VfsNodeOps for DirWrapper<'static> {
         if let Some(rest) = path.strip_prefix("./") {
             return self.remove(rest);
         }
-        self.0.remove(path).map_err(as_vfs_err)
+        let (_, pos) = self.find_entry(path)?;
+        self.dir.remove(path).map_err(as_vfs_err)?;
+        self.fs.forget_file(pos);
+        Ok(())
     }
 
     fn read_dir(&self, start_idx: usize, dirents: &mut [VfsDirEntry]) -> VfsResult<usize> {
-        let mut iter = self.0.iter().skip(start_idx);
+        let mut iter = self.dir.iter().skip(start_idx);
         for (i, out_entry) in dirents.iter_mut().enumerate() {
             let x = iter.next();
             match x {
@@ -192,16 +485,131 @@ impl VfsNodeOps for DirWrapper<'static> {
             src_path, dst_path
         );
 
-        self.0
-            .rename(src_path, &self.0, dst_path)
-            .map_err(as_vfs_err)
+        let (_, pos) = self.find_entry(src_path)?;
+        self.dir
+            .rename(src_path, &self.dir, dst_path)
+            .map_err(as_vfs_err)?;
+        self.fs.forget_file(pos);
+        Ok(())
     }
 }
 
 impl VfsOps for FatFileSystem {
+    fn sync(&self) -> VfsResult {
+        self.disk.flush().map_err(|_| VfsError::Io)
+    }
+
+    fn statfs(&self) -> VfsResult<FileSystemInfo> {
+        let stats = self.inner.stats().map_err(as_vfs_err)?;
+        let cluster_size = stats.cluster_size() as u64;
+        let total = stats.total_clusters() as u64;
+        let free = stats.free_clusters() as u64;
+        // FAT has no inode table, the number of files is limited only by space
+        Ok(FileSystemInfo::new(MSDOS_MAGIC, cluster_size, MAX_NAME_LEN)
+            .with_blocks(total, free, free))
+    }
+
     fn root_dir(&self) -> VfsNodeRef {
-        let root_dir = unsafe { (*self.root_dir.get()).as_ref().unwrap() };
-        root_dir.clone()
+        let fs = self.this.upgrade().unwrap();
+        // SAFETY: the node holds `fs`, which keeps `inner` alive as long as
+        // the directory borrows it.
+        let inner = unsafe { &*core::ptr::addr_of!(fs.inner) };
+        Self::new_dir(fs, inner.root_dir(), 0, FatTimes::default())
+    }
+}
+
+impl FatLayout {
+    fn read(disk: &mut Disk) -> VfsResult<Self> {
+        let mut boot = [0; 512];
+        read_exact_at(disk, 0, &mut boot)?;
+        let u16_at = |i: usize| u16::from_le_bytes([boot[i], boot[i + 1]]) as u64;
+        let u32_at = |i: usize| u32::from_le_bytes(boot[i..i + 4].try_into().unwrap()) as u64;
+
+        let sector_size = u16_at(11);
+        let cluster_sectors = boot[13] as u64;
+        let reserved_sectors = u16_at(14);
+        let fats = boot[16] as u64;
+        let root_dir_sectors = (u16_at(17) * 32).div_ceil(sector_size);
+        let total_sectors = match u16_at(19) {
+            0 => u32_at(32),
+            n => n,
+        };
+        let fat_sectors = match u16_at(22) {
+            0 => u32_at(36),
+            n => n,
+        };
+        let meta_sectors = reserved_sectors + fats * fat_sectors + root_dir_sectors;
+        // the FAT type is determined by the number of clusters
+        let clusters = total_sectors.saturating_sub(meta_sectors) / cluster_sectors;
+        let fat_bits = if clusters < 4085 {
+            12
+        } else if clusters < 65525 {
+            16
+        } else {
+            32
+        };
+        let root_dir_pos = (reserved_sectors + fats * fat_sectors) * sector_size;
+        Ok(Self {
+            fat_bits,
+            fat_pos: reserved_sectors * sector_size,
+            root_dir_pos,
+            root_dir_size: root_dir_sectors * sector_size,
+            root_cluster: if fat_bits == 32 { u32_at(44) as u32 } else { 0 },
+            data_pos: meta_sectors * sector_size,
+            cluster_size: cluster_sectors * sector_size,
+        })
+    }
+
+    fn cluster_pos(&self, cluster: u32) -> u64 {
+        self.data_pos + (cluster as u64 - 2) * self.cluster_size
+    }
+
+    /// Reads the cluster after `cluster` in its chain from the first FAT,
+    /// returns `None` at the end of the chain.
+    fn next_cluster(&self, disk: &mut Disk, cluster: u32) -> VfsResult<Option<u32>> {
+        let n = cluster as u64;
+        let next = match self.fat_bits {
+            12 => {
+                let mut buf = [0; 2];
+                read_exact_at(disk, self.fat_pos + n + n / 2, &mut buf)?;
+                let val = u16::from_le_bytes(buf);
+                let val = if n % 2 == 1 { val >> 4 } else { val & 0xfff };
+                (val < 0xff8).then_some(val as u32)
+            }
+            16 => {
+                let mut buf = [0; 2];
+                read_exact_at(disk, self.fat_pos + n * 2, &mut buf)?;
+                let val = u16::from_le_bytes(buf);
+                (val < 0xfff8).then_some(val as u32)
+            }
+            _ => {
+                let mut buf = [0; 4];
+                read_exact_at(disk, self.fat_pos + n * 4, &mut buf)?;
+                let val = u32::from_le_bytes(buf) & 0x0fff_ffff;
+                (val < 0x0fff_fff8).then_some(val)
+            }
+        };
+        Ok(next.filter(|&next| next >= 2))
+    }
+}
+
+impl FatTimes {
+    fn of_entry(entry: &DirEntry<'_, Disk, AxTimeProvider, LossyOemCpConverter>) -> Self {
+        let accessed = DateTime::new(entry.accessed(), Time::new(0, 0, 0, 0));
+        Self {
+            atime: unix_time(&accessed),
+            mtime: unix_time(&entry.modified()),
+        }
+    }
+}
+
+impl TimeProvider for AxTimeProvider {
+    fn get_current_date(&self) -> Date {
+        self.get_current_date_time().date
+    }
+
+    fn get_current_date_time(&self) -> DateTime {
+        fat_date_time(axhal::time::current_time())
     }
 }
 
@@ -243,7 +651,7 @@ impl Write for Disk {
         Ok(write_len)
     }
     fn flush(&mut self) -> Result<(), Self::Error> {
-        Ok(())
+        Disk::flush(self).map_err(|_| ())
     }
 }
 
@@ -281,3 +689,72 @@ const fn as_vfs_err(err: fatfs::Error<()>) -> VfsError {
         _ => VfsError::Io,
     }
 }
+
+/// Reads exactly `buf.len()` bytes at `pos` of the disk.
+fn read_exact_at(disk: &mut Disk, pos: u64, buf: &mut [u8]) -> VfsResult {
+    disk.set_position(pos);
+    match disk.read(buf) {
+        Ok(n) if n == buf.len() => Ok(()),
+        _ => Err(VfsError::Io),
+    }
+}
+
+/// Converts the time since the Unix epoch to a FAT date and time, which is
+/// clamped to the range from 1980 to 2107.
+fn fat_date_time(time: Duration) -> DateTime {
+    let secs = time.as_secs();
+    let (year, month, day) = civil_from_days(secs / 86400);
+    if year < 1980 {
+        return DateTime::new(Date::new(1980, 1, 1), Time::new(0, 0, 0, 0));
+    } else if year > 2107 {
+        return DateTime::new(Date::new(2107, 12, 31), Time::new(23, 59, 59, 999));
+    }
+    let secs_of_day = secs % 86400;
+    DateTime::new(
+        Date::new(year as u16, month as u16, day as u16),
+        Time::new(
+            (secs_of_day / 3600) as u16,
+            (secs_of_day / 60 % 60) as u16,
+            (secs_of_day % 60) as u16,
+            time.subsec_millis() as u16,
+        ),
+    )
+}
+
+/// Converts a FAT date and time to the time since the Unix epoch.
+fn unix_time(date_time: &DateTime) -> Duration {
+    let (date, time) = (&date_time.date, &date_time.time);
+    let days = days_from_civil(date.year as u64, date.month as u64, date.day as u64);
+    let secs = days * 86400 + time.hour as u64 * 3600 + time.min as u64 * 60 + time.sec as u64;
+    Duration::from_secs(secs) + Duration::from_millis(time.millis as u64)
+}
+
+/// Returns the date of the given days since the Unix epoch.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+const fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as u64;
+    (year, month, day)
+}
+
+/// Returns the days since the Unix epoch of the given date, which must not
+/// be earlier than the epoch.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+const fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let year = year - (month <= 2) as u64;
+    let era = year / 400;
+    let yoe = year - era * 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
@@ -1,6 +1,13 @@
+use alloc::sync::Arc;
+use axfs_vfs::{VfsOps, VfsResult};
+
+use crate::dev::Disk;
+
 cfg_if::cfg_if! {
     if #[cfg(feature = "myfs")] {
         pub mod myfs;
+    } else if #[cfg(feature = "ext2fs")] {
+        pub mod ext2fs;
     } else if #[cfg(feature = "fatfs")] {
         pub mod fatfs;
     }
@@ -9,5 +16,21 @@ cfg_if::cfg_if! {
 #[cfg(feature = "devfs")]
 pub use axfs_devfs as devfs;
 
-#[cfg(feature = "ramfs")]
+#[cfg(any(feature = "ramfs", feature = "sysfs"))]
 pub use axfs_ramfs as ramfs;
+
+#[cfg(feature = "procfs")]
+pub mod procfs;
+
+/// Creates the filesystem selected by the cargo features on the disk.
+pub(crate) fn new_filesystem(disk: Disk) -> VfsResult<Arc<dyn VfsOps>> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "myfs")] {
+            Ok(myfs::new_myfs(disk))
+        } else if #[cfg(feature = "ext2fs")] {
+            Ok(Arc::new(ext2fs::Ext2FileSystem::new(disk)?))
+        } else if #[cfg(feature = "fatfs")] {
+            Ok(fatfs::FatFileSystem::new(disk)?)
+        }
+    }
+}
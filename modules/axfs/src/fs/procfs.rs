@@ -0,0 +1,440 @@
+//! A process filesystem whose files are generated from the live kernel state
+//! when they are read.
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use axfs_vfs::{FileSystemInfo, VfsDirEntry, VfsNodeAttr, VfsNodeOps, VfsNodePerm};
+use axfs_vfs::{VfsError, VfsNodeRef, VfsNodeType, VfsOps, VfsResult};
+use axsync::Mutex;
+
+/// Magic number of the process filesystem, the same as `PROC_SUPER_MAGIC` in
+/// Linux.
+pub const PROC_MAGIC: u64 = 0x9fa0;
+/// Block size reported by [`VfsOps::statfs`].
+const BLOCK_SIZE: u64 = 4096;
+/// Maximum length of file names reported by [`VfsOps::statfs`].
+const MAX_NAME_LEN: u64 = 255;
+
+/// Inode numbers of static nodes start from 1, and those of the nodes of
+/// tasks are generated from the task IDs above this base.
+const TASK_INO_BASE: u64 = 1 << 32;
+
+/// The function that generates the contents of a file.
+type Generator = Box<dyn Fn() -> VfsResult<String> + Send + Sync>;
+
+/// A process filesystem like `/proc` in Linux.
+///
+/// Files are added with the functions that generate their contents. If the
+/// `multitask` feature is enabled, the root directory also contains a
+/// directory for each task named by its ID, and `self` links to the one of
+/// the current task.
+pub struct ProcFileSystem {
+    root: Arc<ProcDir>,
+}
+
+/// A directory of fixed entries, and of tasks if it is the root directory.
+struct ProcDir {
+    ino: u64,
+    parent: Mutex<Option<VfsNodeRef>>,
+    children: Mutex<BTreeMap<String, VfsNodeRef>>,
+    is_root: bool,
+}
+
+/// A read-only file whose contents are generated when it is read.
+struct ProcFile {
+    ino: u64,
+    generate: Generator,
+}
+
+fn next_ino() -> u64 {
+    static NEXT_INO: AtomicU64 = AtomicU64::new(1);
+    NEXT_INO.fetch_add(1, Ordering::Relaxed)
+}
+
+impl ProcFileSystem {
+    /// Create a new instance with an empty root directory.
+    pub fn new() -> Self {
+        Self {
+            root: ProcDir::new(None, true),
+        }
+    }
+
+    /// Adds a file at `path` relative to the root directory, whose contents
+    /// are generated by `generate` on each read. Missing parent directories
+    /// are created.
+    pub fn add_file<F>(&self, path: &str, generate: F)
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        let mut dir: VfsNodeRef = self.root.clone();
+        let mut names = path.split('/').filter(|name| !name.is_empty()).peekable();
+        while let Some(name) = names.next() {
+            let proc_dir = dir.as_any().downcast_ref::<ProcDir>().unwrap();
+            if names.peek().is_none() {
+                let file = ProcFile::new(next_ino(), Box::new(move || Ok(generate())));
+                proc_dir.children.lock().insert(name.into(), Arc::new(file));
+                return;
+            }
+            dir = proc_dir.mkdir(&dir, name);
+        }
+    }
+}
+
+impl Default for ProcFileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VfsOps for ProcFileSystem {
+    fn mount(&self, _path: &str, mount_point: VfsNodeRef) -> VfsResult {
+        *self.root.parent.lock() = mount_point.parent();
+        Ok(())
+    }
+
+    fn statfs(&self) -> VfsResult<FileSystemInfo> {
+        Ok(FileSystemInfo::new(PROC_MAGIC, BLOCK_SIZE, MAX_NAME_LEN))
+    }
+
+    fn root_dir(&self) -> VfsNodeRef {
+        self.root.clone()
+    }
+}
+
+impl ProcDir {
+    fn new(parent: Option<VfsNodeRef>, is_root: bool) -> Arc<Self> {
+        Arc::new(Self {
+            ino: next_ino(),
+            parent: Mutex::new(parent),
+            children: Mutex::new(BTreeMap::new()),
+            is_root,
+        })
+    }
+
+    /// Returns the subdirectory `name`, creates it if it does not exist.
+    ///
+    /// `this` is the node of this directory, which becomes the parent of the
+    /// new subdirectory.
+    fn mkdir(&self, this: &VfsNodeRef, name: &str) -> VfsNodeRef {
+        let mut children = self.children.lock();
+        if let Some(node) = children.get(name).filter(|node| node.as_any().is::<Self>()) {
+            return node.clone();
+        }
+        // subdirectories are never removed, so they can hold their parents
+        let dir: VfsNodeRef = Self::new(Some(this.clone()), false);
+        children.insert(name.into(), dir.clone());
+        dir
+    }
+
+    /// Returns the names and types of all entries in this directory, with
+    /// the ones of tasks after the fixed ones.
+    fn entries(&self) -> VfsResult<Vec<(String, VfsNodeType)>> {
+        let mut entries = Vec::new();
+        for (name, node) in self.children.lock().iter() {
+            entries.push((name.clone(), node.get_attr()?.file_type()));
+        }
+        if self.is_root {
+            entries.extend(tasks::entries());
+        }
+        Ok(entries)
+    }
+
+    fn child(self: &Arc<Self>, name: &str) -> VfsResult<VfsNodeRef> {
+        if let Some(node) = self.children.lock().get(name) {
+            return Ok(node.clone());
+        }
+        if self.is_root {
+            if let Some(node) = tasks::lookup(self, name) {
+                return Ok(node);
+            }
+        }
+        Err(VfsError::NotFound)
+    }
+}
+
+impl VfsNodeOps for ProcDir {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        let perm = VfsNodePerm::from_bits_truncate(0o555);
+        Ok(VfsNodeAttr::new(perm, VfsNodeType::Dir, 0, 0).with_ino(self.ino))
+    }
+
+    fn parent(&self) -> Option<VfsNodeRef> {
+        self.parent.lock().clone()
+    }
+
+    fn lookup(self: Arc<Self>, path: &str) -> VfsResult<VfsNodeRef> {
+        let (name, rest) = split_path(path);
+        let node = match name {
+            "" | "." => Ok(self.clone() as VfsNodeRef),
+            ".." => self.parent().ok_or(VfsError::NotFound),
+            _ => self.child(name),
+        }?;
+
+        if let Some(rest) = rest {
+            node.lookup(rest)
+        } else {
+            Ok(node)
+        }
+    }
+
+    fn read_dir(&self, start_idx: usize, dirents: &mut [VfsDirEntry]) -> VfsResult<usize> {
+        let entries = self.entries()?;
+        let mut entries = entries.iter().skip(start_idx.max(2) - 2);
+        for (i, ent) in dirents.iter_mut().enumerate() {
+            match i + start_idx {
+                0 => *ent = VfsDirEntry::new(".", VfsNodeType::Dir),
+                1 => *ent = VfsDirEntry::new("..", VfsNodeType::Dir),
+                _ => {
+                    if let Some((name, ty)) = entries.next() {
+                        *ent = VfsDirEntry::new(name, *ty);
+                    } else {
+                        return Ok(i);
+                    }
+                }
+            }
+        }
+        Ok(dirents.len())
+    }
+
+    fn create(&self, _path: &str, _ty: VfsNodeType) -> VfsResult {
+        Err(VfsError::PermissionDenied)
+    }
+
+    fn remove(&self, _path: &str) -> VfsResult {
+        Err(VfsError::PermissionDenied)
+    }
+
+    axfs_vfs::impl_vfs_dir_default! {}
+}
+
+impl ProcFile {
+    fn new(ino: u64, generate: Generator) -> Self {
+        Self { ino, generate }
+    }
+}
+
+impl VfsNodeOps for ProcFile {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        // the size is unknown until the file is read, like Linux
+        let perm = VfsNodePerm::from_bits_truncate(0o444);
+        Ok(VfsNodeAttr::new(perm, VfsNodeType::File, 0, 0).with_ino(self.ino))
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> VfsResult<usize> {
+        let contents = (self.generate)()?;
+        let contents = contents.as_bytes();
+        let start = (offset as usize).min(contents.len());
+        let len = buf.len().min(contents.len() - start);
+        buf[..len].copy_from_slice(&contents[start..start + len]);
+        Ok(len)
+    }
+
+    fn write_at(&self, _offset: u64, _buf: &[u8]) -> VfsResult<usize> {
+        Err(VfsError::PermissionDenied)
+    }
+
+    fn truncate(&self, _size: u64) -> VfsResult {
+        Err(VfsError::PermissionDenied)
+    }
+
+    axfs_vfs::impl_vfs_non_dir_default! {}
+}
+
+fn split_path(path: &str) -> (&str, Option<&str>) {
+    let trimmed_path = path.trim_start_matches('/');
+    trimmed_path.find('/').map_or((trimmed_path, None), |n| {
+        (&trimmed_path[..n], Some(&trimmed_path[n + 1..]))
+    })
+}
+
+#[cfg(feature = "multitask")]
+mod tasks {
+    use super::*;
+    use alloc::{format, vec};
+    use axtask::{AxTaskRef, TaskState};
+    use core::time::Duration;
+
+    /// A directory of a task, like `/proc/<pid>` in Linux.
+    struct TaskDir {
+        id: u64,
+        parent: Arc<ProcDir>,
+    }
+
+    /// The symbolic link `self` to the directory of the current task.
+    struct SelfLink;
+
+    const FILES: [&str; 2] = ["stat", "status"];
+
+    pub(super) fn entries() -> Vec<(String, VfsNodeType)> {
+        let mut entries = vec![(String::from("self"), VfsNodeType::SymLink)];
+        for task in axtask::tasks() {
+            entries.push((format!("{}", task.id().as_u64()), VfsNodeType::Dir));
+        }
+        entries
+    }
+
+    pub(super) fn lookup(root: &Arc<ProcDir>, name: &str) -> Option<VfsNodeRef> {
+        if name == "self" {
+            return Some(Arc::new(SelfLink));
+        }
+        let id = name.parse().ok()?;
+        axtask::find_task(id)?;
+        Some(Arc::new(TaskDir {
+            id,
+            parent: root.clone(),
+        }))
+    }
+
+    fn find_task(id: u64) -> VfsResult<AxTaskRef> {
+        axtask::find_task(id).ok_or(VfsError::NotFound)
+    }
+
+    /// Unit of the times in `/proc/<pid>/stat`, the same as `USER_HZ` in
+    /// Linux.
+    const USER_HZ: u128 = 100;
+
+    fn state_char(state: TaskState) -> (char, &'static str) {
+        match state {
+            TaskState::Running | TaskState::Ready => ('R', "running"),
+            TaskState::Blocked => ('S', "sleeping"),
+            TaskState::Exited => ('Z', "zombie"),
+        }
+    }
+
+    /// Generates `/proc/<pid>/stat`.
+    ///
+    /// The user time, system time and start time come from the statistics of
+    /// the task. Tasks have no parents, so the parent ID is 0, and the other
+    /// fields that are not tracked are zero.
+    fn stat(id: u64) -> VfsResult<String> {
+        let stats = find_task(id)?.stats();
+        let (state, _) = state_char(stats.state);
+        let ticks = |time: Duration| time.as_nanos() * USER_HZ / 1_000_000_000;
+        let utime = ticks(stats.usage.user_time);
+        let stime = ticks(stats.usage.kernel_time);
+        let start = ticks(stats.created_at);
+        Ok(format!(
+            "{id} ({}) {state} 0 {id} {id} 0 -1 0 0 0 0 0 {utime} {stime} 0 0 20 0 1 0 {start} 0 0\n",
+            stats.name
+        ))
+    }
+
+    /// Generates `/proc/<pid>/status`.
+    fn status(id: u64) -> VfsResult<String> {
+        let task = find_task(id)?;
+        let (state, state_name) = state_char(task.state());
+        Ok(format!(
+            "Name:\t{}\nState:\t{state} ({state_name})\nTgid:\t{id}\nPid:\t{id}\nPPid:\t0\nThreads:\t1\n",
+            task.name()
+        ))
+    }
+
+    impl TaskDir {
+        fn ino(&self, index: usize) -> u64 {
+            TASK_INO_BASE + self.id * (FILES.len() as u64 + 1) + index as u64
+        }
+
+        fn file(&self, name: &str) -> VfsResult<VfsNodeRef> {
+            let index = FILES
+                .iter()
+                .position(|f| *f == name)
+                .ok_or(VfsError::NotFound)?;
+            let id = self.id;
+            let generate: Generator = match index {
+                0 => Box::new(move || stat(id)),
+                _ => Box::new(move || status(id)),
+            };
+            Ok(Arc::new(ProcFile::new(self.ino(index + 1), generate)))
+        }
+    }
+
+    impl VfsNodeOps for TaskDir {
+        fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+            let perm = VfsNodePerm::from_bits_truncate(0o555);
+            Ok(VfsNodeAttr::new(perm, VfsNodeType::Dir, 0, 0).with_ino(self.ino(0)))
+        }
+
+        fn parent(&self) -> Option<VfsNodeRef> {
+            Some(self.parent.clone())
+        }
+
+        fn lookup(self: Arc<Self>, path: &str) -> VfsResult<VfsNodeRef> {
+            let (name, rest) = split_path(path);
+            let node = match name {
+                "" | "." => Ok(self.clone() as VfsNodeRef),
+                ".." => Ok(self.parent.clone() as VfsNodeRef),
+                _ => self.file(name),
+            }?;
+
+            if let Some(rest) = rest {
+                node.lookup(rest)
+            } else {
+                Ok(node)
+            }
+        }
+
+        fn read_dir(&self, start_idx: usize, dirents: &mut [VfsDirEntry]) -> VfsResult<usize> {
+            let mut files = FILES.iter().skip(start_idx.max(2) - 2);
+            for (i, ent) in dirents.iter_mut().enumerate() {
+                match i + start_idx {
+                    0 => *ent = VfsDirEntry::new(".", VfsNodeType::Dir),
+                    1 => *ent = VfsDirEntry::new("..", VfsNodeType::Dir),
+                    _ => match files.next() {
+                        Some(name) => *ent = VfsDirEntry::new(name, VfsNodeType::File),
+                        None => return Ok(i),
+                    },
+                }
+            }
+            Ok(dirents.len())
+        }
+
+        fn create(&self, _path: &str, _ty: VfsNodeType) -> VfsResult {
+            Err(VfsError::PermissionDenied)
+        }
+
+        fn remove(&self, _path: &str) -> VfsResult {
+            Err(VfsError::PermissionDenied)
+        }
+
+        axfs_vfs::impl_vfs_dir_default! {}
+    }
+
+    impl SelfLink {
+        fn target() -> String {
+            format!("{}", axtask::current().id().as_u64())
+        }
+    }
+
+    impl VfsNodeOps for SelfLink {
+        fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+            let perm = VfsNodePerm::from_bits_truncate(0o777);
+            let size = Self::target().len() as u64;
+            Ok(VfsNodeAttr::new(perm, VfsNodeType::SymLink, size, 0).with_ino(TASK_INO_BASE))
+        }
+
+        fn readlink(&self, buf: &mut [u8]) -> VfsResult<usize> {
+            let target = Self::target();
+            let len = buf.len().min(target.len());
+            buf[..len].copy_from_slice(&target.as_bytes()[..len]);
+            Ok(len)
+        }
+
+        axfs_vfs::impl_vfs_non_dir_default! {}
+    }
+}
+
+#[cfg(not(feature = "multitask"))]
+mod tasks {
+    use super::*;
+
+    pub(super) fn entries() -> Vec<(String, VfsNodeType)> {
+        Vec::new()
+    }
+
+    pub(super) fn lookup(_root: &Arc<ProcDir>, _name: &str) -> Option<VfsNodeRef> {
+        None
+    }
+}
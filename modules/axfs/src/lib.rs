@@ -6,17 +6,62 @@
 //!
 //! - `fatfs`: Use [FAT] as the main filesystem and mount it on `/`. This feature
 //!    is **enabled** by default.
-//! - `devfs`: Mount [`axfs_devfs::DeviceFileSystem`] on `/dev`. This feature is
-//!    **enabled** by default.
+//! - `ext2fs`: Use [ext2] as the main filesystem and mount it on `/`. Filesystems
+//!    created as ext3 or ext4 can also be read, but they are mounted as
+//!    read-only if they use features that cannot be written, such as extents.
+//!    This feature is **disabled** by default, but it will override `fatfs` if
+//!    both are enabled.
+//! - `devfs`: Mount [`axfs_devfs::DeviceFileSystem`] on `/dev`, see
+//!    [Device nodes](#device-nodes). This feature is **enabled** by default.
 //! - `ramfs`: Mount [`axfs_ramfs::RamFileSystem`] on `/tmp`. This feature is
 //!    **enabled** by default.
+//! - `procfs`: Mount a process filesystem on `/proc`, whose files
+//!    are generated from the kernel state when they are read, such as
+//!    `meminfo`, `uptime`, `cpuinfo` and `mounts`. This feature is **enabled**
+//!    by default.
+//! - `sysfs`: Mount a [`axfs_ramfs::RamFileSystem`] with a few fixed files on
+//!    `/sys`. This feature is **enabled** by default.
+//! - `multitask`: List tasks in `/proc` as `/proc/<tid>/stat` and
+//!    `/proc/<tid>/status`, and `/proc/self` links to the current task.
 //! - `myfs`: Allow users to define their custom filesystems to override the
 //!    default. In this case, [`MyFileSystemIf`] is required to be implemented
 //!    to create and initialize other filesystems. This feature is **disabled** by
 //!    by default, but it will override other filesystem selection features if
 //!    both are enabled.
 //!
+//! # Block devices and partitions
+//!
+//! Block devices are named `vda`, `vdb` and so on in the order they are
+//! probed, and each of them appears as a block device node in `/dev`. Probing
+//! more than one block device requires the `dyn` feature of [`axdriver`]. The
+//! root filesystem is on the first device, others can be mounted by
+//! [`api::mount_device`].
+//!
+//! If the first device has an MBR or GPT partition table, the partition to
+//! mount on `/` is chosen by the `AX_ROOT_PART` environment variable at build
+//! time, which is an index starting from 0, `PARTUUID=<guid>` or
+//! `PARTLABEL=<name>`. The first partition is used by default. Other
+//! partitions can be mounted by [`api::mount_partition`].
+//!
+//! # Device nodes
+//!
+//! The following nodes are created in `/dev`:
+//!
+//! - `null` and `zero`.
+//! - `random` and `urandom`: Random bytes from a ChaCha20-based CSPRNG, which
+//!    is seeded at boot by the hardware random number generator (`RDRAND` on
+//!    x86_64, `RNDR` on AArch64) and the timer, and both are mixed in again
+//!    before each read. Without the hardware generator, such as on RISC-V,
+//!    only the timer is used, which is predictable. Both nodes never block.
+//! - `tty` and `console`: The console of [`axhal`].
+//! - `vda`, `vdb` ...: The block devices, and `vda1`, `vda2` ... for the
+//!    partitions of `vda`.
+//!
+//! Drivers can add their own nodes at any time by
+//! [`axdriver::devfs::register_node`], such as `fb0` of `axdisplay`.
+//!
 //! [FAT]: https://en.wikipedia.org/wiki/File_Allocation_Table
+//! [ext2]: https://en.wikipedia.org/wiki/Ext2
 //! [`MyFileSystemIf`]: fops::MyFileSystemIf
 
 #![cfg_attr(all(not(test), not(doc)), no_std)]
@@ -26,6 +71,8 @@
 extern crate log;
 extern crate alloc;
 
+#[cfg(feature = "devfs")]
+mod console;
 mod dev;
 mod fs;
 mod mounts;
@@ -36,11 +83,32 @@ pub mod fops;
 
 use axdriver::{prelude::*, AxDeviceContainer};
 
+pub use self::dev::PartitionSelector;
+#[doc(no_inline)]
+pub use driver_block::partition::Guid;
+
+/// The partition mounted on `/`, see [Block devices and partitions](crate#block-devices-and-partitions).
+const ROOT_PART: Option<&str> = option_env!("AX_ROOT_PART");
+
 /// Initializes filesystems by block devices.
-pub fn init_filesystems(mut blk_devs: AxDeviceContainer<AxBlockDevice>) {
+///
+/// All block devices are named `vda`, `vdb` and so on in the order of
+/// probing, and the root filesystem is on the first one.
+pub fn init_filesystems(blk_devs: AxDeviceContainer<AxBlockDevice>) {
     info!("Initialize filesystems...");
 
-    let dev = blk_devs.take_one().expect("No block device found!");
-    info!("  use block device 0: {:?}", dev.device_name());
-    self::root::init_rootfs(self::dev::Disk::new(dev));
+    let devs = self::dev::init_block_devices(blk_devs);
+    let dev = devs.first().expect("No block device found!");
+    let root_part = match ROOT_PART {
+        Some(part) if !part.is_empty() => Some(part.parse().expect("invalid AX_ROOT_PART")),
+        _ if dev.is_partitioned() => Some(PartitionSelector::Index(0)),
+        _ => None,
+    };
+    if let Some(part) = &root_part {
+        info!("  mount partition {:?} of {} on /", part, dev.name());
+    }
+    let main_fs = dev
+        .filesystem(root_part.as_ref())
+        .expect("failed to initialize the main filesystem");
+    self::root::init_rootfs(main_fs);
 }
@@ -7,49 +7,160 @@ use crate::fs;
 pub(crate) fn devfs() -> Arc<fs::devfs::DeviceFileSystem> {
     let null = fs::devfs::NullDev;
     let zero = fs::devfs::ZeroDev;
-    let bar = fs::devfs::ZeroDev;
-    let devfs = fs::devfs::DeviceFileSystem::new();
-    let foo_dir = devfs.mkdir("foo");
+    let random = fs::devfs::RandomDev::new(&random_seed()).with_entropy(random_entropy);
+    let console = Arc::new(crate::console::ConsoleDev);
+    let devfs = Arc::new(fs::devfs::DeviceFileSystem::new());
     devfs.add("null", Arc::new(null));
     devfs.add("zero", Arc::new(zero));
-    foo_dir.add("bar", Arc::new(bar));
-    Arc::new(devfs)
+    devfs.add("random", Arc::new(random.clone()));
+    devfs.add("urandom", Arc::new(random));
+    devfs.add("tty", console.clone());
+    devfs.add("console", console);
+    for dev in crate::dev::block_devices() {
+        devfs.add(dev.name(), dev.node());
+        for (name, node) in dev.partition_nodes() {
+            devfs.add(&name, node);
+        }
+    }
+
+    // nodes registered by drivers
+    let fs = devfs.clone();
+    axdriver::devfs::on_register(alloc::boxed::Box::new(move |path, node| {
+        fs.add_path(path, node)
+    }));
+    devfs
+}
+
+/// Returns the seed of `/dev/random`, which comes from the hardware random
+/// number generator if there is one, and from the timers otherwise.
+///
+/// The timers are predictable, so `/dev/random` is not cryptographically
+/// secure on platforms without a hardware random number generator.
+#[cfg(feature = "devfs")]
+fn random_seed() -> [u8; 32] {
+    let words = [
+        axhal::arch::hardware_random().unwrap_or(0),
+        axhal::arch::hardware_random().unwrap_or(0),
+        axhal::time::current_ticks(),
+        axhal::time::current_time().as_nanos() as u64,
+    ];
+    let mut seed = [0; 32];
+    for (chunk, word) in seed.chunks_mut(8).zip(words) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    seed
+}
+
+/// Returns the entropy mixed into `/dev/random` before each read.
+#[cfg(feature = "devfs")]
+fn random_entropy() -> u64 {
+    axhal::arch::hardware_random().unwrap_or(0) ^ axhal::time::current_ticks()
+}
+
+/// Returns the free memory in bytes, which is reported as the free space of
+/// RAM filesystems.
+#[cfg(any(feature = "ramfs", feature = "procfs", feature = "sysfs"))]
+fn available_memory() -> usize {
+    let allocator = axalloc::global_allocator();
+    allocator.available_bytes() + allocator.available_pages() * axhal::mem::PAGE_SIZE_4K
 }
 
 #[cfg(feature = "ramfs")]
 pub(crate) fn ramfs() -> Arc<fs::ramfs::RamFileSystem> {
-    Arc::new(fs::ramfs::RamFileSystem::new())
+    let ramfs = fs::ramfs::RamFileSystem::with_clock(axhal::time::current_time)
+        .with_available_memory(available_memory);
+    Arc::new(ramfs)
 }
 
 #[cfg(feature = "procfs")]
-pub(crate) fn procfs() -> VfsResult<Arc<fs::ramfs::RamFileSystem>> {
-    let procfs = fs::ramfs::RamFileSystem::new();
-    let proc_root = procfs.root_dir();
-
-    // Create /proc/sys/net/core/somaxconn
-    proc_root.create("sys", VfsNodeType::Dir)?;
-    proc_root.create("sys/net", VfsNodeType::Dir)?;
-    proc_root.create("sys/net/core", VfsNodeType::Dir)?;
-    proc_root.create("sys/net/core/somaxconn", VfsNodeType::File)?;
-    let file_somaxconn = proc_root.clone().lookup("./sys/net/core/somaxconn")?;
-    file_somaxconn.write_at(0, b"4096\n")?;
-
-    // Create /proc/sys/vm/overcommit_memory
-    proc_root.create("sys/vm", VfsNodeType::Dir)?;
-    proc_root.create("sys/vm/overcommit_memory", VfsNodeType::File)?;
-    let file_over = proc_root.clone().lookup("./sys/vm/overcommit_memory")?;
-    file_over.write_at(0, b"0\n")?;
-
-    // Create /proc/self/stat
-    proc_root.create("self", VfsNodeType::Dir)?;
-    proc_root.create("self/stat", VfsNodeType::File)?;
-
-    Ok(Arc::new(procfs))
+pub(crate) fn procfs() -> Arc<fs::procfs::ProcFileSystem> {
+    let procfs = fs::procfs::ProcFileSystem::new();
+    procfs.add_file("meminfo", proc::meminfo);
+    procfs.add_file("uptime", proc::uptime);
+    procfs.add_file("cpuinfo", proc::cpuinfo);
+    procfs.add_file("mounts", proc::mounts);
+    procfs.add_file("sys/net/core/somaxconn", || "4096\n".into());
+    procfs.add_file("sys/vm/overcommit_memory", || "0\n".into());
+    Arc::new(procfs)
+}
+
+/// Generators of the files in `/proc`.
+#[cfg(feature = "procfs")]
+mod proc {
+    use alloc::{format, string::String};
+    use axfs_vfs::VfsOps;
+    use core::fmt::Write;
+
+    use crate::fs;
+
+    pub fn meminfo() -> String {
+        const PAGE_SIZE: usize = axhal::mem::PAGE_SIZE_4K;
+        let allocator = axalloc::global_allocator();
+        let total = (allocator.used_pages() + allocator.available_pages()) * PAGE_SIZE;
+        let free = super::available_memory();
+        format!(
+            "MemTotal: {:>8} kB\nMemFree:  {:>8} kB\nMemAvailable: {:>8} kB\nHeapUsed: {:>8} kB\nHeapFree: {:>8} kB\n",
+            total / 1024,
+            free / 1024,
+            free / 1024,
+            allocator.used_bytes() / 1024,
+            allocator.available_bytes() / 1024,
+        )
+    }
+
+    pub fn uptime() -> String {
+        let now = axhal::time::current_time();
+        // idle time is not tracked
+        format!("{}.{:02} 0.00\n", now.as_secs(), now.subsec_millis() / 10)
+    }
+
+    pub fn cpuinfo() -> String {
+        let mut info = String::new();
+        for cpu in 0..axconfig::SMP {
+            writeln!(info, "processor\t: {}", cpu).unwrap();
+            writeln!(info, "arch\t\t: {}", axconfig::ARCH).unwrap();
+            writeln!(info, "platform\t: {}\n", axconfig::PLATFORM).unwrap();
+        }
+        info
+    }
+
+    /// Lists mounted filesystems in the format of `/proc/mounts` in Linux.
+    pub fn mounts() -> String {
+        let mut mounts = String::new();
+        for (path, fs) in crate::root::mounts() {
+            let fs_type = fs
+                .statfs()
+                .map_or("unknown", |info| fs_type_name(info.fs_type()));
+            let source = match crate::dev::device_of(&fs) {
+                Some(dev) => format!("/dev/{}", dev),
+                None => fs_type.into(),
+            };
+            writeln!(mounts, "{} {} {} rw 0 0", source, path, fs_type).unwrap();
+        }
+        mounts
+    }
+
+    /// Returns the name of the filesystem type with the magic number.
+    fn fs_type_name(magic: u64) -> &'static str {
+        match magic {
+            fs::procfs::PROC_MAGIC => "proc",
+            #[cfg(feature = "devfs")]
+            fs::devfs::DEVFS_MAGIC => "devfs",
+            #[cfg(any(feature = "ramfs", feature = "sysfs"))]
+            fs::ramfs::RAMFS_MAGIC => "tmpfs",
+            #[cfg(all(feature = "ext2fs", not(feature = "myfs")))]
+            magic if magic == fs::ext2fs::EXT2_MAGIC as u64 => "ext2",
+            #[cfg(all(feature = "fatfs", not(any(feature = "myfs", feature = "ext2fs"))))]
+            fs::fatfs::MSDOS_MAGIC => "vfat",
+            _ => "unknown",
+        }
+    }
 }
 
 #[cfg(feature = "sysfs")]
 pub(crate) fn sysfs() -> VfsResult<Arc<fs::ramfs::RamFileSystem>> {
-    let sysfs = fs::ramfs::RamFileSystem::new();
+    let sysfs = fs::ramfs::RamFileSystem::with_clock(axhal::time::current_time)
+        .with_available_memory(available_memory);
     let sys_root = sysfs.root_dir();
 
     // Create /sys/kernel/mm/transparent_hugepage/enabled
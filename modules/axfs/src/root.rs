@@ -1,73 +1,274 @@
 //! Root directory of the filesystem
 //!
-//! TODO: it doesn't work very well if the mount points have containment relationships.
+//! Filesystems can be mounted at any directory at runtime, including the
+//! directories of other mounted filesystems. Mount points are kept in a trie
+//! indexed by path components, so that the filesystem of a path is found by
+//! the longest prefix match.
 
-use alloc::{string::String, sync::Arc, vec::Vec};
+use alloc::{collections::BTreeMap, string::String, sync::Arc, vec, vec::Vec};
 use axerrno::{ax_err, AxError, AxResult};
-use axfs_vfs::{VfsNodeAttr, VfsNodeOps, VfsNodeRef, VfsNodeType, VfsOps, VfsResult};
+use axfs_vfs::{VfsNodeAttr, VfsNodeOps, VfsNodeRef, VfsNodeType, VfsOps, VfsResult, VfsSetAttr};
 use axsync::Mutex;
 use lazy_init::LazyInit;
 
-use crate::{api::FileType, fs, mounts};
+use crate::{api::FileType, mounts};
+
+/// The maximum number of symbolic links that can be followed during one path
+/// resolution, the same as `MAXSYMLINKS` in Linux.
+const MAX_SYMLINKS: usize = 40;
 
 static CURRENT_DIR_PATH: Mutex<String> = Mutex::new(String::new());
-static CURRENT_DIR: LazyInit<Mutex<VfsNodeRef>> = LazyInit::new();
 
-struct MountPoint {
-    path: &'static str,
-    fs: Arc<dyn VfsOps>,
+/// A node of the mount point trie, indexed by path components.
+#[derive(Default)]
+struct MountNode {
+    fs: Option<Arc<dyn VfsOps>>,
+    children: BTreeMap<String, MountNode>,
 }
 
 struct RootDirectory {
     main_fs: Arc<dyn VfsOps>,
-    mounts: Vec<MountPoint>,
+    mounts: Mutex<MountNode>,
 }
 
 static ROOT_DIR: LazyInit<Arc<RootDirectory>> = LazyInit::new();
 
-impl MountPoint {
-    pub fn new(path: &'static str, fs: Arc<dyn VfsOps>) -> Self {
-        Self { path, fs }
+impl MountNode {
+    /// Finds the filesystem mounted at the longest prefix of `path`, returns
+    /// it with the rest of the path.
+    fn find<'a>(&self, path: &'a str) -> Option<(Arc<dyn VfsOps>, &'a str)> {
+        let mut node = self;
+        let mut found = None;
+        let mut rest = path;
+        loop {
+            rest = rest.trim_start_matches('/');
+            if let Some(next) = rest.strip_prefix("./") {
+                rest = next;
+                continue;
+            }
+            if rest.is_empty() || rest == "." {
+                break;
+            }
+            let (name, next) = rest.split_once('/').unwrap_or((rest, ""));
+            match node.children.get(name) {
+                Some(child) => node = child,
+                None => break,
+            }
+            rest = next;
+            if let Some(fs) = &node.fs {
+                found = Some((fs.clone(), rest));
+            }
+        }
+        found
     }
-}
 
-impl Drop for MountPoint {
-    fn drop(&mut self) {
-        self.fs.umount().ok();
+    /// Returns the node at `path`, creates all missing nodes if `create` is
+    /// `true`.
+    fn node_mut(&mut self, path: &str, create: bool) -> Option<&mut MountNode> {
+        let mut node = self;
+        for name in path.split('/').filter(|n| !n.is_empty()) {
+            node = if create {
+                node.children.entry(name.into()).or_default()
+            } else {
+                node.children.get_mut(name)?
+            };
+        }
+        Some(node)
+    }
+
+    /// Whether any filesystem is mounted under this node.
+    fn has_descendants(&self) -> bool {
+        self.children
+            .values()
+            .any(|child| child.fs.is_some() || child.has_descendants())
+    }
+
+    /// Collects the paths of all mount points under this node, which is at
+    /// `path`, with their filesystems in lexicographical order.
+    fn collect(&self, path: &str, mounts: &mut Vec<(String, Arc<dyn VfsOps>)>) {
+        for (name, child) in &self.children {
+            let child_path = String::from(path.trim_end_matches('/')) + "/" + name;
+            if let Some(fs) = &child.fs {
+                mounts.push((child_path.clone(), fs.clone()));
+            }
+            child.collect(&child_path, mounts);
+        }
+    }
+
+    /// Collects all filesystems mounted under this node.
+    fn filesystems(&self, out: &mut Vec<Arc<dyn VfsOps>>) {
+        for child in self.children.values() {
+            out.extend(child.fs.clone());
+            child.filesystems(out);
+        }
+    }
+
+    /// Removes the filesystem mounted at `path`, and prunes the nodes that
+    /// become empty.
+    fn remove(&mut self, path: &str) -> Option<Arc<dyn VfsOps>> {
+        let path = path.trim_start_matches('/');
+        if path.is_empty() {
+            return self.fs.take();
+        }
+        let (name, rest) = path.split_once('/').unwrap_or((path, ""));
+        let child = self.children.get_mut(name)?;
+        let fs = child.remove(rest);
+        if child.fs.is_none() && child.children.is_empty() {
+            self.children.remove(name);
+        }
+        fs
     }
 }
 
 impl RootDirectory {
-    pub const fn new(main_fs: Arc<dyn VfsOps>) -> Self {
+    pub fn new(main_fs: Arc<dyn VfsOps>) -> Self {
         Self {
             main_fs,
-            mounts: Vec::new(),
+            mounts: Mutex::new(MountNode::default()),
         }
     }
 
-    pub fn mount(&mut self, path: &'static str, fs: Arc<dyn VfsOps>) -> AxResult {
+    pub fn mount(&self, path: &str, fs: Arc<dyn VfsOps>) -> AxResult {
+        if !path.starts_with('/') {
+            return ax_err!(InvalidInput, "mount path must start with '/'");
+        }
+        let path = axfs_vfs::path::canonicalize(path);
         if path == "/" {
             return ax_err!(InvalidInput, "cannot mount root filesystem");
         }
+
+        // Filesystem operations are done without the lock, which is only held
+        // to check conflicts and to update the mount points.
+        let (parent_fs, rest) = {
+            let mut mounts = self.mounts.lock();
+            if mounts
+                .node_mut(&path, false)
+                .is_some_and(|n| n.fs.is_some())
+            {
+                return ax_err!(InvalidInput, "mount point already exists");
+            }
+            mounts
+                .find(&path)
+                .unwrap_or((self.main_fs.clone(), path.as_str()))
+        };
+        // create the mount point in the parent filesystem if it does not exist
+        let parent_root = parent_fs.root_dir();
+        let mount_point = match parent_root.clone().lookup(rest) {
+            Ok(node) => node,
+            Err(AxError::NotFound) => {
+                parent_root.create(rest, FileType::Dir)?;
+                parent_root.lookup(rest)?
+            }
+            Err(e) => return Err(e),
+        };
+        if !mount_point.get_attr()?.is_dir() {
+            return ax_err!(NotADirectory);
+        }
+        fs.mount(&path, mount_point)?;
+
+        let mut mounts = self.mounts.lock();
+        // the mount points may be changed by others in the meantime
+        let parent_changed = mounts
+            .find(&path)
+            .map_or(!Arc::ptr_eq(&self.main_fs, &parent_fs), |(fs, _)| {
+                !Arc::ptr_eq(&fs, &parent_fs)
+            });
+        let mounted = mounts
+            .node_mut(&path, false)
+            .is_some_and(|n| n.fs.is_some());
+        if mounted || parent_changed {
+            drop(mounts);
+            fs.umount()?;
+            return ax_err!(ResourceBusy, "mount points changed during mounting");
+        }
+        mounts.node_mut(&path, true).unwrap().fs = Some(fs);
+        Ok(())
+    }
+
+    pub fn umount(&self, path: &str) -> AxResult {
         if !path.starts_with('/') {
             return ax_err!(InvalidInput, "mount path must start with '/'");
         }
-        if self.mounts.iter().any(|mp| mp.path == path) {
-            return ax_err!(InvalidInput, "mount point already exists");
+        let path = axfs_vfs::path::canonicalize(path);
+        if path == "/" {
+            return ax_err!(ResourceBusy, "cannot unmount root filesystem");
+        }
+        if CURRENT_DIR_PATH.lock().starts_with(&(path.clone() + "/")) {
+            return ax_err!(ResourceBusy, "current directory is in the mount point");
+        }
+
+        // Remove the mount point first, so that no one enters the filesystem
+        // while it is unmounted without the lock.
+        let fs = {
+            let mut mounts = self.mounts.lock();
+            match mounts.node_mut(&path, false) {
+                Some(node) if node.fs.is_some() => {
+                    if node.has_descendants() {
+                        return ax_err!(ResourceBusy, "other filesystems are mounted under it");
+                    }
+                }
+                _ => return ax_err!(InvalidInput, "not a mount point"),
+            }
+            mounts.remove(&path).unwrap()
+        };
+        if let Err(e) = fs.umount() {
+            // mount it back, unless others have mounted another filesystem
+            let mut mounts = self.mounts.lock();
+            let node = mounts.node_mut(&path, true).unwrap();
+            if node.fs.is_none() {
+                node.fs = Some(fs);
+            }
+            return Err(e);
         }
-        // create the mount point in the main filesystem if it does not exist
-        self.main_fs.root_dir().create(path, FileType::Dir)?;
-        fs.mount(path, self.main_fs.root_dir().lookup(path)?)?;
-        self.mounts.push(MountPoint::new(path, fs));
         Ok(())
     }
 
-    pub fn _umount(&mut self, path: &str) {
-        self.mounts.retain(|mp| mp.path != path);
+    pub fn contains(&self, path: &str) -> bool {
+        let mut mounts = self.mounts.lock();
+        mounts.node_mut(path, false).is_some_and(|n| n.fs.is_some())
     }
 
-    pub fn contains(&self, path: &str) -> bool {
-        self.mounts.iter().any(|mp| mp.path == path)
+    /// Returns the paths of all mount points, including the root.
+    pub fn mounts(&self) -> Vec<(String, Arc<dyn VfsOps>)> {
+        let mut mounts = vec![(String::from("/"), self.main_fs.clone())];
+        self.mounts.lock().collect("/", &mut mounts);
+        mounts
+    }
+
+    /// Writes the cached data of all filesystems to the storage.
+    ///
+    /// All filesystems are synchronized even if some of them fail, and the
+    /// first error is returned.
+    pub fn sync(&self) -> AxResult {
+        let mut filesystems = vec![self.main_fs.clone()];
+        self.mounts.lock().filesystems(&mut filesystems);
+        let mut res = Ok(());
+        for fs in filesystems {
+            if let Err(e) = fs.sync() {
+                warn!("failed to sync filesystem: {:?}", e);
+                res = res.and(Err(e));
+            }
+        }
+        res
+    }
+
+    /// Whether the filesystem is mounted on `/` or any other mount point.
+    pub fn is_mounted(&self, fs: &Arc<dyn VfsOps>) -> bool {
+        let mut filesystems = vec![self.main_fs.clone()];
+        self.mounts.lock().filesystems(&mut filesystems);
+        filesystems.iter().any(|f| Arc::ptr_eq(f, fs))
+    }
+
+    /// Returns the filesystem mounted at the canonical `path`.
+    fn mounted_at(&self, path: &str) -> Option<Arc<dyn VfsOps>> {
+        let mut mounts = self.mounts.lock();
+        mounts.node_mut(path, false).and_then(|n| n.fs.clone())
+    }
+
+    /// Returns the filesystem that contains the absolute `path`.
+    pub fn filesystem_of(&self, path: &str) -> Arc<dyn VfsOps> {
+        let found = self.mounts.lock().find(path);
+        found.map_or_else(|| self.main_fs.clone(), |(fs, _)| fs)
     }
 
     fn lookup_mounted_fs<F, T>(&self, path: &str, f: F) -> AxResult<T>
@@ -75,28 +276,17 @@ impl RootDirectory {
         F: FnOnce(Arc<dyn VfsOps>, &str) -> AxResult<T>,
     {
         debug!("lookup at root: {}", path);
+        // Resolve `..` before finding the mount point, so that `..` at the root
+        // of a mounted filesystem goes up to the directory containing the mount
+        // point, instead of being resolved inside the mounted filesystem.
+        let path = axfs_vfs::path::canonicalize(path);
         let path = path.trim_matches('/');
-        if let Some(rest) = path.strip_prefix("./") {
-            return self.lookup_mounted_fs(rest, f);
-        }
-
-        let mut idx = 0;
-        let mut max_len = 0;
 
         // Find the filesystem that has the longest mounted path match
-        // TODO: more efficient, e.g. trie
-        for (i, mp) in self.mounts.iter().enumerate() {
-            // skip the first '/'
-            if path.starts_with(&mp.path[1..]) && mp.path.len() - 1 > max_len {
-                max_len = mp.path.len() - 1;
-                idx = i;
-            }
-        }
-
-        if max_len == 0 {
-            f(self.main_fs.clone(), path) // not matched any mount point
-        } else {
-            f(self.mounts[idx].fs.clone(), &path[max_len..]) // matched at `idx`
+        let found = self.mounts.lock().find(path);
+        match found {
+            Some((fs, rest_path)) => f(fs, rest_path), // matched a mount point
+            None => f(self.main_fs.clone(), path),     // not matched any mount point
         }
     }
 }
@@ -133,29 +323,42 @@ impl VfsNodeOps for RootDirectory {
     }
 
     fn rename(&self, src_path: &str, dst_path: &str) -> VfsResult {
-        self.lookup_mounted_fs(src_path, |fs, rest_path| {
+        self.lookup_mounted_fs(src_path, |src_fs, src_rest| {
+            self.lookup_mounted_fs(dst_path, |dst_fs, dst_rest| {
+                if src_rest.is_empty() || dst_rest.is_empty() {
+                    ax_err!(PermissionDenied) // cannot rename mount points
+                } else if !Arc::ptr_eq(&src_fs, &dst_fs) {
+                    ax_err!(CrossesDevices)
+                } else {
+                    src_fs.root_dir().rename(src_rest, dst_rest)
+                }
+            })
+        })
+    }
+
+    fn symlink(&self, path: &str, target: &str) -> VfsResult {
+        self.lookup_mounted_fs(path, |fs, rest_path| {
             if rest_path.is_empty() {
-                ax_err!(PermissionDenied) // cannot rename mount points
+                ax_err!(AlreadyExists)
             } else {
-                fs.root_dir().rename(rest_path, dst_path)
+                fs.root_dir().symlink(rest_path, target)
             }
         })
     }
-}
 
-pub(crate) fn init_rootfs(disk: crate::dev::Disk) {
-    cfg_if::cfg_if! {
-        if #[cfg(feature = "myfs")] { // override the default filesystem
-            let main_fs = fs::myfs::new_myfs(disk);
-        } else if #[cfg(feature = "fatfs")] {
-            static FAT_FS: LazyInit<Arc<fs::fatfs::FatFileSystem>> = LazyInit::new();
-            FAT_FS.init_by(Arc::new(fs::fatfs::FatFileSystem::new(disk)));
-            FAT_FS.init();
-            let main_fs = FAT_FS.clone();
-        }
+    fn link(&self, path: &str, node: VfsNodeRef) -> VfsResult {
+        self.lookup_mounted_fs(path, |fs, rest_path| {
+            if rest_path.is_empty() {
+                ax_err!(AlreadyExists)
+            } else {
+                fs.root_dir().link(rest_path, node)
+            }
+        })
     }
+}
 
-    let mut root_dir = RootDirectory::new(main_fs);
+pub(crate) fn init_rootfs(main_fs: Arc<dyn VfsOps>) {
+    let root_dir = RootDirectory::new(main_fs);
 
     #[cfg(feature = "devfs")]
     root_dir
@@ -167,10 +370,9 @@ pub(crate) fn init_rootfs(disk: crate::dev::Disk) {
         .mount("/tmp", mounts::ramfs())
         .expect("failed to mount ramfs at /tmp");
 
-    // Mount another ramfs as procfs
     #[cfg(feature = "procfs")]
     root_dir // should not fail
-        .mount("/proc", mounts::procfs().unwrap())
+        .mount("/proc", mounts::procfs())
         .expect("fail to mount procfs at /proc");
 
     // Mount another ramfs as sysfs
@@ -180,15 +382,62 @@ pub(crate) fn init_rootfs(disk: crate::dev::Disk) {
         .expect("fail to mount sysfs at /sys");
 
     ROOT_DIR.init_by(Arc::new(root_dir));
-    CURRENT_DIR.init_by(Mutex::new(ROOT_DIR.clone()));
     *CURRENT_DIR_PATH.lock() = "/".into();
 }
 
-fn parent_node_of(dir: Option<&VfsNodeRef>, path: &str) -> VfsNodeRef {
-    if path.starts_with('/') {
-        ROOT_DIR.clone()
+pub(crate) fn mount(path: &str, fs: Arc<dyn VfsOps>) -> AxResult {
+    ROOT_DIR.mount(path, fs)
+}
+
+pub(crate) fn umount(path: &str) -> AxResult {
+    ROOT_DIR.umount(path)
+}
+
+pub(crate) fn mount_points() -> Vec<String> {
+    ROOT_DIR
+        .mounts()
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect()
+}
+
+/// Returns all mount points with their filesystems, starting with the root
+/// `/`.
+pub(crate) fn mounts() -> Vec<(String, Arc<dyn VfsOps>)> {
+    ROOT_DIR.mounts()
+}
+
+pub(crate) fn sync() -> AxResult {
+    ROOT_DIR.sync()
+}
+
+pub(crate) fn is_mounted(fs: &Arc<dyn VfsOps>) -> bool {
+    ROOT_DIR.is_mounted(fs)
+}
+
+/// Converts `path` to an absolute path if it is relative to the current
+/// directory, so that mount points can be found from the root directory.
+fn path_from(dir: Option<&VfsNodeRef>, path: &str) -> String {
+    if dir.is_none() && !path.starts_with('/') {
+        CURRENT_DIR_PATH.lock().clone() + path
     } else {
-        dir.cloned().unwrap_or_else(|| CURRENT_DIR.lock().clone())
+        String::from(path)
+    }
+}
+
+/// Returns the node where the walk of `path` starts, which is the root
+/// directory of the main filesystem if `path` is absolute.
+fn start_node_of(dir: Option<&VfsNodeRef>, path: &str) -> VfsNodeRef {
+    match dir {
+        Some(dir) if !path.starts_with('/') => dir.clone(),
+        _ => ROOT_DIR.main_fs.root_dir(),
+    }
+}
+
+fn parent_node_of(dir: Option<&VfsNodeRef>, path: &str) -> VfsNodeRef {
+    match dir {
+        Some(dir) if !path.starts_with('/') => dir.clone(),
+        _ => ROOT_DIR.clone(),
     }
 }
 
@@ -201,11 +450,137 @@ pub(crate) fn absolute_path(path: &str) -> AxResult<String> {
     }
 }
 
-pub(crate) fn lookup(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<VfsNodeRef> {
+fn read_link_target(node: &VfsNodeRef) -> AxResult<String> {
+    let mut buf = vec![0; node.get_attr()?.size() as usize];
+    let len = node.readlink(&mut buf)?;
+    buf.truncate(len);
+    String::from_utf8(buf).map_err(|_| AxError::InvalidData)
+}
+
+/// Resolves `path` component by component, and follows the symbolic links
+/// encountered.
+///
+/// The last component is followed only if `follow_last` is `true`. Returns
+/// [`FilesystemLoop`](AxError::FilesystemLoop) if more than [`MAX_SYMLINKS`]
+/// links are followed.
+fn resolve(dir: Option<&VfsNodeRef>, path: &str, follow_last: bool) -> AxResult<VfsNodeRef> {
+    resolve_path(dir, path, follow_last).map(|(node, _)| node)
+}
+
+/// Resolves `path` like [`resolve`], returns the node with the path where it
+/// is found, which does not contain any symbolic links except the last
+/// component.
+///
+/// Each component is looked up from the node of the previous one, and the
+/// mount points are crossed when walking from the root.
+fn resolve_path(
+    dir: Option<&VfsNodeRef>,
+    path: &str,
+    follow_last: bool,
+) -> AxResult<(VfsNodeRef, String)> {
+    let mut path = path_from(dir, path);
+    let mut links = 0;
+    let mut base = start_node_of(dir, &path);
+    // the nodes of the resolved components, with the length of `abs_path`
+    // before each of them
+    let mut nodes: Vec<(VfsNodeRef, usize)> = Vec::new();
+    // the canonical path of the last node, if walking from the root
+    let mut abs_path = String::new();
+    let mut start = 0;
+    while start < path.len() {
+        let end = path[start..].find('/').map_or(path.len(), |n| start + n);
+        let name = &path[start..end];
+        if name.is_empty() || name == "." {
+            start = end + 1;
+            continue;
+        }
+        let parent = nodes.last().map_or(&base, |(node, _)| node);
+        if name == ".." {
+            // `..` is resolved lexically, so check that the node before it is
+            // a directory.
+            if !parent.get_attr()?.is_dir() {
+                return ax_err!(NotADirectory);
+            }
+            if let Some((_, len)) = nodes.pop() {
+                abs_path.truncate(len);
+            } else if !path.starts_with('/') {
+                base = base.clone().lookup("..")?;
+            }
+            start = end + 1;
+            continue;
+        }
+
+        let len = abs_path.len();
+        abs_path.push('/');
+        abs_path.push_str(name);
+        let mounted = if path.starts_with('/') {
+            ROOT_DIR.mounted_at(&abs_path)
+        } else {
+            None
+        };
+        let cur = match mounted {
+            Some(fs) => fs.root_dir(),
+            None => parent.clone().lookup(name)?,
+        };
+        let is_last = path[end..].trim_start_matches('/').is_empty();
+        if (follow_last || !is_last) && cur.get_attr()?.is_symlink() {
+            links += 1;
+            if links > MAX_SYMLINKS {
+                return ax_err!(FilesystemLoop);
+            }
+            let target = read_link_target(&cur)?;
+            if target.is_empty() {
+                return ax_err!(NotFound);
+            }
+            // replace the link with its target, and go on from the directory
+            // containing the link, or from the root if the target is absolute
+            abs_path.truncate(len);
+            if target.starts_with('/') {
+                path = target + &path[end..];
+                base = start_node_of(None, &path);
+                nodes.clear();
+                abs_path.clear();
+                start = 0;
+            } else {
+                path = String::from(&path[..start]) + &target + &path[end..];
+            }
+            continue;
+        }
+        nodes.push((cur, len));
+        start = end + 1;
+    }
+    let node = nodes.pop().map_or(base, |(node, _)| node);
+    Ok((node, path))
+}
+
+/// Resolves the parent directory of `path`, returns it with the last
+/// component of `path`.
+fn resolve_parent(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<(VfsNodeRef, String)> {
+    let path = path_from(dir, path);
+    let trimmed = path.trim_end_matches('/');
+    let (parent_path, name) = match trimmed.rfind('/') {
+        Some(n) => (&trimmed[..=n], &trimmed[n + 1..]),
+        None => ("", trimmed),
+    };
+    let parent = if parent_path.is_empty() {
+        parent_node_of(dir, &path)
+    } else if parent_path.trim_matches('/').is_empty() {
+        ROOT_DIR.clone()
+    } else {
+        let node = resolve(dir, parent_path, true)?;
+        if !node.get_attr()?.is_dir() {
+            return ax_err!(NotADirectory);
+        }
+        node
+    };
+    Ok((parent, name.into()))
+}
+
+fn lookup_with(dir: Option<&VfsNodeRef>, path: &str, follow_last: bool) -> AxResult<VfsNodeRef> {
     if path.is_empty() {
         return ax_err!(NotFound);
     }
-    let node = parent_node_of(dir, path).lookup(path)?;
+    let node = resolve(dir, path, follow_last)?;
     if path.ends_with('/') && !node.get_attr()?.is_dir() {
         ax_err!(NotADirectory)
     } else {
@@ -213,34 +588,114 @@ pub(crate) fn lookup(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<VfsNodeRe
     }
 }
 
+/// Looks up the node at `path`, follows all symbolic links.
+pub(crate) fn lookup(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<VfsNodeRef> {
+    lookup_with(dir, path, true)
+}
+
+/// Looks up the node at `path`, but does not follow the symbolic link if it
+/// is the last component.
+pub(crate) fn lookup_no_follow(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<VfsNodeRef> {
+    lookup_with(dir, path, false)
+}
+
+/// Returns the filesystem that contains the node at `path`, follows all
+/// symbolic links.
+///
+/// `dir_fs` is the filesystem of `dir`, which is returned if the resolved path
+/// is still relative to `dir`.
+pub(crate) fn filesystem_of(
+    dir: Option<&VfsNodeRef>,
+    dir_fs: Option<&Arc<dyn VfsOps>>,
+    path: &str,
+) -> AxResult<Arc<dyn VfsOps>> {
+    let (_, path) = resolve_path(dir, path, true)?;
+    match dir_fs {
+        Some(fs) if !path.starts_with('/') => Ok(fs.clone()),
+        _ => Ok(ROOT_DIR.filesystem_of(&axfs_vfs::path::canonicalize(&path))),
+    }
+}
+
 pub(crate) fn create_file(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<VfsNodeRef> {
     if path.is_empty() {
         return ax_err!(NotFound);
     } else if path.ends_with('/') {
         return ax_err!(NotADirectory);
     }
-    let parent = parent_node_of(dir, path);
-    parent.create(path, VfsNodeType::File)?;
-    parent.lookup(path)
+    let (parent, name) = resolve_parent(dir, path)?;
+    parent.create(&name, VfsNodeType::File)?;
+    parent.lookup(&name)
 }
 
 pub(crate) fn create_dir(dir: Option<&VfsNodeRef>, path: &str) -> AxResult {
     match lookup(dir, path) {
         Ok(_) => ax_err!(AlreadyExists),
-        Err(AxError::NotFound) => parent_node_of(dir, path).create(path, VfsNodeType::Dir),
+        Err(AxError::NotFound) => {
+            let (parent, name) = resolve_parent(dir, path)?;
+            parent.create(&name, VfsNodeType::Dir)
+        }
         Err(e) => Err(e),
     }
 }
 
+pub(crate) fn create_symlink(dir: Option<&VfsNodeRef>, target: &str, path: &str) -> AxResult {
+    if path.is_empty() {
+        return ax_err!(NotFound);
+    }
+    let (parent, name) = resolve_parent(dir, path)?;
+    if name.is_empty() || name == "." || name == ".." {
+        return ax_err!(AlreadyExists);
+    }
+    parent.symlink(&name, target)
+}
+
+pub(crate) fn create_link(dir: Option<&VfsNodeRef>, old_path: &str, new_path: &str) -> AxResult {
+    let node = lookup_no_follow(dir, old_path)?;
+    if node.get_attr()?.is_dir() {
+        return ax_err!(PermissionDenied); // hard links to directories are not allowed
+    }
+    if new_path.is_empty() {
+        return ax_err!(NotFound);
+    }
+    let (parent, name) = resolve_parent(dir, new_path)?;
+    if name.is_empty() || name == "." || name == ".." {
+        return ax_err!(AlreadyExists);
+    }
+    parent.link(&name, node)
+}
+
+pub(crate) fn read_link(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<String> {
+    let node = lookup_no_follow(dir, path)?;
+    if !node.get_attr()?.is_symlink() {
+        return ax_err!(InvalidInput);
+    }
+    read_link_target(&node)
+}
+
+pub(crate) fn set_attr(
+    dir: Option<&VfsNodeRef>,
+    path: &str,
+    attr: &VfsSetAttr,
+    no_follow: bool,
+) -> AxResult {
+    let node = if no_follow {
+        lookup_no_follow(dir, path)?
+    } else {
+        lookup(dir, path)?
+    };
+    node.set_attr(attr)
+}
+
 pub(crate) fn remove_file(dir: Option<&VfsNodeRef>, path: &str) -> AxResult {
-    let node = lookup(dir, path)?;
+    let node = lookup_no_follow(dir, path)?;
     let attr = node.get_attr()?;
     if attr.is_dir() {
         ax_err!(IsADirectory)
     } else if !attr.perm().owner_writable() {
         ax_err!(PermissionDenied)
     } else {
-        parent_node_of(dir, path).remove(path)
+        let (parent, name) = resolve_parent(dir, path)?;
+        parent.remove(&name)
     }
 }
 
@@ -262,14 +717,15 @@ pub(crate) fn remove_dir(dir: Option<&VfsNodeRef>, path: &str) -> AxResult {
         return ax_err!(PermissionDenied);
     }
 
-    let node = lookup(dir, path)?;
+    let node = lookup_no_follow(dir, path)?;
     let attr = node.get_attr()?;
     if !attr.is_dir() {
         ax_err!(NotADirectory)
     } else if !attr.perm().owner_writable() {
         ax_err!(PermissionDenied)
     } else {
-        parent_node_of(dir, path).remove(path)
+        let (parent, name) = resolve_parent(dir, path)?;
+        parent.remove(&name)
     }
 }
 
@@ -283,7 +739,6 @@ pub(crate) fn set_current_dir(path: &str) -> AxResult {
         abs_path += "/";
     }
     if abs_path == "/" {
-        *CURRENT_DIR.lock() = ROOT_DIR.clone();
         *CURRENT_DIR_PATH.lock() = "/".into();
         return Ok(());
     }
@@ -295,16 +750,28 @@ pub(crate) fn set_current_dir(path: &str) -> AxResult {
     } else if !attr.perm().owner_executable() {
         ax_err!(PermissionDenied)
     } else {
-        *CURRENT_DIR.lock() = node;
         *CURRENT_DIR_PATH.lock() = abs_path;
         Ok(())
     }
 }
 
 pub(crate) fn rename(old: &str, new: &str) -> AxResult {
-    if parent_node_of(None, new).lookup(new).is_ok() {
+    if lookup_no_follow(None, new).is_ok() {
         warn!("dst file already exist, now remove it");
         remove_file(None, new)?;
     }
-    parent_node_of(None, old).rename(old, new)
+    ROOT_DIR.rename(&resolve_parent_path(old)?, &resolve_parent_path(new)?)
+}
+
+/// Returns the canonical absolute path of `path`, with the symbolic links in
+/// its parent directories resolved.
+fn resolve_parent_path(path: &str) -> AxResult<String> {
+    let path = path_from(None, path);
+    let trimmed = path.trim_end_matches('/');
+    let (parent_path, name) = trimmed.rsplit_once('/').unwrap_or(("", trimmed));
+    let (parent, parent_path) = resolve_path(None, &(String::from(parent_path) + "/"), true)?;
+    if !parent.get_attr()?.is_dir() {
+        return ax_err!(NotADirectory);
+    }
+    Ok(axfs_vfs::path::canonicalize(&(parent_path + "/" + name)))
 }
@@ -1,7 +1,11 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use axfs::api as fs;
+use axfs_ramfs::RamFileSystem;
 use axio as io;
 
-use fs::{File, FileType, OpenOptions};
+use fs::{File, FileType, OpenOptions, Permissions};
 use io::{prelude::*, Error, Result};
 
 macro_rules! assert_err {
@@ -192,12 +196,21 @@ fn test_devfs_ramfs() -> Result<()> {
     assert!(file.write_all(&buf).is_ok());
     assert_eq!(buf, [0; N]);
 
+    // read /dev/random and /dev/urandom
+    let mut rand1 = [0; N];
+    let mut rand2 = [0; N];
+    File::open("/dev/random")?.read_exact(&mut rand1)?;
+    File::open("/dev/urandom")?.read_exact(&mut rand2)?;
+    assert_ne!(rand1, rand2);
+
     // list /dev
     let dirents = fs::read_dir("/dev")?
         .map(|e| e.unwrap().file_name())
         .collect::<Vec<_>>();
-    assert!(dirents.contains(&"null".into()));
-    assert!(dirents.contains(&"zero".into()));
+    for name in ["null", "zero", "random", "urandom", "tty", "console", "vda"] {
+        assert!(dirents.contains(&name.into()));
+    }
+    assert!(fs::metadata("/dev/tty")?.file_type().is_char_device());
 
     // stat /dev
     let dname = "/dev";
@@ -208,8 +221,8 @@ fn test_devfs_ramfs() -> Result<()> {
     assert!(!md.is_file());
     assert!(md.is_dir());
 
-    // stat /dev/foo/bar
-    let fname = ".//.///././/./dev///.///./foo//././bar";
+    // stat /dev/zero
+    let fname = ".//.///././/./dev///.///.//././zero";
     let file = File::open(fname)?;
     let md = file.metadata()?;
     println!("metadata of {:?}: {:?}", fname, md);
@@ -252,6 +265,241 @@ fn test_devfs_ramfs() -> Result<()> {
     Ok(())
 }
 
+fn test_symlink_link() -> Result<()> {
+    println!("test symbolic and hard links in /tmp:");
+    fs::create_dir("/tmp/links")?;
+    fs::write("/tmp/links/file.txt", "link test\n")?;
+    fs::symlink("file.txt", "/tmp/links/sym")?;
+    fs::symlink("/tmp/links", "tmp/dir_link")?;
+    fs::hard_link("/tmp/links/file.txt", "/tmp/links/hard")?;
+
+    // follow links
+    assert_eq!(fs::read_link("/tmp/links/sym")?, "file.txt");
+    assert_eq!(fs::read_link("/tmp/dir_link")?, "/tmp/links");
+    assert_eq!(fs::read_to_string("/tmp/links/sym")?, "link test\n");
+    assert_eq!(fs::read_to_string("/tmp/dir_link//sym")?, "link test\n");
+    assert_eq!(
+        fs::read_to_string("tmp/dir_link/../links/hard")?,
+        "link test\n"
+    );
+    assert!(fs::symlink_metadata("/tmp/links/sym")?.is_symlink());
+    assert!(fs::metadata("/tmp/links/sym")?.is_file());
+    assert!(fs::metadata("/tmp/dir_link/")?.is_dir());
+    let dirents = fs::read_dir("/tmp/dir_link")?
+        .map(|e| e.unwrap().file_name())
+        .collect::<Vec<_>>();
+    assert_eq!(dirents.len(), 3);
+
+    // hard links share the same content
+    fs::write("/tmp/links/hard", "new content\n")?;
+    assert_eq!(fs::read_to_string("/tmp/links/file.txt")?, "new content\n");
+    fs::remove_file("/tmp/links/file.txt")?;
+    assert_eq!(fs::read_to_string("/tmp/links/hard")?, "new content\n");
+    assert_err!(fs::read("/tmp/links/sym"), NotFound);
+
+    // rename through a link to the parent directory
+    fs::rename("/tmp/dir_link/hard", "tmp/dir_link/renamed")?;
+    assert_eq!(fs::read_to_string("/tmp/links/renamed")?, "new content\n");
+    fs::rename("/tmp/dir_link/renamed", "/tmp/links/hard")?;
+
+    // error cases
+    fs::symlink("/not/exist", "/tmp/dangling")?;
+    fs::symlink("loop2", "/tmp/loop1")?;
+    fs::symlink("loop1", "/tmp/loop2")?;
+    assert_err!(fs::metadata("/tmp/dangling"), NotFound);
+    assert!(fs::symlink_metadata("/tmp/dangling")?.is_symlink());
+    assert_err!(fs::read("/tmp/loop1"), FilesystemLoop);
+    assert_err!(fs::read_link("/tmp/links/hard"), InvalidInput);
+    assert_err!(fs::symlink("foo", "/tmp/loop1"), AlreadyExists);
+    assert_err!(
+        fs::hard_link("/tmp/links", "/tmp/dir_hard"),
+        PermissionDenied
+    );
+    assert_err!(fs::remove_dir("/tmp/dir_link"), NotADirectory);
+
+    // remove links, not the targets
+    fs::remove_file("/tmp/dir_link")?;
+    assert!(fs::metadata("/tmp/links")?.is_dir());
+    for name in ["links/sym", "links/hard", "dangling", "loop1", "loop2"] {
+        fs::remove_file(&format!("/tmp/{}", name))?;
+    }
+    fs::remove_dir("/tmp/links")?;
+    assert_eq!(fs::read_dir("/tmp")?.count(), 0);
+
+    println!("test_symlink_link() OK!");
+    Ok(())
+}
+
+fn test_metadata() -> Result<()> {
+    println!("test metadata in /tmp:");
+    fs::write("/tmp/meta.txt", "metadata\n")?;
+    fs::write("/tmp/other.txt", "metadata\n")?;
+    fs::hard_link("/tmp/meta.txt", "/tmp/meta_link")?;
+
+    let md = fs::metadata("/tmp/meta.txt")?;
+    let attr = md.raw_metadata();
+    assert_eq!(attr.nlink(), 2);
+    assert_eq!(
+        attr.ino(),
+        fs::metadata("/tmp/meta_link")?.raw_metadata().ino()
+    );
+    assert_ne!(
+        attr.ino(),
+        fs::metadata("/tmp/other.txt")?.raw_metadata().ino()
+    );
+    assert_eq!(
+        attr.ino(),
+        File::open("/tmp/meta.txt")?
+            .metadata()?
+            .raw_metadata()
+            .ino()
+    );
+    assert!(attr.mtime() >= attr.atime());
+
+    fs::set_permissions("/tmp/meta_link", Permissions::from_bits_truncate(0o600))?;
+    assert_eq!(fs::metadata("/tmp/meta.txt")?.permissions().mode(), 0o600);
+
+    fs::set_owner("/tmp/meta_link", Some(0), None)?;
+    fs::set_times("/tmp/meta.txt", Some(Duration::from_secs(1)), None)?;
+    let attr = *fs::metadata("/tmp/meta.txt")?.raw_metadata();
+    assert_eq!((attr.uid(), attr.gid()), (0, axfs_vfs::DEFAULT_GID));
+    assert_eq!(attr.atime(), Duration::from_secs(1));
+
+    fs::remove_file("/tmp/meta_link")?;
+    assert_eq!(fs::metadata("/tmp/meta.txt")?.raw_metadata().nlink(), 1);
+    fs::remove_file("/tmp/meta.txt")?;
+    fs::remove_file("/tmp/other.txt")?;
+
+    println!("test_metadata() OK!");
+    Ok(())
+}
+
+fn test_mount_umount() -> Result<()> {
+    println!("test mount and umount at runtime:");
+    fs::create_dir("/mnt")?;
+    fs::mount("/mnt/ram", Arc::new(RamFileSystem::new()))?;
+    fs::write("/mnt/ram/test.txt", "ramfs\n")?;
+    assert!(fs::metadata("/mnt/ram")?.is_dir());
+    assert_eq!(fs::read_to_string("/mnt//./ram/test.txt")?, "ramfs\n");
+
+    // nested mount point
+    fs::mount("/mnt/ram/sub/", Arc::new(RamFileSystem::new()))?;
+    fs::write("/mnt/ram/sub/test.txt", "nested\n")?;
+    assert_eq!(fs::read_to_string("/mnt/ram/test.txt")?, "ramfs\n");
+    assert_eq!(fs::read_to_string("/mnt/ram/sub/test.txt")?, "nested\n");
+    assert_eq!(fs::read_dir("/mnt/ram")?.count(), 2);
+    assert_eq!(fs::read_dir("/mnt/ram/sub")?.count(), 1);
+
+    // `..` at the root of a mounted filesystem goes up to the parent mount
+    fs::write("/mnt/main.txt", "main\n")?;
+    assert_eq!(fs::read_to_string("/mnt/ram/../main.txt")?, "main\n");
+    assert_eq!(fs::read_to_string("/mnt/ram/sub/../test.txt")?, "ramfs\n");
+    assert_eq!(fs::read_to_string("/mnt/ram/sub/../../main.txt")?, "main\n");
+    assert!(fs::read_dir("/mnt/ram/..")?.any(|e| e.unwrap().file_name() == "ram"));
+    assert_err!(fs::metadata("/mnt/main.txt/.."), NotADirectory);
+    fs::remove_file("/mnt/main.txt")?;
+
+    // error cases
+    assert_err!(
+        fs::mount("/mnt/ram", Arc::new(RamFileSystem::new())),
+        InvalidInput
+    );
+    assert_err!(
+        fs::mount("/mnt/ram/test.txt", Arc::new(RamFileSystem::new())),
+        NotADirectory
+    );
+    assert_err!(fs::mount("/", Arc::new(RamFileSystem::new())), InvalidInput);
+    assert_err!(fs::umount("/mnt"), InvalidInput);
+    assert_err!(fs::umount("/mnt/ram"), ResourceBusy);
+    assert_err!(fs::remove_dir("/mnt/ram/sub"), PermissionDenied);
+    assert_err!(
+        fs::rename("/mnt/ram/test.txt", "/mnt/ram/sub/foo.txt"),
+        CrossesDevices
+    );
+
+    // unmount the nested filesystem first
+    fs::set_current_dir("/mnt/ram/sub")?;
+    assert_err!(fs::umount("/mnt/ram/sub"), ResourceBusy);
+    fs::set_current_dir("/")?;
+    fs::umount("/mnt/ram/sub")?;
+    assert_err!(fs::metadata("/mnt/ram/sub/test.txt"), NotFound);
+    assert!(fs::metadata("/mnt/ram/sub")?.is_dir());
+    fs::umount("/mnt/ram")?;
+    assert_err!(fs::metadata("/mnt/ram/test.txt"), NotFound);
+
+    fs::remove_dir("/mnt/ram")?;
+    fs::remove_dir("/mnt")?;
+
+    println!("test_mount_umount() OK!");
+    Ok(())
+}
+
+fn test_statfs() -> Result<()> {
+    println!("test statfs:");
+    let mount_points = fs::mount_points();
+    assert_eq!(mount_points[0], "/");
+    assert!(mount_points.iter().any(|p| p == "/dev"));
+    assert!(mount_points.iter().any(|p| p == "/tmp"));
+
+    assert_eq!(fs::statfs("/dev/null")?.fs_type(), 0x1373);
+    assert_eq!(fs::statfs("/tmp")?.fs_type(), 0x0102_1994);
+    fs::symlink("/dev/zero", "/tmp/dev_link")?;
+    assert_eq!(fs::statfs("/tmp/dev_link")?.fs_type(), 0x1373);
+    fs::remove_file("/tmp/dev_link")?;
+    assert_err!(fs::statfs("/tmp/not_exist"), NotFound);
+
+    // through an opened directory, as `fstatfs` does
+    let mut opts = axfs::fops::OpenOptions::new();
+    opts.read(true);
+    let dir = axfs::fops::Directory::open_dir("/tmp", &opts)?;
+    assert_eq!(dir.statfs()?.fs_type(), 0x0102_1994);
+
+    let info = fs::statfs("/")?;
+    assert!(info.block_size() > 0);
+    assert!(info.blocks_free() <= info.blocks());
+
+    println!("test_statfs() OK!");
+    Ok(())
+}
+
+fn test_procfs() -> Result<()> {
+    println!("test procfs:");
+    assert_eq!(fs::statfs("/proc")?.fs_type(), 0x9fa0);
+
+    let meminfo = fs::read_to_string("/proc/meminfo")?;
+    assert!(meminfo.starts_with("MemTotal:"));
+    let uptime = fs::read_to_string("/proc/uptime")?;
+    assert!(uptime
+        .split_whitespace()
+        .next()
+        .unwrap()
+        .parse::<f64>()
+        .is_ok());
+    assert!(fs::read_to_string("/proc/cpuinfo")?.starts_with("processor\t: 0\n"));
+    assert_eq!(fs::read_to_string("/proc/sys/vm/overcommit_memory")?, "0\n");
+
+    let mounts = fs::read_to_string("/proc/mounts")?;
+    println!("/proc/mounts:\n{}", mounts);
+    assert!(mounts.lines().next().unwrap().split(' ').nth(1) == Some("/"));
+    assert!(mounts.contains("devfs /dev devfs rw 0 0\n"));
+    assert!(mounts.contains("tmpfs /tmp tmpfs rw 0 0\n"));
+    assert!(mounts.contains("proc /proc proc rw 0 0\n"));
+
+    // files are read-only and generated on each read
+    assert_err!(fs::write("/proc/meminfo", "test"), PermissionDenied);
+    assert_err!(fs::create_dir("/proc/test"), PermissionDenied);
+    assert_eq!(fs::metadata("/proc/mounts")?.len(), 0);
+    let dirents = fs::read_dir("/proc")?
+        .map(|e| e.unwrap().file_name())
+        .collect::<Vec<_>>();
+    for name in ["meminfo", "uptime", "cpuinfo", "mounts", "sys"] {
+        assert!(dirents.contains(&name.into()));
+    }
+
+    println!("test_procfs() OK!");
+    Ok(())
+}
+
 pub fn test_all() {
     test_read_write_file().expect("test_read_write_file() failed");
     test_read_dir().expect("test_read_dir() failed");
@@ -259,4 +507,10 @@ pub fn test_all() {
     test_create_file_dir().expect("test_create_file_dir() failed");
     test_remove_file_dir().expect("test_remove_file_dir() failed");
     test_devfs_ramfs().expect("test_devfs_ramfs() failed");
+    test_symlink_link().expect("test_symlink_link() failed");
+    test_metadata().expect("test_metadata() failed");
+    test_mount_umount().expect("test_mount_umount() failed");
+    test_statfs().expect("test_statfs() failed");
+    test_procfs().expect("test_procfs() failed");
+    fs::sync().expect("failed to sync filesystems");
 }
@@ -0,0 +1,69 @@
+#![cfg(all(feature = "ext2fs", not(feature = "myfs")))]
+
+mod test_common;
+
+use axdriver::AxDeviceContainer;
+use axfs::api as fs;
+use axio::Result;
+use driver_block::ramdisk::RamDisk;
+
+const IMG_PATH: &str = "resources/ext2.img";
+
+fn make_disk() -> std::io::Result<RamDisk> {
+    let path = std::env::current_dir()?.join(IMG_PATH);
+    println!("Loading disk image from {:?} ...", path);
+    let data = std::fs::read(path)?;
+    println!("size = {} bytes", data.len());
+    Ok(RamDisk::from(&data))
+}
+
+fn test_large_file() -> Result<()> {
+    println!("test large file in ext2:");
+    let free = fs::statfs("/")?.blocks_free();
+
+    // large enough to use the double indirect blocks of 1K blocks
+    let data: Vec<u8> = (0..400_000u32).map(|i| (i % 251) as u8).collect();
+    fs::write("/large.bin", &data)?;
+    assert_eq!(fs::read("/large.bin")?, data);
+    assert!(fs::statfs("/")?.blocks_free() < free);
+
+    fs::rename("/large.bin", "/very/large.bin")?;
+    assert_eq!(fs::read("/very/large.bin")?, data);
+    fs::remove_file("/very/large.bin")?;
+    assert_eq!(fs::statfs("/")?.blocks_free(), free);
+
+    println!("test_large_file() OK!");
+    Ok(())
+}
+
+fn test_rename_dir() -> Result<()> {
+    println!("test rename directories in ext2:");
+    fs::create_dir("/a")?;
+    fs::create_dir("/a/b")?;
+    fs::write("/a/b/test.txt", "rename\n")?;
+    fs::rename("/a/b", "/very/c")?;
+    assert_eq!(fs::read_to_string("/very/c/../c/test.txt")?, "rename\n");
+    assert_eq!(fs::metadata("/a")?.raw_metadata().nlink(), 2);
+    assert_eq!(fs::metadata("/very/c")?.raw_metadata().nlink(), 2);
+
+    fs::remove_file("/very/c/test.txt")?;
+    fs::remove_dir("/very/c")?;
+    fs::remove_dir("/a")?;
+    assert_eq!(fs::metadata("/very")?.raw_metadata().nlink(), 3);
+
+    println!("test_rename_dir() OK!");
+    Ok(())
+}
+
+#[test]
+fn test_ext2fs() {
+    println!("Testing ext2fs with ramdisk ...");
+
+    let disk = make_disk().expect("failed to load disk image");
+    axtask::init_scheduler(); // call this to use `axsync::Mutex`.
+    axfs::init_filesystems(AxDeviceContainer::from_one(disk));
+
+    test_common::test_all();
+    test_large_file().expect("test_large_file() failed");
+    test_rename_dir().expect("test_rename_dir() failed");
+}
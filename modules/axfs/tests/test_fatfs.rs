@@ -1,8 +1,11 @@
-#![cfg(not(feature = "myfs"))]
+#![cfg(not(any(feature = "myfs", feature = "ext2fs")))]
 
 mod test_common;
 
+use std::time::Duration;
+
 use axdriver::AxDeviceContainer;
+use axfs::api as fs;
 use driver_block::ramdisk::RamDisk;
 
 const IMG_PATH: &str = "resources/fat16.img";
@@ -24,4 +27,24 @@ fn test_fatfs() {
     axfs::init_filesystems(AxDeviceContainer::from_one(disk));
 
     test_common::test_all();
+    test_ino_times().expect("test_ino_times() failed");
+}
+
+/// Inode numbers follow the data of files across renames, and all handles of
+/// a file share its timestamps.
+fn test_ino_times() -> axio::Result<()> {
+    fs::write("/ino.txt", "inode\n")?;
+    let ino = fs::metadata("/ino.txt")?.raw_metadata().ino();
+    fs::rename("/ino.txt", "/ino_renamed.txt")?;
+    assert_eq!(fs::metadata("/ino_renamed.txt")?.raw_metadata().ino(), ino);
+    assert_ne!(fs::metadata("/")?.raw_metadata().ino(), ino);
+
+    let file = fs::File::open("/ino_renamed.txt")?;
+    let mtime = Duration::from_secs(946684800); // 2000-01-01
+    fs::set_times("/ino_renamed.txt", None, Some(mtime))?;
+    assert_eq!(file.metadata()?.raw_metadata().mtime(), mtime);
+    drop(file);
+    fs::remove_file("/ino_renamed.txt")?;
+    println!("test_ino_times() OK!");
+    Ok(())
 }
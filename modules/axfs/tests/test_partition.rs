@@ -0,0 +1,100 @@
+#![cfg(not(any(feature = "myfs", feature = "ext2fs")))]
+
+mod test_common;
+
+use axdriver::AxDeviceContainer;
+use axfs::{api as fs, api::File, PartitionSelector};
+use axio::{Error, Read, Result};
+use driver_block::ramdisk::RamDisk;
+
+const IMG_PATH: &str = "resources/fat16.img";
+const BLOCK_SIZE: usize = 512;
+const PART_START: usize = 64;
+
+/// Creates a disk with an MBR and two copies of the FAT image as partitions.
+fn make_disk() -> std::io::Result<RamDisk> {
+    let path = std::env::current_dir()?.join(IMG_PATH);
+    println!("Loading disk image from {:?} ...", path);
+    let img = std::fs::read(path)?;
+    let part_blocks = img.len() / BLOCK_SIZE;
+
+    let mut data = vec![0; (PART_START + part_blocks * 2) * BLOCK_SIZE];
+    for i in 0..2 {
+        let start = PART_START + part_blocks * i;
+        let entry = &mut data[446 + i * 16..462 + i * 16];
+        entry[4] = 0x06; // FAT16
+        entry[8..12].copy_from_slice(&(start as u32).to_le_bytes());
+        entry[12..16].copy_from_slice(&(part_blocks as u32).to_le_bytes());
+        data[start * BLOCK_SIZE..][..img.len()].copy_from_slice(&img);
+    }
+    data[510..512].copy_from_slice(&[0x55, 0xaa]);
+    println!("size = {} bytes", data.len());
+    Ok(RamDisk::from(&data))
+}
+
+fn test_mount_partition() -> Result<()> {
+    println!("test_mount_partition() ...");
+    fs::write("/short.txt", "changed on the first partition")?;
+
+    fs::mount_partition("/mnt", &PartitionSelector::Index(1))?;
+    assert_eq!(fs::read_to_string("/mnt/short.txt")?, "Rust is cool!\n");
+    assert_eq!(fs::read_dir("/mnt/very/long/path")?.count(), 1);
+
+    let busy = fs::mount_partition("/mnt2", &PartitionSelector::Index(0));
+    assert_eq!(busy.err(), Some(Error::ResourceBusy));
+    let label = PartitionSelector::Label("data".into());
+    assert_eq!(
+        fs::mount_partition("/mnt2", &label).err(),
+        Some(Error::NotFound)
+    );
+
+    // the partition can be mounted again after it is unmounted
+    fs::umount("/mnt")?;
+    fs::mount_partition("/mnt", &PartitionSelector::Index(1))?;
+    assert_eq!(fs::read_to_string("/mnt/short.txt")?, "Rust is cool!\n");
+    fs::umount("/mnt")?;
+    println!("test_mount_partition() OK!");
+    Ok(())
+}
+
+fn test_device_node() -> Result<()> {
+    println!("test_device_node() ...");
+    let metadata = fs::metadata("/dev/vda")?;
+    assert!(metadata.file_type().is_block_device());
+    assert_eq!(metadata.len(), fs::read("/dev/vda")?.len() as u64);
+
+    let mut file = File::open("/dev/vda")?;
+    let mut mbr = [0; BLOCK_SIZE];
+    file.read_exact(&mut mbr)?;
+    assert_eq!(mbr[510..], [0x55, 0xaa]);
+
+    // the partitions have their own nodes, which start at the partitions
+    let part = fs::read("/dev/vda2")?;
+    assert_eq!(fs::metadata("/dev/vda1")?.len(), part.len() as u64);
+    let whole = fs::read("/dev/vda")?;
+    let start = PART_START * BLOCK_SIZE + part.len();
+    assert_eq!(whole[start..start + part.len()], part[..]);
+
+    fs::mount_device("/mnt", "/dev/vda", Some(&PartitionSelector::Index(1)))?;
+    assert_eq!(fs::read_to_string("/mnt/short.txt")?, "Rust is cool!\n");
+    fs::umount("/mnt")?;
+    assert_eq!(
+        fs::mount_device("/mnt", "vdb", None).err(),
+        Some(Error::NotFound)
+    );
+    println!("test_device_node() OK!");
+    Ok(())
+}
+
+#[test]
+fn test_partition() {
+    println!("Testing partitions with ramdisk ...");
+
+    let disk = make_disk().expect("failed to load disk image");
+    axtask::init_scheduler(); // call this to use `axsync::Mutex`.
+    axfs::init_filesystems(AxDeviceContainer::from_one(disk));
+
+    test_common::test_all();
+    test_mount_partition().expect("test_mount_partition() failed");
+    test_device_node().expect("test_device_node() failed");
+}
@@ -35,6 +35,18 @@ pub fn wait_for_irqs() {
     aarch64_cpu::asm::wfi();
 }
 
+/// Enables interrupts and waits for the next one.
+///
+/// Unlike [`enable_irqs`] followed by [`wait_for_irqs`], an interrupt arriving
+/// in between is not missed, so the caller can check for pending work with
+/// interrupts disabled before calling it.
+#[inline]
+pub fn enable_irqs_and_wait() {
+    // `wfi` wakes up on pending interrupts even if they are masked
+    aarch64_cpu::asm::wfi();
+    enable_irqs();
+}
+
 /// Halt the current CPU.
 #[inline]
 pub fn halt() {
@@ -135,3 +147,23 @@ pub fn read_thread_pointer() -> usize {
 pub unsafe fn write_thread_pointer(tpidr_el0: usize) {
     TPIDR_EL0.set(tpidr_el0 as _)
 }
+
+/// Reads a random number from the hardware random number generator
+/// (`RNDR`), or returns [`None`] if the CPU does not support it.
+pub fn hardware_random() -> Option<u64> {
+    let isar0: u64;
+    unsafe { asm!("mrs {}, ID_AA64ISAR0_EL1", out(reg) isar0) };
+    // ID_AA64ISAR0_EL1.RNDR, bits [63:60]
+    if isar0 >> 60 == 0 {
+        return None;
+    }
+    for _ in 0..10 {
+        let (value, ok): (u64, u64);
+        // `RNDR` sets the Z flag if no random number is available in time
+        unsafe { asm!("mrs {}, s3_3_c2_c4_0", "cset {}, ne", out(reg) value, out(reg) ok) };
+        if ok != 0 {
+            return Some(value);
+        }
+    }
+    None
+}
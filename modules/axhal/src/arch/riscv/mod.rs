@@ -36,6 +36,18 @@ pub fn wait_for_irqs() {
     unsafe { riscv::asm::wfi() }
 }
 
+/// Enables interrupts and waits for the next one.
+///
+/// Unlike [`enable_irqs`] followed by [`wait_for_irqs`], an interrupt arriving
+/// in between is not missed, so the caller can check for pending work with
+/// interrupts disabled before calling it.
+#[inline]
+pub fn enable_irqs_and_wait() {
+    // `wfi` wakes up on pending interrupts even if they are masked
+    unsafe { riscv::asm::wfi() };
+    enable_irqs();
+}
+
 /// Halt the current CPU.
 #[inline]
 pub fn halt() {
@@ -107,3 +119,12 @@ pub fn read_thread_pointer() -> usize {
 pub unsafe fn write_thread_pointer(tp: usize) {
     core::arch::asm!("mv tp, {}", in(reg) tp)
 }
+
+/// Reads a random number from the hardware random number generator, or
+/// returns [`None`] if there is none.
+///
+/// The `seed` CSR of the Zkr extension is not accessible in S-mode unless the
+/// firmware allows it, so it always returns [`None`].
+pub fn hardware_random() -> Option<u64> {
+    None
+}
@@ -48,6 +48,22 @@ pub fn wait_for_irqs() {
     }
 }
 
+/// Enables interrupts and waits for the next one.
+///
+/// Unlike [`enable_irqs`] followed by [`wait_for_irqs`], an interrupt arriving
+/// in between is not missed, so the caller can check for pending work with
+/// interrupts disabled before calling it.
+#[inline]
+pub fn enable_irqs_and_wait() {
+    if cfg!(target_os = "none") {
+        // `hlt` right after `sti` is executed before any interrupt is taken
+        unsafe { asm!("sti; hlt") }
+    } else {
+        enable_irqs();
+        core::hint::spin_loop()
+    }
+}
+
 /// Halt the current CPU.
 #[inline]
 pub fn halt() {
@@ -108,3 +124,28 @@ pub fn read_thread_pointer() -> usize {
 pub unsafe fn write_thread_pointer(fs_base: usize) {
     unsafe { msr::wrmsr(msr::IA32_FS_BASE, fs_base as u64) }
 }
+
+/// Reads a random number from the hardware random number generator
+/// (`RDRAND`), or returns [`None`] if the CPU does not support it.
+pub fn hardware_random() -> Option<u64> {
+    use core::arch::x86_64::{__cpuid, _rdrand64_step};
+
+    #[target_feature(enable = "rdrand")]
+    fn rdrand() -> Option<u64> {
+        let mut value = 0;
+        // it may fail transiently, so retry a few times as Intel recommends
+        for _ in 0..10 {
+            if _rdrand64_step(&mut value) == 1 {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    // CPUID.01H:ECX.RDRAND[bit 30]
+    if __cpuid(1).ecx & (1 << 30) == 0 {
+        return None;
+    }
+    // SAFETY: the CPU supports `RDRAND` as checked above
+    unsafe { rdrand() }
+}
@@ -28,13 +28,15 @@ pub fn nanos_to_ticks(nanos: u64) -> u64 {
 /// Set a one-shot timer.
 ///
 /// A timer interrupt will be triggered at the given deadline (in nanoseconds).
+/// If the deadline is beyond the range of the hardware timer, the interrupt is
+/// triggered earlier, and the handler should set the timer again.
 #[cfg(feature = "irq")]
 pub fn set_oneshot_timer(deadline_ns: u64) {
     let cnptct = CNTPCT_EL0.get();
     let cnptct_deadline = nanos_to_ticks(deadline_ns);
     if cnptct < cnptct_deadline {
-        let interval = cnptct_deadline - cnptct;
-        debug_assert!(interval <= u32::MAX as u64);
+        // TVAL is a signed 32-bit value
+        let interval = (cnptct_deadline - cnptct).min(i32::MAX as u64);
         CNTP_TVAL_EL0.set(interval);
     } else {
         CNTP_TVAL_EL0.set(0);
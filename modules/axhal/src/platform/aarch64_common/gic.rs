@@ -13,6 +13,10 @@ pub const TIMER_IRQ_NUM: usize = translate_irq(14, InterruptType::PPI).unwrap();
 /// The UART IRQ number.
 pub const UART_IRQ_NUM: usize = translate_irq(axconfig::UART_IRQ, InterruptType::SPI).unwrap();
 
+/// The IRQ number of inter-processor interrupts.
+#[cfg(feature = "smp")]
+pub const IPI_IRQ_NUM: usize = translate_irq(0, InterruptType::SGI).unwrap();
+
 const GICD_BASE: PhysAddr = PhysAddr::from(axconfig::GICD_PADDR);
 const GICC_BASE: PhysAddr = PhysAddr::from(axconfig::GICC_PADDR);
 
@@ -37,6 +41,12 @@ pub fn register_handler(irq_num: usize, handler: IrqHandler) -> bool {
     crate::irq::register_handler_common(irq_num, handler)
 }
 
+/// Sends an inter-processor interrupt to the given CPU.
+#[cfg(feature = "smp")]
+pub fn send_ipi(cpu_id: usize) {
+    GICD.lock().send_sgi(IPI_IRQ_NUM, cpu_id);
+}
+
 /// Dispatches the IRQ.
 ///
 /// This function is called by the common interrupt handler. It looks
@@ -56,5 +66,7 @@ pub(crate) fn init_primary() {
 /// Initializes GICC on secondary CPUs.
 #[cfg(feature = "smp")]
 pub(crate) fn init_secondary() {
+    // SGIs and PPIs are banked, enable them on each CPU
+    GICD.lock().set_enable(IPI_IRQ_NUM, true);
     GICC.init();
 }
@@ -52,6 +52,8 @@ pub mod time {
     /// Set a one-shot timer.
     ///
     /// A timer interrupt will be triggered at the given deadline (in nanoseconds).
+    /// If the deadline is beyond the range of the hardware timer, the interrupt is
+    /// triggered earlier, and the handler should set the timer again.
     pub fn set_oneshot_timer(deadline_ns: u64) {}
 }
 
@@ -63,6 +65,10 @@ pub mod irq {
     /// The timer IRQ number.
     pub const TIMER_IRQ_NUM: usize = 0;
 
+    /// The IRQ number of inter-processor interrupts.
+    #[cfg(feature = "smp")]
+    pub const IPI_IRQ_NUM: usize = 1;
+
     /// Enables or disables the given IRQ.
     pub fn set_enable(irq_num: usize, enabled: bool) {}
 
@@ -71,6 +77,10 @@ pub mod irq {
         false
     }
 
+    /// Sends an inter-processor interrupt to the given CPU.
+    #[cfg(feature = "smp")]
+    pub fn send_ipi(cpu_id: usize) {}
+
     /// Dispatches the IRQ.
     ///
     /// This function is called by the common interrupt handler. It looks
@@ -2,13 +2,12 @@
 
 use crate::irq::IrqHandler;
 use lazy_init::LazyInit;
-use riscv::register::sie;
+use riscv::register::{sie, sip};
 
 /// `Interrupt` bit in `scause`
 pub(super) const INTC_IRQ_BASE: usize = 1 << (usize::BITS - 1);
 
 /// Supervisor software interrupt in `scause`
-#[allow(unused)]
 pub(super) const S_SOFT: usize = INTC_IRQ_BASE + 1;
 
 /// Supervisor timer interrupt in `scause`
@@ -19,15 +18,23 @@ pub(super) const S_EXT: usize = INTC_IRQ_BASE + 9;
 
 static TIMER_HANDLER: LazyInit<IrqHandler> = LazyInit::new();
 
+static IPI_HANDLER: LazyInit<IrqHandler> = LazyInit::new();
+
 /// The maximum number of IRQs.
 pub const MAX_IRQ_COUNT: usize = 1024;
 
 /// The timer IRQ number (supervisor timer interrupt in `scause`).
 pub const TIMER_IRQ_NUM: usize = S_TIMER;
 
+/// The IRQ number of inter-processor interrupts (supervisor software interrupt
+/// in `scause`).
+#[cfg(feature = "smp")]
+pub const IPI_IRQ_NUM: usize = S_SOFT;
+
 macro_rules! with_cause {
-    ($cause: expr, @TIMER => $timer_op: expr, @EXT => $ext_op: expr $(,)?) => {
+    ($cause: expr, @IPI => $ipi_op: expr, @TIMER => $timer_op: expr, @EXT => $ext_op: expr $(,)?) => {
         match $cause {
+            S_SOFT => $ipi_op,
             S_TIMER => $timer_op,
             S_EXT => $ext_op,
             _ => panic!("invalid trap cause: {:#x}", $cause),
@@ -49,6 +56,12 @@ pub fn set_enable(scause: usize, _enabled: bool) {
 pub fn register_handler(scause: usize, handler: IrqHandler) -> bool {
     with_cause!(
         scause,
+        @IPI => if !IPI_HANDLER.is_init() {
+            IPI_HANDLER.init_by(handler);
+            true
+        } else {
+            false
+        },
         @TIMER => if !TIMER_HANDLER.is_init() {
             TIMER_HANDLER.init_by(handler);
             true
@@ -67,6 +80,13 @@ pub fn register_handler(scause: usize, handler: IrqHandler) -> bool {
 pub fn dispatch_irq(scause: usize) {
     with_cause!(
         scause,
+        @IPI => {
+            trace!("IRQ: IPI");
+            unsafe { sip::clear_ssoft() };
+            if IPI_HANDLER.is_init() {
+                IPI_HANDLER();
+            }
+        },
         @TIMER => {
             trace!("IRQ: timer");
             TIMER_HANDLER();
@@ -75,6 +95,12 @@ pub fn dispatch_irq(scause: usize) {
     );
 }
 
+/// Sends an inter-processor interrupt to the given CPU.
+#[cfg(feature = "smp")]
+pub fn send_ipi(cpu_id: usize) {
+    sbi_rt::send_ipi(1 << cpu_id, 0);
+}
+
 pub(super) fn init_percpu() {
     // enable soft interrupts, timer interrupts, and external interrupts
     unsafe {
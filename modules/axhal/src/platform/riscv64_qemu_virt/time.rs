@@ -23,6 +23,8 @@ pub const fn nanos_to_ticks(nanos: u64) -> u64 {
 /// Set a one-shot timer.
 ///
 /// A timer interrupt will be triggered at the given deadline (in nanoseconds).
+/// If the deadline is beyond the range of the hardware timer, the interrupt is
+/// triggered earlier, and the handler should set the timer again.
 #[cfg(feature = "irq")]
 pub fn set_oneshot_timer(deadline_ns: u64) {
     sbi_rt::set_timer(nanos_to_ticks(deadline_ns));
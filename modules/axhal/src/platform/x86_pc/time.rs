@@ -27,6 +27,8 @@ pub fn nanos_to_ticks(nanos: u64) -> u64 {
 /// Set a one-shot timer.
 ///
 /// A timer interrupt will be triggered at the given deadline (in nanoseconds).
+/// If the deadline is beyond the range of the hardware timer, the interrupt is
+/// triggered earlier, and the handler should set the timer again.
 #[cfg(feature = "irq")]
 pub fn set_oneshot_timer(deadline_ns: u64) {
     let lapic = super::apic::local_apic();
@@ -34,8 +36,7 @@ pub fn set_oneshot_timer(deadline_ns: u64) {
     unsafe {
         if now_ns < deadline_ns {
             let apic_ticks = NANOS_TO_LAPIC_TICKS_RATIO.mul_trunc(deadline_ns - now_ns);
-            assert!(apic_ticks <= u32::MAX as u64);
-            lapic.set_timer_initial(apic_ticks.max(1) as u32);
+            lapic.set_timer_initial(apic_ticks.clamp(1, u32::MAX as u64) as u32);
         } else {
             lapic.set_timer_initial(1);
         }
@@ -10,10 +10,18 @@
 //! - [`UdpSocket`]: A UDP socket that provides POSIX-like APIs.
 //! - [`dns_query`]: Function for DNS query.
 //!
+//! The blocking operations of the sockets also have async variants, such as
+//! [`TcpSocket::accept_async`] and [`UdpSocket::recv_from_async`], so that
+//! many connections can be served by the futures on one task (see
+//! [`axtask::future::Executor`]).
+//!
 //! # Cargo Features
 //!
 //! - `smoltcp`: Use [smoltcp] as the underlying network stack. This is enabled
 //!   by default.
+//! - `multitask`: Poll the network stack by a background task while async
+//!   operations are pending, which wakes up the futures waiting for the sockets.
+//!   Without it, the pending futures keep waking themselves up.
 //!
 //! [smoltcp]: https://github.com/smoltcp-rs/smoltcp
 
@@ -35,7 +43,7 @@ cfg_if::cfg_if! {
 pub use self::net_impl::TcpSocket;
 pub use self::net_impl::UdpSocket;
 pub use self::net_impl::{bench_receive, bench_transmit};
-pub use self::net_impl::{dns_query, poll_interfaces};
+pub use self::net_impl::{dns_query, poll_events, poll_interfaces};
 
 use axdriver::{prelude::*, AxDeviceContainer};
 
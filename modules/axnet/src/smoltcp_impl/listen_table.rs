@@ -1,5 +1,6 @@
 use alloc::{boxed::Box, collections::VecDeque};
 use core::ops::{Deref, DerefMut};
+use core::task::Waker;
 
 use axerrno::{ax_err, AxError, AxResult};
 use axsync::Mutex;
@@ -14,6 +15,9 @@ const PORT_NUM: usize = 65536;
 struct ListenTableEntry {
     listen_endpoint: IpListenEndpoint,
     syn_queue: VecDeque<SocketHandle>,
+    /// The waker of a pending async accept, registered on the sockets in the
+    /// SYN queue to be woken up when they are connected.
+    waker: Option<Waker>,
 }
 
 impl ListenTableEntry {
@@ -21,6 +25,7 @@ impl ListenTableEntry {
         Self {
             listen_endpoint,
             syn_queue: VecDeque::with_capacity(LISTEN_QUEUE_SIZE),
+            waker: None,
         }
     }
 
@@ -86,6 +91,19 @@ impl ListenTable {
         }
     }
 
+    /// Registers the waker of an async accept on the listening port, which
+    /// is woken up when the state of a socket in the SYN queue changes.
+    pub fn register_waker(&self, port: u16, waker: &Waker) {
+        if let Some(entry) = self.tcp[port as usize].lock().deref_mut() {
+            for &handle in &entry.syn_queue {
+                SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
+                    socket.register_recv_waker(waker)
+                });
+            }
+            entry.waker = Some(waker.clone());
+        }
+    }
+
     pub fn accept(&self, port: u16) -> AxResult<(SocketHandle, (IpEndpoint, IpEndpoint))> {
         if let Some(entry) = self.tcp[port as usize].lock().deref_mut() {
             let syn_queue = &mut entry.syn_queue;
@@ -127,6 +145,9 @@ impl ListenTable {
                 return;
             }
             let mut socket = SocketSetWrapper::new_tcp_socket();
+            if let Some(waker) = &entry.waker {
+                socket.register_recv_waker(waker);
+            }
             if socket.listen(entry.listen_endpoint).is_ok() {
                 let handle = sockets.add(socket);
                 debug!(
@@ -7,9 +7,13 @@ mod udp;
 
 use alloc::vec;
 use core::cell::RefCell;
+use core::future::Future;
 use core::ops::DerefMut;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::task::{Poll, Waker};
 
 use axdriver::prelude::*;
+use axerrno::{AxError, AxResult};
 use axhal::time::{current_time_nanos, NANOS_PER_MICROS};
 use axsync::Mutex;
 use driver_net::{DevError, NetBufPtr};
@@ -54,6 +58,9 @@ static LISTEN_TABLE: LazyInit<ListenTable> = LazyInit::new();
 static SOCKET_SET: LazyInit<SocketSetWrapper> = LazyInit::new();
 static ETH0: LazyInit<InterfaceWrapper> = LazyInit::new();
 
+/// The number of times that polling the interfaces has processed some packets.
+static POLL_EVENTS: AtomicUsize = AtomicUsize::new(0);
+
 struct SocketSetWrapper<'a>(Mutex<SocketSet<'a>>);
 
 struct DeviceWrapper {
@@ -120,7 +127,9 @@ impl<'a> SocketSetWrapper<'a> {
     }
 
     pub fn poll_interfaces(&self) {
-        ETH0.poll(&self.0);
+        if ETH0.poll(&self.0) {
+            POLL_EVENTS.fetch_add(1, Ordering::Release);
+        }
     }
 
     pub fn remove(&self, handle: SocketHandle) {
@@ -170,12 +179,14 @@ impl InterfaceWrapper {
         };
     }
 
-    pub fn poll(&self, sockets: &Mutex<SocketSet>) {
+    /// Returns `true` if any packets were processed, i.e., the readiness of
+    /// some sockets may have changed.
+    pub fn poll(&self, sockets: &Mutex<SocketSet>) -> bool {
         let mut dev = self.dev.lock();
         let mut iface = self.iface.lock();
         let mut sockets = sockets.lock();
         let timestamp = Self::current_time();
-        iface.poll(timestamp, dev.deref_mut(), &mut sockets);
+        iface.poll(timestamp, dev.deref_mut(), &mut sockets)
     }
 }
 
@@ -300,6 +311,103 @@ pub fn poll_interfaces() {
     SOCKET_SET.poll_interfaces();
 }
 
+/// Returns the number of times that polling the network stack has processed
+/// some packets, including the polls inside socket operations.
+///
+/// The readiness of sockets may have changed when it increases. As the network
+/// stack is not driven by interrupts, it can be checked after
+/// [`poll_interfaces`] to detect socket events.
+pub fn poll_events() -> usize {
+    POLL_EVENTS.load(Ordering::Acquire)
+}
+
+/// Returns a future that completes when `f` completes or fails.
+///
+/// Before calling `f`, the waker of the future is registered by `register` on
+/// the sockets that `f` waits for, and it is woken up when polling the
+/// interfaces changes their states. While `f` returns
+/// [`Err(WouldBlock)`](AxError::WouldBlock), the interfaces are polled by a
+/// background task with the `multitask` feature. Otherwise, the future wakes
+/// itself up, and polls the interfaces in the next round of the executor.
+fn poll_async<F, R, T>(mut f: F, mut register: R) -> impl Future<Output = AxResult<T>>
+where
+    F: FnMut() -> AxResult<T>,
+    R: FnMut(&Waker),
+{
+    #[cfg(feature = "multitask")]
+    let mut waiting = None;
+    core::future::poll_fn(move |cx| {
+        SOCKET_SET.poll_interfaces();
+        register(cx.waker());
+        match f() {
+            Err(AxError::WouldBlock) => {
+                #[cfg(feature = "multitask")]
+                if waiting.is_none() {
+                    waiting = Some(poller::Waiting::new());
+                }
+                #[cfg(not(feature = "multitask"))]
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            res => {
+                #[cfg(feature = "multitask")]
+                {
+                    waiting = None;
+                }
+                Poll::Ready(res)
+            }
+        }
+    })
+}
+
+/// Polls the interfaces in the background while some futures are waiting for
+/// the sockets.
+#[cfg(feature = "multitask")]
+mod poller {
+    use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use core::time::Duration;
+
+    use axtask::WaitQueue;
+
+    use super::SOCKET_SET;
+
+    const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+    /// The number of the futures waiting for the sockets.
+    static NUM_WAITING: AtomicUsize = AtomicUsize::new(0);
+    static POLLER_WQ: WaitQueue = WaitQueue::new();
+
+    /// Keeps the poller running while a future is waiting for the sockets.
+    pub struct Waiting;
+
+    impl Waiting {
+        pub fn new() -> Self {
+            static STARTED: AtomicBool = AtomicBool::new(false);
+            if !STARTED.swap(true, Ordering::AcqRel) {
+                axtask::spawn(poller_task);
+            }
+            if NUM_WAITING.fetch_add(1, Ordering::AcqRel) == 0 {
+                POLLER_WQ.notify_one(true);
+            }
+            Self
+        }
+    }
+
+    impl Drop for Waiting {
+        fn drop(&mut self) {
+            NUM_WAITING.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+
+    fn poller_task() {
+        loop {
+            POLLER_WQ.wait_until(|| NUM_WAITING.load(Ordering::Acquire) > 0);
+            SOCKET_SET.poll_interfaces();
+            axtask::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
 /// Benchmark raw socket transmit bandwidth.
 pub fn bench_transmit() {
     ETH0.dev.lock().bench_transmit_bandwidth();
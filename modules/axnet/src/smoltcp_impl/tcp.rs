@@ -223,17 +223,21 @@ impl TcpSocket {
     ///
     /// It's must be called after [`bind`](Self::bind) and [`listen`](Self::listen).
     pub fn accept(&self) -> AxResult<TcpSocket> {
-        if !self.is_listening() {
-            return ax_err!(InvalidInput, "socket accept() failed: not listen");
-        }
+        let local_port = self.listening_port()?;
+        self.block_on(|| Self::accept_once(local_port))
+    }
 
-        // SAFETY: `self.local_addr` should be initialized after `bind()`.
-        let local_port = unsafe { self.local_addr.get().read().port };
-        self.block_on(|| {
-            let (handle, (local_addr, peer_addr)) = LISTEN_TABLE.accept(local_port)?;
-            debug!("TCP socket accepted a new connection {}", peer_addr);
-            Ok(TcpSocket::new_connected(handle, local_addr, peer_addr))
-        })
+    /// Accepts a new connection asynchronously, see [`accept`](Self::accept).
+    ///
+    /// The returned future waits for the connection regardless of the
+    /// non-blocking flag.
+    pub async fn accept_async(&self) -> AxResult<TcpSocket> {
+        let local_port = self.listening_port()?;
+        super::poll_async(
+            || Self::accept_once(local_port),
+            |waker| LISTEN_TABLE.register_waker(local_port, waker),
+        )
+        .await
     }
 
     /// Close the connection.
@@ -271,65 +275,50 @@ impl TcpSocket {
 
     /// Receives data from the socket, stores it in the given buffer.
     pub fn recv(&self, buf: &mut [u8]) -> AxResult<usize> {
-        if self.is_connecting() {
-            return Err(AxError::WouldBlock);
-        } else if !self.is_connected() {
-            return ax_err!(NotConnected, "socket recv() failed");
-        }
+        let handle = self.stream_handle("socket recv() failed")?;
+        self.block_on(|| Self::recv_once(handle, buf))
+    }
 
-        // SAFETY: `self.handle` should be initialized in a connected socket.
-        let handle = unsafe { self.handle.get().read().unwrap() };
-        self.block_on(|| {
-            SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
-                if !socket.is_active() {
-                    // not open
-                    ax_err!(ConnectionRefused, "socket recv() failed")
-                } else if !socket.may_recv() {
-                    // connection closed
-                    Ok(0)
-                } else if socket.recv_queue() > 0 {
-                    // data available
-                    // TODO: use socket.recv(|buf| {...})
-                    let len = socket
-                        .recv_slice(buf)
-                        .map_err(|_| ax_err_type!(BadState, "socket recv() failed"))?;
-                    Ok(len)
-                } else {
-                    // no more data
-                    Err(AxError::WouldBlock)
-                }
-            })
-        })
+    /// Receives data from the socket asynchronously, see [`recv`](Self::recv).
+    ///
+    /// The returned future waits for the data regardless of the non-blocking
+    /// flag, and for the connection if the socket is still connecting.
+    pub async fn recv_async(&self, buf: &mut [u8]) -> AxResult<usize> {
+        let handle = self.connection_handle("socket recv() failed")?;
+        super::poll_async(
+            || Self::recv_once(self.poll_stream_handle("socket recv() failed")?, buf),
+            |waker| {
+                SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
+                    socket.register_recv_waker(waker)
+                })
+            },
+        )
+        .await
     }
 
     /// Transmits data in the given buffer.
     pub fn send(&self, buf: &[u8]) -> AxResult<usize> {
-        if self.is_connecting() {
-            return Err(AxError::WouldBlock);
-        } else if !self.is_connected() {
-            return ax_err!(NotConnected, "socket send() failed");
-        }
+        let handle = self.stream_handle("socket send() failed")?;
+        self.block_on(|| Self::send_once(handle, buf))
+    }
 
-        // SAFETY: `self.handle` should be initialized in a connected socket.
-        let handle = unsafe { self.handle.get().read().unwrap() };
-        self.block_on(|| {
-            SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
-                if !socket.is_active() || !socket.may_send() {
-                    // closed by remote
-                    ax_err!(ConnectionReset, "socket send() failed")
-                } else if socket.can_send() {
-                    // connected, and the tx buffer is not full
-                    // TODO: use socket.send(|buf| {...})
-                    let len = socket
-                        .send_slice(buf)
-                        .map_err(|_| ax_err_type!(BadState, "socket send() failed"))?;
-                    Ok(len)
-                } else {
-                    // tx buffer is full
-                    Err(AxError::WouldBlock)
-                }
-            })
-        })
+    /// Transmits data in the given buffer asynchronously, see
+    /// [`send`](Self::send).
+    ///
+    /// The returned future waits for the space in the transmit buffer
+    /// regardless of the non-blocking flag, and for the connection if the
+    /// socket is still connecting.
+    pub async fn send_async(&self, buf: &[u8]) -> AxResult<usize> {
+        let handle = self.connection_handle("socket send() failed")?;
+        super::poll_async(
+            || Self::send_once(self.poll_stream_handle("socket send() failed")?, buf),
+            |waker| {
+                SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
+                    socket.register_send_waker(waker)
+                })
+            },
+        )
+        .await
     }
 
     /// Whether the socket is readable or writable.
@@ -341,6 +330,7 @@ impl TcpSocket {
             _ => Ok(PollState {
                 readable: false,
                 writable: false,
+                hangup: false,
             }),
         }
     }
@@ -446,6 +436,7 @@ impl TcpSocket {
         Ok(PollState {
             readable: false,
             writable,
+            hangup: false,
         })
     }
 
@@ -456,6 +447,7 @@ impl TcpSocket {
             Ok(PollState {
                 readable: !socket.may_recv() || socket.can_recv(),
                 writable: !socket.may_send() || socket.can_send(),
+                hangup: false,
             })
         })
     }
@@ -466,6 +458,7 @@ impl TcpSocket {
         Ok(PollState {
             readable: LISTEN_TABLE.can_accept(local_addr.port)?,
             writable: false,
+            hangup: false,
         })
     }
 
@@ -491,6 +484,93 @@ impl TcpSocket {
             }
         }
     }
+
+    /// Returns the local port of a listening socket.
+    fn listening_port(&self) -> AxResult<u16> {
+        if !self.is_listening() {
+            return ax_err!(InvalidInput, "socket accept() failed: not listen");
+        }
+        // SAFETY: `self.local_addr` should be initialized after `bind()`.
+        Ok(unsafe { self.local_addr.get().read().port })
+    }
+
+    /// Returns the handle of a connected socket, `msg` is logged if it is not
+    /// connected.
+    fn stream_handle(&self, msg: &str) -> AxResult<SocketHandle> {
+        if self.is_connecting() {
+            return Err(AxError::WouldBlock);
+        } else if !self.is_connected() {
+            return ax_err!(NotConnected, msg);
+        }
+        // SAFETY: `self.handle` should be initialized in a connected socket.
+        Ok(unsafe { self.handle.get().read().unwrap() })
+    }
+
+    /// Like [`stream_handle`](Self::stream_handle), but a connecting socket
+    /// checks whether it has been connected first.
+    fn poll_stream_handle(&self, msg: &str) -> AxResult<SocketHandle> {
+        if self.is_connecting() {
+            self.poll_connect()?;
+        }
+        self.stream_handle(msg)
+    }
+
+    /// Returns the handle of a connecting or connected socket, `msg` is
+    /// logged if it is neither.
+    fn connection_handle(&self, msg: &str) -> AxResult<SocketHandle> {
+        if !self.is_connecting() && !self.is_connected() {
+            return ax_err!(NotConnected, msg);
+        }
+        // SAFETY: `self.handle` should be initialized in a connecting socket.
+        Ok(unsafe { self.handle.get().read().unwrap() })
+    }
+
+    fn accept_once(local_port: u16) -> AxResult<TcpSocket> {
+        let (handle, (local_addr, peer_addr)) = LISTEN_TABLE.accept(local_port)?;
+        debug!("TCP socket accepted a new connection {}", peer_addr);
+        Ok(TcpSocket::new_connected(handle, local_addr, peer_addr))
+    }
+
+    fn recv_once(handle: SocketHandle, buf: &mut [u8]) -> AxResult<usize> {
+        SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
+            if !socket.is_active() {
+                // not open
+                ax_err!(ConnectionRefused, "socket recv() failed")
+            } else if !socket.may_recv() {
+                // connection closed
+                Ok(0)
+            } else if socket.recv_queue() > 0 {
+                // data available
+                // TODO: use socket.recv(|buf| {...})
+                let len = socket
+                    .recv_slice(buf)
+                    .map_err(|_| ax_err_type!(BadState, "socket recv() failed"))?;
+                Ok(len)
+            } else {
+                // no more data
+                Err(AxError::WouldBlock)
+            }
+        })
+    }
+
+    fn send_once(handle: SocketHandle, buf: &[u8]) -> AxResult<usize> {
+        SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
+            if !socket.is_active() || !socket.may_send() {
+                // closed by remote
+                ax_err!(ConnectionReset, "socket send() failed")
+            } else if socket.can_send() {
+                // connected, and the tx buffer is not full
+                // TODO: use socket.send(|buf| {...})
+                let len = socket
+                    .send_slice(buf)
+                    .map_err(|_| ax_err_type!(BadState, "socket send() failed"))?;
+                Ok(len)
+            } else {
+                // tx buffer is full
+                Err(AxError::WouldBlock)
+            }
+        })
+    }
 }
 
 impl Drop for TcpSocket {
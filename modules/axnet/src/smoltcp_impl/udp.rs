@@ -118,6 +118,19 @@ impl UdpSocket {
         })
     }
 
+    /// Receives a single datagram message on the socket asynchronously, see
+    /// [`recv_from`](Self::recv_from).
+    ///
+    /// The returned future waits for the message regardless of the
+    /// non-blocking flag.
+    pub async fn recv_from_async(&self, buf: &mut [u8]) -> AxResult<(usize, SocketAddr)> {
+        self.recv_impl_async(|socket| match socket.recv_slice(buf) {
+            Ok((len, meta)) => Ok((len, into_core_sockaddr(meta.endpoint))),
+            Err(_) => ax_err!(BadState, "socket recv_from() failed"),
+        })
+        .await
+    }
+
     /// Receives a single datagram message on the socket, without removing it from
     /// the queue. On success, returns the number of bytes read and the origin.
     pub fn peek_from(&self, buf: &mut [u8]) -> AxResult<(usize, SocketAddr)> {
@@ -186,12 +199,14 @@ impl UdpSocket {
             return Ok(PollState {
                 readable: false,
                 writable: false,
+                hangup: false,
             });
         }
         SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(self.handle, |socket| {
             Ok(PollState {
                 readable: socket.can_recv(),
                 writable: socket.can_send(),
+                hangup: false,
             })
         })
     }
@@ -239,16 +254,40 @@ impl UdpSocket {
             return ax_err!(NotConnected, "socket send() failed");
         }
 
-        self.block_on(|| {
-            SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(self.handle, |socket| {
-                if socket.can_recv() {
-                    // data available
-                    op(socket)
-                } else {
-                    // no more data
-                    Err(AxError::WouldBlock)
-                }
-            })
+        self.block_on(|| self.recv_once(&mut op))
+    }
+
+    async fn recv_impl_async<F, T>(&self, mut op: F) -> AxResult<T>
+    where
+        F: FnMut(&mut udp::Socket) -> AxResult<T>,
+    {
+        if self.local_addr.read().is_none() {
+            return ax_err!(NotConnected, "socket send() failed");
+        }
+
+        super::poll_async(
+            || self.recv_once(&mut op),
+            |waker| {
+                SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(self.handle, |socket| {
+                    socket.register_recv_waker(waker)
+                })
+            },
+        )
+        .await
+    }
+
+    fn recv_once<F, T>(&self, op: &mut F) -> AxResult<T>
+    where
+        F: FnMut(&mut udp::Socket) -> AxResult<T>,
+    {
+        SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(self.handle, |socket| {
+            if socket.can_recv() {
+                // data available
+                op(socket)
+            } else {
+                // no more data
+                Err(AxError::WouldBlock)
+            }
         })
     }
 
@@ -191,6 +191,11 @@ pub extern "C" fn rust_main(cpu_id: usize, dtb: usize) -> ! {
 
     unsafe { main() };
 
+    #[cfg(feature = "fs")]
+    if let Err(e) = axfs::api::sync() {
+        warn!("failed to sync filesystems: {:?}", e);
+    }
+
     #[cfg(feature = "multitask")]
     axtask::exit(0);
     #[cfg(not(feature = "multitask"))]
@@ -259,29 +264,39 @@ fn remap_kernel_memory() -> Result<(), axhal::paging::PagingError> {
 fn init_interrupt() {
     use axhal::time::TIMER_IRQ_NUM;
 
-    // Setup timer interrupt handler
-    const PERIODIC_INTERVAL_NANOS: u64 =
-        axhal::time::NANOS_PER_SEC / axconfig::TICKS_PER_SEC as u64;
-
-    #[percpu::def_percpu]
-    static NEXT_DEADLINE: u64 = 0;
+    // Setup timer interrupt handler. The task manager sets the timer by itself
+    // for tickless idle, otherwise the timer fires periodically.
+    #[cfg(feature = "multitask")]
+    axhal::irq::register_handler(TIMER_IRQ_NUM, axtask::on_timer_tick);
 
-    fn update_timer() {
-        let now_ns = axhal::time::current_time_nanos();
-        // Safety: we have disabled preemption in IRQ handler.
-        let mut deadline = unsafe { NEXT_DEADLINE.read_current_raw() };
-        if now_ns >= deadline {
-            deadline = now_ns + PERIODIC_INTERVAL_NANOS;
+    #[cfg(not(feature = "multitask"))]
+    {
+        const PERIODIC_INTERVAL_NANOS: u64 =
+            axhal::time::NANOS_PER_SEC / axconfig::TICKS_PER_SEC as u64;
+
+        #[percpu::def_percpu]
+        static NEXT_DEADLINE: u64 = 0;
+
+        fn update_timer() {
+            let now_ns = axhal::time::current_time_nanos();
+            // Safety: we have disabled preemption in IRQ handler.
+            let mut deadline = unsafe { NEXT_DEADLINE.read_current_raw() };
+            if now_ns >= deadline {
+                deadline = now_ns + PERIODIC_INTERVAL_NANOS;
+            }
+            unsafe { NEXT_DEADLINE.write_current_raw(deadline + PERIODIC_INTERVAL_NANOS) };
+            axhal::time::set_oneshot_timer(deadline);
         }
-        unsafe { NEXT_DEADLINE.write_current_raw(deadline + PERIODIC_INTERVAL_NANOS) };
-        axhal::time::set_oneshot_timer(deadline);
+
+        axhal::irq::register_handler(TIMER_IRQ_NUM, update_timer);
     }
 
-    axhal::irq::register_handler(TIMER_IRQ_NUM, || {
-        update_timer();
-        #[cfg(feature = "multitask")]
-        axtask::on_timer_tick();
-    });
+    // IPIs wake up idle CPUs, which then check their run queues, and ask busy
+    // CPUs to reschedule after waking up tasks on them.
+    #[cfg(all(feature = "smp", feature = "multitask"))]
+    axhal::irq::register_handler(axhal::irq::IPI_IRQ_NUM, axtask::on_ipi);
+    #[cfg(all(feature = "smp", not(feature = "multitask")))]
+    axhal::irq::register_handler(axhal::irq::IPI_IRQ_NUM, || {});
 
     // Enable IRQs before starting app
     axhal::arch::enable_irqs();
@@ -6,7 +6,11 @@ impl axhal::trap::TrapHandler for TrapHandlerImpl {
         #[cfg(feature = "irq")]
         {
             let guard = kernel_guard::NoPreempt::new();
+            #[cfg(feature = "multitask")]
+            axtask::enter_kernel();
             axhal::irq::dispatch_irq(_irq_num);
+            #[cfg(feature = "multitask")]
+            axtask::leave_kernel();
             drop(guard); // rescheduling may occur when preemption is re-enabled.
         }
     }
@@ -3,26 +3,113 @@
 //! Currently supported primitives:
 //!
 //! - [`Mutex`]: A mutual exclusion primitive.
+//! - [`PiMutex`]: A mutual exclusion primitive with priority inheritance.
+//! - [`Condvar`]: A condition variable.
+//! - [`RwLock`]: A readers-writer lock.
+//! - [`Semaphore`]: A counting semaphore.
+//! - [`Barrier`]: A barrier for tasks to rendezvous.
 //! - mod [`spin`](spinlock): spin-locks.
 //!
 //! # Cargo Features
 //!
 //! - `multitask`: For use in the multi-threaded environments. If the feature is
-//!   not enabled, [`Mutex`] will be an alias of [`spin::SpinNoIrq`]. This
-//!   feature is enabled by default.
+//!   not enabled, [`Mutex`] will be an alias of [`spin::SpinNoIrq`], and other
+//!   primitives are not available. This feature is enabled by default.
+//! - `irq`: Timed waits (e.g., [`Condvar::wait_timeout`]) block the task until
+//!   the timer fires. If the feature is not enabled, they keep yielding the CPU
+//!   until the condition is met or the time is up.
 
 #![cfg_attr(not(test), no_std)]
 #![feature(doc_cfg)]
 
 pub use spinlock as spin;
 
+#[cfg(feature = "multitask")]
+mod barrier;
+#[cfg(feature = "multitask")]
+mod condvar;
 #[cfg(feature = "multitask")]
 mod mutex;
+#[cfg(feature = "multitask")]
+mod pi_mutex;
+#[cfg(feature = "multitask")]
+mod rwlock;
+#[cfg(feature = "multitask")]
+mod semaphore;
 
 #[cfg(feature = "multitask")]
 #[doc(cfg(feature = "multitask"))]
-pub use self::mutex::{Mutex, MutexGuard};
+pub use self::{
+    barrier::{Barrier, BarrierWaitResult},
+    condvar::{Condvar, WaitTimeoutResult},
+    mutex::{Mutex, MutexGuard},
+    pi_mutex::{PiMutex, PiMutexGuard},
+    rwlock::{RwLock, RwLockReadGuard, RwLockWriteGuard},
+    semaphore::Semaphore,
+};
+
+#[cfg(test)]
+mod tests;
 
 #[cfg(not(feature = "multitask"))]
 #[doc(cfg(not(feature = "multitask")))]
 pub use spinlock::{SpinNoIrq as Mutex, SpinNoIrqGuard as MutexGuard};
+
+/// Blocks the current task on the wait queue until `condition` becomes true,
+/// or `dur` has elapsed. Returns `true` if it timed out.
+#[cfg(feature = "multitask")]
+fn wait_timeout_until<F>(wq: &axtask::WaitQueue, dur: core::time::Duration, condition: F) -> bool
+where
+    F: Fn() -> bool,
+{
+    #[cfg(feature = "irq")]
+    {
+        wq.wait_timeout_until(dur, condition)
+    }
+    #[cfg(not(feature = "irq"))]
+    {
+        let _ = wq;
+        yield_until(dur, condition, false)
+    }
+}
+
+/// Same as [`wait_timeout_until`], but it also returns `true` at once if the
+/// current task is [canceled](axtask::cancel).
+#[cfg(feature = "multitask")]
+fn wait_timeout_until_cancelable<F>(
+    wq: &axtask::WaitQueue,
+    dur: core::time::Duration,
+    condition: F,
+) -> bool
+where
+    F: Fn() -> bool,
+{
+    #[cfg(feature = "irq")]
+    {
+        wq.wait_timeout_until_cancelable(dur, condition)
+    }
+    #[cfg(not(feature = "irq"))]
+    {
+        let _ = wq;
+        yield_until(dur, condition, true)
+    }
+}
+
+/// Keeps yielding the CPU until `condition` becomes true, or `dur` has
+/// elapsed, as there are no timers to wake up the task.
+#[cfg(all(feature = "multitask", not(feature = "irq")))]
+fn yield_until<F>(dur: core::time::Duration, condition: F, cancelable: bool) -> bool
+where
+    F: Fn() -> bool,
+{
+    let deadline = axhal::time::current_time() + dur;
+    while !condition() {
+        if axhal::time::current_time() >= deadline
+            || (cancelable && axtask::current().is_cancel_pending())
+        {
+            return true;
+        }
+        axtask::yield_now();
+    }
+    false
+}
@@ -23,7 +23,7 @@ pub struct Mutex<T: ?Sized> {
 ///
 /// When the guard falls out of scope it will release the lock.
 pub struct MutexGuard<'a, T: ?Sized + 'a> {
-    lock: &'a Mutex<T>,
+    pub(crate) lock: &'a Mutex<T>,
     data: *mut T,
 }
 
@@ -118,6 +118,21 @@ impl<T: ?Sized> Mutex<T> {
         }
     }
 
+    /// Creates a guard of the [`Mutex`] without locking it.
+    ///
+    /// # Safety
+    ///
+    /// The mutex must be locked by the current thread, and the guard that
+    /// locked it must have been discarded (e.g., by [`core::mem::forget`]).
+    /// This can be useful for exposing the lock to FFI.
+    #[inline(always)]
+    pub unsafe fn make_guard_unchecked(&self) -> MutexGuard<T> {
+        MutexGuard {
+            lock: self,
+            data: &mut *self.data.get(),
+        }
+    }
+
     /// Force unlock the [`Mutex`].
     ///
     /// # Safety
@@ -201,9 +216,6 @@ impl<'a, T: ?Sized> Drop for MutexGuard<'a, T> {
 mod tests {
     use crate::Mutex;
     use axtask as thread;
-    use std::sync::Once;
-
-    static INIT: Once = Once::new();
 
     fn may_interrupt() {
         // simulate interrupts
@@ -214,7 +226,7 @@ mod tests {
 
     #[test]
     fn lots_and_lots() {
-        INIT.call_once(thread::init_scheduler);
+        let _lock = crate::tests::init();
 
         const NUM_TASKS: u32 = 10;
         const NUM_ITERS: u32 = 10_000;
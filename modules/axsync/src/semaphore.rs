@@ -0,0 +1,82 @@
+//! A counting semaphore based on the wait queue.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration;
+
+use axtask::WaitQueue;
+
+/// A counting semaphore.
+///
+/// It maintains a number of permits. [`acquire`](Semaphore::acquire) takes a
+/// permit, blocking the current task until one is available, and
+/// [`release`](Semaphore::release) adds a permit back, waking up a waiting
+/// task.
+pub struct Semaphore {
+    wq: WaitQueue,
+    count: AtomicUsize,
+}
+
+impl Semaphore {
+    /// Creates a new semaphore with the initial number of permits.
+    pub const fn new(count: usize) -> Self {
+        Self {
+            wq: WaitQueue::new(),
+            count: AtomicUsize::new(count),
+        }
+    }
+
+    /// Returns the number of available permits.
+    ///
+    /// The result should be considered 'out of date' the instant it is called.
+    pub fn available_permits(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Acquires a permit, blocking the current task until one is available.
+    pub fn acquire(&self) {
+        while !self.try_acquire() {
+            self.wq.wait_until(|| self.available_permits() > 0);
+        }
+    }
+
+    /// Acquires a permit, blocking the current task until one is available,
+    /// or the specified duration has elapsed.
+    ///
+    /// Returns `false` if it timed out.
+    pub fn acquire_timeout(&self, dur: Duration) -> bool {
+        let deadline = axhal::time::current_time() + dur;
+        while !self.try_acquire() {
+            let now = axhal::time::current_time();
+            if now >= deadline {
+                return false;
+            }
+            crate::wait_timeout_until(&self.wq, deadline - now, || self.available_permits() > 0);
+        }
+        true
+    }
+
+    /// Tries to acquire a permit without blocking.
+    ///
+    /// Returns `false` if no permit is available.
+    pub fn try_acquire(&self) -> bool {
+        let mut count = self.count.load(Ordering::Relaxed);
+        while count > 0 {
+            match self.count.compare_exchange_weak(
+                count,
+                count - 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return true,
+                Err(c) => count = c,
+            }
+        }
+        false
+    }
+
+    /// Releases a permit, and wakes up a task waiting for it.
+    pub fn release(&self) {
+        self.count.fetch_add(1, Ordering::Release);
+        self.wq.notify_one(true);
+    }
+}
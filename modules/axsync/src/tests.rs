@@ -0,0 +1,171 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration;
+use std::sync::{Mutex as StdMutex, MutexGuard as StdMutexGuard, Once};
+
+use crate::{Barrier, Condvar, Mutex, PiMutex, RwLock, Semaphore};
+use axtask as thread;
+
+static INIT: Once = Once::new();
+static SERIAL: StdMutex<()> = StdMutex::new(());
+
+/// Initializes the scheduler, and runs tests one by one.
+pub(crate) fn init() -> StdMutexGuard<'static, ()> {
+    let lock = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+    INIT.call_once(thread::init_scheduler);
+    lock
+}
+
+#[test]
+fn test_condvar() {
+    let _lock = init();
+
+    static PAIR: (Mutex<bool>, Condvar) = (Mutex::new(false), Condvar::new());
+
+    let task = thread::spawn(|| {
+        let (lock, cvar) = &PAIR;
+        *lock.lock() = true;
+        cvar.notify_one();
+    });
+
+    let (lock, cvar) = &PAIR;
+    let started = cvar.wait_while(lock.lock(), |started| !*started);
+    assert!(*started);
+    drop(started);
+    task.join();
+
+    // nobody notifies
+    let (guard, result) = cvar.wait_timeout(lock.lock(), Duration::from_millis(10));
+    assert!(result.timed_out());
+    drop(guard);
+
+    // the cancelable waits return when the task is canceled
+    let task = thread::spawn(|| {
+        let (lock, cvar) = &PAIR;
+        drop(cvar.wait_cancelable(lock.lock()));
+        let (guard, result) = cvar.wait_timeout_cancelable(lock.lock(), Duration::from_secs(3600));
+        assert!(result.timed_out());
+        drop(guard);
+    });
+    thread::cancel(&task);
+    task.join();
+}
+
+#[test]
+fn test_pi_mutex() {
+    let _lock = init();
+
+    const NUM_TASKS: u32 = 5;
+    const NUM_ITERS: u32 = 1000;
+    static M: PiMutex<u32> = PiMutex::new(0);
+
+    let tasks: Vec<_> = (0..NUM_TASKS)
+        .map(|_| {
+            thread::spawn(|| {
+                for _ in 0..NUM_ITERS {
+                    let mut val = M.lock();
+                    *val += 1;
+                    thread::yield_now();
+                    drop(val);
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.join();
+    }
+
+    assert!(!M.is_locked());
+    assert_eq!(*M.try_lock().unwrap(), NUM_TASKS * NUM_ITERS);
+}
+
+#[test]
+fn test_rwlock() {
+    let _lock = init();
+
+    const NUM_TASKS: usize = 10;
+    static LOCK: RwLock<usize> = RwLock::new(0);
+    static FINISHED_TASKS: AtomicUsize = AtomicUsize::new(0);
+
+    let reader = LOCK.read();
+    assert!(LOCK.try_read().is_some());
+    assert!(LOCK.try_write().is_none());
+    for _ in 0..NUM_TASKS {
+        thread::spawn(|| {
+            *LOCK.write() += 1;
+            let value = *LOCK.read();
+            assert!(value > 0);
+            FINISHED_TASKS.fetch_add(1, Ordering::Relaxed);
+        });
+    }
+    thread::yield_now();
+    // writers are blocked by the reader
+    assert_eq!(*reader, 0);
+    drop(reader);
+
+    while FINISHED_TASKS.load(Ordering::Relaxed) < NUM_TASKS {
+        thread::yield_now();
+    }
+    assert_eq!(*LOCK.read(), NUM_TASKS);
+}
+
+#[test]
+fn test_semaphore() {
+    let _lock = init();
+
+    static SEM: Semaphore = Semaphore::new(0);
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    assert!(!SEM.try_acquire());
+    assert!(!SEM.acquire_timeout(Duration::from_millis(10)));
+
+    let task = thread::spawn(|| {
+        for _ in 0..3 {
+            SEM.acquire();
+            COUNTER.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+    for i in 0..3 {
+        SEM.release();
+        while COUNTER.load(Ordering::Relaxed) == i {
+            thread::yield_now();
+        }
+    }
+    task.join();
+    assert_eq!(COUNTER.load(Ordering::Relaxed), 3);
+    assert_eq!(SEM.available_permits(), 0);
+}
+
+#[test]
+fn test_barrier() {
+    let _lock = init();
+
+    const NUM_TASKS: usize = 5;
+    static BARRIER: Barrier = Barrier::new(NUM_TASKS);
+    static ARRIVED: AtomicUsize = AtomicUsize::new(0);
+    static LEADERS: AtomicUsize = AtomicUsize::new(0);
+
+    let tasks: Vec<_> = (0..NUM_TASKS - 1)
+        .map(|_| {
+            thread::spawn(|| {
+                for _ in 0..2 {
+                    ARRIVED.fetch_add(1, Ordering::Relaxed);
+                    if BARRIER.wait().is_leader() {
+                        LEADERS.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for round in 1..=2 {
+        ARRIVED.fetch_add(1, Ordering::Relaxed);
+        if BARRIER.wait().is_leader() {
+            LEADERS.fetch_add(1, Ordering::Relaxed);
+        }
+        assert!(ARRIVED.load(Ordering::Relaxed) >= NUM_TASKS * round);
+    }
+    for task in tasks {
+        task.join();
+    }
+    assert_eq!(LEADERS.load(Ordering::Relaxed), 2);
+}
@@ -1,19 +1,41 @@
 //! Task APIs for multi-task configuration.
 
-use alloc::{string::String, sync::Arc};
+use alloc::{string::String, sync::Arc, vec::Vec};
 
-pub(crate) use crate::run_queue::{AxRunQueue, RUN_QUEUE};
+pub(crate) use crate::run_queue::{current_run_queue, AxRunQueue};
 
 #[doc(cfg(feature = "multitask"))]
-pub use crate::task::{CurrentTask, TaskId, TaskInner};
+pub use crate::pi_lock::PiLock;
+#[doc(cfg(feature = "multitask"))]
+pub use crate::stats::{CpuUsage, TaskStats};
+#[doc(cfg(feature = "multitask"))]
+pub use crate::task::{CpuMask, CurrentTask, TaskId, TaskInner, TaskState};
 #[doc(cfg(feature = "multitask"))]
 pub use crate::wait_queue::WaitQueue;
 
+#[cfg(feature = "irq")]
+#[doc(cfg(feature = "irq"))]
+pub use crate::timers::Alarm;
+#[cfg(feature = "sched_rt")]
+#[doc(cfg(feature = "sched_rt"))]
+pub use scheduler::{RTPolicy, RT_PRIO_LEVELS};
+
 /// The reference type of a task.
 pub type AxTaskRef = Arc<AxTask>;
 
 cfg_if::cfg_if! {
-    if #[cfg(feature = "sched_rr")] {
+    if #[cfg(all(feature = "sched_rt", feature = "sched_cfs"))] {
+        const MAX_TIME_SLICE: usize = 5;
+        pub(crate) type AxTask = scheduler::CFSTask<scheduler::RTTask<TaskInner, MAX_TIME_SLICE>>;
+        pub(crate) type Scheduler = scheduler::RTCFScheduler<TaskInner, MAX_TIME_SLICE>;
+    } else if #[cfg(feature = "sched_rt")] {
+        const MAX_TIME_SLICE: usize = 5;
+        pub(crate) type AxTask = scheduler::RTTask<TaskInner, MAX_TIME_SLICE>;
+        pub(crate) type Scheduler = scheduler::RTScheduler<TaskInner, MAX_TIME_SLICE>;
+    } else if #[cfg(feature = "sched_edf")] {
+        pub(crate) type AxTask = scheduler::EDFTask<TaskInner>;
+        pub(crate) type Scheduler = scheduler::EDFScheduler<TaskInner>;
+    } else if #[cfg(feature = "sched_rr")] {
         const MAX_TIME_SLICE: usize = 5;
         pub(crate) type AxTask = scheduler::RRTask<TaskInner, MAX_TIME_SLICE>;
         pub(crate) type Scheduler = scheduler::RRScheduler<TaskInner, MAX_TIME_SLICE>;
@@ -61,6 +83,90 @@ pub fn current() -> CurrentTask {
     CurrentTask::get()
 }
 
+/// Returns all tasks that are not dropped yet, including exited ones and the
+/// idle tasks, in the order of their IDs.
+pub fn tasks() -> Vec<AxTaskRef> {
+    crate::task::all_tasks()
+}
+
+/// Returns the task with the ID, or [`None`] if it does not exist or is
+/// dropped.
+pub fn find_task(id: u64) -> Option<AxTaskRef> {
+    crate::task::find_task(id)
+}
+
+/// Returns the task with the name, or [`None`] if it does not exist or is
+/// dropped. If several tasks have the name, the one with the smallest ID is
+/// returned.
+pub fn find_task_by_name(name: &str) -> Option<AxTaskRef> {
+    crate::task::find_task_by_name(name)
+}
+
+/// Requests a task to be canceled.
+///
+/// The cancellation is cooperative: the task is not stopped, but its
+/// [`is_cancel_pending`](TaskInner::is_cancel_pending) flag is set. The
+/// cancellation points, i.e., [`sleep`], [`sleep_until`],
+/// [`TaskInner::join_cancelable`] and the `_cancelable` waits of
+/// [`WaitQueue`], return at once when the flag is set, and the task blocked in
+/// them is woken up. The task should check the flag after them, and exit by
+/// itself. The other blocking operations, such as [`WaitQueue::wait`] and
+/// locks, are not affected.
+///
+/// Returns `false` if the task has exited.
+pub fn cancel(task: &AxTaskRef) -> bool {
+    current_run_queue().cancel_task(task)
+}
+
+/// Withdraws the cancellation request of the current task, so that the
+/// cancellation points block again until it is [canceled](cancel) again.
+pub fn clear_cancel() {
+    current().clear_cancel_pending();
+}
+
+/// Detaches a task, so that it is not joined by anyone, and dropped by the
+/// GC task as soon as it exits.
+///
+/// The task is kept only until the other references to it are dropped, and
+/// [`join`](TaskInner::join) on it returns [`None`] at once.
+pub fn detach(task: AxTaskRef) {
+    debug!("task detach: {}", task.id_name());
+    task.set_detached();
+    let cpu_id = task.cpu_id();
+    drop(task);
+    // it may have exited while we held the reference
+    crate::run_queue::wake_gc(cpu_id);
+}
+
+/// Returns the statistics of all tasks that are not dropped yet, in the order
+/// of their IDs, see [`tasks`].
+pub fn task_stats() -> Vec<TaskStats> {
+    crate::task::all_tasks()
+        .iter()
+        .map(|task| task.stats())
+        .collect()
+}
+
+/// Returns the total CPU usage of all tasks except the idle tasks, including
+/// the exited and dropped ones.
+pub fn total_cpu_usage() -> CpuUsage {
+    crate::stats::total_cpu_usage()
+}
+
+/// Marks the current task entering the kernel, e.g., at the beginning of a
+/// system call or an interrupt handler. The time until the matching
+/// [`leave_kernel`] is accounted as the kernel time of the task.
+///
+/// The calls can be nested.
+pub fn enter_kernel() {
+    crate::stats::enter_kernel();
+}
+
+/// Marks the current task leaving the kernel, see [`enter_kernel`].
+pub fn leave_kernel() {
+    crate::stats::leave_kernel();
+}
+
 /// Initializes the task scheduler (for the primary CPU).
 pub fn init_scheduler() {
     info!("Initialize scheduling...");
@@ -77,14 +183,26 @@ pub fn init_scheduler_secondary() {
     crate::run_queue::init_secondary();
 }
 
-/// Handles periodic timer ticks for the task manager.
+/// Handles timer interrupts for the task manager.
 ///
-/// For example, advance scheduler states, checks timed events, etc.
+/// For example, advance scheduler states, checks timed events, etc. It also
+/// sets the timer of the current CPU for the next interrupt, at the next
+/// periodic tick or the nearest timed event. The periodic ticks are stopped
+/// while the CPU is idle.
 #[cfg(feature = "irq")]
 #[doc(cfg(feature = "irq"))]
 pub fn on_timer_tick() {
-    crate::timers::check_events();
-    RUN_QUEUE.lock().scheduler_timer_tick();
+    crate::timers::on_timer_irq();
+}
+
+/// Handles inter-processor interrupts for the task manager.
+///
+/// Other CPUs send IPIs to wake up this CPU if it is idle, or to reschedule
+/// it after waking up tasks on it, which may preempt the current task.
+#[cfg(all(feature = "smp", feature = "irq"))]
+#[doc(cfg(all(feature = "smp", feature = "irq")))]
+pub fn on_ipi() {
+    crate::run_queue::on_ipi();
 }
 
 /// Spawns a new task with the given parameters.
@@ -95,10 +213,40 @@ where
     F: FnOnce() + Send + 'static,
 {
     let task = TaskInner::new(f, name, stack_size);
-    RUN_QUEUE.lock().add_task(task.clone());
+    current_run_queue().add_task(task.clone());
     task
 }
 
+/// Spawns a new task with the given parameters, which runs only on the CPUs
+/// in `cpumask`.
+///
+/// Returns the task reference.
+pub fn spawn_raw_on<F>(f: F, name: String, stack_size: usize, cpumask: CpuMask) -> AxTaskRef
+where
+    F: FnOnce() + Send + 'static,
+{
+    let task = TaskInner::new(f, name, stack_size);
+    task.set_cpumask(cpumask);
+    current_run_queue().add_task(task.clone());
+    task
+}
+
+/// Spawns a new task with the default parameters, which runs only on the
+/// given CPU.
+///
+/// Returns the task reference.
+///
+/// # Panics
+///
+/// Panics if `cpu_id` is not less than [`axconfig::SMP`].
+pub fn spawn_on<F>(f: F, cpu_id: usize) -> AxTaskRef
+where
+    F: FnOnce() + Send + 'static,
+{
+    let cpumask = CpuMask::one(cpu_id);
+    spawn_raw_on(f, "".into(), axconfig::TASK_STACK_SIZE, cpumask)
+}
+
 /// Spawns a new task with the default parameters.
 ///
 /// The default task name is an empty string. The default task stack size is
@@ -112,28 +260,71 @@ where
     spawn_raw(f, "".into(), axconfig::TASK_STACK_SIZE)
 }
 
-/// Set the priority for current task.
+/// Set the priority for a task.
+///
+/// The task can be the current task, a task running on another CPU, a ready
+/// task, or a blocked one. A ready task is requeued according to the new
+/// priority.
 ///
 /// The range of the priority is dependent on the underlying scheduler. For
 /// example, in the [CFS] scheduler, the priority is the nice value, ranging from
 /// -20 to 19.
 ///
-/// Returns `true` if the priority is set successfully.
+/// If the task holds [`PiLock`]s and runs with a priority inherited from the
+/// waiters, the new priority takes effect after the locks are released.
+///
+/// Returns `true` if the priority is set successfully. It fails if the
+/// scheduler does not support priorities, the priority is out of range, or
+/// the task is an idle task or has exited.
 ///
 /// [CFS]: https://en.wikipedia.org/wiki/Completely_Fair_Scheduler
-pub fn set_priority(prio: isize) -> bool {
-    RUN_QUEUE.lock().set_current_priority(prio)
+pub fn set_priority(task: &AxTaskRef, prio: isize) -> bool {
+    crate::pi_lock::set_priority(task, prio)
+}
+
+/// Get the priority of a task, in the same form as [`set_priority`].
+///
+/// It is the priority the task currently runs with, which may be inherited
+/// from the waiters of its [`PiLock`]s. It is `0` if the scheduler does not
+/// support priorities.
+pub fn get_priority(task: &AxTaskRef) -> isize {
+    crate::run_queue::get_priority(task)
+}
+
+/// Set the scheduling policy and the real-time priority for current task.
+///
+/// Real-time tasks ([`RTPolicy::Fifo`] and [`RTPolicy::RoundRobin`]) always
+/// run before [`RTPolicy::Normal`] ones, and a task with a higher `prio`
+/// (less than [`RT_PRIO_LEVELS`]) runs first. `prio` is ignored for normal
+/// tasks.
+///
+/// Returns `true` if the policy is set successfully.
+#[cfg(feature = "sched_rt")]
+#[doc(cfg(feature = "sched_rt"))]
+pub fn set_sched_policy(policy: RTPolicy, prio: usize) -> bool {
+    current_run_queue().set_current_policy(policy, prio)
+}
+
+/// Set the CPUs on which the current task is allowed to run.
+///
+/// If the current CPU is not in `cpumask`, the task is moved to one of the
+/// CPUs in it before returning.
+///
+/// Returns `false` if `cpumask` is empty or the current task is an idle task.
+pub fn set_current_affinity(cpumask: CpuMask) -> bool {
+    current_run_queue().set_current_affinity(cpumask)
 }
 
 /// Current task gives up the CPU time voluntarily, and switches to another
 /// ready task.
 pub fn yield_now() {
-    RUN_QUEUE.lock().yield_current();
+    current_run_queue().yield_current();
 }
 
 /// Current task is going to sleep for the given duration.
 ///
-/// If the feature `irq` is not enabled, it uses busy-wait instead.
+/// If the feature `irq` is not enabled, it uses busy-wait instead. Otherwise,
+/// it returns early if the current task is [canceled](cancel).
 pub fn sleep(dur: core::time::Duration) {
     sleep_until(axhal::time::current_time() + dur);
 }
@@ -143,24 +334,56 @@ pub fn sleep(dur: core::time::Duration) {
 /// If the feature `irq` is not enabled, it uses busy-wait instead.
 pub fn sleep_until(deadline: axhal::time::TimeValue) {
     #[cfg(feature = "irq")]
-    RUN_QUEUE.lock().sleep_until(deadline);
+    current_run_queue().sleep_until(deadline);
     #[cfg(not(feature = "irq"))]
     axhal::time::busy_wait_until(deadline);
 }
 
+/// Calls `callback` with the current time when the time reaches `deadline`.
+///
+/// The callback is called in the timer interrupt handler, so it must not
+/// block, e.g., by locking a sleeping mutex. The alarm can be cancelled by
+/// [`cancel_alarm`] before it fires.
+#[cfg(feature = "irq")]
+#[doc(cfg(feature = "irq"))]
+pub fn set_alarm<F>(deadline: axhal::time::TimeValue, callback: F) -> Alarm
+where
+    F: FnOnce(axhal::time::TimeValue) + Send + 'static,
+{
+    let _guard = kernel_guard::NoPreemptIrqSave::new();
+    crate::timers::set_alarm_callback(deadline, alloc::boxed::Box::new(callback))
+}
+
+/// Cancels the alarm set by [`set_alarm`], if it has not fired yet.
+#[cfg(feature = "irq")]
+#[doc(cfg(feature = "irq"))]
+pub fn cancel_alarm(alarm: Alarm) {
+    crate::timers::cancel_alarm_event(alarm)
+}
+
 /// Exits the current task.
 pub fn exit(exit_code: i32) -> ! {
-    RUN_QUEUE.lock().exit_current(exit_code)
+    current_run_queue().exit_current(exit_code)
 }
 
 /// The idle task routine.
 ///
-/// It runs an infinite loop that keeps calling [`yield_now()`].
+/// It runs an infinite loop that keeps calling [`yield_now()`], and halts the
+/// CPU until the next interrupt if there is no task to run.
 pub fn run_idle() -> ! {
     loop {
         yield_now();
-        debug!("idle task: waiting for IRQs...");
         #[cfg(feature = "irq")]
-        axhal::arch::wait_for_irqs();
+        {
+            // Check with IRQs disabled, so that a task woken up after the check
+            // also wakes up the CPU.
+            axhal::arch::disable_irqs();
+            if crate::run_queue::has_ready_tasks() {
+                axhal::arch::enable_irqs();
+            } else {
+                debug!("idle task: waiting for IRQs...");
+                axhal::arch::enable_irqs_and_wait();
+            }
+        }
     }
 }
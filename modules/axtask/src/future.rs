@@ -0,0 +1,330 @@
+//! A lightweight executor to run futures on tasks.
+//!
+//! Futures are woken up by other tasks through [`WaitQueue`]s, or by the
+//! timer events (e.g., [`sleep`]). One task can run many futures with an
+//! [`Executor`], so that they do not need a kernel stack each.
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::{boxed::Box, sync::Arc, sync::Weak, task::Wake};
+use core::future::Future;
+use core::pin::{pin, Pin};
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering};
+use core::task::{Context, Poll, Waker};
+use core::time::Duration;
+
+use axhal::time::{current_time, TimeValue};
+use spinlock::SpinNoIrq;
+
+use crate::WaitQueue;
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// Wakes up the task blocked in [`block_on`].
+struct TaskWaker {
+    wq: WaitQueue,
+    woken: AtomicBool,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+        self.wq.notify_one(true);
+    }
+}
+
+/// Runs a future to completion on the current task.
+///
+/// The task is blocked while the future is pending, until it is woken up.
+pub fn block_on<F: Future>(fut: F) -> F::Output {
+    let mut fut = pin!(fut);
+    let task_waker = Arc::new(TaskWaker {
+        wq: WaitQueue::new(),
+        woken: AtomicBool::new(false),
+    });
+    let waker = Waker::from(task_waker.clone());
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = fut.as_mut().poll(&mut cx) {
+            return output;
+        }
+        task_waker
+            .wq
+            .wait_until(|| task_waker.woken.swap(false, Ordering::Acquire));
+    }
+}
+
+/// The future is waiting to be woken up.
+const IDLE: u8 = 0;
+/// The future is in the ready queue of the executor.
+const QUEUED: u8 = 1;
+/// The future is being polled.
+const POLLING: u8 = 2;
+/// The future is woken up while being polled, and will be queued again.
+const REPOLL: u8 = 3;
+
+/// Puts the future back to the ready queue of the executor.
+struct FutureWaker {
+    id: u64,
+    state: AtomicU8,
+    executor: Weak<ExecutorInner>,
+}
+
+impl Wake for FutureWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        let mut state = self.state.load(Ordering::Acquire);
+        loop {
+            let new_state = match state {
+                IDLE => QUEUED,
+                POLLING => REPOLL,
+                _ => return,
+            };
+            match self.state.compare_exchange_weak(
+                state,
+                new_state,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(s) => state = s,
+            }
+        }
+        if state == IDLE {
+            // the executor may have been dropped
+            if let Some(executor) = self.executor.upgrade() {
+                executor.schedule(self.id);
+            }
+        }
+    }
+}
+
+/// A pending future, which is `None` while being polled, and its waker.
+type FutureEntry = (Option<BoxFuture>, Arc<FutureWaker>);
+
+struct ExecutorInner {
+    /// The pending futures. A future is taken out of its entry while being
+    /// polled, and the entry is kept so that the wakeups are not lost.
+    futures: SpinNoIrq<BTreeMap<u64, FutureEntry>>,
+    /// The IDs of the futures that are woken up.
+    ready: SpinNoIrq<VecDeque<u64>>,
+    /// The tasks running the executor wait here for ready futures.
+    wq: WaitQueue,
+    next_id: AtomicU64,
+    num_futures: AtomicUsize,
+}
+
+impl ExecutorInner {
+    fn schedule(&self, id: u64) {
+        self.ready.lock().push_back(id);
+        self.wq.notify_one(true);
+    }
+
+    fn poll_future(&self, id: u64) {
+        let (mut fut, waker) = {
+            let mut futures = self.futures.lock();
+            // it may be woken up after completion
+            let Some((slot, waker)) = futures.get_mut(&id) else {
+                return;
+            };
+            let Some(fut) = slot.take() else {
+                return;
+            };
+            waker.state.store(POLLING, Ordering::Release);
+            (fut, waker.clone())
+        };
+        let cx_waker = Waker::from(waker.clone());
+        match fut.as_mut().poll(&mut Context::from_waker(&cx_waker)) {
+            Poll::Ready(()) => {
+                self.futures.lock().remove(&id);
+                if self.num_futures.fetch_sub(1, Ordering::AcqRel) == 1 {
+                    // wake up the tasks waiting for no futures left
+                    self.wq.notify_all(true);
+                }
+            }
+            Poll::Pending => {
+                if let Some((slot, _)) = self.futures.lock().get_mut(&id) {
+                    *slot = Some(fut);
+                }
+                // poll it again if it is woken up during the poll
+                if waker
+                    .state
+                    .compare_exchange(POLLING, IDLE, Ordering::AcqRel, Ordering::Acquire)
+                    .is_err()
+                {
+                    waker.state.store(QUEUED, Ordering::Release);
+                    self.schedule(id);
+                }
+            }
+        }
+    }
+}
+
+/// An executor that runs many futures on the tasks calling [`Executor::run`].
+///
+/// The executor polls a future only when it is woken up. Between the rounds
+/// of polling, the task yields the CPU so that the other tasks are not starved
+/// by a busy executor, and it blocks when no futures are ready.
+///
+/// It can be cloned to spawn futures from within the futures.
+///
+/// # Examples
+///
+/// ```
+/// use axtask::future::{self, Executor};
+///
+/// axtask::init_scheduler();
+/// let executor = Executor::new();
+/// let spawner = executor.clone();
+/// executor.spawn(async move {
+///     spawner.spawn(async {
+///         future::sleep(core::time::Duration::from_millis(1)).await;
+///     });
+/// });
+/// executor.run(); // returns when all futures are done
+/// ```
+#[derive(Clone)]
+pub struct Executor {
+    inner: Arc<ExecutorInner>,
+}
+
+impl Executor {
+    /// Creates a new executor without futures.
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(ExecutorInner {
+                futures: SpinNoIrq::new(BTreeMap::new()),
+                ready: SpinNoIrq::new(VecDeque::new()),
+                wq: WaitQueue::new(),
+                next_id: AtomicU64::new(0),
+                num_futures: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Spawns a future on the executor. It is polled the first time by the
+    /// next round of [`Executor::run`].
+    pub fn spawn<F>(&self, fut: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        let waker = Arc::new(FutureWaker {
+            id,
+            state: AtomicU8::new(QUEUED),
+            executor: Arc::downgrade(&self.inner),
+        });
+        self.inner.num_futures.fetch_add(1, Ordering::AcqRel);
+        self.inner
+            .futures
+            .lock()
+            .insert(id, (Some(Box::pin(fut)), waker));
+        self.inner.schedule(id);
+    }
+
+    /// Returns the number of futures that are not completed.
+    pub fn num_futures(&self) -> usize {
+        self.inner.num_futures.load(Ordering::Acquire)
+    }
+
+    /// Runs the futures on the current task, until all of them are completed.
+    ///
+    /// It can be called by several tasks at the same time, each of them polls
+    /// different futures.
+    pub fn run(&self) {
+        let inner = &self.inner;
+        loop {
+            let ready = core::mem::take(&mut *inner.ready.lock());
+            if ready.is_empty() {
+                if self.num_futures() == 0 {
+                    break;
+                }
+                inner
+                    .wq
+                    .wait_until(|| !inner.ready.lock().is_empty() || self.num_futures() == 0);
+                continue;
+            }
+            for id in ready {
+                inner.poll_future(id);
+            }
+            crate::yield_now();
+        }
+    }
+}
+
+impl Default for Executor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A future that completes at the deadline, see [`sleep`] and
+/// [`sleep_until`].
+pub struct Sleep {
+    deadline: TimeValue,
+    #[cfg_attr(not(feature = "irq"), allow(dead_code))]
+    waker: Option<Waker>,
+    /// The alarm that wakes up `waker`, cancelled when the future is dropped.
+    #[cfg(feature = "irq")]
+    alarm: Option<crate::timers::Alarm>,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if current_time() >= self.deadline {
+            return Poll::Ready(());
+        }
+        #[cfg(feature = "irq")]
+        {
+            let this = self.get_mut();
+            if !this.waker.as_ref().is_some_and(|w| w.will_wake(cx.waker())) {
+                let _guard = kernel_guard::NoPreemptIrqSave::new();
+                if let Some(alarm) = this.alarm.take() {
+                    crate::timers::cancel_alarm_event(alarm);
+                }
+                let alarm = crate::timers::set_alarm_waker(this.deadline, cx.waker().clone());
+                this.alarm = Some(alarm);
+                this.waker = Some(cx.waker().clone());
+            }
+        }
+        // No timer interrupts to wake us up, poll again in the next round.
+        #[cfg(not(feature = "irq"))]
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+#[cfg(feature = "irq")]
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(alarm) = self.alarm.take() {
+            crate::timers::cancel_alarm_event(alarm);
+        }
+    }
+}
+
+/// Returns a future that completes after the given duration.
+///
+/// The future is woken up by the timer. If the feature `irq` is not enabled,
+/// it wakes itself up every time it is polled until the time is up.
+pub fn sleep(dur: Duration) -> Sleep {
+    sleep_until(current_time() + dur)
+}
+
+/// Returns a future that completes at the given deadline, see [`sleep`].
+pub fn sleep_until(deadline: TimeValue) -> Sleep {
+    Sleep {
+        deadline,
+        waker: None,
+        #[cfg(feature = "irq")]
+        alarm: None,
+    }
+}
@@ -4,6 +4,21 @@
 //! creation, scheduling, sleeping, termination, etc. The scheduler algorithm
 //! is configurable by cargo features.
 //!
+//! Each CPU has its own run queue and scheduler instance. New tasks go to the
+//! least loaded CPU, and a CPU without ready tasks steals them from others.
+//! Tasks can be restricted to some CPUs by [`CpuMask`], see [`spawn_on`] and
+//! [`set_current_affinity`].
+//!
+//! Timed events are also kept per CPU. With the `irq` feature, the timer of
+//! each CPU is set to the next scheduler tick or the nearest timed event, and
+//! the ticks are stopped while the CPU is idle (tickless idle).
+//!
+//! The CPU time, wait time and context switches of each task are recorded,
+//! see [`task_stats`] and [`CpuUsage`].
+//!
+//! Futures can also be run on tasks, by [`future::block_on`] or a lightweight
+//! [`future::Executor`] that runs many of them on one task.
+//!
 //! # Cargo Features
 //!
 //! - `multitask`: Enable multi-task support. If it's enabled, complex task
@@ -12,6 +27,8 @@
 //! - `irq`: Interrupts are enabled. If this feature is enabled, timer-based
 //!    APIs can be used, such as [`sleep`], [`sleep_until`], and
 //!    [`WaitQueue::wait_timeout`].
+//! - `smp`: Enable multi-core support. Idle CPUs are woken up by
+//!   inter-processor interrupts when tasks are added to their run queues.
 //! - `preempt`: Enable preemptive scheduling.
 //! - `sched_fifo`: Use the [FIFO cooperative scheduler][1]. It also enables the
 //!   `multitask` feature if it is enabled. This feature is enabled by default,
@@ -20,10 +37,18 @@
 //!   the `multitask` and `preempt` features if it is enabled.
 //! - `sched_cfs`: Use the [Completely Fair Scheduler][3]. It also enables the
 //!   the `multitask` and `preempt` features if it is enabled.
+//! - `sched_rt`: Use the [real-time scheduler][4] with fixed priorities. If
+//!   `sched_cfs` is also enabled, non-real-time tasks are scheduled by
+//!   [CFS][5]. It also enables the `multitask` and `preempt` features.
+//! - `sched_edf`: Use the [Earliest Deadline First scheduler][6]. It also
+//!   enables the `multitask` and `preempt` features.
 //!
 //! [1]: scheduler::FifoScheduler
 //! [2]: scheduler::RRScheduler
 //! [3]: scheduler::CFScheduler
+//! [4]: scheduler::RTScheduler
+//! [5]: scheduler::RTCFScheduler
+//! [6]: scheduler::EDFScheduler
 
 #![cfg_attr(not(test), no_std)]
 #![feature(doc_cfg)]
@@ -38,8 +63,13 @@ cfg_if::cfg_if! {
         mod run_queue;
         mod task;
         mod api;
+        mod pi_lock;
+        mod stats;
         mod wait_queue;
 
+        #[doc(cfg(feature = "multitask"))]
+        pub mod future;
+
         #[cfg(feature = "irq")]
         mod timers;
 
@@ -0,0 +1,303 @@
+//! Locks with priority inheritance.
+
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use spinlock::{SpinNoIrq, SpinRaw};
+
+use crate::run_queue::{get_priority, set_priority as set_task_priority};
+use crate::{AxTaskRef, WaitQueue};
+
+/// Serializes the changes of PI lock owners and waiters, and the priority
+/// boosts of tasks.
+static PI_LOCK: SpinNoIrq<()> = SpinNoIrq::new(());
+
+/// The priority inheritance state of a task, protected by [`PI_LOCK`].
+#[derive(Default)]
+pub(crate) struct PiState {
+    /// The own priority of the task, saved while its priority is boosted.
+    saved_prio: Option<isize>,
+    /// The PI locks held by the task.
+    held: Vec<*const PiLock>,
+    /// The PI lock the task is waiting for.
+    blocked_on: Option<*const PiLock>,
+}
+
+struct PiLockState {
+    owner: Option<AxTaskRef>,
+    waiters: Vec<AxTaskRef>,
+}
+
+/// A raw lock with priority inheritance.
+///
+/// While a task is waiting for the lock, the owner runs with the priority of
+/// the waiter if it is higher, so that tasks with priorities in between can
+/// not starve the waiter (priority inversion). The priority is also passed on
+/// if the owner is waiting for another PI lock, and it is restored when the
+/// owner releases the lock, unless it still holds other locks with higher
+/// priority waiters.
+///
+/// The lock is handed over to the waiter with the highest priority when it is
+/// released.
+///
+/// The priorities are inherited in the range of the scheduler: nice values
+/// for CFS, real-time priorities for real-time tasks and relative deadlines
+/// for EDF. With both `sched_rt` and `sched_cfs`, priorities are only
+/// inherited between tasks of the same class. Without priorities (FIFO and
+/// Round-robin), it works as a plain sleeping lock.
+pub struct PiLock {
+    wq: WaitQueue,
+    owner_id: AtomicU64,
+    state: SpinRaw<PiLockState>,
+}
+
+impl PiLock {
+    /// Creates a new unlocked PI lock.
+    pub const fn new() -> Self {
+        Self {
+            wq: WaitQueue::new(),
+            owner_id: AtomicU64::new(0),
+            state: SpinRaw::new(PiLockState {
+                owner: None,
+                waiters: Vec::new(),
+            }),
+        }
+    }
+
+    /// Returns `true` if the lock is currently held.
+    ///
+    /// The result should be considered 'out of date' the instant it is called.
+    #[inline]
+    pub fn is_locked(&self) -> bool {
+        self.owner_id.load(Ordering::Relaxed) != 0
+    }
+
+    /// Acquires the lock, blocking the current task until it is released.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current task already holds the lock.
+    pub fn lock(&self) {
+        let curr = crate::current();
+        let curr_id = curr.id().as_u64();
+        {
+            let _guard = PI_LOCK.lock();
+            let mut state = self.state.lock();
+            match &state.owner {
+                None => {
+                    self.set_owner(&mut state, curr.as_task_ref());
+                    return;
+                }
+                Some(owner) => {
+                    assert!(
+                        !curr.ptr_eq(owner),
+                        "{} tried to acquire a PI lock it already owns.",
+                        curr.id_name(),
+                    );
+                    state.waiters.push(curr.as_task_ref().clone());
+                }
+            }
+            drop(state);
+            curr.pi_state().lock().blocked_on = Some(self as *const _);
+            self.propagate_priority();
+        }
+        // the owner hands over the lock when releasing it
+        self.wq
+            .wait_until(|| self.owner_id.load(Ordering::Acquire) == curr_id);
+    }
+
+    /// Tries to acquire the lock without blocking.
+    ///
+    /// Returns `true` if the lock is acquired.
+    pub fn try_lock(&self) -> bool {
+        let curr = crate::current();
+        let _guard = PI_LOCK.lock();
+        let mut state = self.state.lock();
+        if state.owner.is_none() {
+            self.set_owner(&mut state, curr.as_task_ref());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Releases the lock, and hands it over to the waiter with the highest
+    /// priority. The priority of the current task is restored if it was
+    /// boosted by the waiters.
+    ///
+    /// # Safety
+    ///
+    /// The lock must be held by the current task.
+    pub unsafe fn unlock(&self) {
+        let curr = crate::current();
+        let next = {
+            let _guard = PI_LOCK.lock();
+            let mut state = self.state.lock();
+            assert!(
+                state.owner.as_ref().is_some_and(|owner| curr.ptr_eq(owner)),
+                "{} tried to release a PI lock it doesn't own.",
+                curr.id_name(),
+            );
+            curr.pi_state()
+                .lock()
+                .held
+                .retain(|&lock| !core::ptr::eq(lock, self));
+
+            let next = Self::pop_top_waiter(&mut state);
+            match &next {
+                Some(next) => {
+                    next.pi_state().lock().blocked_on = None;
+                    self.set_owner(&mut state, next);
+                    drop(state);
+                    // boosted by the remaining waiters
+                    update_priority(next);
+                }
+                None => {
+                    state.owner = None;
+                    self.owner_id.store(0, Ordering::Release);
+                    drop(state);
+                }
+            }
+            update_priority(curr.as_task_ref());
+            next
+        };
+        if let Some(next) = next {
+            self.wq.notify_task(true, &next);
+        }
+    }
+
+    fn set_owner(&self, state: &mut PiLockState, owner: &AxTaskRef) {
+        state.owner = Some(owner.clone());
+        self.owner_id.store(owner.id().as_u64(), Ordering::Release);
+        owner.pi_state().lock().held.push(self);
+    }
+
+    /// Removes the waiter with the highest priority, the earliest one first
+    /// if there are several.
+    fn pop_top_waiter(state: &mut PiLockState) -> Option<AxTaskRef> {
+        if state.waiters.is_empty() {
+            return None;
+        }
+        let mut top = 0;
+        let mut top_prio = get_priority(&state.waiters[0]);
+        for (i, waiter) in state.waiters.iter().enumerate().skip(1) {
+            let prio = get_priority(waiter);
+            if is_higher(waiter, prio, &state.waiters[top], top_prio) {
+                top = i;
+                top_prio = prio;
+            }
+        }
+        Some(state.waiters.remove(top))
+    }
+
+    /// Passes the priorities of the waiters to the owner, and on along the
+    /// chain of locks the owners are waiting for.
+    fn propagate_priority(&self) {
+        let mut lock = self;
+        loop {
+            let Some(owner) = lock.state.lock().owner.clone() else {
+                break;
+            };
+            if !update_priority(&owner) {
+                break;
+            }
+            let blocked_on = owner.pi_state().lock().blocked_on;
+            match blocked_on {
+                // Safety: the lock can not be dropped while a task waits for it.
+                Some(next) => lock = unsafe { &*next },
+                None => break,
+            }
+        }
+    }
+}
+
+impl Default for PiLock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns `true` if priority `prio` of `waiter` is higher than priority
+/// `task_prio` of `task`, so that `task` can inherit it.
+fn is_higher(waiter: &AxTaskRef, prio: isize, task: &AxTaskRef, task_prio: isize) -> bool {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "sched_rt")] {
+            // Larger real-time priorities are higher, while smaller nice values
+            // are. Tasks of different classes are not comparable.
+            let is_rt = task.policy().is_rt();
+            if waiter.policy().is_rt() != is_rt {
+                false
+            } else if is_rt {
+                prio > task_prio
+            } else {
+                cfg!(feature = "sched_cfs") && prio < task_prio
+            }
+        } else if #[cfg(feature = "sched_cfs")] {
+            // smaller nice values
+            let _ = (waiter, task);
+            prio < task_prio
+        } else if #[cfg(feature = "sched_edf")] {
+            // Shorter relative deadlines are higher, and 0 means no deadline,
+            // which is the lowest.
+            let _ = (waiter, task);
+            prio != 0 && (task_prio == 0 || prio < task_prio)
+        } else {
+            let _ = (waiter, prio, task, task_prio);
+            false
+        }
+    }
+}
+
+/// Sets the priority of `task` to the highest one of its own priority and
+/// the priorities of the tasks waiting for its locks.
+///
+/// Returns `true` if the priority is changed.
+fn update_priority(task: &AxTaskRef) -> bool {
+    let mut pi = task.pi_state().lock();
+    let curr_prio = get_priority(task);
+    let own_prio = pi.saved_prio.unwrap_or(curr_prio);
+
+    let mut prio = own_prio;
+    for &lock in pi.held.iter() {
+        // Safety: the lock can not be dropped while it is held.
+        for waiter in unsafe { &*lock }.state.lock().waiters.iter() {
+            let waiter_prio = get_priority(waiter);
+            if is_higher(waiter, waiter_prio, task, prio) {
+                prio = waiter_prio;
+            }
+        }
+    }
+
+    if prio == own_prio {
+        pi.saved_prio = None;
+    }
+    if prio == curr_prio || !set_task_priority(task, prio) {
+        return false;
+    }
+    if prio != own_prio {
+        pi.saved_prio = Some(own_prio);
+    }
+    true
+}
+
+/// Sets the own priority of `task`. If its priority is boosted, it keeps
+/// running with the boosted one until the locks are released.
+pub(crate) fn set_priority(task: &AxTaskRef, prio: isize) -> bool {
+    let _guard = PI_LOCK.lock();
+    if !set_task_priority(task, prio) {
+        return false;
+    }
+    let blocked_on = {
+        let mut pi = task.pi_state().lock();
+        if pi.saved_prio.is_some() {
+            pi.saved_prio = Some(prio);
+        }
+        pi.blocked_on
+    };
+    update_priority(task);
+    if let Some(lock) = blocked_on {
+        // Safety: the lock can not be dropped while a task waits for it.
+        unsafe { &*lock }.propagate_priority();
+    }
+    true
+}
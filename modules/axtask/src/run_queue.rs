@@ -1,39 +1,196 @@
 use alloc::collections::VecDeque;
 use alloc::sync::Arc;
+use core::cell::UnsafeCell;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use kernel_guard::NoPreemptIrqSave;
 use lazy_init::LazyInit;
 use scheduler::BaseScheduler;
-use spinlock::SpinNoIrq;
+use spinlock::SpinRaw;
 
-use crate::task::{CurrentTask, TaskState};
+use crate::task::{CpuMask, CurrentTask, TaskState};
 use crate::{AxTaskRef, Scheduler, TaskInner, WaitQueue};
 
-// TODO: per-CPU
-pub(crate) static RUN_QUEUE: LazyInit<SpinNoIrq<AxRunQueue>> = LazyInit::new();
+/// The run queues of all CPUs, indexed by CPU IDs.
+static RUN_QUEUES: [LazyInit<PerCpuRunQueue>; axconfig::SMP] =
+    [const { LazyInit::new() }; axconfig::SMP];
 
-// TODO: per-CPU
-static EXITED_TASKS: SpinNoIrq<VecDeque<AxTaskRef>> = SpinNoIrq::new(VecDeque::new());
-
-static WAIT_FOR_EXIT: WaitQueue = WaitQueue::new();
+/// The run queue of the current CPU.
+#[percpu::def_percpu]
+static RUN_QUEUE: LazyInit<&'static PerCpuRunQueue> = LazyInit::new();
 
 #[percpu::def_percpu]
 static IDLE_TASK: LazyInit<AxTaskRef> = LazyInit::new();
 
+/// The run queue of a CPU, and its states accessed by other CPUs.
+struct PerCpuRunQueue {
+    locked: AtomicBool,
+    rq: UnsafeCell<AxRunQueue>,
+    /// Number of ready tasks in `rq` and `remote`.
+    nr_ready: AtomicUsize,
+    /// Whether a task other than the idle task is running.
+    busy: AtomicBool,
+    /// Whether the CPU has initialized its scheduler.
+    online: AtomicBool,
+    /// Tasks added by other CPUs, moved into `rq` by the owner CPU.
+    remote: SpinRaw<VecDeque<AxTaskRef>>, // we already disabled IRQs when lock a run queue
+    /// Whether other CPUs have woken up tasks in `remote` that may preempt
+    /// the current task, checked at the IPI.
+    resched: AtomicBool,
+    /// The queue where the GC task waits for exited tasks.
+    wait_for_exit: WaitQueue,
+}
+
+unsafe impl Sync for PerCpuRunQueue {}
+
 pub(crate) struct AxRunQueue {
+    cpu_id: usize,
     scheduler: Scheduler,
+    exited_tasks: VecDeque<AxTaskRef>,
+    /// The task switched out by the last context switch, whose `on_cpu` flag
+    /// is cleared after the switch completes.
+    prev_task: Option<AxTaskRef>,
+}
+
+/// A guard of the locked run queue of the current CPU.
+///
+/// Unlike usual lock guards, it releases the run queue of the CPU where it is
+/// dropped, since the current task may migrate to another CPU while holding
+/// it across a context switch. On the new CPU, the lock has been acquired by
+/// the task that switches to the current task.
+pub(crate) struct AxRunQueueGuard {
+    _irq_guard: NoPreemptIrqSave,
+}
+
+/// Locks the run queue of the current CPU, with IRQs and preemption disabled.
+pub(crate) fn current_run_queue() -> AxRunQueueGuard {
+    let irq_guard = NoPreemptIrqSave::new();
+    local_run_queue().lock();
+    AxRunQueueGuard {
+        _irq_guard: irq_guard,
+    }
+}
+
+impl Deref for AxRunQueueGuard {
+    type Target = AxRunQueue;
+    fn deref(&self) -> &AxRunQueue {
+        unsafe { local_run_queue().rq_mut() }
+    }
+}
+
+impl DerefMut for AxRunQueueGuard {
+    fn deref_mut(&mut self) -> &mut AxRunQueue {
+        unsafe { local_run_queue().rq_mut() }
+    }
+}
+
+impl Drop for AxRunQueueGuard {
+    fn drop(&mut self) {
+        // IRQs and preemption are enabled after this, by `_irq_guard`
+        unsafe { local_run_queue().unlock() };
+    }
+}
+
+fn cpu_run_queue(cpu_id: usize) -> &'static PerCpuRunQueue {
+    &RUN_QUEUES[cpu_id]
+}
+
+/// Adds a ready task to the run queue of another CPU, and wakes up the CPU if
+/// it is idle, or asks it to reschedule if `resched` is true.
+fn push_remote(cpu_id: usize, task: AxTaskRef, resched: bool) {
+    let rq = cpu_run_queue(cpu_id);
+    rq.remote.lock().push_back(task);
+    if resched {
+        rq.resched.store(true, Ordering::Release);
+    }
+    // pairs with the idle CPU storing `busy` and loading `nr_ready`
+    rq.nr_ready.fetch_add(1, Ordering::SeqCst);
+    #[cfg(all(feature = "smp", feature = "irq"))]
+    if resched || !rq.busy.load(Ordering::SeqCst) {
+        axhal::irq::send_ipi(cpu_id);
+    }
+}
+
+fn local_run_queue() -> &'static PerCpuRunQueue {
+    // Safety: IRQs and preemption are disabled when accessing the run queue,
+    // or the current task is pinned to the CPU.
+    unsafe { RUN_QUEUE.current_ref_raw().get_unchecked() }
+}
+
+impl PerCpuRunQueue {
+    fn new(cpu_id: usize) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            rq: UnsafeCell::new(AxRunQueue::new(cpu_id)),
+            nr_ready: AtomicUsize::new(0),
+            busy: AtomicBool::new(false),
+            online: AtomicBool::new(false),
+            remote: SpinRaw::new(VecDeque::new()),
+            resched: AtomicBool::new(false),
+            wait_for_exit: WaitQueue::new(),
+        }
+    }
+
+    fn lock(&self) {
+        while !self.try_lock() {
+            while self.locked.load(Ordering::Relaxed) {
+                core::hint::spin_loop();
+            }
+        }
+    }
+
+    fn try_lock(&self) -> bool {
+        self.locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    unsafe fn unlock(&self) {
+        self.locked.store(false, Ordering::Release);
+    }
+
+    /// # Safety
+    ///
+    /// The caller must hold the lock.
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn rq_mut(&self) -> &mut AxRunQueue {
+        &mut *self.rq.get()
+    }
+
+    /// Number of tasks to run, used for load balancing.
+    fn load(&self) -> usize {
+        self.nr_ready.load(Ordering::Relaxed) + self.busy.load(Ordering::Relaxed) as usize
+    }
+}
+
+/// Returns the number of timer ticks since boot, as the clock of the EDF
+/// scheduler. It is shared by all CPUs and also advances without timer
+/// interrupts.
+#[cfg(feature = "sched_edf")]
+fn edf_clock() -> u64 {
+    const NANOS_PER_TICK: u64 = axhal::time::NANOS_PER_SEC / axconfig::TICKS_PER_SEC as u64;
+    axhal::time::current_time_nanos() / NANOS_PER_TICK
 }
 
 impl AxRunQueue {
-    pub fn new() -> SpinNoIrq<Self> {
-        let gc_task = TaskInner::new(gc_entry, "gc".into(), axconfig::TASK_STACK_SIZE);
-        let mut scheduler = Scheduler::new();
-        scheduler.add_task(gc_task);
-        SpinNoIrq::new(Self { scheduler })
+    fn new(cpu_id: usize) -> Self {
+        Self {
+            cpu_id,
+            #[cfg(not(feature = "sched_edf"))]
+            scheduler: Scheduler::new(),
+            #[cfg(feature = "sched_edf")]
+            scheduler: Scheduler::with_clock(edf_clock),
+            exited_tasks: VecDeque::new(),
+            prev_task: None,
+        }
     }
 
     pub fn add_task(&mut self, task: AxTaskRef) {
         debug!("task spawn: {}", task.id_name());
         assert!(task.is_ready());
-        self.scheduler.add_task(task);
+        let _sched_lock = task.sched_lock().lock();
+        let cpu_id = self.select_cpu_for_new(&task);
+        self.enqueue(cpu_id, task.clone(), false);
     }
 
     #[cfg(feature = "irq")]
@@ -52,9 +209,29 @@ impl AxRunQueue {
         self.resched(false);
     }
 
-    pub fn set_current_priority(&mut self, prio: isize) -> bool {
+    #[cfg(feature = "sched_rt")]
+    pub fn set_current_policy(&mut self, policy: scheduler::RTPolicy, prio: usize) -> bool {
         self.scheduler
-            .set_priority(crate::current().as_task_ref(), prio)
+            .set_policy(crate::current().as_task_ref(), policy, prio)
+    }
+
+    /// Sets the CPUs on which the current task is allowed to run, and moves
+    /// it to one of them if the current CPU is not allowed.
+    pub fn set_current_affinity(&mut self, cpumask: CpuMask) -> bool {
+        let curr = crate::current();
+        if cpumask.is_empty() || curr.is_idle() {
+            return false;
+        }
+        curr.set_cpumask(cpumask);
+        if !cpumask.get(self.cpu_id) {
+            let cpu_id = self.select_cpu_for_new(curr.as_task_ref());
+            debug!("task migrate: {} -> CPU {}", curr.id_name(), cpu_id);
+            curr.set_state(TaskState::Ready);
+            curr.set_cpu_id(cpu_id);
+            push_remote(cpu_id, curr.clone(), false);
+            self.resched(false);
+        }
+        true
     }
 
     #[cfg(feature = "preempt")]
@@ -87,13 +264,13 @@ impl AxRunQueue {
         assert!(curr.is_running());
         assert!(!curr.is_idle());
         if curr.is_init() {
-            EXITED_TASKS.lock().clear();
+            self.exited_tasks.clear();
             axhal::misc::terminate();
         } else {
             curr.set_state(TaskState::Exited);
             curr.notify_exit(exit_code, self);
-            EXITED_TASKS.lock().push_back(curr.clone());
-            WAIT_FOR_EXIT.notify_one_locked(false, self);
+            self.exited_tasks.push_back(curr.clone());
+            self.shared().wait_for_exit.notify_one_locked(false, self);
             self.resched(false);
         }
         unreachable!("task exited!");
@@ -117,16 +294,59 @@ impl AxRunQueue {
         self.resched(false);
     }
 
+    /// Blocks the current task like [`block_current`](Self::block_current),
+    /// but the task is also woken up if it is canceled.
+    ///
+    /// Returns `false` without blocking if the task is already canceled.
+    pub fn block_current_cancelable<F>(&mut self, wait_queue_push: F) -> bool
+    where
+        F: FnOnce(AxTaskRef),
+    {
+        let curr = crate::current();
+        debug!("task block: {}", curr.id_name());
+        assert!(curr.is_running());
+        assert!(!curr.is_idle());
+
+        #[cfg(feature = "preempt")]
+        assert!(curr.can_preempt(1));
+
+        // serialized with `cancel_task`
+        let sched_lock = curr.sched_lock().lock();
+        if curr.is_cancel_pending() {
+            return false;
+        }
+        curr.set_in_cancelable_wait(true);
+        curr.set_state(TaskState::Blocked);
+        drop(sched_lock);
+        wait_queue_push(curr.clone());
+        self.resched(false);
+        curr.set_in_cancelable_wait(false);
+        true
+    }
+
     pub fn unblock_task(&mut self, task: AxTaskRef, resched: bool) {
         debug!("task unblock: {}", task.id_name());
-        if task.is_blocked() {
-            task.set_state(TaskState::Ready);
-            self.scheduler.add_task(task); // TODO: priority
-            if resched {
-                #[cfg(feature = "preempt")]
-                crate::current().set_preempt_pending(true);
-            }
+        // the task may be woken up by several CPUs at the same time
+        let _sched_lock = task.sched_lock().lock();
+        self.unblock_task_locked(&task, resched);
+    }
+
+    /// Requests the task to be canceled, and wakes it up if it is blocked at
+    /// a cancellation point.
+    ///
+    /// Returns `false` if the task has exited.
+    pub fn cancel_task(&mut self, task: &AxTaskRef) -> bool {
+        debug!("task cancel: {}", task.id_name());
+        // the task can not be woken up by others while we hold the lock
+        let _sched_lock = task.sched_lock().lock();
+        if task.state() == TaskState::Exited {
+            return false;
+        }
+        task.set_cancel_pending();
+        if task.state() == TaskState::Blocked && task.in_cancelable_wait() {
+            self.unblock_task_locked(task, true);
         }
+        true
     }
 
     #[cfg(feature = "irq")]
@@ -138,9 +358,21 @@ impl AxRunQueue {
 
         let now = axhal::time::current_time();
         if now < deadline {
-            crate::timers::set_alarm_wakeup(deadline, curr.clone());
+            // block first, the timer may fire on another CPU at once
+            let sched_lock = curr.sched_lock().lock();
+            if curr.is_cancel_pending() {
+                return;
+            }
+            curr.set_in_cancelable_wait(true);
             curr.set_state(TaskState::Blocked);
+            crate::timers::set_alarm_wakeup(deadline, curr.clone());
+            drop(sched_lock);
             self.resched(false);
+            curr.set_in_cancelable_wait(false);
+            if curr.in_timer_list() {
+                // woken up by `cancel_task`
+                crate::timers::cancel_alarm(curr.as_task_ref());
+            }
         }
     }
 }
@@ -148,22 +380,27 @@ impl AxRunQueue {
 impl AxRunQueue {
     /// Common reschedule subroutine. If `preempt`, keep current task's time
     /// slice, otherwise reset it.
+    ///
+    /// The switch is voluntary if the current task is blocked, exited or
+    /// migrating to another CPU, and involuntary if it is still running.
     fn resched(&mut self, preempt: bool) {
         let prev = crate::current();
+        let voluntary = !prev.is_running();
         if prev.is_running() {
             prev.set_state(TaskState::Ready);
             if !prev.is_idle() {
                 self.scheduler.put_prev_task(prev.clone(), preempt);
+                self.shared().nr_ready.fetch_add(1, Ordering::Relaxed);
             }
         }
-        let next = self.scheduler.pick_next_task().unwrap_or_else(|| unsafe {
+        let next = self.pick_next_task().unwrap_or_else(|| unsafe {
             // Safety: IRQs must be disabled at this time.
             IDLE_TASK.current_ref_raw().get_unchecked().clone()
         });
-        self.switch_to(prev, next);
+        self.switch_to(prev, next, voluntary);
     }
 
-    fn switch_to(&mut self, prev_task: CurrentTask, next_task: AxTaskRef) {
+    fn switch_to(&mut self, prev_task: CurrentTask, next_task: AxTaskRef, voluntary: bool) {
         trace!(
             "context switch: {} -> {}",
             prev_task.id_name(),
@@ -176,6 +413,24 @@ impl AxRunQueue {
             return;
         }
 
+        // The next task may be still being switched out of another CPU.
+        while next_task.on_cpu() {
+            core::hint::spin_loop();
+        }
+        next_task.set_on_cpu(true);
+        next_task.set_cpu_id(self.cpu_id);
+        let now = axhal::time::current_time_nanos();
+        prev_task.times().switch_out(now, voluntary);
+        next_task.times().switch_in(now, self.cpu_id);
+        self.shared()
+            .busy
+            .store(!next_task.is_idle(), Ordering::SeqCst);
+        #[cfg(feature = "irq")]
+        if prev_task.is_idle() && !next_task.is_idle() {
+            crate::timers::start_tick();
+        }
+        self.prev_task = Some(prev_task.clone());
+
         unsafe {
             let prev_ctx_ptr = prev_task.ctx_mut_ptr();
             let next_ctx_ptr = next_task.ctx_mut_ptr();
@@ -187,47 +442,285 @@ impl AxRunQueue {
 
             CurrentTask::set_current(prev_task, next_task);
             (*prev_ctx_ptr).switch_to(&*next_ctx_ptr);
+
+            // `self` may belong to another CPU now.
+            finish_task_switch();
+        }
+    }
+
+    /// Wakes up the task if it is blocked, with its `sched_lock` held.
+    fn unblock_task_locked(&mut self, task: &AxTaskRef, resched: bool) {
+        if task.transition_state(TaskState::Blocked, TaskState::Ready) {
+            let cpu_id = self.select_cpu_for_wakeup(task);
+            self.enqueue(cpu_id, task.clone(), resched);
+            if resched && cpu_id == self.cpu_id {
+                #[cfg(feature = "preempt")]
+                crate::current().set_preempt_pending(true);
+            }
+        }
+    }
+
+    fn shared(&self) -> &'static PerCpuRunQueue {
+        cpu_run_queue(self.cpu_id)
+    }
+
+    /// Adds a ready task to the run queue of the CPU, which is asked to
+    /// reschedule if it is another CPU and `resched` is true.
+    fn enqueue(&mut self, cpu_id: usize, task: AxTaskRef, resched: bool) {
+        task.set_cpu_id(cpu_id);
+        task.times().set_ready(axhal::time::current_time_nanos());
+        if cpu_id == self.cpu_id {
+            self.scheduler.add_task(task);
+            self.shared().nr_ready.fetch_add(1, Ordering::Relaxed);
+        } else {
+            push_remote(cpu_id, task, resched);
+        }
+    }
+
+    /// Picks the next task from the local run queue, or steals one from
+    /// other CPUs if there is none.
+    fn pick_next_task(&mut self) -> Option<AxTaskRef> {
+        self.take_remote_tasks();
+        if let Some(task) = self.scheduler.pick_next_task() {
+            self.shared().nr_ready.fetch_sub(1, Ordering::Relaxed);
+            return Some(task);
+        }
+        self.steal_task()
+    }
+
+    /// Moves the tasks added by other CPUs into the scheduler.
+    fn take_remote_tasks(&mut self) {
+        let mut remote = self.shared().remote.lock();
+        while let Some(task) = remote.pop_front() {
+            self.scheduler.add_task(task);
+        }
+    }
+
+    /// Steals a ready task from other CPUs, trying the CPUs after the current
+    /// one in turn.
+    ///
+    /// Run queues being locked by their CPUs are skipped, so that two CPUs
+    /// stealing from each other do not deadlock.
+    fn steal_task(&mut self) -> Option<AxTaskRef> {
+        for i in 1..axconfig::SMP {
+            let victim = cpu_run_queue((self.cpu_id + i) % axconfig::SMP);
+            if victim.nr_ready.load(Ordering::Relaxed) == 0 || !victim.try_lock() {
+                continue;
+            }
+            let task = unsafe { victim.rq_mut() }.give_task(self.cpu_id);
+            unsafe { victim.unlock() };
+            if let Some(task) = task {
+                debug!("task steal: {} -> CPU {}", task.id_name(), self.cpu_id);
+                return Some(task);
+            }
+        }
+        None
+    }
+
+    /// Takes the first ready task that is allowed to run on the CPU `cpu_id`,
+    /// and keeps the order of other tasks.
+    fn give_task(&mut self, cpu_id: usize) -> Option<AxTaskRef> {
+        self.take_remote_tasks();
+        let task = self
+            .scheduler
+            .pick_next_task_if(|task| task.cpumask().get(cpu_id))?;
+        self.shared().nr_ready.fetch_sub(1, Ordering::Relaxed);
+        // before others can lock the new run queue of the task
+        task.set_cpu_id(cpu_id);
+        Some(task)
+    }
+
+    /// Selects the CPU for a woken-up task: the current CPU if it is allowed,
+    /// otherwise the CPU on which the task ran last time if allowed.
+    fn select_cpu_for_wakeup(&self, task: &AxTaskRef) -> usize {
+        let cpumask = task.cpumask();
+        if cpumask.get(self.cpu_id) {
+            self.cpu_id
+        } else if cpumask.get(task.cpu_id()) {
+            task.cpu_id()
+        } else {
+            cpumask.first().unwrap_or(self.cpu_id)
         }
     }
+
+    /// Selects the CPU for a new task: the allowed online CPU with the least
+    /// load, preferring the current CPU.
+    fn select_cpu_for_new(&self, task: &AxTaskRef) -> usize {
+        let cpumask = task.cpumask();
+        let online = cpumask
+            .iter()
+            .filter(|cpu_id| cpu_run_queue(*cpu_id).online.load(Ordering::Relaxed));
+        let least_loaded = online.min_by_key(|cpu_id| {
+            let load = cpu_run_queue(*cpu_id).load();
+            (load, *cpu_id != self.cpu_id)
+        });
+        // tasks pinned to offline CPUs wait for them to start
+        least_loaded
+            .or_else(|| cpumask.first())
+            .unwrap_or(self.cpu_id)
+    }
+}
+
+/// Calls `f` with the run queue of the CPU that owns the task locked, and
+/// the `sched_lock` of the task held so that it stays on the CPU.
+fn with_task_run_queue<T>(task: &AxTaskRef, f: impl FnOnce(&mut AxRunQueue) -> T) -> T {
+    let _guard = NoPreemptIrqSave::new();
+    loop {
+        let sched_lock = task.sched_lock().lock();
+        // The task may be stolen by another CPU before we lock the run queue.
+        // Use `try_lock` as CPUs waking up tasks lock them in the reverse
+        // order.
+        let cpu_id = task.cpu_id();
+        let shared = cpu_run_queue(cpu_id);
+        if shared.try_lock() {
+            if task.cpu_id() == cpu_id {
+                // Safety: we hold the lock.
+                let ret = f(unsafe { shared.rq_mut() });
+                unsafe { shared.unlock() };
+                return ret;
+            }
+            unsafe { shared.unlock() };
+        }
+        drop(sched_lock);
+        core::hint::spin_loop();
+    }
+}
+
+/// Sets the priority of a task, which may be running or ready on any CPU, or
+/// blocked.
+///
+/// A ready task is moved to the right place in its run queue.
+pub(crate) fn set_priority(task: &AxTaskRef, prio: isize) -> bool {
+    if task.is_idle() {
+        return false;
+    }
+    with_task_run_queue(task, |rq| {
+        if task.state() == TaskState::Exited {
+            return false;
+        }
+        if task.is_ready() {
+            // the task may be in the remote queue
+            rq.take_remote_tasks();
+        }
+        rq.scheduler.set_priority(task, prio)
+    })
+}
+
+/// Gets the priority of a task from the scheduler of the CPU that owns it.
+pub(crate) fn get_priority(task: &AxTaskRef) -> isize {
+    with_task_run_queue(task, |rq| rq.scheduler.get_priority(task))
+}
+
+/// Handles the IPI from other CPUs, and preempts the current task if they
+/// have woken up tasks on this CPU with rescheduling.
+#[cfg(all(feature = "smp", feature = "irq"))]
+pub(crate) fn on_ipi() {
+    if local_run_queue().resched.swap(false, Ordering::Acquire) {
+        #[cfg(feature = "preempt")]
+        crate::current().set_preempt_pending(true);
+    }
+}
+
+/// Whether there are ready tasks in the run queue of the current CPU.
+///
+/// It is called by the idle task with IRQs disabled before halting the CPU.
+#[cfg(feature = "irq")]
+pub(crate) fn has_ready_tasks() -> bool {
+    local_run_queue().nr_ready.load(Ordering::SeqCst) > 0
+}
+
+/// Finishes the context switch in the context of the next task, with the
+/// run queue of the current CPU still locked.
+///
+/// # Safety
+///
+/// It must be called right after switching to the next task.
+pub(crate) unsafe fn finish_task_switch() {
+    if let Some(prev) = local_run_queue().rq_mut().prev_task.take() {
+        prev.set_on_cpu(false);
+    }
+}
+
+/// Releases the run queue of the current CPU without a guard.
+///
+/// # Safety
+///
+/// The run queue must be locked, and the lock is not released elsewhere.
+pub(crate) unsafe fn force_unlock() {
+    local_run_queue().unlock();
+}
+
+/// Wakes up the GC task of the CPU to drop the exited tasks that are no
+/// longer referenced.
+pub(crate) fn wake_gc(cpu_id: usize) {
+    cpu_run_queue(cpu_id).wait_for_exit.notify_one(false);
 }
 
 fn gc_entry() {
+    // the GC task is pinned to its CPU
+    let shared = local_run_queue();
     loop {
         // Drop all exited tasks and recycle resources.
-        let n = EXITED_TASKS.lock().len();
+        let n = current_run_queue().exited_tasks.len();
         for _ in 0..n {
             // Do not do the slow drops in the critical section.
-            let task = EXITED_TASKS.lock().pop_front();
+            let task = current_run_queue().exited_tasks.pop_front();
             if let Some(task) = task {
-                if Arc::strong_count(&task) == 1 {
+                if Arc::strong_count(&task) == 1 && !task.on_cpu() {
                     // If I'm the last holder of the task, drop it immediately.
                     drop(task);
                 } else {
                     // Otherwise (e.g, `switch_to` is not compeleted, held by the
                     // joiner, etc), push it back and wait for them to drop first.
-                    EXITED_TASKS.lock().push_back(task);
+                    current_run_queue().exited_tasks.push_back(task);
                 }
             }
         }
-        WAIT_FOR_EXIT.wait();
+        shared.wait_for_exit.wait();
     }
 }
 
+/// Initializes the run queue of the current CPU, and marks the CPU online.
+fn init_local(curr: &AxTaskRef) {
+    let cpu_id = axhal::cpu::this_cpu_id();
+    let shared = cpu_run_queue(cpu_id);
+    RUN_QUEUE.with_current(|rq| rq.init_by(shared));
+
+    curr.set_state(TaskState::Running);
+    curr.set_cpu_id(cpu_id);
+    curr.set_on_cpu(true);
+    curr.times()
+        .switch_in(axhal::time::current_time_nanos(), cpu_id);
+
+    let gc_task = TaskInner::new(gc_entry, "gc".into(), axconfig::TASK_STACK_SIZE);
+    gc_task.set_cpumask(CpuMask::one(cpu_id));
+    gc_task.set_cpu_id(cpu_id);
+    shared.lock();
+    unsafe {
+        shared.rq_mut().scheduler.add_task(gc_task);
+        shared.unlock();
+    }
+    shared.nr_ready.fetch_add(1, Ordering::Relaxed);
+    shared.online.store(true, Ordering::Release);
+}
+
 pub(crate) fn init() {
+    for (cpu_id, rq) in RUN_QUEUES.iter().enumerate() {
+        rq.init_by(PerCpuRunQueue::new(cpu_id));
+    }
+
     const IDLE_TASK_STACK_SIZE: usize = 4096;
     let idle_task = TaskInner::new(|| crate::run_idle(), "idle".into(), IDLE_TASK_STACK_SIZE);
     IDLE_TASK.with_current(|i| i.init_by(idle_task.clone()));
 
     let main_task = TaskInner::new_init("main".into());
-    main_task.set_state(TaskState::Running);
-
-    RUN_QUEUE.init_by(AxRunQueue::new());
+    init_local(&main_task);
     unsafe { CurrentTask::init_current(main_task) }
 }
 
 pub(crate) fn init_secondary() {
     let idle_task = TaskInner::new_init("idle".into());
-    idle_task.set_state(TaskState::Running);
     IDLE_TASK.with_current(|i| i.init_by(idle_task.clone()));
+    init_local(&idle_task);
     unsafe { CurrentTask::init_current(idle_task) }
 }
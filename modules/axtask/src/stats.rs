@@ -0,0 +1,235 @@
+//! CPU time accounting and statistics of tasks.
+
+use alloc::string::String;
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use core::time::Duration;
+
+use axhal::time::{current_time_nanos, TimeValue};
+use spinlock::SpinNoIrq;
+
+use crate::{TaskId, TaskInner, TaskState};
+
+/// The CPU usage of a task, or of a group of tasks.
+///
+/// As all code runs in the kernel mode in ArceOS, the "kernel time" is the
+/// time spent in system calls and interrupt handlers, which are marked by
+/// [`enter_kernel`](crate::enter_kernel) and
+/// [`leave_kernel`](crate::leave_kernel). The rest of the running time is
+/// the "user time".
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct CpuUsage {
+    /// The time spent running the application code.
+    pub user_time: Duration,
+    /// The time spent in system calls and interrupt handlers.
+    pub kernel_time: Duration,
+    /// Number of times the task gives up the CPU because it is blocked or
+    /// exited.
+    pub voluntary_switches: u64,
+    /// Number of times the task is switched out while still runnable, e.g.,
+    /// preempted or yielding the CPU.
+    pub involuntary_switches: u64,
+}
+
+/// A snapshot of the statistics of a task.
+#[derive(Debug, Clone)]
+pub struct TaskStats {
+    /// The ID of the task.
+    pub id: TaskId,
+    /// The name of the task.
+    pub name: String,
+    /// The state of the task.
+    pub state: TaskState,
+    /// Whether the task is the idle task of a CPU.
+    pub is_idle: bool,
+    /// The CPU time used by the task, and its context switches.
+    pub usage: CpuUsage,
+    /// The time spent waiting in run queues while ready.
+    pub wait_time: Duration,
+    /// The ID of the CPU on which the task ran last time, or [`None`] if it
+    /// has never run.
+    pub last_cpu: Option<usize>,
+    /// The time when the task is created, since boot.
+    pub created_at: TimeValue,
+}
+
+impl CpuUsage {
+    /// The total CPU time, i.e., the user time plus the kernel time.
+    pub fn cpu_time(&self) -> Duration {
+        self.user_time + self.kernel_time
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.user_time += other.user_time;
+        self.kernel_time += other.kernel_time;
+        self.voluntary_switches += other.voluntary_switches;
+        self.involuntary_switches += other.involuntary_switches;
+    }
+}
+
+/// The CPU usage of the tasks that have been dropped, except the idle tasks.
+static DROPPED_USAGE: SpinNoIrq<CpuUsage> = SpinNoIrq::new(CpuUsage {
+    user_time: Duration::ZERO,
+    kernel_time: Duration::ZERO,
+    voluntary_switches: 0,
+    involuntary_switches: 0,
+});
+
+/// `last_cpu` of a task that has never run.
+const NO_CPU: usize = usize::MAX;
+
+/// The statistics kept in a task.
+///
+/// The running time is only updated by the CPU running the task, with IRQs
+/// disabled. Others may read it at any time, so it can be slightly out of
+/// date.
+pub(crate) struct TaskTimes {
+    created_ns: u64,
+    /// When the running time was accounted last time.
+    stamp_ns: AtomicU64,
+    /// When the task became ready.
+    ready_since_ns: AtomicU64,
+    /// Nesting depth of [`enter_kernel`](crate::enter_kernel).
+    kernel_depth: AtomicUsize,
+    user_ns: AtomicU64,
+    kernel_ns: AtomicU64,
+    wait_ns: AtomicU64,
+    nvcsw: AtomicU64,
+    nivcsw: AtomicU64,
+    last_cpu: AtomicUsize,
+}
+
+impl TaskTimes {
+    pub fn new() -> Self {
+        let now = current_time_nanos();
+        Self {
+            created_ns: now,
+            stamp_ns: AtomicU64::new(now),
+            ready_since_ns: AtomicU64::new(now),
+            kernel_depth: AtomicUsize::new(0),
+            user_ns: AtomicU64::new(0),
+            kernel_ns: AtomicU64::new(0),
+            wait_ns: AtomicU64::new(0),
+            nvcsw: AtomicU64::new(0),
+            nivcsw: AtomicU64::new(0),
+            last_cpu: AtomicUsize::new(NO_CPU),
+        }
+    }
+
+    /// Charges the time since the last stamp to the user or kernel time.
+    fn account(&self, now: u64) {
+        let elapsed = now.saturating_sub(self.stamp_ns.swap(now, Ordering::Relaxed));
+        if self.kernel_depth.load(Ordering::Relaxed) > 0 {
+            self.kernel_ns.fetch_add(elapsed, Ordering::Relaxed);
+        } else {
+            self.user_ns.fetch_add(elapsed, Ordering::Relaxed);
+        }
+    }
+
+    /// Called when the task starts running on the CPU `cpu_id`.
+    pub fn switch_in(&self, now: u64, cpu_id: usize) {
+        let ready_since = self.ready_since_ns.load(Ordering::Relaxed);
+        self.wait_ns
+            .fetch_add(now.saturating_sub(ready_since), Ordering::Relaxed);
+        self.stamp_ns.store(now, Ordering::Relaxed);
+        self.last_cpu.store(cpu_id, Ordering::Relaxed);
+    }
+
+    /// Called when the task is switched out.
+    pub fn switch_out(&self, now: u64, voluntary: bool) {
+        self.account(now);
+        if voluntary {
+            self.nvcsw.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.nivcsw.fetch_add(1, Ordering::Relaxed);
+        }
+        // it can not run elsewhere before switched out, even if woken up
+        self.ready_since_ns.store(now, Ordering::Relaxed);
+    }
+
+    /// Called when the task is added to a run queue.
+    pub fn set_ready(&self, now: u64) {
+        self.ready_since_ns.store(now, Ordering::Relaxed);
+    }
+
+    pub fn enter_kernel(&self, now: u64) {
+        self.account(now);
+        self.kernel_depth.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn leave_kernel(&self, now: u64) {
+        self.account(now);
+        self.kernel_depth.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Returns the CPU usage, including the running time not accounted yet
+    /// if the task is running.
+    pub fn usage(&self, running: bool) -> CpuUsage {
+        let mut user_ns = self.user_ns.load(Ordering::Relaxed);
+        let mut kernel_ns = self.kernel_ns.load(Ordering::Relaxed);
+        if running {
+            let stamp = self.stamp_ns.load(Ordering::Relaxed);
+            let elapsed = current_time_nanos().saturating_sub(stamp);
+            if self.kernel_depth.load(Ordering::Relaxed) > 0 {
+                kernel_ns += elapsed;
+            } else {
+                user_ns += elapsed;
+            }
+        }
+        CpuUsage {
+            user_time: Duration::from_nanos(user_ns),
+            kernel_time: Duration::from_nanos(kernel_ns),
+            voluntary_switches: self.nvcsw.load(Ordering::Relaxed),
+            involuntary_switches: self.nivcsw.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn wait_time(&self) -> Duration {
+        Duration::from_nanos(self.wait_ns.load(Ordering::Relaxed))
+    }
+
+    pub fn last_cpu(&self) -> Option<usize> {
+        match self.last_cpu.load(Ordering::Relaxed) {
+            NO_CPU => None,
+            cpu_id => Some(cpu_id),
+        }
+    }
+
+    pub fn created_at(&self) -> TimeValue {
+        TimeValue::from_nanos(self.created_ns)
+    }
+}
+
+/// Adds the CPU usage of a dropped task to the total.
+pub(crate) fn on_task_drop(task: &TaskInner) {
+    if !task.is_idle() {
+        DROPPED_USAGE.lock().add(&task.cpu_usage());
+    }
+}
+
+/// Returns the CPU usage of all tasks except the idle tasks, including the
+/// dropped ones.
+pub(crate) fn total_cpu_usage() -> CpuUsage {
+    let mut total = *DROPPED_USAGE.lock();
+    for task in crate::task::all_tasks() {
+        if !task.is_idle() {
+            total.add(&task.cpu_usage());
+        }
+    }
+    total
+}
+
+/// Marks the current task entering the kernel, see [`CpuUsage`].
+pub(crate) fn enter_kernel() {
+    let _guard = kernel_guard::IrqSave::new();
+    if let Some(curr) = crate::current_may_uninit() {
+        curr.times().enter_kernel(current_time_nanos());
+    }
+}
+
+/// Marks the current task leaving the kernel, see [`CpuUsage`].
+pub(crate) fn leave_kernel() {
+    let _guard = kernel_guard::IrqSave::new();
+    if let Some(curr) = crate::current_may_uninit() {
+        curr.times().leave_kernel(current_time_nanos());
+    }
+}
@@ -1,17 +1,19 @@
-use alloc::{boxed::Box, string::String, sync::Arc};
+use alloc::collections::BTreeMap;
+use alloc::sync::{Arc, Weak};
+use alloc::{boxed::Box, string::String, vec::Vec};
 use core::ops::Deref;
-use core::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicU8, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicU8, AtomicUsize, Ordering};
 use core::{alloc::Layout, cell::UnsafeCell, fmt, ptr::NonNull};
 
-#[cfg(feature = "preempt")]
-use core::sync::atomic::AtomicUsize;
-
 #[cfg(feature = "tls")]
 use axhal::tls::TlsArea;
 
 use axhal::arch::TaskContext;
 use memory_addr::{align_up_4k, VirtAddr};
+use spinlock::{SpinNoIrq, SpinRaw};
 
+use crate::pi_lock::PiState;
+use crate::stats::{CpuUsage, TaskStats, TaskTimes};
 use crate::{AxRunQueue, AxTask, AxTaskRef, WaitQueue};
 
 /// A unique identifier for a thread.
@@ -21,13 +23,27 @@ pub struct TaskId(u64);
 /// The possible states of a task.
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
-pub(crate) enum TaskState {
+pub enum TaskState {
+    /// The task is running on a CPU.
     Running = 1,
+    /// The task is ready to run, waiting in the run queue.
     Ready = 2,
+    /// The task is blocked, waiting in a wait queue or for a timer.
     Blocked = 3,
+    /// The task has exited, but it is not dropped yet.
     Exited = 4,
 }
 
+/// Number of words in a [`CpuMask`].
+const CPU_MASK_WORDS: usize = axconfig::SMP.div_ceil(usize::BITS as usize);
+
+/// A set of CPUs, such as the CPUs on which a task is allowed to run.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CpuMask([usize; CPU_MASK_WORDS]);
+
+/// All tasks that are not dropped, indexed by their IDs.
+static TASKS: SpinNoIrq<BTreeMap<u64, Weak<AxTask>>> = SpinNoIrq::new(BTreeMap::new());
+
 /// The inner task structure.
 pub struct TaskInner {
     id: TaskId,
@@ -38,9 +54,26 @@ pub struct TaskInner {
     entry: Option<*mut dyn FnOnce()>,
     state: AtomicU8,
 
+    cpu_id: AtomicUsize,
+    on_cpu: AtomicBool,
+    cpumask: SpinNoIrq<CpuMask>,
+    /// Serializes wakeups with changes of the scheduling parameters.
+    sched_lock: SpinRaw<()>,
+    /// Priority inheritance state, see [`PiLock`](crate::PiLock).
+    pi_state: SpinRaw<PiState>,
+    /// CPU time and context switch statistics.
+    times: TaskTimes,
+
     in_wait_queue: AtomicBool,
+    /// Whether the task is blocked at a point where it can be canceled.
+    in_cancelable_wait: AtomicBool,
+    cancel_pending: AtomicBool,
+    detached: AtomicBool,
     #[cfg(feature = "irq")]
     in_timer_list: AtomicBool,
+    /// The CPU whose timer list the task is in.
+    #[cfg(feature = "irq")]
+    timer_cpu_id: AtomicUsize,
 
     #[cfg(feature = "preempt")]
     need_resched: AtomicBool,
@@ -69,6 +102,73 @@ impl TaskId {
     }
 }
 
+impl CpuMask {
+    const BITS: usize = usize::BITS as usize;
+
+    /// Creates an empty mask.
+    pub const fn empty() -> Self {
+        Self([0; CPU_MASK_WORDS])
+    }
+
+    /// Creates a mask of all CPUs.
+    pub const fn full() -> Self {
+        let mut mask = Self::empty();
+        let mut cpu_id = 0;
+        while cpu_id < axconfig::SMP {
+            mask.0[cpu_id / Self::BITS] |= 1 << (cpu_id % Self::BITS);
+            cpu_id += 1;
+        }
+        mask
+    }
+
+    /// Creates a mask of only one CPU.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cpu_id` is not less than [`axconfig::SMP`].
+    pub const fn one(cpu_id: usize) -> Self {
+        assert!(cpu_id < axconfig::SMP);
+        let mut mask = Self::empty();
+        mask.0[cpu_id / Self::BITS] = 1 << (cpu_id % Self::BITS);
+        mask
+    }
+
+    /// Whether the CPU is in the mask.
+    pub const fn get(&self, cpu_id: usize) -> bool {
+        cpu_id < axconfig::SMP && self.0[cpu_id / Self::BITS] & (1 << (cpu_id % Self::BITS)) != 0
+    }
+
+    /// Adds the CPU to the mask if `value` is true, otherwise removes it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cpu_id` is not less than [`axconfig::SMP`].
+    pub fn set(&mut self, cpu_id: usize, value: bool) {
+        assert!(cpu_id < axconfig::SMP);
+        let bit = 1 << (cpu_id % Self::BITS);
+        if value {
+            self.0[cpu_id / Self::BITS] |= bit;
+        } else {
+            self.0[cpu_id / Self::BITS] &= !bit;
+        }
+    }
+
+    /// Whether the mask contains no CPU.
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|word| *word == 0)
+    }
+
+    /// Returns the IDs of the CPUs in the mask in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..axconfig::SMP).filter(|cpu_id| self.get(*cpu_id))
+    }
+
+    /// Returns the smallest ID of the CPUs in the mask.
+    pub fn first(&self) -> Option<usize> {
+        self.iter().next()
+    }
+}
+
 impl From<u8> for TaskState {
     #[inline]
     fn from(state: u8) -> Self {
@@ -101,14 +201,90 @@ impl TaskInner {
         alloc::format!("Task({}, {:?})", self.id.as_u64(), self.name)
     }
 
+    /// Gets the state of the task.
+    #[inline]
+    pub fn state(&self) -> TaskState {
+        self.state.load(Ordering::Acquire).into()
+    }
+
+    /// Gets the ID of the CPU on which the task is running, in whose run
+    /// queue it is waiting, or on which it ran last time.
+    #[inline]
+    pub fn cpu_id(&self) -> usize {
+        self.cpu_id.load(Ordering::Acquire)
+    }
+
+    /// Gets the CPUs on which the task is allowed to run.
+    pub fn cpumask(&self) -> CpuMask {
+        *self.cpumask.lock()
+    }
+
+    /// Whether the task is the idle task of a CPU.
+    #[inline]
+    pub const fn is_idle(&self) -> bool {
+        self.is_idle
+    }
+
+    /// Gets the CPU time used by the task and the number of its context
+    /// switches.
+    pub fn cpu_usage(&self) -> CpuUsage {
+        self.times.usage(self.is_running())
+    }
+
+    /// Gets a snapshot of the statistics of the task.
+    pub fn stats(&self) -> TaskStats {
+        TaskStats {
+            id: self.id,
+            name: self.name.clone(),
+            state: self.state(),
+            is_idle: self.is_idle,
+            usage: self.cpu_usage(),
+            wait_time: self.times.wait_time(),
+            last_cpu: self.times.last_cpu(),
+            created_at: self.times.created_at(),
+        }
+    }
+
+    /// Whether the task is requested to be canceled, see
+    /// [`cancel`](crate::cancel).
+    #[inline]
+    pub fn is_cancel_pending(&self) -> bool {
+        self.cancel_pending.load(Ordering::Acquire)
+    }
+
+    /// Whether the task is detached, see [`detach`](crate::detach).
+    #[inline]
+    pub fn is_detached(&self) -> bool {
+        self.detached.load(Ordering::Acquire)
+    }
+
     /// Wait for the task to exit, and return the exit code.
     ///
     /// It will return immediately if the task has already exited (but not dropped).
+    /// Returns [`None`] if the task is detached.
     pub fn join(&self) -> Option<i32> {
+        if self.is_detached() {
+            return None;
+        }
         self.wait_for_exit
             .wait_until(|| self.state() == TaskState::Exited);
         Some(self.exit_code.load(Ordering::Acquire))
     }
+
+    /// Same as [`join`](Self::join), but it is also a cancellation point: it
+    /// returns [`None`] at once if the current task is [canceled](crate::cancel).
+    pub fn join_cancelable(&self) -> Option<i32> {
+        if self.is_detached() {
+            return None;
+        }
+        if !self
+            .wait_for_exit
+            .wait_until_cancelable(|| self.state() == TaskState::Exited)
+        {
+            return None;
+        }
+        Some(self.exit_code.load(Ordering::Acquire))
+    }
 }
 
 // private methods
@@ -121,9 +297,20 @@ impl TaskInner {
             is_init: false,
             entry: None,
             state: AtomicU8::new(TaskState::Ready as u8),
+            cpu_id: AtomicUsize::new(0),
+            on_cpu: AtomicBool::new(false),
+            cpumask: SpinNoIrq::new(CpuMask::full()),
+            sched_lock: SpinRaw::new(()),
+            pi_state: SpinRaw::new(PiState::default()),
+            times: TaskTimes::new(),
             in_wait_queue: AtomicBool::new(false),
+            in_cancelable_wait: AtomicBool::new(false),
+            cancel_pending: AtomicBool::new(false),
+            detached: AtomicBool::new(false),
             #[cfg(feature = "irq")]
             in_timer_list: AtomicBool::new(false),
+            #[cfg(feature = "irq")]
+            timer_cpu_id: AtomicUsize::new(0),
             #[cfg(feature = "preempt")]
             need_resched: AtomicBool::new(false),
             #[cfg(feature = "preempt")]
@@ -157,7 +344,7 @@ impl TaskInner {
         if t.name == "idle" {
             t.is_idle = true;
         }
-        Arc::new(AxTask::new(t))
+        t.register()
     }
 
     /// Creates an "init task" using the current CPU states, to use as the
@@ -174,12 +361,18 @@ impl TaskInner {
         if t.name == "idle" {
             t.is_idle = true;
         }
-        Arc::new(AxTask::new(t))
+        t.register()
     }
 
-    #[inline]
-    pub(crate) fn state(&self) -> TaskState {
-        self.state.load(Ordering::Acquire).into()
+    /// Adds the task to the task table, which is removed when it is dropped.
+    /// Wraps the task for the scheduler, and adds it to the task table.
+    fn register(self) -> AxTaskRef {
+        #[cfg(all(feature = "sched_rt", feature = "sched_cfs"))]
+        let task = Arc::new(AxTask::new(scheduler::RTTask::new(self)));
+        #[cfg(not(all(feature = "sched_rt", feature = "sched_cfs")))]
+        let task = Arc::new(AxTask::new(self));
+        TASKS.lock().insert(task.id.as_u64(), Arc::downgrade(&task));
+        task
     }
 
     #[inline]
@@ -187,6 +380,20 @@ impl TaskInner {
         self.state.store(state as u8, Ordering::Release)
     }
 
+    /// Changes the state from `current` to `new` atomically, returns `false`
+    /// if the task is not in the `current` state.
+    #[inline]
+    pub(crate) fn transition_state(&self, current: TaskState, new: TaskState) -> bool {
+        self.state
+            .compare_exchange(
+                current as u8,
+                new as u8,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .is_ok()
+    }
+
     #[inline]
     pub(crate) fn is_running(&self) -> bool {
         matches!(self.state(), TaskState::Running)
@@ -198,18 +405,54 @@ impl TaskInner {
     }
 
     #[inline]
-    pub(crate) fn is_blocked(&self) -> bool {
-        matches!(self.state(), TaskState::Blocked)
+    pub(crate) const fn is_init(&self) -> bool {
+        self.is_init
     }
 
     #[inline]
-    pub(crate) const fn is_init(&self) -> bool {
-        self.is_init
+    pub(crate) fn set_cpu_id(&self, cpu_id: usize) {
+        self.cpu_id.store(cpu_id, Ordering::Release);
     }
 
+    /// The lock held when waking up the task or changing its priority, with
+    /// IRQs disabled.
+    ///
+    /// If the task is ready or running, its [`cpu_id`](Self::cpu_id) does not
+    /// change while either the lock or the run queue of the CPU is held.
     #[inline]
-    pub(crate) const fn is_idle(&self) -> bool {
-        self.is_idle
+    pub(crate) fn sched_lock(&self) -> &SpinRaw<()> {
+        &self.sched_lock
+    }
+
+    /// The priority inheritance state, which must be accessed with the global
+    /// PI lock held.
+    #[inline]
+    pub(crate) fn pi_state(&self) -> &SpinRaw<PiState> {
+        &self.pi_state
+    }
+
+    #[inline]
+    pub(crate) fn times(&self) -> &TaskTimes {
+        &self.times
+    }
+
+    #[inline]
+    pub(crate) fn set_cpumask(&self, cpumask: CpuMask) {
+        *self.cpumask.lock() = cpumask;
+    }
+
+    /// Whether the task is running on a CPU, or is being switched out of it.
+    ///
+    /// Other CPUs must not switch to the task until it is cleared, i.e., its
+    /// context is saved.
+    #[inline]
+    pub(crate) fn on_cpu(&self) -> bool {
+        self.on_cpu.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub(crate) fn set_on_cpu(&self, on_cpu: bool) {
+        self.on_cpu.store(on_cpu, Ordering::Release);
     }
 
     #[inline]
@@ -222,6 +465,34 @@ impl TaskInner {
         self.in_wait_queue.store(in_wait_queue, Ordering::Release);
     }
 
+    /// Whether the task is blocked at a cancellation point. It must be
+    /// accessed with the `sched_lock` held while the task is blocked.
+    #[inline]
+    pub(crate) fn in_cancelable_wait(&self) -> bool {
+        self.in_cancelable_wait.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub(crate) fn set_in_cancelable_wait(&self, in_cancelable_wait: bool) {
+        self.in_cancelable_wait
+            .store(in_cancelable_wait, Ordering::Release);
+    }
+
+    #[inline]
+    pub(crate) fn set_cancel_pending(&self) {
+        self.cancel_pending.store(true, Ordering::Release);
+    }
+
+    #[inline]
+    pub(crate) fn clear_cancel_pending(&self) {
+        self.cancel_pending.store(false, Ordering::Release);
+    }
+
+    #[inline]
+    pub(crate) fn set_detached(&self) {
+        self.detached.store(true, Ordering::Release);
+    }
+
     #[inline]
     #[cfg(feature = "irq")]
     pub(crate) fn in_timer_list(&self) -> bool {
@@ -234,6 +505,18 @@ impl TaskInner {
         self.in_timer_list.store(in_timer_list, Ordering::Release);
     }
 
+    #[inline]
+    #[cfg(feature = "irq")]
+    pub(crate) fn timer_cpu_id(&self) -> usize {
+        self.timer_cpu_id.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    #[cfg(feature = "irq")]
+    pub(crate) fn set_timer_cpu_id(&self, cpu_id: usize) {
+        self.timer_cpu_id.store(cpu_id, Ordering::Release);
+    }
+
     #[inline]
     #[cfg(feature = "preempt")]
     pub(crate) fn set_preempt_pending(&self, pending: bool) {
@@ -265,7 +548,7 @@ impl TaskInner {
     fn current_check_preempt_pending() {
         let curr = crate::current();
         if curr.need_resched.load(Ordering::Acquire) && curr.can_preempt(0) {
-            let mut rq = crate::RUN_QUEUE.lock();
+            let mut rq = crate::current_run_queue();
             if curr.need_resched.load(Ordering::Acquire) {
                 rq.preempt_resched();
             }
@@ -296,9 +579,27 @@ impl fmt::Debug for TaskInner {
 impl Drop for TaskInner {
     fn drop(&mut self) {
         debug!("task drop: {}", self.id_name());
+        crate::stats::on_task_drop(self);
+        TASKS.lock().remove(&self.id.as_u64());
     }
 }
 
+/// Returns all tasks that are not dropped, in the order of their IDs.
+pub(crate) fn all_tasks() -> Vec<AxTaskRef> {
+    TASKS.lock().values().filter_map(Weak::upgrade).collect()
+}
+
+/// Returns the task with the ID if it is not dropped.
+pub(crate) fn find_task(id: u64) -> Option<AxTaskRef> {
+    TASKS.lock().get(&id).and_then(Weak::upgrade)
+}
+
+/// Returns the task with the name and the smallest ID if it is not dropped.
+pub(crate) fn find_task_by_name(name: &str) -> Option<AxTaskRef> {
+    // the other tasks are dropped after releasing the table
+    all_tasks().into_iter().find(|task| task.name() == name)
+}
+
 struct TaskStack {
     ptr: NonNull<u8>,
     layout: Layout,
@@ -380,7 +681,10 @@ impl Deref for CurrentTask {
 
 extern "C" fn task_entry() -> ! {
     // release the lock that was implicitly held across the reschedule
-    unsafe { crate::RUN_QUEUE.force_unlock() };
+    unsafe {
+        crate::run_queue::finish_task_switch();
+        crate::run_queue::force_unlock();
+    }
     #[cfg(feature = "irq")]
     axhal::arch::enable_irqs();
     let task = crate::current();
@@ -1,7 +1,11 @@
-use core::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Mutex, Once};
+use core::future::{poll_fn, Future};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use core::task::{Poll, Waker};
+use core::time::Duration;
+use std::sync::{Arc, Mutex, Once};
 
-use crate::{self as axtask, current, WaitQueue};
+use crate::future::{self, Executor};
+use crate::{self as axtask, current, PiLock, WaitQueue};
 
 static INIT: Once = Once::new();
 static SERIAL: Mutex<()> = Mutex::new(());
@@ -128,3 +132,394 @@ fn test_task_join() {
         assert_eq!(tasks[i].join(), Some(i as _));
     }
 }
+
+#[test]
+fn test_task_table() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    let task = axtask::spawn_raw(|| axtask::exit(7), "table".into(), 0x1000);
+    let id = task.id().as_u64();
+    assert!(axtask::tasks().iter().any(|t| t.id() == task.id()));
+    assert_eq!(axtask::find_task(id).unwrap().name(), "table");
+    assert_eq!(task.state(), axtask::TaskState::Ready);
+
+    assert_eq!(task.join(), Some(7));
+    assert_eq!(task.state(), axtask::TaskState::Exited);
+    let current_id = current().id().as_u64();
+    assert!(axtask::find_task(current_id).is_some());
+}
+
+#[test]
+fn test_task_cancel_detach() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    static WQ: WaitQueue = WaitQueue::new();
+
+    // woken up at the cancellation point
+    let task = axtask::spawn_raw(
+        || {
+            while WQ.wait_cancelable() {}
+            assert!(current().is_cancel_pending());
+            axtask::exit(1);
+        },
+        "cancel".into(),
+        0x1000,
+    );
+    let found = axtask::find_task_by_name("cancel").unwrap();
+    assert!(Arc::ptr_eq(&found, &task));
+    drop(found);
+    while task.state() != axtask::TaskState::Blocked {
+        axtask::yield_now();
+    }
+    assert!(axtask::cancel(&task));
+    assert!(task.is_cancel_pending());
+    assert_eq!(task.join(), Some(1));
+    assert!(!axtask::cancel(&task));
+
+    // canceled before blocking
+    let task = axtask::spawn(|| {
+        assert!(!WQ.wait_cancelable());
+        assert!(current().is_cancel_pending());
+    });
+    assert!(axtask::cancel(&task));
+    assert_eq!(task.join(), Some(0));
+    assert!(!WQ.notify_one(false));
+
+    // a cancelable wait for a condition, then the request is withdrawn
+    let task = axtask::spawn(|| {
+        assert!(!WQ.wait_until_cancelable(|| false));
+        axtask::clear_cancel();
+        assert!(!current().is_cancel_pending());
+        assert!(WQ.wait_until_cancelable(|| true));
+    });
+    assert!(axtask::cancel(&task));
+    assert_eq!(task.join(), Some(0));
+    assert!(!WQ.notify_one(false));
+
+    // the plain waits are not cancellation points
+    let task = axtask::spawn(|| {
+        WQ.wait();
+        assert!(current().is_cancel_pending());
+    });
+    while task.state() != axtask::TaskState::Blocked {
+        axtask::yield_now();
+    }
+    assert!(axtask::cancel(&task));
+    axtask::yield_now();
+    assert_eq!(task.state(), axtask::TaskState::Blocked);
+    assert!(WQ.notify_one(false));
+    assert_eq!(task.join(), Some(0));
+
+    // a joining task is woken up
+    let blocked = axtask::spawn(|| WQ.wait());
+    let joiner = {
+        let blocked = blocked.clone();
+        axtask::spawn(move || assert_eq!(blocked.join_cancelable(), None))
+    };
+    while joiner.state() != axtask::TaskState::Blocked {
+        axtask::yield_now();
+    }
+    assert!(axtask::cancel(&joiner));
+    assert_eq!(joiner.join(), Some(0));
+    while !WQ.notify_one(false) {
+        axtask::yield_now();
+    }
+    assert_eq!(blocked.join_cancelable(), Some(0));
+
+    // a sleeping task is woken up
+    #[cfg(feature = "irq")]
+    {
+        let task = axtask::spawn(|| axtask::sleep(Duration::from_secs(3600)));
+        while task.state() != axtask::TaskState::Blocked {
+            axtask::yield_now();
+        }
+        assert!(axtask::cancel(&task));
+        assert_eq!(task.join(), Some(0));
+    }
+
+    // dropped by the GC task after exit
+    let task = axtask::spawn_raw(axtask::yield_now, "detach".into(), 0x1000);
+    let weak = Arc::downgrade(&task);
+    axtask::detach(task);
+    assert_eq!(weak.upgrade().unwrap().join(), None);
+    while weak.upgrade().is_some() {
+        axtask::yield_now();
+    }
+    assert!(axtask::find_task_by_name("detach").is_none());
+}
+
+#[test]
+fn test_cpu_affinity() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    let mut cpumask = axtask::CpuMask::empty();
+    assert!(cpumask.is_empty());
+    cpumask.set(0, true);
+    assert_eq!(cpumask, axtask::CpuMask::one(0));
+    assert_eq!(cpumask.first(), Some(0));
+    assert!(axtask::CpuMask::full().get(0));
+    assert!(!axtask::CpuMask::full().get(axconfig::SMP));
+
+    let task = axtask::spawn_on(|| assert_eq!(current().cpu_id(), 0), 0);
+    assert_eq!(task.cpumask(), cpumask);
+    assert_eq!(task.join(), Some(0));
+
+    assert!(!axtask::set_current_affinity(axtask::CpuMask::empty()));
+    assert!(axtask::set_current_affinity(cpumask));
+    assert_eq!(current().cpu_id(), 0);
+    assert!(axtask::set_current_affinity(axtask::CpuMask::full()));
+    assert_eq!(current().cpumask(), axtask::CpuMask::full());
+}
+
+#[test]
+#[cfg(any(feature = "sched_cfs", feature = "sched_edf"))]
+fn test_set_priority() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    static WQ: WaitQueue = WaitQueue::new();
+    static WOKEN: AtomicUsize = AtomicUsize::new(0);
+
+    // valid as nice values and as relative deadlines
+    let check = |task: &axtask::AxTaskRef, prio| {
+        assert!(axtask::set_priority(task, prio));
+        assert_eq!(axtask::get_priority(task), prio);
+    };
+
+    // a ready task
+    let task = axtask::spawn(|| {
+        WQ.wait_until(|| WOKEN.load(Ordering::Acquire) == 1);
+    });
+    check(&task, 5);
+    axtask::yield_now();
+    // a blocked task
+    check(&task, 3);
+    WOKEN.store(1, Ordering::Release);
+    WQ.notify_one(true);
+    assert_eq!(task.join(), Some(0));
+    // an exited task
+    assert!(!axtask::set_priority(&task, 1));
+
+    // the current task
+    let old_prio = axtask::get_priority(current().as_task_ref());
+    check(current().as_task_ref(), 2);
+    check(current().as_task_ref(), old_prio);
+}
+
+#[test]
+#[cfg(feature = "sched_edf")]
+fn test_priority_inheritance() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    // Relative deadlines in ticks, where a smaller one is a higher priority.
+    // They are much longer than the test, so the order of the tasks only
+    // depends on them.
+    const MAIN_PRIO: isize = 10000;
+    const LOW_PRIO: isize = 1000;
+    const MEDIUM_PRIO: isize = 100;
+    const HIGH_PRIO: isize = 10;
+
+    static LOCK_A: PiLock = PiLock::new();
+    static LOCK_B: PiLock = PiLock::new();
+    static WQ: WaitQueue = WaitQueue::new();
+    static STAGE: AtomicUsize = AtomicUsize::new(0);
+    static FINISHED: AtomicUsize = AtomicUsize::new(0);
+    static HIGH_ORDER: AtomicUsize = AtomicUsize::new(0);
+    static MEDIUM_ORDER: AtomicUsize = AtomicUsize::new(0);
+
+    // the main task only waits, so it must not keep the others from running
+    let old_prio = axtask::get_priority(current().as_task_ref());
+    assert!(axtask::set_priority(current().as_task_ref(), MAIN_PRIO));
+
+    // The low priority task holds two locks, and the high priority task waits
+    // for one of them. Without priority inheritance, the medium priority task
+    // that does not need the locks runs before the owner, and delays the high
+    // priority task.
+    let low = axtask::spawn(|| {
+        LOCK_A.lock();
+        LOCK_B.lock();
+        STAGE.store(1, Ordering::Release);
+        WQ.wait_until(|| STAGE.load(Ordering::Acquire) == 2);
+        // still boosted by the waiter of `LOCK_B`
+        unsafe { LOCK_A.unlock() };
+        assert_eq!(axtask::get_priority(current().as_task_ref()), HIGH_PRIO);
+        unsafe { LOCK_B.unlock() };
+        assert_eq!(axtask::get_priority(current().as_task_ref()), LOW_PRIO);
+    });
+    assert!(axtask::set_priority(&low, LOW_PRIO));
+    while STAGE.load(Ordering::Acquire) < 1 {
+        axtask::yield_now();
+    }
+
+    let high = axtask::spawn(|| {
+        LOCK_B.lock();
+        // the lock is handed over directly
+        assert!(LOCK_B.is_locked());
+        unsafe { LOCK_B.unlock() };
+        HIGH_ORDER.store(FINISHED.fetch_add(1, Ordering::AcqRel), Ordering::Release);
+    });
+    assert!(axtask::set_priority(&high, HIGH_PRIO));
+    while high.state() != axtask::TaskState::Blocked {
+        axtask::yield_now();
+    }
+
+    // the owner runs with the priority of the waiter
+    assert_eq!(axtask::get_priority(&low), HIGH_PRIO);
+    assert!(!LOCK_A.try_lock());
+
+    // It is ready when the owner is woken up, and finishes as soon as it runs
+    // after that.
+    let medium = axtask::spawn(|| {
+        while STAGE.load(Ordering::Acquire) < 2 {
+            axtask::yield_now();
+        }
+        MEDIUM_ORDER.store(FINISHED.fetch_add(1, Ordering::AcqRel), Ordering::Release);
+    });
+    assert!(axtask::set_priority(&medium, MEDIUM_PRIO));
+
+    STAGE.store(2, Ordering::Release);
+    WQ.notify_one(true);
+    assert_eq!(high.join(), Some(0));
+    assert_eq!(low.join(), Some(0));
+    assert_eq!(medium.join(), Some(0));
+    // the high priority task is not delayed by the medium priority one
+    assert!(HIGH_ORDER.load(Ordering::Acquire) < MEDIUM_ORDER.load(Ordering::Acquire));
+    assert!(axtask::set_priority(current().as_task_ref(), old_prio));
+    assert!(!LOCK_A.is_locked() && !LOCK_B.is_locked());
+}
+
+#[test]
+fn test_task_stats() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    static WQ: WaitQueue = WaitQueue::new();
+    static STAGE: AtomicUsize = AtomicUsize::new(0);
+    const BUSY: Duration = Duration::from_millis(2);
+
+    let task = axtask::spawn_raw(
+        || {
+            axhal::time::busy_wait(BUSY);
+            axtask::yield_now(); // involuntary, as it is still runnable
+            STAGE.store(1, Ordering::Release);
+            WQ.wait_until(|| STAGE.load(Ordering::Acquire) == 2); // voluntary
+        },
+        "stats".into(),
+        0x1000,
+    );
+    while STAGE.load(Ordering::Acquire) < 1 || task.state() != axtask::TaskState::Blocked {
+        axtask::yield_now();
+    }
+    let stats = axtask::task_stats()
+        .into_iter()
+        .find(|stats| stats.id == task.id())
+        .unwrap();
+    assert_eq!(stats.name, "stats");
+    assert_eq!(stats.state, axtask::TaskState::Blocked);
+    assert!(stats.usage.user_time >= BUSY);
+    assert!(stats.usage.involuntary_switches >= 1);
+    assert!(stats.usage.voluntary_switches >= 1);
+    assert_eq!(stats.last_cpu, Some(0));
+    assert!(stats.created_at <= axhal::time::current_time());
+
+    STAGE.store(2, Ordering::Release);
+    WQ.notify_one(true);
+    assert_eq!(task.join(), Some(0));
+    assert!(task.cpu_usage().cpu_time() >= stats.usage.cpu_time());
+    assert!(axtask::total_cpu_usage().cpu_time() >= task.cpu_usage().cpu_time());
+
+    // the time between `enter_kernel` and `leave_kernel` is the kernel time
+    let before = current().cpu_usage();
+    axtask::enter_kernel();
+    axhal::time::busy_wait(BUSY);
+    axtask::leave_kernel();
+    let after = current().cpu_usage();
+    assert!(after.kernel_time - before.kernel_time >= BUSY);
+    assert!(after.user_time >= before.user_time);
+}
+
+#[test]
+fn test_executor() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    const NUM_FUTURES: usize = 100;
+    static FLAG: AtomicBool = AtomicBool::new(false);
+    static WAKERS: Mutex<Vec<Waker>> = Mutex::new(Vec::new());
+    static FINISHED: AtomicUsize = AtomicUsize::new(0);
+
+    // completes after `FLAG` is set by another task
+    fn wait_flag() -> impl Future<Output = ()> {
+        poll_fn(|cx| {
+            if !FLAG.load(Ordering::Acquire) {
+                WAKERS.lock().unwrap().push(cx.waker().clone());
+                if !FLAG.load(Ordering::Acquire) {
+                    return Poll::Pending;
+                }
+            }
+            Poll::Ready(())
+        })
+    }
+
+    fn set_flag(num_waiters: usize) {
+        while WAKERS.lock().unwrap().len() < num_waiters {
+            axtask::yield_now();
+        }
+        FLAG.store(true, Ordering::Release);
+        for waker in WAKERS.lock().unwrap().drain(..) {
+            waker.wake();
+        }
+    }
+
+    // all futures run on the current task
+    let executor = Executor::new();
+    let spawner = executor.clone();
+    executor.spawn(async move {
+        for _ in 0..NUM_FUTURES {
+            spawner.spawn(async {
+                wait_flag().await;
+                future::sleep(Duration::from_millis(1)).await;
+                FINISHED.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+    });
+    let task = axtask::spawn(|| set_flag(NUM_FUTURES));
+    executor.run();
+    assert_eq!(FINISHED.load(Ordering::Relaxed), NUM_FUTURES);
+    assert_eq!(executor.num_futures(), 0);
+    assert_eq!(task.join(), Some(0));
+
+    // the current task blocks until woken up
+    FLAG.store(false, Ordering::Release);
+    let task = axtask::spawn(|| set_flag(1));
+    let result = future::block_on(async {
+        wait_flag().await;
+        42
+    });
+    assert_eq!(result, 42);
+    assert_eq!(task.join(), Some(0));
+}
+
+#[test]
+#[cfg(feature = "irq")]
+fn test_alarm() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    static FIRED: AtomicUsize = AtomicUsize::new(0);
+    let deadline = axhal::time::current_time() + Duration::from_millis(10);
+    axtask::set_alarm(deadline, move |now| {
+        assert!(now >= deadline);
+        FIRED.fetch_add(1, Ordering::Release);
+    });
+    let alarm = axtask::set_alarm(deadline, |_| {
+        FIRED.fetch_add(10, Ordering::Release);
+    });
+    axtask::cancel_alarm(alarm);
+    axtask::sleep(Duration::from_millis(30));
+    assert_eq!(FIRED.load(Ordering::Acquire), 1);
+}
@@ -1,40 +1,150 @@
-use alloc::sync::Arc;
-use axhal::time::current_time;
+use alloc::{boxed::Box, sync::Arc};
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::task::Waker;
+
+use axhal::time::{current_time, current_time_nanos, NANOS_PER_SEC};
 use lazy_init::LazyInit;
 use spinlock::SpinNoIrq;
 use timer_list::{TimeValue, TimerEvent, TimerList};
 
-use crate::{AxTaskRef, RUN_QUEUE};
+use crate::{current_run_queue, AxTaskRef};
+
+const PERIODIC_INTERVAL_NANOS: u64 = NANOS_PER_SEC / axconfig::TICKS_PER_SEC as u64;
+
+/// The timer lists of all CPUs, indexed by CPU IDs.
+static TIMER_LISTS: [LazyInit<SpinNoIrq<TimerList<AlarmEvent>>>; axconfig::SMP] =
+    [const { LazyInit::new() }; axconfig::SMP];
 
-// TODO: per-CPU
-static TIMER_LIST: LazyInit<SpinNoIrq<TimerList<TaskWakeupEvent>>> = LazyInit::new();
+/// The deadline that the timer of the current CPU is set to, in nanoseconds,
+/// or `u64::MAX` if it is not set.
+#[percpu::def_percpu]
+static TIMER_DEADLINE: u64 = u64::MAX;
 
-struct TaskWakeupEvent(AxTaskRef);
+/// The deadline of the next scheduler tick of the current CPU, in nanoseconds.
+#[percpu::def_percpu]
+static NEXT_TICK: u64 = 0;
+
+enum AlarmEvent {
+    /// Wakes up a blocked task.
+    Task(AxTaskRef),
+    /// Wakes up a future, see [`Sleep`](crate::future::Sleep). The ID is
+    /// used to cancel it.
+    Waker(u64, Waker),
+    /// Calls a function, see [`set_alarm`](crate::set_alarm). The ID is used
+    /// to cancel it.
+    Callback(u64, Box<dyn FnOnce(TimeValue) + Send>),
+}
+
+/// An alarm set by [`set_alarm`](crate::set_alarm), which can be cancelled
+/// by [`cancel_alarm`](crate::cancel_alarm).
+pub struct Alarm {
+    cpu_id: usize,
+    id: u64,
+}
+
+impl Alarm {
+    /// Allocates an alarm on the current CPU.
+    fn new() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Self {
+            cpu_id: axhal::cpu::this_cpu_id(),
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+impl TimerEvent for AlarmEvent {
+    fn callback(self, now: TimeValue) {
+        match self {
+            Self::Task(task) => {
+                let mut rq = current_run_queue();
+                task.set_in_timer_list(false);
+                rq.unblock_task(task, true);
+            }
+            Self::Waker(_, waker) => waker.wake(),
+            Self::Callback(_, callback) => callback(now),
+        }
+    }
+}
+
+fn local_timer_list() -> &'static SpinNoIrq<TimerList<AlarmEvent>> {
+    &TIMER_LISTS[axhal::cpu::this_cpu_id()]
+}
 
-impl TimerEvent for TaskWakeupEvent {
-    fn callback(self, _now: TimeValue) {
-        let mut rq = RUN_QUEUE.lock();
-        self.0.set_in_timer_list(false);
-        rq.unblock_task(self.0, true);
+/// Sets the timer of the current CPU to fire no later than `deadline_ns`.
+///
+/// IRQs must be disabled.
+fn set_timer_before(deadline_ns: u64) {
+    unsafe {
+        if deadline_ns < TIMER_DEADLINE.read_current_raw() {
+            TIMER_DEADLINE.write_current_raw(deadline_ns);
+            axhal::time::set_oneshot_timer(deadline_ns);
+        }
     }
 }
 
+/// Wakes up the task at the deadline, by the timer of the current CPU.
+///
+/// IRQs must be disabled.
 pub fn set_alarm_wakeup(deadline: TimeValue, task: AxTaskRef) {
-    let mut timers = TIMER_LIST.lock();
+    let cpu_id = axhal::cpu::this_cpu_id();
+    let mut timers = TIMER_LISTS[cpu_id].lock();
+    task.set_timer_cpu_id(cpu_id);
     task.set_in_timer_list(true);
-    timers.set(deadline, TaskWakeupEvent(task));
+    timers.set(deadline, AlarmEvent::Task(task));
+    drop(timers);
+    set_timer_before(deadline.as_nanos() as u64);
+}
+
+/// Wakes up the future by `waker` at the deadline, by the timer of the
+/// current CPU.
+///
+/// IRQs must be disabled.
+pub fn set_alarm_waker(deadline: TimeValue, waker: Waker) -> Alarm {
+    let alarm = Alarm::new();
+    TIMER_LISTS[alarm.cpu_id]
+        .lock()
+        .set(deadline, AlarmEvent::Waker(alarm.id, waker));
+    set_timer_before(deadline.as_nanos() as u64);
+    alarm
+}
+
+/// Calls `callback` at the deadline, by the timer of the current CPU.
+///
+/// IRQs must be disabled.
+pub fn set_alarm_callback(
+    deadline: TimeValue,
+    callback: Box<dyn FnOnce(TimeValue) + Send>,
+) -> Alarm {
+    let alarm = Alarm::new();
+    TIMER_LISTS[alarm.cpu_id]
+        .lock()
+        .set(deadline, AlarmEvent::Callback(alarm.id, callback));
+    set_timer_before(deadline.as_nanos() as u64);
+    alarm
+}
+
+/// Cancels the alarm set by [`set_alarm_waker`] or [`set_alarm_callback`],
+/// if it has not fired yet.
+pub fn cancel_alarm_event(alarm: Alarm) {
+    // the alarm may be set on another CPU
+    TIMER_LISTS[alarm.cpu_id].lock().cancel(|t| match t {
+        AlarmEvent::Waker(id, _) | AlarmEvent::Callback(id, _) => *id == alarm.id,
+        AlarmEvent::Task(_) => false,
+    });
 }
 
 pub fn cancel_alarm(task: &AxTaskRef) {
-    let mut timers = TIMER_LIST.lock();
+    // the alarm may be set on another CPU
+    let mut timers = TIMER_LISTS[task.timer_cpu_id()].lock();
     task.set_in_timer_list(false);
-    timers.cancel(|t| Arc::ptr_eq(&t.0, task));
+    timers.cancel(|t| matches!(t, AlarmEvent::Task(t) if Arc::ptr_eq(t, task)));
 }
 
-pub fn check_events() {
+fn check_events() {
     loop {
         let now = current_time();
-        let event = TIMER_LIST.lock().expire_one(now);
+        let event = local_timer_list().lock().expire_one(now);
         if let Some((_deadline, event)) = event {
             event.callback(now);
         } else {
@@ -43,6 +153,48 @@ pub fn check_events() {
     }
 }
 
+/// Starts the periodic scheduler ticks on the current CPU, when it switches
+/// from the idle task to other tasks.
+///
+/// IRQs must be disabled.
+pub fn start_tick() {
+    let next_tick = current_time_nanos() + PERIODIC_INTERVAL_NANOS;
+    unsafe { NEXT_TICK.write_current_raw(next_tick) };
+    set_timer_before(next_tick);
+}
+
+/// Handles the timer IRQ of the current CPU.
+///
+/// It wakes up the tasks whose deadlines have passed, ticks the scheduler if
+/// the periodic tick is due, and sets the timer for the next one. The periodic
+/// ticks are stopped when the CPU is idle, so that the timer only fires at the
+/// nearest deadline.
+pub fn on_timer_irq() {
+    // Safety: IRQs are disabled in IRQ handlers.
+    unsafe { TIMER_DEADLINE.write_current_raw(u64::MAX) };
+    check_events();
+
+    if !crate::current().is_idle() {
+        let now = current_time_nanos();
+        let mut next_tick = unsafe { NEXT_TICK.read_current_raw() };
+        if now >= next_tick {
+            current_run_queue().scheduler_timer_tick();
+            next_tick = (next_tick + PERIODIC_INTERVAL_NANOS).max(now + 1);
+            unsafe { NEXT_TICK.write_current_raw(next_tick) };
+        }
+        set_timer_before(next_tick);
+    }
+    if let Some(deadline) = local_timer_list().lock().next_deadline() {
+        set_timer_before(deadline.as_nanos() as u64);
+    }
+    if unsafe { TIMER_DEADLINE.read_current_raw() } == u64::MAX {
+        // nothing to wait for, but the timer still needs to be reset
+        axhal::time::set_oneshot_timer(u64::MAX);
+    }
+}
+
 pub fn init() {
-    TIMER_LIST.init_by(SpinNoIrq::new(TimerList::new()));
+    for timers in TIMER_LISTS.iter() {
+        timers.init_by(SpinNoIrq::new(TimerList::new()));
+    }
 }
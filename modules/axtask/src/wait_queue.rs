@@ -2,7 +2,7 @@ use alloc::collections::VecDeque;
 use alloc::sync::Arc;
 use spinlock::SpinRaw;
 
-use crate::{AxRunQueue, AxTaskRef, CurrentTask, RUN_QUEUE};
+use crate::{current_run_queue, AxRunQueue, AxTaskRef, CurrentTask};
 
 /// A queue to store sleeping tasks.
 ///
@@ -27,7 +27,7 @@ use crate::{AxRunQueue, AxTaskRef, CurrentTask, RUN_QUEUE};
 /// assert_eq!(VALUE.load(Ordering::Relaxed), 1);
 /// ```
 pub struct WaitQueue {
-    queue: SpinRaw<VecDeque<AxTaskRef>>, // we already disabled IRQs when lock the `RUN_QUEUE`
+    queue: SpinRaw<VecDeque<AxTaskRef>>, // we already disabled IRQs when lock the run queue
 }
 
 impl WaitQueue {
@@ -50,7 +50,7 @@ impl WaitQueue {
         // the event from another queue.
         if curr.in_wait_queue() {
             // wake up by timer (timeout).
-            // `RUN_QUEUE` is not locked here, so disable IRQs.
+            // The run queue is not locked here, so disable IRQs.
             let _guard = kernel_guard::IrqSave::new();
             self.queue.lock().retain(|t| !curr.ptr_eq(t));
             curr.set_in_wait_queue(false);
@@ -65,13 +65,30 @@ impl WaitQueue {
     /// Blocks the current task and put it into the wait queue, until other task
     /// notifies it.
     pub fn wait(&self) {
-        RUN_QUEUE.lock().block_current(|task| {
+        current_run_queue().block_current(|task| {
             task.set_in_wait_queue(true);
             self.queue.lock().push_back(task)
         });
         self.cancel_events(crate::current());
     }
 
+    /// Blocks the current task like [`wait`](Self::wait), but it is also a
+    /// cancellation point.
+    ///
+    /// Returns `false` if it returns as the current task is
+    /// [canceled](crate::cancel).
+    pub fn wait_cancelable(&self) -> bool {
+        let curr = crate::current();
+        let blocked = current_run_queue().block_current_cancelable(|task| {
+            task.set_in_wait_queue(true);
+            self.queue.lock().push_back(task)
+        });
+        // still in the wait queue, must have been canceled
+        let canceled = !blocked || curr.in_wait_queue();
+        self.cancel_events(curr);
+        !canceled
+    }
+
     /// Blocks the current task and put it into the wait queue, until the given
     /// `condition` becomes true.
     ///
@@ -81,23 +98,68 @@ impl WaitQueue {
     where
         F: Fn() -> bool,
     {
+        self.wait_until_impl(condition, false);
+    }
+
+    /// Blocks the current task like [`wait_until`](Self::wait_until), but it
+    /// is also a cancellation point.
+    ///
+    /// Returns `false` if it returns before the condition becomes true, as the
+    /// current task is [canceled](crate::cancel).
+    pub fn wait_until_cancelable<F>(&self, condition: F) -> bool
+    where
+        F: Fn() -> bool,
+    {
+        self.wait_until_impl(condition, true)
+    }
+
+    fn wait_until_impl<F>(&self, condition: F, cancelable: bool) -> bool
+    where
+        F: Fn() -> bool,
+    {
+        let mut canceled = false;
         loop {
-            let mut rq = RUN_QUEUE.lock();
+            let mut rq = current_run_queue();
+            // Hold the queue while checking the condition, so that a task that
+            // makes it true on another CPU can not notify before we are queued.
+            let mut wq = self.queue.lock();
             if condition() {
                 break;
             }
-            rq.block_current(|task| {
+            let push = move |task: AxTaskRef| {
                 task.set_in_wait_queue(true);
-                self.queue.lock().push_back(task);
-            });
+                wq.push_back(task);
+            };
+            if !cancelable {
+                rq.block_current(push);
+            } else if !rq.block_current_cancelable(push) {
+                canceled = true;
+                break;
+            }
         }
         self.cancel_events(crate::current());
+        !canceled
     }
 
     /// Blocks the current task and put it into the wait queue, until other tasks
     /// notify it, or the given duration has elapsed.
     #[cfg(feature = "irq")]
     pub fn wait_timeout(&self, dur: core::time::Duration) -> bool {
+        self.wait_timeout_impl(dur, false)
+    }
+
+    /// Blocks the current task like [`wait_timeout`](Self::wait_timeout), but
+    /// it is also a cancellation point.
+    ///
+    /// Returns `true` if it is not notified, i.e., timed out, or the current
+    /// task is [canceled](crate::cancel).
+    #[cfg(feature = "irq")]
+    pub fn wait_timeout_cancelable(&self, dur: core::time::Duration) -> bool {
+        self.wait_timeout_impl(dur, true)
+    }
+
+    #[cfg(feature = "irq")]
+    fn wait_timeout_impl(&self, dur: core::time::Duration, cancelable: bool) -> bool {
         let curr = crate::current();
         let deadline = axhal::time::current_time() + dur;
         debug!(
@@ -105,13 +167,23 @@ impl WaitQueue {
             curr.id_name(),
             deadline
         );
-        crate::timers::set_alarm_wakeup(deadline, curr.clone());
 
-        RUN_QUEUE.lock().block_current(|task| {
+        let push = |task: AxTaskRef| {
+            // set after blocked, the timer may fire on another CPU at once
+            crate::timers::set_alarm_wakeup(deadline, task.clone());
             task.set_in_wait_queue(true);
             self.queue.lock().push_back(task)
-        });
-        let timeout = curr.in_wait_queue(); // still in the wait queue, must have timed out
+        };
+        let mut rq = current_run_queue();
+        let blocked = if cancelable {
+            rq.block_current_cancelable(push)
+        } else {
+            rq.block_current(push);
+            true
+        };
+        drop(rq);
+        // still in the wait queue, must have timed out or been canceled
+        let timeout = !blocked || curr.in_wait_queue();
         self.cancel_events(curr);
         timeout
     }
@@ -123,6 +195,33 @@ impl WaitQueue {
     /// the above conditions are met.
     #[cfg(feature = "irq")]
     pub fn wait_timeout_until<F>(&self, dur: core::time::Duration, condition: F) -> bool
+    where
+        F: Fn() -> bool,
+    {
+        self.wait_timeout_until_impl(dur, condition, false)
+    }
+
+    /// Blocks the current task like
+    /// [`wait_timeout_until`](Self::wait_timeout_until), but it is also a
+    /// cancellation point.
+    ///
+    /// Returns `true` if the condition is not met, i.e., timed out, or the
+    /// current task is [canceled](crate::cancel).
+    #[cfg(feature = "irq")]
+    pub fn wait_timeout_until_cancelable<F>(&self, dur: core::time::Duration, condition: F) -> bool
+    where
+        F: Fn() -> bool,
+    {
+        self.wait_timeout_until_impl(dur, condition, true)
+    }
+
+    #[cfg(feature = "irq")]
+    fn wait_timeout_until_impl<F>(
+        &self,
+        dur: core::time::Duration,
+        condition: F,
+        cancelable: bool,
+    ) -> bool
     where
         F: Fn() -> bool,
     {
@@ -133,19 +232,28 @@ impl WaitQueue {
             curr.id_name(),
             deadline
         );
-        crate::timers::set_alarm_wakeup(deadline, curr.clone());
 
         let mut timeout = true;
         while axhal::time::current_time() < deadline {
-            let mut rq = RUN_QUEUE.lock();
+            let mut rq = current_run_queue();
+            let mut wq = self.queue.lock();
             if condition() {
                 timeout = false;
                 break;
             }
-            rq.block_current(|task| {
+            let push = move |task: AxTaskRef| {
+                // the alarm is cleared if it fired while the task was running
+                if !task.in_timer_list() {
+                    crate::timers::set_alarm_wakeup(deadline, task.clone());
+                }
                 task.set_in_wait_queue(true);
-                self.queue.lock().push_back(task);
-            });
+                wq.push_back(task);
+            };
+            if !cancelable {
+                rq.block_current(push);
+            } else if !rq.block_current_cancelable(push) {
+                break;
+            }
         }
         self.cancel_events(curr);
         timeout
@@ -156,7 +264,7 @@ impl WaitQueue {
     /// If `resched` is true, the current task will be preempted when the
     /// preemption is enabled.
     pub fn notify_one(&self, resched: bool) -> bool {
-        let mut rq = RUN_QUEUE.lock();
+        let mut rq = current_run_queue();
         if !self.queue.lock().is_empty() {
             self.notify_one_locked(resched, &mut rq)
         } else {
@@ -170,14 +278,14 @@ impl WaitQueue {
     /// preemption is enabled.
     pub fn notify_all(&self, resched: bool) {
         loop {
-            let mut rq = RUN_QUEUE.lock();
+            let mut rq = current_run_queue();
             if let Some(task) = self.queue.lock().pop_front() {
                 task.set_in_wait_queue(false);
                 rq.unblock_task(task, resched);
             } else {
                 break;
             }
-            drop(rq); // we must unlock `RUN_QUEUE` after unlocking `self.queue`.
+            drop(rq); // we must unlock the run queue after unlocking `self.queue`.
         }
     }
 
@@ -185,8 +293,8 @@ impl WaitQueue {
     ///
     /// If `resched` is true, the current task will be preempted when the
     /// preemption is enabled.
-    pub fn notify_task(&mut self, resched: bool, task: &AxTaskRef) -> bool {
-        let mut rq = RUN_QUEUE.lock();
+    pub fn notify_task(&self, resched: bool, task: &AxTaskRef) -> bool {
+        let mut rq = current_run_queue();
         let mut wq = self.queue.lock();
         if let Some(index) = wq.iter().position(|t| Arc::ptr_eq(t, task)) {
             task.set_in_wait_queue(false);
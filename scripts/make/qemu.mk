@@ -39,6 +39,11 @@ qemu_args-$(BLK) += \
   -device virtio-blk-$(vdev-suffix),drive=disk0 \
   -drive id=disk0,if=none,format=raw,file=$(DISK_IMG)
 
+extra_disks := $(shell seq 1 $(words $(EXTRA_DISK_IMGS)))
+qemu_args-$(BLK) += $(foreach i,$(extra_disks), \
+  -device virtio-blk-$(vdev-suffix),drive=disk$(i) \
+  -drive id=disk$(i),if=none,format=raw,file=$(word $(i),$(EXTRA_DISK_IMGS)))
+
 qemu_args-$(NET) += \
   -device virtio-net-$(vdev-suffix),netdev=net0
 
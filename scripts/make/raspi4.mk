@@ -12,6 +12,11 @@ BSP ?= rpi4
 # Default to a serial device name that is common in Linux.
 DEV_SERIAL ?= /dev/ttyUSB0
 
+# SD card images have a boot partition before the data partition.
+ifeq ($(ROOT_PART),)
+  ROOT_PART := 1
+endif
+
 ##--------------------------------------------------------------------------------------------------
 ## BSP-specific configuration values
 ##--------------------------------------------------------------------------------------------------
@@ -118,6 +118,8 @@ if [ -z "$1" ]; then
         "apps/c/helloworld"
         "apps/c/memtest"
         "apps/c/sqlite3"
+        "apps/c/statfs"
+        "apps/c/signal"
         "apps/c/httpclient"
         "apps/c/pthread/basic"
         "apps/c/pthread/sleep"
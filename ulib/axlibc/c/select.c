@@ -9,9 +9,18 @@
 int pselect(int n, fd_set *restrict rfds, fd_set *restrict wfds, fd_set *restrict efds,
             const struct timespec *restrict ts, const sigset_t *restrict mask)
 {
-    struct timeval tv = {ts->tv_sec, ts->tv_nsec / 1000};
-    select(n, rfds, wfds, efds, &tv);
-    return 0;
+    // TODO: `mask` is ignored, as signals are not supported yet.
+    struct timeval tv, *tvp = NULL;
+    if (ts) {
+        if (ts->tv_sec < 0 || ts->tv_nsec < 0 || ts->tv_nsec >= 1000000000) {
+            errno = EINVAL;
+            return -1;
+        }
+        tv.tv_sec = ts->tv_sec;
+        tv.tv_usec = ts->tv_nsec / 1000;
+        tvp = &tv;
+    }
+    return select(n, rfds, wfds, efds, tvp);
 }
 
 #endif // AX_CONFIG_SELECT
@@ -2,6 +2,7 @@
 #include <signal.h>
 #include <stddef.h>
 #include <stdio.h>
+#include <unistd.h>
 
 int sigaction_helper(int signum, const struct sigaction *act, struct sigaction *oldact,
                      size_t sigsetsize)
@@ -33,13 +34,6 @@ int sigaction(int sig, const struct sigaction *restrict act, struct sigaction *r
     return sigaction_helper(sig, act, oact, sizeof(sigset_t));
 }
 
-// TODO
-int kill(pid_t __pid, int __sig)
-{
-    unimplemented();
-    return 0;
-}
-
 int sigemptyset(sigset_t *set)
 {
     set->__bits[0] = 0;
@@ -52,11 +46,9 @@ int sigemptyset(sigset_t *set)
     return 0;
 }
 
-// TODO
 int raise(int __sig)
 {
-    unimplemented();
-    return 0;
+    return kill(getpid(), __sig);
 }
 
 int sigaddset(sigset_t *set, int sig)
@@ -70,13 +62,6 @@ int sigaddset(sigset_t *set, int sig)
     return 0;
 }
 
-// TODO
-int pthread_sigmask(int __how, const sigset_t *restrict __newmask, sigset_t *restrict __oldmask)
-{
-    unimplemented();
-    return 0;
-}
-
 #ifdef AX_CONFIG_MULTITASK
 // TODO
 int pthread_kill(pthread_t t, int sig)
@@ -1,7 +1,9 @@
 #include <errno.h>
+#include <fcntl.h>
 #include <limits.h>
 #include <stddef.h>
 #include <stdio.h>
+#include <sys/stat.h>
 #include <sys/time.h>
 #include <time.h>
 
@@ -160,11 +162,16 @@ int gettimeofday(struct timeval *tv, struct timezone *tz)
     return 0;
 }
 
-// TODO:
 int utimes(const char *filename, const struct timeval times[2])
 {
-    unimplemented();
-    return 0;
+    struct timespec ts[2];
+    if (!times)
+        return utimensat(AT_FDCWD, filename, NULL, 0);
+    for (int i = 0; i < 2; i++) {
+        ts[i].tv_sec = times[i].tv_sec;
+        ts[i].tv_nsec = times[i].tv_usec * 1000;
+    }
+    return utimensat(AT_FDCWD, filename, ts, 0);
 }
 
 // TODO
@@ -188,11 +195,15 @@ char *ctime_r(const time_t *t, char *buf)
     return NULL;
 }
 
-// TODO
 clock_t clock(void)
 {
-    unimplemented();
-    return 0;
+    struct timespec ts;
+
+    if (clock_gettime(CLOCK_PROCESS_CPUTIME_ID, &ts))
+        return -1;
+    if (ts.tv_sec > LONG_MAX / 1000000 - 1)
+        return -1;
+    return ts.tv_sec * 1000000 + ts.tv_nsec / 1000;
 }
 
 #ifdef AX_CONFIG_FP_SIMD
@@ -54,74 +54,21 @@ int usleep(unsigned useconds)
 
 #ifdef AX_CONFIG_FS
 
-// TODO:
-int access(const char *pathname, int mode)
-{
-    unimplemented();
-    return 0;
-}
-
-// TODO:
-ssize_t readlink(const char *path, char *buf, size_t bufsiz)
+int fdatasync(int fd)
 {
-    unimplemented();
-    return 0;
-}
-
-// TODO:
-int unlink(const char *pathname)
-{
-    unimplemented();
-    return 0;
-}
-
-// TODO:
-int rmdir(const char *pathname)
-{
-    unimplemented();
-    return 0;
-}
-
-// TODO:
-int fsync(int fd)
-{
-    unimplemented();
-    return 0;
+    return fsync(fd);
 }
 
-// TODO
-int fdatasync(int __fildes)
-{
-    unimplemented();
-    return 0;
-}
-
-// TODO:
-int fchown(int fd, uid_t owner, gid_t group)
-{
-    unimplemented("owner: %x group: %x", owner, group);
-    return 0;
-}
-
-// TODO:
-int ftruncate(int fd, off_t length)
-{
-    unimplemented();
-    return 0;
-}
-
-// TODO
-int chdir(const char *__path)
-{
-    unimplemented();
-    return 0;
-}
-
-// TODO
 int truncate(const char *path, off_t length)
 {
-    unimplemented();
-    return 0;
+    int fd = open(path, O_WRONLY);
+    if (fd < 0)
+        return -1;
+    int ret = ftruncate(fd, length);
+    int errno_save = errno;
+    close(fd);
+    errno = errno_save;
+    return ret;
 }
 
 #endif // AX_CONFIG_FS
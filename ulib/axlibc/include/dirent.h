@@ -29,6 +29,7 @@ struct dirent *readdir(DIR *);
 int readdir_r(DIR *__restrict, struct dirent *__restrict, struct dirent **__restrict);
 void rewinddir(DIR *);
 int dirfd(DIR *);
+ssize_t getdents64(int, void *, size_t);
 
 #define DT_UNKNOWN 0
 #define DT_FIFO    1
@@ -15,12 +15,20 @@ typedef struct {
     unsigned __attr;
 } pthread_condattr_t;
 
-#include <ax_pthread_mutex.h>
+#include <ax_pthread_sync.h>
 
 typedef struct {
     unsigned __attr;
 } pthread_mutexattr_t;
 
+typedef struct {
+    unsigned __attr;
+} pthread_rwlockattr_t;
+
+typedef struct {
+    unsigned __attr;
+} pthread_barrierattr_t;
+
 typedef struct {
     union {
         int __i[sizeof(long) == 8 ? 14 : 9];
@@ -32,21 +40,13 @@ typedef struct {
 #define _a_guardsize __u.__s[1]
 #define _a_stackaddr __u.__s[2]
 
-typedef struct {
-    union {
-        int __i[12];
-        volatile int __vi[12];
-        void *__p[12 * sizeof(int) / sizeof(void *)];
-    } __u;
-} pthread_cond_t;
-#define _c_clock  __u.__i[4]
-#define _c_shared __u.__p[0]
-
 typedef void *pthread_t;
 
 #define PTHREAD_CANCELED ((void *)-1)
 #define SIGCANCEL        33
 
+#define PTHREAD_BARRIER_SERIAL_THREAD (-1)
+
 #ifdef AX_CONFIG_MULTITASK
 
 _Noreturn void pthread_exit(void *);
@@ -55,6 +55,7 @@ pthread_t pthread_self(void);
 int pthread_create(pthread_t *__restrict, const pthread_attr_t *__restrict, void *(*)(void *),
                    void *__restrict);
 int pthread_join(pthread_t t, void **res);
+int pthread_detach(pthread_t);
 
 int pthread_setcancelstate(int, int *);
 int pthread_setcanceltype(int, int *);
@@ -70,10 +71,27 @@ int pthread_setname_np(pthread_t, const char *);
 
 int pthread_cond_init(pthread_cond_t *__restrict__ __cond,
                       const pthread_condattr_t *__restrict__ __cond_attr);
+int pthread_cond_destroy(pthread_cond_t *__cond);
 int pthread_cond_signal(pthread_cond_t *__cond);
 int pthread_cond_wait(pthread_cond_t *__restrict__ __cond, pthread_mutex_t *__restrict__ __mutex);
+int pthread_cond_timedwait(pthread_cond_t *__restrict__ __cond,
+                           pthread_mutex_t *__restrict__ __mutex,
+                           const struct timespec *__restrict__ __abstime);
 int pthread_cond_broadcast(pthread_cond_t *);
 
+int pthread_rwlock_init(pthread_rwlock_t *__restrict, const pthread_rwlockattr_t *__restrict);
+int pthread_rwlock_destroy(pthread_rwlock_t *);
+int pthread_rwlock_rdlock(pthread_rwlock_t *);
+int pthread_rwlock_tryrdlock(pthread_rwlock_t *);
+int pthread_rwlock_wrlock(pthread_rwlock_t *);
+int pthread_rwlock_trywrlock(pthread_rwlock_t *);
+int pthread_rwlock_unlock(pthread_rwlock_t *);
+
+int pthread_barrier_init(pthread_barrier_t *__restrict, const pthread_barrierattr_t *__restrict,
+                         unsigned);
+int pthread_barrier_destroy(pthread_barrier_t *);
+int pthread_barrier_wait(pthread_barrier_t *);
+
 int pthread_attr_init(pthread_attr_t *__attr);
 int pthread_attr_getstacksize(const pthread_attr_t *__restrict__ __attr,
                               size_t *__restrict__ __stacksize);
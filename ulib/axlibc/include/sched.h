@@ -2,6 +2,11 @@
 #define _SCHED_H
 
 #include <stddef.h>
+#include <sys/types.h>
+
+struct sched_param {
+    int sched_priority;
+};
 
 typedef struct cpu_set_t {
     unsigned long __bits[128 / sizeof(long)];
@@ -20,4 +25,7 @@ typedef struct cpu_set_t {
 
 int sched_setaffinity(pid_t, size_t, const cpu_set_t *);
 
+int sched_getparam(pid_t, struct sched_param *);
+int sched_setparam(pid_t, const struct sched_param *);
+
 #endif // _SCHED_H
@@ -0,0 +1,22 @@
+#ifndef _SEMAPHORE_H
+#define _SEMAPHORE_H
+
+#include <ax_pthread_sync.h>
+#include <time.h>
+
+#define SEM_FAILED    ((sem_t *)0)
+#define SEM_VALUE_MAX 0x7fffffff
+
+#ifdef AX_CONFIG_MULTITASK
+
+int sem_init(sem_t *, int, unsigned);
+int sem_destroy(sem_t *);
+int sem_wait(sem_t *);
+int sem_trywait(sem_t *);
+int sem_timedwait(sem_t *__restrict, const struct timespec *__restrict);
+int sem_post(sem_t *);
+int sem_getvalue(sem_t *__restrict, int *__restrict);
+
+#endif // AX_CONFIG_MULTITASK
+
+#endif // _SEMAPHORE_H
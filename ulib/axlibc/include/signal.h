@@ -169,6 +169,7 @@ int sigemptyset(sigset_t *);
 int raise(int);
 int sigaddset(sigset_t *, int);
 int pthread_sigmask(int, const sigset_t *__restrict, sigset_t *__restrict);
+int sigprocmask(int, const sigset_t *__restrict, sigset_t *__restrict);
 
 int kill(pid_t, int);
 
@@ -61,6 +61,12 @@ int fclose(FILE *);
 
 int remove(const char *);
 int rename(const char *, const char *);
+int renameat(int, const char *, int, const char *);
+int renameat2(int, const char *, int, const char *, unsigned);
+
+#define RENAME_NOREPLACE 1
+#define RENAME_EXCHANGE  2
+#define RENAME_WHITEOUT  4
 
 int feof(FILE *__stream);
 int ferror(FILE *);
@@ -2,6 +2,7 @@
 #define _SYS_RESOURCE_H
 
 #include <sys/time.h>
+#include <sys/types.h>
 
 typedef unsigned long long rlim_t;
 
@@ -30,8 +31,13 @@ struct rlimit {
 #define RLIMIT_RTTIME     15
 #define RLIMIT_NLIMITS    16
 
+#define PRIO_PROCESS 0
+#define PRIO_PGRP    1
+#define PRIO_USER    2
+
 #define RUSAGE_SELF     0
 #define RUSAGE_CHILDREN -1
+#define RUSAGE_THREAD   1
 
 struct rusage {
     struct timeval ru_utime;
@@ -60,4 +66,7 @@ int getrlimit(int __resource, struct rlimit *__rlimits);
 
 int getrusage(int __who, struct rusage *__usage);
 
+int getpriority(int __which, id_t __who);
+int setpriority(int __which, id_t __who, int __prio);
+
 #endif
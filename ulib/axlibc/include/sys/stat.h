@@ -65,14 +65,21 @@ struct stat {
 #define S_IRWXO 0007
 #endif
 
+#define UTIME_NOW  0x3fffffff
+#define UTIME_OMIT 0x3ffffffe
+
 int stat(const char *path, struct stat *buf);
 int fstat(int fd, struct stat *buf);
 int lstat(const char *path, struct stat *buf);
 
 int fchmod(int fd, mode_t mode);
 int chmod(const char *file, mode_t mode);
+int fchmodat(int dirfd, const char *pathname, mode_t mode, int flags);
 int mkdir(const char *pathname, mode_t mode);
+int mkdirat(int dirfd, const char *pathname, mode_t mode);
 mode_t umask(mode_t mask);
 int fstatat(int, const char *__restrict, struct stat *__restrict, int);
+int utimensat(int dirfd, const char *pathname, const struct timespec times[2], int flags);
+int futimens(int fd, const struct timespec times[2]);
 
 #endif
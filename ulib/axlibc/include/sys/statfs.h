@@ -0,0 +1,28 @@
+#ifndef __SYS_STATFS_H__
+#define __SYS_STATFS_H__
+
+#include <sys/types.h>
+
+typedef struct __fsid_t {
+    int __val[2];
+} fsid_t;
+
+struct statfs {
+    unsigned long f_type;  /* type of filesystem */
+    unsigned long f_bsize; /* optimal transfer block size */
+    fsblkcnt_t f_blocks;   /* total data blocks in filesystem */
+    fsblkcnt_t f_bfree;    /* free blocks in filesystem */
+    fsblkcnt_t f_bavail;   /* free blocks available to unprivileged user */
+    fsfilcnt_t f_files;    /* total file nodes in filesystem */
+    fsfilcnt_t f_ffree;    /* free file nodes in filesystem */
+    fsid_t f_fsid;         /* filesystem ID */
+    unsigned long f_namelen; /* maximum length of filenames */
+    unsigned long f_frsize;  /* fragment size */
+    unsigned long f_flags;   /* mount flags of filesystem */
+    unsigned long f_spare[4];
+};
+
+int statfs(const char *path, struct statfs *buf);
+int fstatfs(int fd, struct statfs *buf);
+
+#endif // __SYS_STATFS_H__
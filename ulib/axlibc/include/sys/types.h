@@ -12,9 +12,12 @@ typedef uint64_t ino_t;
 typedef uint64_t dev_t;
 typedef long blksize_t;
 typedef int64_t blkcnt_t;
+typedef uint64_t fsblkcnt_t;
+typedef uint64_t fsfilcnt_t;
 
 typedef int pid_t;
 typedef unsigned uid_t;
 typedef unsigned gid_t;
+typedef unsigned id_t;
 
 #endif // __SYS_TYPES_H__
@@ -4,9 +4,11 @@
 #include <stddef.h>
 #include <sys/time.h>
 
-#define CLOCK_REALTIME  0
-#define CLOCK_MONOTONIC 1
-#define CLOCKS_PER_SEC  1000000L
+#define CLOCK_REALTIME           0
+#define CLOCK_MONOTONIC          1
+#define CLOCK_PROCESS_CPUTIME_ID 2
+#define CLOCK_THREAD_CPUTIME_ID  3
+#define CLOCKS_PER_SEC           1000000L
 
 struct tm {
     int tm_sec;   /* seconds of minute */
@@ -22,6 +24,11 @@ struct tm {
     const char *__tm_zone;
 };
 
+struct itimerspec {
+    struct timespec it_interval;
+    struct timespec it_value;
+};
+
 clock_t clock(void);
 time_t time(time_t *);
 double difftime(time_t, time_t);
@@ -1,7 +1,10 @@
 use crate::{ctypes, utils::e};
-use arceos_posix_api::{sys_close, sys_dup, sys_dup2, sys_fcntl};
+use arceos_posix_api::{
+    sys_close, sys_dup, sys_dup2, sys_eventfd, sys_fcntl, sys_read, sys_signalfd,
+    sys_timerfd_create, sys_timerfd_gettime, sys_timerfd_settime, sys_write,
+};
 use axerrno::LinuxError;
-use core::ffi::c_int;
+use core::ffi::{c_int, c_uint, c_void};
 
 /// Close a file by `fd`.
 #[no_mangle]
@@ -52,3 +55,66 @@ pub unsafe extern "C" fn dup3(old_fd: c_int, new_fd: c_int, flags: c_int) -> c_i
 pub unsafe extern "C" fn ax_fcntl(fd: c_int, cmd: c_int, arg: usize) -> c_int {
     e(sys_fcntl(fd, cmd, arg))
 }
+
+/// Create a file descriptor for event notification.
+#[no_mangle]
+pub unsafe extern "C" fn eventfd(initval: c_uint, flags: c_int) -> c_int {
+    e(sys_eventfd(initval, flags))
+}
+
+/// Read the counter of an eventfd into `value`.
+///
+/// Return 0 if succeed
+#[no_mangle]
+pub unsafe extern "C" fn eventfd_read(fd: c_int, value: *mut u64) -> c_int {
+    let size = core::mem::size_of::<u64>();
+    if e(sys_read(fd, value as *mut c_void, size) as _) as usize == size {
+        0
+    } else {
+        -1
+    }
+}
+
+/// Add `value` to the counter of an eventfd.
+///
+/// Return 0 if succeed
+#[no_mangle]
+pub unsafe extern "C" fn eventfd_write(fd: c_int, value: u64) -> c_int {
+    let size = core::mem::size_of::<u64>();
+    let buf = &value as *const u64 as *const c_void;
+    if e(sys_write(fd, buf, size) as _) as usize == size {
+        0
+    } else {
+        -1
+    }
+}
+
+/// Create a timer that notifies via a file descriptor.
+#[no_mangle]
+pub unsafe extern "C" fn timerfd_create(clockid: c_int, flags: c_int) -> c_int {
+    e(sys_timerfd_create(clockid as _, flags))
+}
+
+/// Arm or disarm the timer referred to by `fd`.
+#[no_mangle]
+pub unsafe extern "C" fn timerfd_settime(
+    fd: c_int,
+    flags: c_int,
+    new_value: *const ctypes::itimerspec,
+    old_value: *mut ctypes::itimerspec,
+) -> c_int {
+    e(sys_timerfd_settime(fd, flags, new_value, old_value))
+}
+
+/// Get the current setting of the timer referred to by `fd`.
+#[no_mangle]
+pub unsafe extern "C" fn timerfd_gettime(fd: c_int, curr_value: *mut ctypes::itimerspec) -> c_int {
+    e(sys_timerfd_gettime(fd, curr_value))
+}
+
+/// Create a file descriptor for accepting signals, or update the mask of an
+/// existing one.
+#[no_mangle]
+pub unsafe extern "C" fn signalfd(fd: c_int, mask: *const ctypes::sigset_t, flags: c_int) -> c_int {
+    e(sys_signalfd(fd, mask, flags))
+}
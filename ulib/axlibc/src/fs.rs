@@ -1,10 +1,16 @@
-use core::ffi::{c_char, c_int};
+use core::ffi::{c_char, c_int, c_uint, c_void};
 
 use arceos_posix_api::{
-    sys_fstat, sys_getcwd, sys_lseek, sys_lstat, sys_open, sys_rename, sys_stat,
+    sys_access, sys_chdir, sys_chmod, sys_chown, sys_faccessat, sys_fchmod, sys_fchmodat,
+    sys_fchown, sys_fchownat, sys_fstat, sys_fstatat, sys_fstatfs, sys_fsync, sys_ftruncate,
+    sys_futimens, sys_getcwd, sys_getdents64, sys_lchown, sys_link, sys_lseek, sys_lstat,
+    sys_mkdir, sys_mkdirat, sys_open, sys_openat, sys_readlink, sys_readlinkat, sys_rename,
+    sys_renameat2, sys_rmdir, sys_stat, sys_statfs, sys_symlink, sys_unlink, sys_unlinkat,
+    sys_utimensat,
 };
 
-use crate::{ctypes, utils::e};
+use crate::ctypes;
+use crate::utils::{e, e_ssize};
 
 /// Open a file by `filename` and insert it into the file descriptor table.
 ///
@@ -19,6 +25,19 @@ pub unsafe extern "C" fn ax_open(
     e(sys_open(filename, flags, mode))
 }
 
+/// Open a file by `filename` relative to the directory `dirfd`.
+///
+/// Return its index in the file table (`fd`).
+#[no_mangle]
+pub unsafe extern "C" fn ax_openat(
+    dirfd: c_int,
+    filename: *const c_char,
+    flags: c_int,
+    mode: ctypes::mode_t,
+) -> c_int {
+    e(sys_openat(dirfd, filename, flags, mode))
+}
+
 /// Set the position of the file indicated by `fd`.
 ///
 /// Return its position after seek.
@@ -51,6 +70,38 @@ pub unsafe extern "C" fn lstat(path: *const c_char, buf: *mut ctypes::stat) -> c
     e(sys_lstat(path, buf) as _)
 }
 
+/// Get the metadata of the file `path` relative to the directory `dirfd` and
+/// write into `buf`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn fstatat(
+    dirfd: c_int,
+    path: *const c_char,
+    buf: *mut ctypes::stat,
+    flags: c_int,
+) -> c_int {
+    e(sys_fstatat(dirfd, path, buf, flags))
+}
+
+/// Get the statistics of the filesystem that contains `path` and write into
+/// `buf`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn statfs(path: *const c_char, buf: *mut ctypes::statfs) -> c_int {
+    e(sys_statfs(path, buf))
+}
+
+/// Get the statistics of the filesystem that contains the file `fd` and write
+/// into `buf`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn fstatfs(fd: c_int, buf: *mut ctypes::statfs) -> c_int {
+    e(sys_fstatfs(fd, buf))
+}
+
 /// Get the path of the current directory.
 #[no_mangle]
 pub unsafe extern "C" fn getcwd(buf: *mut c_char, size: usize) -> *mut c_char {
@@ -65,3 +116,270 @@ pub unsafe extern "C" fn getcwd(buf: *mut c_char, size: usize) -> *mut c_char {
 pub unsafe extern "C" fn rename(old: *const c_char, new: *const c_char) -> c_int {
     e(sys_rename(old, new))
 }
+
+/// Rename `old` relative to the directory `olddirfd` to `new` relative to
+/// `newdirfd`.
+///
+/// Return 0 if the operation succeeds, otherwise return -1.
+#[no_mangle]
+pub unsafe extern "C" fn renameat(
+    olddirfd: c_int,
+    old: *const c_char,
+    newdirfd: c_int,
+    new: *const c_char,
+) -> c_int {
+    e(sys_renameat2(olddirfd, old, newdirfd, new, 0))
+}
+
+/// Rename `old` relative to the directory `olddirfd` to `new` relative to
+/// `newdirfd`, with the `RENAME_*` flags.
+///
+/// Return 0 if the operation succeeds, otherwise return -1.
+#[no_mangle]
+pub unsafe extern "C" fn renameat2(
+    olddirfd: c_int,
+    old: *const c_char,
+    newdirfd: c_int,
+    new: *const c_char,
+    flags: c_uint,
+) -> c_int {
+    e(sys_renameat2(olddirfd, old, newdirfd, new, flags))
+}
+
+/// Read the target of the symbolic link `path` into `buf`.
+///
+/// Return the number of bytes placed in `buf`.
+#[no_mangle]
+pub unsafe extern "C" fn readlink(
+    path: *const c_char,
+    buf: *mut c_char,
+    bufsize: usize,
+) -> ctypes::ssize_t {
+    e_ssize(sys_readlink(path, buf, bufsize))
+}
+
+/// Read the target of the symbolic link `path` relative to the directory
+/// `dirfd` into `buf`.
+///
+/// Return the number of bytes placed in `buf`.
+#[no_mangle]
+pub unsafe extern "C" fn readlinkat(
+    dirfd: c_int,
+    path: *const c_char,
+    buf: *mut c_char,
+    bufsize: usize,
+) -> ctypes::ssize_t {
+    e_ssize(sys_readlinkat(dirfd, path, buf, bufsize))
+}
+
+/// Create a symbolic link `linkpath` which contains the string `target`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn symlink(target: *const c_char, linkpath: *const c_char) -> c_int {
+    e(sys_symlink(target, linkpath))
+}
+
+/// Create a new hard link `new` to the existing file `old`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn link(old: *const c_char, new: *const c_char) -> c_int {
+    e(sys_link(old, new))
+}
+
+/// Create a directory `path`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn mkdir(path: *const c_char, mode: ctypes::mode_t) -> c_int {
+    e(sys_mkdir(path, mode))
+}
+
+/// Create a directory `path` relative to the directory `dirfd`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn mkdirat(dirfd: c_int, path: *const c_char, mode: ctypes::mode_t) -> c_int {
+    e(sys_mkdirat(dirfd, path, mode))
+}
+
+/// Remove the file `path`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn unlink(path: *const c_char) -> c_int {
+    e(sys_unlink(path))
+}
+
+/// Remove the empty directory `path`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn rmdir(path: *const c_char) -> c_int {
+    e(sys_rmdir(path))
+}
+
+/// Remove the file or the empty directory (with `AT_REMOVEDIR`) `path`
+/// relative to the directory `dirfd`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn unlinkat(dirfd: c_int, path: *const c_char, flags: c_int) -> c_int {
+    e(sys_unlinkat(dirfd, path, flags))
+}
+
+/// Truncate or extend the file `fd` to `length` bytes.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn ftruncate(fd: c_int, length: ctypes::off_t) -> c_int {
+    e(sys_ftruncate(fd, length))
+}
+
+/// Flush the file `fd` to the storage device.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn fsync(fd: c_int) -> c_int {
+    e(sys_fsync(fd))
+}
+
+/// Change the current directory to `path`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn chdir(path: *const c_char) -> c_int {
+    e(sys_chdir(path))
+}
+
+/// Check whether the file `path` can be accessed with the permissions in
+/// `mode`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn access(path: *const c_char, mode: c_int) -> c_int {
+    e(sys_access(path, mode))
+}
+
+/// Check whether the file `path` relative to the directory `dirfd` can be
+/// accessed with the permissions in `mode`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn faccessat(
+    dirfd: c_int,
+    path: *const c_char,
+    mode: c_int,
+    flags: c_int,
+) -> c_int {
+    e(sys_faccessat(dirfd, path, mode, flags))
+}
+
+/// Read the entries of the directory `fd` into `dirp`, up to `count` bytes.
+///
+/// Return the number of bytes read, or 0 at the end of the directory.
+#[no_mangle]
+pub unsafe extern "C" fn getdents64(fd: c_int, dirp: *mut c_void, count: usize) -> ctypes::ssize_t {
+    e_ssize(sys_getdents64(fd, dirp, count))
+}
+
+/// Change the permissions of the file `path` to `mode`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn chmod(path: *const c_char, mode: ctypes::mode_t) -> c_int {
+    e(sys_chmod(path, mode))
+}
+
+/// Change the permissions of the opened file `fd` to `mode`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn fchmod(fd: c_int, mode: ctypes::mode_t) -> c_int {
+    e(sys_fchmod(fd, mode))
+}
+
+/// Change the permissions of the file `path` relative to the directory
+/// `dirfd` to `mode`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn fchmodat(
+    dirfd: c_int,
+    path: *const c_char,
+    mode: ctypes::mode_t,
+    flags: c_int,
+) -> c_int {
+    e(sys_fchmodat(dirfd, path, mode, flags))
+}
+
+/// Change the owner and group of the file `path`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn chown(
+    path: *const c_char,
+    uid: ctypes::uid_t,
+    gid: ctypes::gid_t,
+) -> c_int {
+    e(sys_chown(path, uid, gid))
+}
+
+/// Change the owner and group of the file `path` without following symbolic
+/// links.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn lchown(
+    path: *const c_char,
+    uid: ctypes::uid_t,
+    gid: ctypes::gid_t,
+) -> c_int {
+    e(sys_lchown(path, uid, gid))
+}
+
+/// Change the owner and group of the opened file `fd`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn fchown(fd: c_int, uid: ctypes::uid_t, gid: ctypes::gid_t) -> c_int {
+    e(sys_fchown(fd, uid, gid))
+}
+
+/// Change the owner and group of the file `path` relative to the directory
+/// `dirfd`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn fchownat(
+    dirfd: c_int,
+    path: *const c_char,
+    uid: ctypes::uid_t,
+    gid: ctypes::gid_t,
+    flags: c_int,
+) -> c_int {
+    e(sys_fchownat(dirfd, path, uid, gid, flags))
+}
+
+/// Change the last access and modification times of the file `path` relative
+/// to the directory `dirfd`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn utimensat(
+    dirfd: c_int,
+    path: *const c_char,
+    times: *const ctypes::timespec,
+    flags: c_int,
+) -> c_int {
+    e(sys_utimensat(dirfd, path, times, flags))
+}
+
+/// Change the last access and modification times of the opened file `fd`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn futimens(fd: c_int, times: *const ctypes::timespec) -> c_int {
+    e(sys_futimens(fd, times))
+}
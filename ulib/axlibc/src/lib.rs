@@ -19,10 +19,12 @@
 //!     - `fd`: Enable file descriptor table.
 //!     - `pipe`: Enable pipe support.
 //!     - `select`: Enable synchronous I/O multiplexing ([select]) support.
+//!     - `poll`: Enable synchronous I/O multiplexing ([poll]) support.
 //!     - `epoll`: Enable event polling ([epoll]) support.
 //!
 //! [ArceOS]: https://github.com/rcore-os/arceos
 //! [select]: https://man7.org/linux/man-pages/man2/select.2.html
+//! [poll]: https://man7.org/linux/man-pages/man2/poll.2.html
 //! [epoll]: https://man7.org/linux/man-pages/man7/epoll.7.html
 
 #![cfg_attr(all(not(test), not(doc)), no_std)]
@@ -53,7 +55,7 @@ mod utils;
 mod fd_ops;
 #[cfg(feature = "fs")]
 mod fs;
-#[cfg(any(feature = "select", feature = "epoll"))]
+#[cfg(any(feature = "select", feature = "poll", feature = "epoll"))]
 mod io_mpx;
 #[cfg(feature = "alloc")]
 mod malloc;
@@ -63,6 +65,10 @@ mod net;
 mod pipe;
 #[cfg(feature = "multitask")]
 mod pthread;
+#[cfg(feature = "multitask")]
+mod sched;
+#[cfg(feature = "multitask")]
+mod semaphore;
 #[cfg(feature = "alloc")]
 mod strftime;
 #[cfg(feature = "fp_simd")]
@@ -74,6 +80,7 @@ mod mktime;
 mod rand;
 mod resource;
 mod setjmp;
+mod signal;
 mod sys;
 mod time;
 mod unistd;
@@ -85,8 +92,9 @@ pub use self::io::{read, writev};
 pub use self::errno::strerror;
 pub use self::mktime::mktime;
 pub use self::rand::{rand, random, srand};
-pub use self::resource::{getrlimit, setrlimit};
+pub use self::resource::{getrlimit, getrusage, setrlimit};
 pub use self::setjmp::{longjmp, setjmp};
+pub use self::signal::{kill, pthread_sigmask, sigprocmask};
 pub use self::sys::sysconf;
 pub use self::time::{clock_gettime, nanosleep};
 pub use self::unistd::{abort, exit, getpid};
@@ -97,10 +105,18 @@ pub use self::malloc::{free, malloc};
 pub use self::strftime::strftime;
 
 #[cfg(feature = "fd")]
-pub use self::fd_ops::{ax_fcntl, close, dup, dup2, dup3};
+pub use self::fd_ops::{
+    ax_fcntl, close, dup, dup2, dup3, eventfd, eventfd_read, eventfd_write, signalfd,
+    timerfd_create, timerfd_gettime, timerfd_settime,
+};
 
 #[cfg(feature = "fs")]
-pub use self::fs::{ax_open, fstat, getcwd, lseek, lstat, rename, stat};
+pub use self::fs::{
+    access, ax_open, ax_openat, chdir, chmod, chown, faccessat, fchmod, fchmodat, fchown, fchownat,
+    fstat, fstatat, fstatfs, fsync, ftruncate, futimens, getcwd, getdents64, lchown, link, lseek,
+    lstat, mkdir, mkdirat, readlink, readlinkat, rename, renameat, renameat2, rmdir, stat, statfs,
+    symlink, unlink, unlinkat, utimensat,
+};
 
 #[cfg(feature = "net")]
 pub use self::net::{
@@ -109,9 +125,31 @@ pub use self::net::{
 };
 
 #[cfg(feature = "multitask")]
-pub use self::pthread::{pthread_create, pthread_exit, pthread_join, pthread_self};
+pub use self::pthread::{
+    pthread_barrier_destroy, pthread_barrier_init, pthread_barrier_wait, pthread_cond_broadcast,
+    pthread_cond_destroy, pthread_cond_init, pthread_cond_signal, pthread_cond_timedwait,
+    pthread_cond_wait,
+};
+#[cfg(feature = "multitask")]
+pub use self::pthread::{
+    pthread_cancel, pthread_create, pthread_detach, pthread_exit, pthread_join, pthread_self,
+    pthread_setcancelstate, pthread_setcanceltype, pthread_testcancel,
+};
 #[cfg(feature = "multitask")]
 pub use self::pthread::{pthread_mutex_init, pthread_mutex_lock, pthread_mutex_unlock};
+#[cfg(feature = "multitask")]
+pub use self::pthread::{
+    pthread_rwlock_destroy, pthread_rwlock_init, pthread_rwlock_rdlock, pthread_rwlock_tryrdlock,
+    pthread_rwlock_trywrlock, pthread_rwlock_unlock, pthread_rwlock_wrlock,
+};
+#[cfg(feature = "multitask")]
+pub use self::resource::{getpriority, setpriority};
+#[cfg(feature = "multitask")]
+pub use self::sched::{sched_getparam, sched_setparam};
+#[cfg(feature = "multitask")]
+pub use self::semaphore::{
+    sem_destroy, sem_getvalue, sem_init, sem_post, sem_timedwait, sem_trywait, sem_wait,
+};
 
 #[cfg(feature = "pipe")]
 pub use self::pipe::pipe;
@@ -120,6 +158,8 @@ pub use self::pipe::pipe;
 pub use self::io_mpx::select;
 #[cfg(feature = "epoll")]
 pub use self::io_mpx::{epoll_create, epoll_ctl, epoll_wait};
+#[cfg(feature = "poll")]
+pub use self::io_mpx::{poll, ppoll};
 
 #[cfg(feature = "fp_simd")]
 pub use self::strtod::{strtod, strtof};
@@ -1,6 +1,6 @@
 use crate::{ctypes, utils::e};
 use arceos_posix_api as api;
-use core::ffi::{c_int, c_void};
+use core::ffi::{c_int, c_uint, c_void};
 
 /// Returns the `pthread` struct of current thread.
 #[no_mangle]
@@ -37,6 +37,36 @@ pub unsafe extern "C" fn pthread_join(
     e(api::sys_pthread_join(thread, retval))
 }
 
+/// Marks the given thread as detached, so that it is freed when it exits.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_detach(thread: ctypes::pthread_t) -> c_int {
+    pe(api::sys_pthread_detach(thread))
+}
+
+/// Requests the given thread to be canceled at its next cancellation point.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cancel(thread: ctypes::pthread_t) -> c_int {
+    pe(api::sys_pthread_cancel(thread))
+}
+
+/// Enables or disables the cancellation of the current thread.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_setcancelstate(state: c_int, oldstate: *mut c_int) -> c_int {
+    pe(api::sys_pthread_setcancelstate(state, oldstate))
+}
+
+/// Sets the cancellation type of the current thread.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_setcanceltype(ty: c_int, oldtype: *mut c_int) -> c_int {
+    pe(api::sys_pthread_setcanceltype(ty, oldtype))
+}
+
+/// Exits the current thread if it is canceled.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_testcancel() {
+    api::sys_pthread_testcancel()
+}
+
 /// Initialize a mutex.
 #[no_mangle]
 pub unsafe extern "C" fn pthread_mutex_init(
@@ -57,3 +87,132 @@ pub unsafe extern "C" fn pthread_mutex_lock(mutex: *mut ctypes::pthread_mutex_t)
 pub unsafe extern "C" fn pthread_mutex_unlock(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
     e(api::sys_pthread_mutex_unlock(mutex))
 }
+
+/// Initialize a condition variable.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cond_init(
+    cond: *mut ctypes::pthread_cond_t,
+    attr: *const ctypes::pthread_condattr_t,
+) -> c_int {
+    pe(api::sys_pthread_cond_init(cond, attr))
+}
+
+/// Destroy a condition variable.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cond_destroy(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    pe(api::sys_pthread_cond_destroy(cond))
+}
+
+/// Unlock the mutex and wait on the condition variable, then lock the mutex
+/// again before returning.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cond_wait(
+    cond: *mut ctypes::pthread_cond_t,
+    mutex: *mut ctypes::pthread_mutex_t,
+) -> c_int {
+    pe(api::sys_pthread_cond_wait(cond, mutex))
+}
+
+/// Same as [`pthread_cond_wait`], but returns `ETIMEDOUT` if the condition
+/// variable is not signaled before the absolute time `abstime`.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cond_timedwait(
+    cond: *mut ctypes::pthread_cond_t,
+    mutex: *mut ctypes::pthread_mutex_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    pe(api::sys_pthread_cond_timedwait(cond, mutex, abstime))
+}
+
+/// Wake up one thread waiting on the condition variable.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cond_signal(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    pe(api::sys_pthread_cond_signal(cond))
+}
+
+/// Wake up all threads waiting on the condition variable.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cond_broadcast(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    pe(api::sys_pthread_cond_broadcast(cond))
+}
+
+/// Initialize a readers-writer lock.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_init(
+    rwlock: *mut ctypes::pthread_rwlock_t,
+    attr: *const ctypes::pthread_rwlockattr_t,
+) -> c_int {
+    pe(api::sys_pthread_rwlock_init(rwlock, attr))
+}
+
+/// Destroy a readers-writer lock.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_destroy(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    pe(api::sys_pthread_rwlock_destroy(rwlock))
+}
+
+/// Lock the readers-writer lock for reading.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_rdlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    pe(api::sys_pthread_rwlock_rdlock(rwlock))
+}
+
+/// Try to lock the readers-writer lock for reading, returns `EBUSY` if a
+/// writer holds it.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_tryrdlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    pe(api::sys_pthread_rwlock_tryrdlock(rwlock))
+}
+
+/// Lock the readers-writer lock for writing.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_wrlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    pe(api::sys_pthread_rwlock_wrlock(rwlock))
+}
+
+/// Try to lock the readers-writer lock for writing, returns `EBUSY` if it is
+/// held by others.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_trywrlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    pe(api::sys_pthread_rwlock_trywrlock(rwlock))
+}
+
+/// Unlock the readers-writer lock.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_unlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    pe(api::sys_pthread_rwlock_unlock(rwlock))
+}
+
+/// Initialize a barrier for `count` threads.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_barrier_init(
+    barrier: *mut ctypes::pthread_barrier_t,
+    attr: *const ctypes::pthread_barrierattr_t,
+    count: c_uint,
+) -> c_int {
+    pe(api::sys_pthread_barrier_init(barrier, attr, count))
+}
+
+/// Destroy a barrier.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_barrier_destroy(barrier: *mut ctypes::pthread_barrier_t) -> c_int {
+    pe(api::sys_pthread_barrier_destroy(barrier))
+}
+
+/// Wait on the barrier until enough threads have reached it.
+///
+/// Returns `PTHREAD_BARRIER_SERIAL_THREAD` in one of the threads and 0 in the
+/// others.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_barrier_wait(barrier: *mut ctypes::pthread_barrier_t) -> c_int {
+    match api::sys_pthread_barrier_wait(barrier) {
+        ctypes::PTHREAD_BARRIER_SERIAL_THREAD => ctypes::PTHREAD_BARRIER_SERIAL_THREAD,
+        ret => pe(ret),
+    }
+}
+
+/// Unlike other functions, `pthread_*` functions for synchronization return
+/// the error number instead of setting `errno`.
+fn pe(ret: c_int) -> c_int {
+    -ret.min(0)
+}
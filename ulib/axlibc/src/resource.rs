@@ -1,6 +1,6 @@
 use core::ffi::c_int;
 
-use arceos_posix_api::{sys_getrlimit, sys_setrlimit};
+use arceos_posix_api::{sys_getrlimit, sys_getrusage, sys_setrlimit};
 
 use crate::utils::e;
 
@@ -15,3 +15,28 @@ pub unsafe extern "C" fn getrlimit(resource: c_int, rlimits: *mut crate::ctypes:
 pub unsafe extern "C" fn setrlimit(resource: c_int, rlimits: *mut crate::ctypes::rlimit) -> c_int {
     e(sys_setrlimit(resource, rlimits))
 }
+
+/// Get resource usage
+#[no_mangle]
+pub unsafe extern "C" fn getrusage(who: c_int, usage: *mut crate::ctypes::rusage) -> c_int {
+    e(sys_getrusage(who, usage))
+}
+
+/// Get the nice value of a thread
+#[cfg(feature = "multitask")]
+#[no_mangle]
+pub unsafe extern "C" fn getpriority(which: c_int, who: crate::ctypes::id_t) -> c_int {
+    let ret = e(arceos_posix_api::sys_getpriority(which, who));
+    if ret < 0 {
+        -1
+    } else {
+        20 - ret
+    }
+}
+
+/// Set the nice value of a thread
+#[cfg(feature = "multitask")]
+#[no_mangle]
+pub unsafe extern "C" fn setpriority(which: c_int, who: crate::ctypes::id_t, prio: c_int) -> c_int {
+    e(arceos_posix_api::sys_setpriority(which, who, prio))
+}
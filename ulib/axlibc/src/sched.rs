@@ -0,0 +1,21 @@
+use crate::{ctypes, utils::e};
+use arceos_posix_api::{sys_sched_getparam, sys_sched_setparam};
+use core::ffi::c_int;
+
+/// Get the scheduling parameters of a thread
+#[no_mangle]
+pub unsafe extern "C" fn sched_getparam(
+    pid: ctypes::pid_t,
+    param: *mut ctypes::sched_param,
+) -> c_int {
+    e(sys_sched_getparam(pid, param))
+}
+
+/// Set the scheduling parameters of a thread
+#[no_mangle]
+pub unsafe extern "C" fn sched_setparam(
+    pid: ctypes::pid_t,
+    param: *const ctypes::sched_param,
+) -> c_int {
+    e(sys_sched_setparam(pid, param))
+}
@@ -0,0 +1,49 @@
+use crate::{ctypes, utils::e};
+use arceos_posix_api as api;
+use core::ffi::{c_int, c_uint};
+
+/// Initialize an unnamed semaphore with the given value.
+#[no_mangle]
+pub unsafe extern "C" fn sem_init(sem: *mut ctypes::sem_t, pshared: c_int, value: c_uint) -> c_int {
+    e(api::sys_sem_init(sem, pshared, value))
+}
+
+/// Destroy an unnamed semaphore.
+#[no_mangle]
+pub unsafe extern "C" fn sem_destroy(sem: *mut ctypes::sem_t) -> c_int {
+    e(api::sys_sem_destroy(sem))
+}
+
+/// Decrement the semaphore, blocking until its value is greater than zero.
+#[no_mangle]
+pub unsafe extern "C" fn sem_wait(sem: *mut ctypes::sem_t) -> c_int {
+    e(api::sys_sem_wait(sem))
+}
+
+/// Decrement the semaphore, fails with `EAGAIN` if its value is zero.
+#[no_mangle]
+pub unsafe extern "C" fn sem_trywait(sem: *mut ctypes::sem_t) -> c_int {
+    e(api::sys_sem_trywait(sem))
+}
+
+/// Same as [`sem_wait`], but fails with `ETIMEDOUT` if the semaphore can not
+/// be decremented before the absolute time `abstime`.
+#[no_mangle]
+pub unsafe extern "C" fn sem_timedwait(
+    sem: *mut ctypes::sem_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    e(api::sys_sem_timedwait(sem, abstime))
+}
+
+/// Increment the semaphore, and wake up a thread waiting on it.
+#[no_mangle]
+pub unsafe extern "C" fn sem_post(sem: *mut ctypes::sem_t) -> c_int {
+    e(api::sys_sem_post(sem))
+}
+
+/// Get the current value of the semaphore.
+#[no_mangle]
+pub unsafe extern "C" fn sem_getvalue(sem: *mut ctypes::sem_t, sval: *mut c_int) -> c_int {
+    e(api::sys_sem_getvalue(sem, sval))
+}
@@ -0,0 +1,34 @@
+use core::ffi::c_int;
+
+use arceos_posix_api::{sys_kill, sys_sigprocmask};
+
+use crate::{ctypes, utils::e};
+
+/// Send a signal to a process.
+#[no_mangle]
+pub unsafe extern "C" fn kill(pid: ctypes::pid_t, sig: c_int) -> c_int {
+    e(sys_kill(pid, sig))
+}
+
+/// Examine and change the blocked signals.
+#[no_mangle]
+pub unsafe extern "C" fn sigprocmask(
+    how: c_int,
+    set: *const ctypes::sigset_t,
+    oldset: *mut ctypes::sigset_t,
+) -> c_int {
+    e(sys_sigprocmask(how, set, oldset))
+}
+
+/// Examine and change the blocked signals of the current thread.
+///
+/// The signals are blocked for the whole process, as they are not delivered
+/// to threads. It returns the error number instead of setting `errno`.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_sigmask(
+    how: c_int,
+    set: *const ctypes::sigset_t,
+    oldset: *mut ctypes::sigset_t,
+) -> c_int {
+    -sys_sigprocmask(how, set, oldset).min(0)
+}
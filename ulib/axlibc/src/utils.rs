@@ -1,5 +1,7 @@
 use core::ffi::c_int;
 
+use crate::ctypes;
+
 pub fn e(ret: c_int) -> c_int {
     if ret < 0 {
         crate::errno::set_errno(ret.abs());
@@ -8,3 +10,13 @@ pub fn e(ret: c_int) -> c_int {
         ret as _
     }
 }
+
+/// Like [`e`], but for a `ssize_t` result, which may not fit in a `c_int`.
+pub fn e_ssize(ret: ctypes::ssize_t) -> ctypes::ssize_t {
+    if ret < 0 {
+        crate::errno::set_errno(ret.abs() as _);
+        -1
+    } else {
+        ret
+    }
+}
@@ -27,15 +27,21 @@
 //!     - `sched_fifo`: Use the FIFO cooperative scheduler.
 //!     - `sched_rr`: Use the Round-robin preemptive scheduler.
 //!     - `sched_cfs`: Use the Completely Fair Scheduler (CFS) preemptive scheduler.
+//!     - `sched_rt`: Use the real-time preemptive scheduler with fixed priorities.
+//!       Together with `sched_cfs`, non-real-time tasks are scheduled by CFS.
+//!     - `sched_edf`: Use the Earliest Deadline First (EDF) preemptive scheduler.
 //! - Upperlayer stacks
 //!     - `fs`: Enable file system support.
 //!     - `myfs`: Allow users to define their custom filesystems to override the default.
+//!     - `ext2fs`: Use ext2 instead of FAT as the main filesystem.
 //!     - `net`: Enable networking support.
 //!     - `dns`: Enable DNS lookup support.
 //!     - `display`: Enable graphics support.
 //! - Device drivers
 //!     - `bus-mmio`: Use device tree to probe all MMIO devices.
 //!     - `bus-pci`: Use PCI bus to probe all PCI devices.
+//!     - `driver-dyn`: Use trait objects for devices, which allows more than one device
+//!       of each kind, such as multiple disks.
 //!     - `driver-ramdisk`: Use the RAM disk to emulate the block device.
 //!     - `driver-ixgbe`: Enable the Intel 82599 10Gbit NIC driver.
 //!     - `driver-bcm2835-sdhci`: Enable the BCM2835 SDHCI driver (Raspberry Pi SD card).
@@ -72,6 +78,9 @@ pub mod sync;
 pub mod thread;
 pub mod time;
 
+#[cfg(feature = "multitask")]
+pub mod task;
+
 #[cfg(feature = "fs")]
 pub mod fs;
 #[cfg(feature = "net")]
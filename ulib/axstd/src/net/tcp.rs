@@ -45,6 +45,20 @@ impl TcpStream {
     }
 }
 
+impl TcpStream {
+    /// Reads data from the stream asynchronously. On success, returns the
+    /// number of bytes read.
+    pub async fn read_async(&self, buf: &mut [u8]) -> io::Result<usize> {
+        api::ax_tcp_recv_async(&self.0, buf).await
+    }
+
+    /// Writes data to the stream asynchronously. On success, returns the
+    /// number of bytes written.
+    pub async fn write_async(&self, buf: &[u8]) -> io::Result<usize> {
+        api::ax_tcp_send_async(&self.0, buf).await
+    }
+}
+
 impl Read for TcpStream {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         api::ax_tcp_recv(&self.0, buf)
@@ -102,4 +116,12 @@ impl TcpListener {
     pub fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
         api::ax_tcp_accept(&self.0).map(|(a, b)| (TcpStream(a), b))
     }
+
+    /// Accept a new incoming connection from this listener asynchronously,
+    /// see [`accept`](Self::accept).
+    pub async fn accept_async(&self) -> io::Result<(TcpStream, SocketAddr)> {
+        api::ax_tcp_accept_async(&self.0)
+            .await
+            .map(|(a, b)| (TcpStream(a), b))
+    }
 }
@@ -41,6 +41,12 @@ impl UdpSocket {
         api::ax_udp_recv_from(&self.0, buf)
     }
 
+    /// Receives a single datagram message on the socket asynchronously, see
+    /// [`recv_from`](Self::recv_from).
+    pub async fn recv_from_async(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        api::ax_udp_recv_from_async(&self.0, buf).await
+    }
+
     /// Receives a single datagram message on the socket, without removing it from
     /// the queue. On success, returns the number of bytes read and the origin.
     pub fn peek_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
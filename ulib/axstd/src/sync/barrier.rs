@@ -0,0 +1,69 @@
+//! A barrier based on the wait queue.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use arceos_api::task::{self as api, AxWaitQueueHandle};
+
+/// A barrier enables multiple tasks to synchronize the beginning of some
+/// computation, similar to
+/// [`std::sync::Barrier`](https://doc.rust-lang.org/std/sync/struct.Barrier.html).
+pub struct Barrier {
+    wq: AxWaitQueueHandle,
+    count: AtomicUsize,
+    generation: AtomicUsize,
+    num_tasks: usize,
+}
+
+/// A `BarrierWaitResult` is returned by [`Barrier::wait()`] when all tasks in
+/// the [`Barrier`] have rendezvoused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarrierWaitResult(bool);
+
+impl BarrierWaitResult {
+    /// Returns `true` if this task is the "leader task" for the call to
+    /// [`Barrier::wait()`].
+    ///
+    /// Only one task will have `true` returned from their result, all other
+    /// tasks will have `false` returned.
+    pub fn is_leader(&self) -> bool {
+        self.0
+    }
+}
+
+impl Barrier {
+    /// Creates a new barrier that can block a given number of tasks.
+    ///
+    /// A barrier will block `n`-1 tasks which call [`wait()`](Self::wait)
+    /// and then wake up all tasks at once when the `n`th task calls
+    /// [`wait()`](Self::wait).
+    pub const fn new(n: usize) -> Self {
+        Self {
+            wq: AxWaitQueueHandle::new(),
+            count: AtomicUsize::new(0),
+            generation: AtomicUsize::new(0),
+            num_tasks: n,
+        }
+    }
+
+    /// Blocks the current task until all tasks have rendezvoused here.
+    ///
+    /// Barriers are re-usable after all tasks have rendezvoused once, and can
+    /// be used continuously.
+    pub fn wait(&self) -> BarrierWaitResult {
+        let generation = self.generation.load(Ordering::Acquire);
+        if self.count.fetch_add(1, Ordering::AcqRel) + 1 < self.num_tasks {
+            api::ax_wait_queue_wait(
+                &self.wq,
+                || self.generation.load(Ordering::Acquire) != generation,
+                None,
+            );
+            BarrierWaitResult(false)
+        } else {
+            // reset the count before others can pass the next generation
+            self.count.store(0, Ordering::Relaxed);
+            self.generation.fetch_add(1, Ordering::Release);
+            api::ax_wait_queue_wake(&self.wq, u32::MAX);
+            BarrierWaitResult(true)
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Types and functions for asynchronous tasks.
+//!
+//! Besides the types in [`core::task`], it provides a lightweight [`Executor`]
+//! that runs many futures on a few threads, and [`block_on`] to run a future
+//! on the current thread. The futures can wait for the network I/O (e.g.,
+//! [`TcpListener::accept_async`](crate::net::TcpListener::accept_async)) and
+//! the timer ([`sleep`]) without blocking the thread.
+
+#[doc(no_inline)]
+pub use core::task::*;
+
+#[cfg(feature = "alloc")]
+#[doc(no_inline)]
+pub use alloc::task::Wake;
+
+use arceos_api::task::{self as api, AxExecutorHandle};
+use core::future::Future;
+use core::pin::pin;
+use core::time::Duration;
+
+/// Runs a future to completion on the current thread.
+///
+/// The thread is blocked while the future is pending, until it is woken up.
+pub fn block_on<F: Future>(fut: F) -> F::Output {
+    let mut output = None;
+    {
+        let fut = pin!(async { output = Some(fut.await) });
+        api::ax_block_on(fut);
+    }
+    output.unwrap()
+}
+
+/// An executor that runs many futures on the threads calling
+/// [`Executor::run`].
+///
+/// A future is polled only when it is woken up, so that thousands of them can
+/// wait for I/O on one thread. The executor can be cloned to spawn futures
+/// from within the futures.
+///
+/// # Examples
+///
+/// ```ignore
+/// use std::net::TcpListener;
+/// use std::task::Executor;
+///
+/// let listener = TcpListener::bind("0.0.0.0:5555").unwrap();
+/// let executor = Executor::new();
+/// let spawner = executor.clone();
+/// executor.spawn(async move {
+///     loop {
+///         let (stream, _) = listener.accept_async().await.unwrap();
+///         spawner.spawn(async move {
+///             let mut buf = [0; 1024];
+///             while let Ok(n @ 1..) = stream.read_async(&mut buf).await {
+///                 stream.write_async(&buf[..n]).await.unwrap();
+///             }
+///         });
+///     }
+/// });
+/// executor.run();
+/// ```
+#[derive(Clone, Default)]
+pub struct Executor(AxExecutorHandle);
+
+impl Executor {
+    /// Creates a new executor without futures.
+    pub fn new() -> Self {
+        Self(AxExecutorHandle::new())
+    }
+
+    /// Spawns a future on the executor.
+    pub fn spawn<F>(&self, fut: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        api::ax_executor_spawn(&self.0, fut)
+    }
+
+    /// Runs the futures on the current thread, until all of them are
+    /// completed.
+    pub fn run(&self) {
+        api::ax_executor_run(&self.0)
+    }
+}
+
+/// Waits for the given duration asynchronously.
+///
+/// If the `irq` feature is not enabled, the future is polled again and again
+/// until the time is up.
+pub async fn sleep(dur: Duration) {
+    sleep_until(arceos_api::time::ax_current_time() + dur).await
+}
+
+/// Waits until the given deadline asynchronously, see [`sleep`].
+pub async fn sleep_until(deadline: arceos_api::time::AxTimeValue) {
+    api::ax_sleep_until_async(deadline).await
+}
@@ -49,6 +49,8 @@ pub struct Builder {
     name: Option<String>,
     // The size of the stack for the spawned thread in bytes
     stack_size: Option<usize>,
+    // The scheduling priority of the spawned thread
+    priority: Option<isize>,
 }
 
 impl Builder {
@@ -58,6 +60,7 @@ impl Builder {
         Builder {
             name: None,
             stack_size: None,
+            priority: None,
         }
     }
 
@@ -73,6 +76,17 @@ impl Builder {
         self
     }
 
+    /// Sets the scheduling priority of the new thread.
+    ///
+    /// The range of the priority is dependent on the underlying scheduler
+    /// (e.g., the nice value from -20 to 19 for the CFS scheduler). Spawning
+    /// the thread fails if the scheduler does not accept the priority, while
+    /// the thread is still started with the default priority.
+    pub fn priority(mut self, prio: isize) -> Builder {
+        self.priority = Some(prio);
+        self
+    }
+
     /// Spawns a new thread by taking ownership of the `Builder`, and returns an
     /// [`io::Result`] to its [`JoinHandle`].
     ///
@@ -116,6 +130,9 @@ impl Builder {
         };
 
         let task = api::ax_spawn(main, name, stack_size);
+        if let Some(prio) = self.priority {
+            api::ax_set_priority(&task, prio)?;
+        }
         Ok(JoinHandle {
             thread: Thread::from_id(task.id()),
             native: task,